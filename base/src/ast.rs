@@ -318,8 +318,18 @@ pub enum Pattern<'ast, Id> {
         typ: ArcType<Id>,
         elems: &'ast mut [SpannedPattern<'ast, Id>],
     },
+    /// Array pattern, eg. `[x, y, ..rest]`. If `rest` is set then `elems[rest]` is matched against
+    /// the elements which are not matched by the other patterns (`..` is stored as `_`)
+    Array {
+        typ: ArcType<Id>,
+        elems: &'ast mut [SpannedPattern<'ast, Id>],
+        rest: Option<usize>,
+    },
     /// A literal pattern
     Literal(Literal),
+    /// An or-pattern which matches if any of its patterns match, eg. `Some 1 | None`. Every
+    /// pattern binds the same variables.
+    Or(&'ast mut [SpannedPattern<'ast, Id>]),
     /// An invalid pattern
     Error,
 }
@@ -384,6 +394,8 @@ impl<Id> Default for Pattern<'_, Id> {
 #[derive(Eq, PartialEq, Debug, AstClone)]
 pub struct Alternative<'ast, Id> {
     pub pattern: SpannedPattern<'ast, Id>,
+    /// Guard which must evaluate to `True` for the alternative to be selected, eg. `if x > 0`
+    pub guard: Option<SpannedExpr<'ast, Id>>,
     pub expr: SpannedExpr<'ast, Id>,
 }
 
@@ -428,6 +440,9 @@ pub struct Do<'ast, Id> {
 pub enum Expr<'ast, Id> {
     /// Identifiers
     Ident(TypedIdent<Id>),
+    /// A typed hole, eg. `_` or `?name`. Typechecking reports the type expected in its place (the
+    /// name of an anonymous hole is `_`)
+    Hole(TypedIdent<Id>),
     /// Literal values
     Literal(Literal),
     /// Function application, eg. `f x`
@@ -549,6 +564,7 @@ impl<'ast, Id> Expr<'ast, Id> {
             Expr::Lambda(..) => "Lambda",
             Expr::TypeBindings(..) => "TypeBindings",
             Expr::Ident(..) => "Ident",
+            Expr::Hole(..) => "Hole",
             Expr::MacroExpansion { .. } => "MacroExpansion",
             Expr::Literal(..) => "Literal",
             Expr::Annotated(..) => "Annotated",
@@ -827,6 +843,9 @@ pub fn walk_expr<'a, 'ast, V>(v: &mut V, e: &'a $($mut)* SpannedExpr<'ast, V::Id
             v.visit_expr(expr);
             for alt in &$($mut)* **alts {
                 v.visit_pattern(&$($mut)* alt.pattern);
+                if let Some(guard) = &$($mut)* alt.guard {
+                    v.visit_expr(guard);
+                }
                 v.visit_expr(&$($mut)* alt.expr);
             }
         }
@@ -901,6 +920,7 @@ pub fn walk_expr<'a, 'ast, V>(v: &mut V, e: &'a $($mut)* SpannedExpr<'ast, V::Id
             v.visit_expr(expr)
         }
         Expr::Ident(ref $($mut)* id) => v.visit_ident(id),
+        Expr::Hole(ref $($mut)* id) => v.visit_typ(&$($mut)* id.typ),
         Expr::MacroExpansion {
             ref $($mut)* replacement,
             ..
@@ -955,7 +975,22 @@ pub fn walk_pattern<'a, 'ast,V: ?Sized + $trait_name<'a, 'ast>>(v: &mut V, p: &'
                 v.visit_pattern(elem);
             }
         }
+        Pattern::Array {
+            typ,
+            elems,
+            ..
+        } => {
+            v.visit_typ(typ);
+            for elem in &$($mut)* **elems {
+                v.visit_pattern(elem);
+            }
+        }
         Pattern::Ident(id) => v.visit_ident(id),
+        Pattern::Or(patterns) => {
+            for pattern in &$($mut)* **patterns {
+                v.visit_pattern(pattern);
+            }
+        }
         Pattern::Literal(_) | Pattern::Error => (),
     }
 }
@@ -1088,7 +1123,7 @@ impl Typed for Expr<'_, Symbol> {
 
     fn try_type_of(&self, env: &dyn TypeEnv<Type = ArcType>) -> Result<ArcType, String> {
         match *self {
-            Expr::Ident(ref id) => Ok(id.typ.clone()),
+            Expr::Ident(ref id) | Expr::Hole(ref id) => Ok(id.typ.clone()),
             Expr::Tuple { ref elems, .. } if elems.len() == 1 => elems[0].try_type_of(env),
             Expr::Projection(_, _, ref typ)
             | Expr::Record { ref typ, .. }
@@ -1132,7 +1167,9 @@ impl Typed for Pattern<'_, Symbol> {
             Pattern::Ident(ref id) => Ok(id.typ.clone()),
             Pattern::Record { ref typ, .. } => Ok(typ.clone()),
             Pattern::Tuple { ref typ, .. } => Ok(typ.clone()),
+            Pattern::Array { ref typ, .. } => Ok(typ.clone()),
             Pattern::Constructor(ref id, ref args) => get_return_type(env, &id.typ, args.len()),
+            Pattern::Or(ref patterns) => patterns[0].try_type_of(env),
             Pattern::Error => Ok(Type::hole()),
             Pattern::Literal(ref l) => l.try_type_of(env),
         }
@@ -324,18 +324,34 @@ where
 {
     fn as_diagnostic(&self, map: &crate::source::CodeMap) -> Diagnostic<FileId> {
         let mut diagnostic = self.value.as_diagnostic(map);
+        let span_range = self.span.to_range(map);
         for label in &mut diagnostic.labels {
             if label.file_id == FileId::default() {
                 label.file_id = self.span.start();
             }
             if label.range == (0..0) {
-                if let Some(range) = self.span.to_range(map) {
+                if let Some(range) = span_range.clone() {
                     label.range = range;
                 }
             }
         }
+        if span_range.is_none() {
+            // The span is not part of any source (the code may be generated by a macro) so labels
+            // which should point to it can't be displayed. Keep their messages as notes instead.
+            let (unplaced, labels) = diagnostic
+                .labels
+                .into_iter()
+                .partition::<Vec<_>, _>(|label| label.range == (0..0));
+            diagnostic.labels = labels;
+            diagnostic.notes.extend(
+                unplaced
+                    .into_iter()
+                    .map(|label| label.message)
+                    .filter(|message| !message.is_empty()),
+            );
+        }
         if diagnostic.labels.is_empty() {
-            if let Some(range) = self.span.to_range(map) {
+            if let Some(range) = span_range {
                 diagnostic
                     .labels
                     .push(Label::primary(self.span.start(), range));
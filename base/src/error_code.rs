@@ -0,0 +1,533 @@
+//! Stable codes which identify each kind of diagnostic emitted by the compiler.
+//!
+//! Every code has a long-form explanation which can be displayed with `gluon explain <CODE>`.
+//! Codes are never reused, if a diagnostic is removed its code is simply retired.
+//!
+//! * `E00xx` - Parse errors
+//! * `E01xx` - Type errors
+//! * `E02xx` - Kind errors
+//! * `E03xx` - Import errors
+//! * `E04xx` - Macro errors
+//! * `W00xx` - Lints
+
+/// A stable diagnostic code together with its explanation
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    /// A one line summary of the diagnostic
+    pub title: &'static str,
+    /// A long-form explanation of the diagnostic, including examples of code that trigger it
+    pub explanation: &'static str,
+}
+
+macro_rules! error_codes {
+    ($($code: ident, $title: expr, $explanation: expr;)*) => {
+        $(
+            pub const $code: &str = stringify!($code);
+        )*
+
+        /// Every known diagnostic code, sorted by code
+        pub static ERROR_CODES: &[ErrorCode] = &[
+            $(
+                ErrorCode {
+                    code: $code,
+                    title: $title,
+                    explanation: $explanation,
+                },
+            )*
+        ];
+    };
+}
+
+/// Returns the explanation for `code`. The lookup ignores case so `e0101` finds `E0101`.
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES
+        .iter()
+        .find(|error_code| error_code.code.eq_ignore_ascii_case(code))
+}
+
+error_codes! {
+E0001, "Invalid token", r#"
+The source code contains characters which do not form a valid token, for instance an
+unterminated string literal, an invalid escape code or an integer literal which is too large.
+
+```gluon
+let x = "missing the closing quote
+let y = 99999999999999999999999
+```
+
+Check the literal or character indicated by the error and correct it.
+"#;
+
+E0002, "Invalid indentation", r#"
+A line was unindented past the block it belongs to. Gluon uses indentation to decide where
+`let`, `type`, `match` and other blocks end, so every line in a block must be indented at least
+as much as the first line of the block.
+
+```gluon
+let f x =
+    match x with
+    | _ ->
+  let y = 1
+  y
+f 1
+```
+
+Indent the line so that it lines up with (or is to the right of) the block it belongs to.
+"#;
+
+E0003, "Unrecognized token", r#"
+The parser encountered a token that it could not recognize at all.
+
+Check the indicated location for stray characters.
+"#;
+
+E0004, "Unexpected token", r#"
+The parser found a token where it expected something else. The error lists the tokens that
+would have been valid at that location.
+
+```gluon
+let x = 1 +
+in x
+```
+
+Here `in` is not allowed after `+` since an expression is expected.
+"#;
+
+E0005, "Unexpected end of file", r#"
+The file ended before the expression or declaration being parsed was complete.
+
+```gluon
+(1 + 2
+```
+
+Add the missing code at the end of the file, in this case the closing `)`.
+"#;
+
+E0006, "Extra token", r#"
+A token was found after the parser had already read a complete expression. Since the layout
+rules close every block at the end of the file, stray tokens are usually reported as `E0004`
+instead.
+
+Remove the token or add the missing opening delimiter.
+"#;
+
+E0007, "Invalid operator fixity", r#"
+An operator expression could not be resolved because of the fixities of its operators. This
+happens when two operators with the same precedence but different associativities are used next
+to each other, when an operator has no fixity, or when a `#[infix]` attribute is malformed.
+
+```gluon
+#[infix(left, 4)]
+let (+++) l r = l + r
+#[infix(right, 4)]
+let (---) l r = l - r
+1 +++ 2 --- 3
+```
+
+Add parentheses to group the operators explicitly, `(1 +++ 2) --- 3`, or give the operators
+fixities that agree with each other.
+"#;
+
+E0008, "Parse error", r#"
+A construct is syntactically invalid in a way which is not covered by any of the more specific
+parse errors. The message describes what was wrong.
+"#;
+
+E0101, "Undefined variable", r#"
+A variable was used that has not been bound.
+
+```gluon
+let x = 1
+y
+```
+
+Check the spelling of the variable, or bind it before it is used. If the variable is defined in
+another module it needs to be imported first, the error suggests which module exports it if it
+is found in one of the loaded modules.
+
+```gluon
+let { map } = import! std.functor
+map
+```
+"#;
+
+E0102, "Not a function", r#"
+A value was called as a function but its type is not a function type. The type checker
+currently reports most of these calls as a type mismatch (`E0106`) which points to the call with
+"Attempted to call a non-function value".
+
+Remove the extra arguments or make sure that the called value is a function.
+"#;
+
+E0103, "Undefined type", r#"
+A type was referred to that is not in scope.
+
+```gluon
+let x : Strin = "abc"
+x
+```
+
+Check the spelling of the type or import it from the module which defines it.
+
+```gluon
+let { Option } = import! std.option
+let x : Option Int = None
+x
+```
+"#;
+
+E0104, "Undefined field", r#"
+A type field was destructured from, or projected out of, a record which does not have a type
+with that name.
+
+```gluon
+let { Test } = { x = 1 }
+1
+```
+
+Check the spelling of the field, the error suggests fields of the record with similar names.
+Accessing a missing value field (`record.y`) is reported as a type mismatch (`E0106`) instead.
+"#;
+
+E0105, "Wrong number of arguments in pattern", r#"
+A constructor in a pattern was given a different number of arguments than the constructor
+was declared with.
+
+```gluon
+type Pair = | Pair Int Int
+match Pair 1 2 with
+| Pair x -> x
+```
+
+Match on every argument of the constructor, using `_` for the ones that are not needed,
+`| Pair x _ -> x`.
+"#;
+
+E0106, "Mismatched types", r#"
+An expression had a different type than the type that was expected at that location.
+
+```gluon
+let x : Int = "abc"
+x
+```
+
+The error shows both the expected and the found type followed by every difference between the
+two types that was found. Change either the expression or the type annotation so that they agree.
+"#;
+
+E0107, "Invalid recursive value", r#"
+A value in a recursive binding group refers to itself (or to a later value in the group) in a
+way that requires the value to exist before it has been constructed.
+
+```gluon
+rec let x = x
+x
+```
+
+Only functions and values which refer to the recursive bindings inside of functions may be
+recursive. Move the reference inside a function or break the recursion.
+"#;
+
+E0108, "Duplicate type definition", r#"
+Two types with the same name were defined in the same binding group.
+
+```gluon
+type Test = Int
+type Test = Float
+1
+```
+
+Rename one of the types.
+"#;
+
+E0109, "Duplicate field", r#"
+A record expression, record type or record pattern specified the same field more than once.
+
+```gluon
+{ x = 1, x = 2 }
+```
+
+Remove or rename one of the fields.
+"#;
+
+E0110, "Invalid projection", r#"
+A field was accessed on a value whose type is not a record.
+
+```gluon
+let x = 1
+x.field
+```
+
+Field access (`.`) only works on records.
+"#;
+
+E0111, "Undefined record", r#"
+No record type was found with the fields that were used to construct or match on a record.
+The type checker currently reports records with unknown fields as a type mismatch (`E0106`).
+
+Check the field names against the type of the record.
+"#;
+
+E0112, "Empty match", r#"
+A `match` expression did not have any alternatives. The parser requires at least one
+alternative so this can only happen in code which is generated by a macro or constructed directly
+through the AST.
+
+Add at least one alternative to the `match` expression.
+"#;
+
+E0113, "Non-exhaustive patterns", r#"
+The alternatives of a `match` expression do not cover every possible value. The error lists the
+patterns which are not matched.
+
+```gluon
+type Option a = | None | Some a
+match None with
+| Some x -> x
+```
+
+Add alternatives for the missing patterns or add a catch-all alternative `| _ -> ...` at the end.
+"#;
+
+E0114, "Type error", r#"
+An error that is not covered by any of the more specific type errors. The message describes what
+was wrong.
+"#;
+
+E0115, "Unable to resolve implicit argument", r#"
+A function takes an implicit argument but no implicit instance with a matching type was found
+in scope.
+
+```gluon
+type Test = | Test
+let f ?x y : [Eq a] -> a -> a = y
+f Test
+```
+
+Bring an implicit instance of the required type into scope, for example by deriving it with
+`#[derive(Eq)]` on the type, by importing the module which defines it or by passing the argument
+explicitly with `f ?eq_Test Test`. Candidates that were considered but whose types did not match are listed in the
+error.
+"#;
+
+E0116, "Constructor returns the wrong type", r#"
+A variant constructor in a generalized algebraic data type returns a type other than the type
+being defined.
+
+```gluon
+type Test a = | Test : a -> Int
+1
+```
+
+The return type of every constructor must be the type which is being defined (`Test a`).
+"#;
+
+E0117, "Inconsistent or-pattern", r#"
+A variable is bound in some, but not all, of the alternatives of an or-pattern.
+
+```gluon
+type Shape = | Circle Int | Square Int | Point
+match Point with
+| Circle r | Point -> r
+| Square s -> s
+```
+
+Every alternative of an or-pattern must bind the same variables with the same types.
+"#;
+
+E0118, "Typed hole", r#"
+The expression contains a typed hole (`_` or `?name`). Holes let the type checker report the
+type that is expected at a location, together with the bindings in scope and the values whose
+types fit in the hole.
+
+```gluon
+let add x y : Int -> Int -> Int = x + y
+add 1 _
+```
+
+Replace the hole with an expression of the reported type.
+"#;
+
+E0119, "Ambiguous implicit argument", r#"
+More than one implicit instance in scope matches the type of an implicit argument, so the
+type checker can't decide which one to use. The error lists the matching candidates.
+
+```gluon
+let f ?x y : [Show a] -> a -> String = x.show y
+#[implicit]
+let show_a : Show Int = { show = \_ -> "a" }
+#[implicit]
+let show_b : Show Int = { show = \_ -> "b" }
+f 1
+```
+
+Remove one of the instances from scope, or pass the argument explicitly with `f ?show_a 1`.
+"#;
+
+E0120, "Infinite loop in implicit resolution", r#"
+Resolving an implicit argument required resolving an implicit argument of the same type again,
+which would never terminate. The error shows the chain of instances which were tried.
+
+```gluon
+#[implicit]
+type Test a = | Test a
+let f ?x : [Test a] -> Test a = x
+let g ?x y : [Test a] -> Int -> Test a = x
+g 1
+```
+
+Here `f` is an implicit instance of `Test a` which itself requires a `Test a`. Pass the argument
+explicitly or restructure the instances so that each step resolves a smaller type.
+"#;
+
+E0201, "Mismatched kinds", r#"
+A type was applied to arguments in a way that does not match its kind.
+
+```gluon
+type Test = Int Int
+1
+```
+
+`Int` has kind `Type` and can't take any arguments. Check the number of arguments given to each
+type.
+"#;
+
+E0202, "Infinite kind", r#"
+Inferring the kind of a type would require the kind to contain itself, for instance when a type
+parameter is applied to itself.
+
+Check the way type parameters are applied to each other.
+"#;
+
+E0301, "Cyclic module dependency", r#"
+A module imports itself, either directly or through other modules.
+
+```text
+// a.glu
+let b = import! b
+b
+
+// b.glu
+let a = import! a
+a
+```
+
+Move the code which is shared between the modules into a separate module.
+"#;
+
+E0302, "Unable to import module", r#"
+An `import!` could not be resolved. Either no module with the given name exists, the macro was
+given something other than a module path, or the imported module itself failed to compile (in
+which case the errors of that module are shown as part of this error).
+
+```gluon
+let x = import! std.does_not_exist
+x
+```
+
+Check the module name against the files in the search path.
+"#;
+
+E0303, "Unable to read module", r#"
+An I/O error occurred while loading the file of an imported module.
+
+Check that the file exists and that it can be read.
+"#;
+
+E0401, "Macro error", r#"
+A macro failed to expand. The message describes what was wrong with the macro invocation.
+
+```gluon
+#[derive(Foo)]
+type Test = | Test
+1
+```
+"#;
+
+W0001, "Unused variable", r#"
+A variable is bound but never used.
+
+```gluon
+let x = 1
+2
+```
+
+Remove the binding, prefix the variable with `_` or add `#[allow(unused_variables)]` to the
+binding.
+"#;
+
+W0002, "Unused import", r#"
+A binding was imported from another module but is never used.
+
+```gluon
+let { map } = import! std.functor
+1
+```
+
+Remove the import or add `#[allow(unused_imports)]` to it.
+"#;
+
+W0003, "Shadowed variable", r#"
+A binding has the same name as another variable which is already in scope, making the outer
+variable inaccessible. Rebinding a name is common in gluon so this lint is only reported inside
+bindings marked with `#[warn(shadowing)]`.
+
+```gluon
+#[warn(shadowing)]
+let f x =
+    let x = x + 1
+    x
+f 1
+```
+
+Rename one of the variables or remove the `#[warn(shadowing)]` attribute.
+"#;
+
+W0004, "Unused type variable", r#"
+A type variable is declared but never used in the type.
+
+```gluon
+type Test a = Int
+1
+```
+
+Remove the type variable or add `#[allow(unused_type_variables)]` to the type.
+"#;
+
+W0005, "Unreachable pattern", r#"
+An alternative in a `match` expression can never be reached since the previous alternatives
+already match everything it matches.
+
+```gluon
+match 1 with
+| _ -> 1
+| 2 -> 2
+```
+
+Remove the unreachable alternative or move it before the alternatives that cover it.
+"#;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_sorted_and_unique() {
+        for pair in ERROR_CODES.windows(2) {
+            assert!(
+                pair[0].code < pair[1].code,
+                "`{}` must come before `{}`",
+                pair[0].code,
+                pair[1].code
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_case() {
+        assert_eq!(lookup("e0101").map(|code| code.code), Some(E0101));
+        assert_eq!(lookup("E9999"), None);
+    }
+}
@@ -9,6 +9,7 @@ use std::sync::Arc;
 use crate::serde::de::{DeserializeSeed, DeserializeState, Deserializer, Error};
 use crate::serde::ser::{SerializeState, Serializer};
 
+use crate::fnv::FnvMap;
 use crate::kind::ArcKind;
 use crate::symbol::Symbol;
 use crate::types::{AliasData, ArcType, Generic, Type, TypeExt, TypePtr};
@@ -32,6 +33,8 @@ impl AsRef<NodeToId> for SeSeed {
 
 pub struct Seed<Id, T> {
     nodes: crate::serialization::NodeMap,
+    symbols: Rc<FnvMap<String, Symbol>>,
+    aliases: Rc<FnvMap<Symbol, Arc<[AliasData<Symbol, ArcType>]>>>,
     _marker: PhantomData<(Id, T)>,
 }
 
@@ -57,15 +60,55 @@ impl<Id, T> Seed<Id, T> {
     pub fn new(nodes: crate::serialization::NodeMap) -> Self {
         Seed {
             nodes,
+            symbols: Default::default(),
+            aliases: Default::default(),
             _marker: PhantomData,
         }
     }
+
+    /// Makes deserialized symbols named as a key in `symbols` resolve to the symbol in `symbols`
+    /// instead of a new symbol. Lets deserialized types refer to the same aliases as types which
+    /// already exist.
+    pub fn set_symbols(&mut self, symbols: FnvMap<String, Symbol>) {
+        self.symbols = Rc::new(symbols);
+    }
+
+    pub(crate) fn symbol(&self, name: &str) -> Symbol {
+        self.symbols
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| Symbol::from(name))
+    }
+}
+
+impl Seed<Symbol, ArcType> {
+    /// Makes deserialized alias groups which have the same name as one of `groups` resolve to
+    /// that group, letting deserialized types unify with the types of already loaded modules.
+    pub fn set_aliases<I>(&mut self, groups: I)
+    where
+        I: IntoIterator<Item = Arc<[AliasData<Symbol, ArcType>]>>,
+    {
+        let mut symbols = FnvMap::default();
+        let mut aliases = FnvMap::default();
+        for group in groups {
+            for alias in &group[..] {
+                symbols.insert(alias.name.to_string(), alias.name.clone());
+            }
+            if let Some(alias) = group.first() {
+                aliases.insert(alias.name.clone(), group.clone());
+            }
+        }
+        self.symbols = Rc::new(symbols);
+        self.aliases = Rc::new(aliases);
+    }
 }
 
 impl<Id, T> Clone for Seed<Id, T> {
     fn clone(&self) -> Self {
         Seed {
             nodes: self.nodes.clone(),
+            symbols: self.symbols.clone(),
+            aliases: self.aliases.clone(),
             _marker: PhantomData,
         }
     }
@@ -88,8 +131,38 @@ where
         + DeserializeState<'de, Seed<Id, T>>,
     T::Generics: Default + Extend<Generic<Id>> + Clone,
 {
-    let seed = SharedSeed::new(seed);
-    DeserializeSeed::deserialize(seed, deserializer).map(|vec: Vec<_>| Arc::from(vec))
+    // Only `Seed<Symbol, ArcType>` can have aliases set so the group is downcast to look it up
+    let resolve = |seed: &Seed<Id, T>, group: Vec<AliasData<Id, T>>| {
+        let existing = (&group as &dyn Any)
+            .downcast_ref::<Vec<AliasData<Symbol, ArcType>>>()
+            .and_then(|group| {
+                let existing = seed.aliases.get(&group.first()?.name)?;
+                if existing.len() == group.len() {
+                    Some(Box::new(existing.clone()) as Box<dyn Any>)
+                } else {
+                    None
+                }
+            })
+            .and_then(|existing| existing.downcast::<Arc<[AliasData<Id, T>]>>().ok());
+        match existing {
+            Some(existing) => *existing,
+            None => Arc::from(group),
+        }
+    };
+    Ok(
+        match Variant::<Vec<AliasData<Id, T>>>::deserialize_state(seed, deserializer)? {
+            Variant::Marked(id, group) => {
+                let group = resolve(seed, group);
+                seed.nodes.insert(id, group.clone());
+                group
+            }
+            Variant::Plain(group) => resolve(seed, group),
+            Variant::Reference(id) => seed
+                .nodes
+                .get::<Arc<[AliasData<Id, T>]>>(id)
+                .ok_or_else(|| D::Error::custom(format_args!("missing id {}", id)))?,
+        },
+    )
 }
 
 impl<'a, T> Shared for &'a T {
@@ -61,12 +61,23 @@ mod serialization {
         where
             D: Deserializer<'de>,
         {
-            use crate::serde::de::DeserializeSeed;
-            use crate::serialization::SharedSeed;
-
-            let seed = SharedSeed::new(seed);
-            seed.deserialize(deserializer)
-                .map(|s: String| Symbol::from(&s[..]))
+            use crate::serde::de::Error;
+            use crate::serialization::{NodeMap, Variant};
+
+            // Share symbols (rather than their names) so that every occurrence of a symbol
+            // deserializes to the same symbol
+            let variant = Variant::<String>::deserialize_state(seed, deserializer)?;
+            Ok(match variant {
+                Variant::Marked(id, name) => {
+                    let symbol = seed.symbol(&name);
+                    AsMut::<NodeMap>::as_mut(seed).insert(id, symbol.clone());
+                    symbol
+                }
+                Variant::Plain(name) => seed.symbol(&name),
+                Variant::Reference(id) => AsMut::<NodeMap>::as_mut(seed)
+                    .get::<Symbol>(id)
+                    .ok_or_else(|| D::Error::custom(format_args!("missing id {}", id)))?,
+            })
         }
     }
 
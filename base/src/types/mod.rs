@@ -103,6 +103,12 @@ pub trait TypeEnv: KindEnv {
 
     /// Returns information about the type `id`
     fn find_type_info(&self, id: &SymbolRef) -> Option<Alias<Symbol, Self::Type>>;
+
+    /// Returns the name and type of each module which has been loaded. Only used to suggest
+    /// imports when reporting errors so environments which do not load modules can leave it empty
+    fn loaded_modules(&self) -> Vec<(String, Self::Type)> {
+        Vec::new()
+    }
 }
 
 impl<'a, T: ?Sized + TypeEnv> TypeEnv for &'a T {
@@ -115,6 +121,10 @@ impl<'a, T: ?Sized + TypeEnv> TypeEnv for &'a T {
     fn find_type_info(&self, id: &SymbolRef) -> Option<Alias<Symbol, Self::Type>> {
         (**self).find_type_info(id)
     }
+
+    fn loaded_modules(&self) -> Vec<(String, Self::Type)> {
+        (**self).loaded_modules()
+    }
 }
 
 impl TypeEnv for EmptyEnv<Symbol> {
@@ -1039,7 +1049,7 @@ where
 
 #[cfg(target_pointer_width = "64")]
 // Safeguard against accidentally growing Type as it is a core type
-const _: [(); 8 * 6] = [(); std::mem::size_of::<Type<Symbol, ArcType>>()];
+const _: [(); 8 * 5] = [(); std::mem::size_of::<Type<Symbol, ArcType>>()];
 
 impl<Id, T> Type<Id, T>
 where
@@ -1582,7 +1592,17 @@ where
     {
         use crate::serialization::SharedSeed;
         let seed = SharedSeed::new(seed);
-        crate::serde::de::DeserializeSeed::deserialize(seed, deserializer).map(ArcType::new)
+        crate::serde::de::DeserializeSeed::deserialize(seed, deserializer).map(
+            |typ: Type<Id, ArcType<Id>>| match &typ {
+                // Implicit aliases are flagged when their group is created which does not happen
+                // when they are deserialized (see `alias_group`)
+                Type::Alias(alias) if alias.is_implicit() => {
+                    let flags = Flags::from_type(&typ) | Flags::HAS_IMPLICIT;
+                    ArcType::with_flags(typ, flags)
+                }
+                _ => ArcType::new(typ),
+            },
+        )
     }
 }
 
@@ -133,7 +133,7 @@ fn example_24_up_to_date() {
 }
 
 fn generate_std_include() {
-    let tuples = WalkDir::new("std")
+    let modules: Vec<_> = WalkDir::new("std")
         .into_iter()
         .filter_map(|entry| entry.ok())
         .filter(|entry| {
@@ -142,13 +142,25 @@ fn generate_std_include() {
         })
         .map(|entry| {
             let module_name = filename_to_module(entry.path().to_str().expect("Invalid path"));
+            (
+                module_name,
+                entry.path().display().to_string().replace('\\', "/"),
+            )
+        })
+        .collect();
+    let tuples = modules
+        .iter()
+        .map(|(module_name, path)| {
             format!(
                 r#"("{}", include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/{}"))),"#,
-                module_name,
-                entry.path().display().to_string().replace('\\', "/")
+                module_name, path
             )
         })
         .format("\n");
+    let names = modules
+        .iter()
+        .map(|(module_name, _)| format!(r#""{}","#, module_name))
+        .format("\n");
 
     let out_file_name = Path::new(&env::var("OUT_DIR").unwrap()).join("std_modules.rs");
     let mut file = File::create(&out_file_name).unwrap();
@@ -168,6 +180,13 @@ static STD_LIBS: &[(&str, &str)] = "#
     )
     .unwrap();
     writeln!(file, "&[{}];", tuples).unwrap();
+    writeln!(
+        file,
+        r#"
+static STD_LIB_NAMES: &[&str] = &[{}];"#,
+        names
+    )
+    .unwrap();
 }
 
 fn main() {
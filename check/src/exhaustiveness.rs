@@ -0,0 +1,674 @@
+//! Checks that `match` expressions cover every possible value of the scrutinee and that every
+//! alternative can actually be reached.
+//!
+//! The algorithm is the "usefulness" algorithm described in
+//! [Warnings for pattern matching](http://moscova.inria.fr/~maranget/papers/warn/index.html). The
+//! AST patterns are first lowered into the simpler `Pat` form where records and tuples are
+//! treated as a single constructor, variants as one constructor per alternative and literals as
+//! constructors of an infinite (and therefore never complete) type. Arrays have one constructor
+//! for each length, all lengths larger than those mentioned by the patterns are matched the same
+//! way and are therefore represented by a single constructor.
+use std::{fmt, sync::Arc};
+
+use crate::base::{
+    ast::{self, Literal, Pattern, SpannedPattern},
+    symbol::Symbol,
+};
+
+/// The constructors (and their number of arguments) of a variant type. `None` if the variant is
+/// open (as polymorphic variants are), in which case the constructors can never be exhaustively
+/// enumerated.
+pub(crate) type Constructors = Option<Arc<[(Symbol, usize)]>>;
+
+#[derive(Clone, Debug)]
+pub(crate) enum Pat {
+    /// A pattern which matches anything, a variable or an error pattern
+    Wild,
+    Variant {
+        name: Symbol,
+        args: Vec<Pat>,
+        constructors: Constructors,
+    },
+    /// Records and tuples, tuples are represented with the fields `_0`, `_1` ...
+    Record {
+        fields: Vec<(Symbol, Pat)>,
+        tuple: bool,
+    },
+    /// An array pattern. If `suffix` is `None` the array must have exactly as many elements as
+    /// `prefix`, otherwise the pattern matches any array which has at least enough elements for
+    /// both the prefix and the suffix.
+    Array {
+        prefix: Vec<Pat>,
+        suffix: Option<Vec<Pat>>,
+    },
+    Literal(Literal),
+    /// Matches if any of the patterns match
+    Or(Vec<Pat>),
+}
+
+impl fmt::Display for Pat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_prec(f, false)
+    }
+}
+
+impl Pat {
+    /// Lowers `pattern`. `constructors` is used to retrieve all constructors of the variant type
+    /// which a constructor pattern belongs to.
+    pub(crate) fn new(
+        pattern: &SpannedPattern<Symbol>,
+        constructors: &mut dyn FnMut(&Symbol) -> Constructors,
+    ) -> Pat {
+        match &pattern.value {
+            Pattern::As(_, pattern) => Pat::new(pattern, constructors),
+            Pattern::Ident(_) | Pattern::Error => Pat::Wild,
+            Pattern::Constructor(id, args) => Pat::Variant {
+                name: id.name.clone(),
+                args: args.iter().map(|arg| Pat::new(arg, constructors)).collect(),
+                constructors: constructors(&id.name),
+            },
+            Pattern::Record { fields, .. } => Pat::Record {
+                fields: ast::pattern_values(fields)
+                    .map(|(name, value)| {
+                        let pat = match value {
+                            Some(value) => Pat::new(value, constructors),
+                            None => Pat::Wild,
+                        };
+                        (name.value.clone(), pat)
+                    })
+                    .collect(),
+                tuple: false,
+            },
+            Pattern::Tuple { elems, .. } => Pat::Record {
+                fields: elems
+                    .iter()
+                    .enumerate()
+                    .map(|(i, elem)| (tuple_field(i), Pat::new(elem, constructors)))
+                    .collect(),
+                tuple: true,
+            },
+            Pattern::Array { elems, rest, .. } => {
+                let mut lower = |elems: &[SpannedPattern<Symbol>]| -> Vec<Pat> {
+                    elems
+                        .iter()
+                        .map(|elem| Pat::new(elem, constructors))
+                        .collect()
+                };
+                match *rest {
+                    Some(rest) => Pat::Array {
+                        prefix: lower(&elems[..rest]),
+                        suffix: Some(lower(&elems[rest + 1..])),
+                    },
+                    None => Pat::Array {
+                        prefix: lower(elems),
+                        suffix: None,
+                    },
+                }
+            }
+            Pattern::Literal(l) => Pat::Literal(l.clone()),
+            Pattern::Or(patterns) => Pat::Or(
+                patterns
+                    .iter()
+                    .map(|pattern| Pat::new(pattern, constructors))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn fmt_prec(&self, f: &mut fmt::Formatter, parens: bool) -> fmt::Result {
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Variant { name, args, .. } => {
+                if args.is_empty() {
+                    return write!(f, "{}", name.declared_name());
+                }
+                if parens {
+                    write!(f, "(")?;
+                }
+                write!(f, "{}", name.declared_name())?;
+                for arg in args {
+                    write!(f, " ")?;
+                    arg.fmt_prec(f, true)?;
+                }
+                if parens {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Pat::Record {
+                fields,
+                tuple: true,
+            } => {
+                write!(f, "(")?;
+                for (i, (_, pat)) in fields.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", pat)?;
+                }
+                write!(f, ")")
+            }
+            Pat::Record {
+                fields,
+                tuple: false,
+            } => {
+                let mut fields = fields.iter().filter(|(_, pat)| match pat {
+                    Pat::Wild => false,
+                    _ => true,
+                });
+                match fields.next() {
+                    Some((name, pat)) => {
+                        write!(f, "{{ {} = {}", name.declared_name(), pat)?;
+                        for (name, pat) in fields {
+                            write!(f, ", {} = {}", name.declared_name(), pat)?;
+                        }
+                        write!(f, " }}")
+                    }
+                    None => write!(f, "_"),
+                }
+            }
+            Pat::Array { prefix, suffix } => {
+                write!(f, "[")?;
+                let rest = suffix.as_ref().map(|_| Pat::Wild);
+                let elems = prefix
+                    .iter()
+                    .chain(rest.as_ref())
+                    .chain(suffix.iter().flatten());
+                for (i, pat) in elems.enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    if i == prefix.len() && suffix.is_some() {
+                        write!(f, "..")?;
+                    } else {
+                        write!(f, "{}", pat)?;
+                    }
+                }
+                write!(f, "]")
+            }
+            Pat::Literal(l) => match l {
+                Literal::Byte(b) => write!(f, "{}b", b),
+                Literal::Int(i) => write!(f, "{}", i),
+                Literal::Float(x) => write!(f, "{}", x),
+                Literal::String(s) => write!(f, "{:?}", s),
+                Literal::Char(c) => write!(f, "{:?}", c),
+            },
+            Pat::Or(pats) => {
+                if parens {
+                    write!(f, "(")?;
+                }
+                for (i, pat) in pats.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, " | ")?;
+                    }
+                    pat.fmt_prec(f, true)?;
+                }
+                if parens {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn is_wild(&self) -> bool {
+        match self {
+            Pat::Wild => true,
+            _ => false,
+        }
+    }
+}
+
+fn tuple_field(i: usize) -> Symbol {
+    Symbol::from(format!("_{}", i))
+}
+
+/// The result of checking the alternatives of a `match` expression
+#[derive(Debug, Default)]
+pub(crate) struct Report {
+    /// Example patterns which are not matched by any alternative
+    pub missing: Vec<Pat>,
+    /// The indexes of the alternatives that can never be reached
+    pub unreachable: Vec<usize>,
+}
+
+/// The maximum number of missing patterns that are reported for a single `match`
+const MAX_MISSING: usize = 3;
+
+/// Checks the (lowered) patterns of each alternative in a `match` expression. Alternatives with a
+/// guard are checked for reachability but, as the guard may fail, are not considered to cover
+/// any values.
+pub(crate) fn check(alternatives: Vec<(Pat, bool)>) -> Report {
+    let mut rows: Vec<Vec<Pat>> = Vec::with_capacity(alternatives.len());
+    let mut report = Report::default();
+    for (i, (pat, guarded)) in alternatives.into_iter().enumerate() {
+        let row = vec![pat];
+        if !is_useful(&rows, &row) {
+            report.unreachable.push(i);
+        }
+        if !guarded {
+            rows.push(row);
+        }
+    }
+
+    report.missing = missing(&rows, 1)
+        .into_iter()
+        .take(MAX_MISSING)
+        .map(|mut witness| witness.pop().expect("Witness"))
+        .collect();
+    report
+}
+
+/// The constructor at the head of a pattern
+#[derive(Clone, Debug)]
+enum Ctor {
+    Variant(Symbol, usize),
+    Record(Vec<Symbol>, bool),
+    /// Arrays with exactly this many elements
+    Array(usize),
+    /// Arrays with at least `min_len` elements. Only the first `prefix` and the last `suffix`
+    /// elements are matched against.
+    ArrayVarLen {
+        prefix: usize,
+        suffix: usize,
+        min_len: usize,
+    },
+    Literal(Literal),
+}
+
+impl Ctor {
+    fn arity(&self) -> usize {
+        match self {
+            Ctor::Variant(_, arity) => *arity,
+            Ctor::Record(fields, _) => fields.len(),
+            Ctor::Array(len) => *len,
+            Ctor::ArrayVarLen { prefix, suffix, .. } => prefix + suffix,
+            Ctor::Literal(_) => 0,
+        }
+    }
+
+    fn construct(&self, mut args: Vec<Pat>, constructors: &Constructors) -> Pat {
+        match self {
+            Ctor::Variant(name, _) => Pat::Variant {
+                name: name.clone(),
+                args,
+                constructors: constructors.clone(),
+            },
+            Ctor::Record(fields, tuple) => Pat::Record {
+                fields: fields.iter().cloned().zip(args.drain(..)).collect(),
+                tuple: *tuple,
+            },
+            Ctor::Array(_) => Pat::Array {
+                prefix: args,
+                suffix: None,
+            },
+            Ctor::ArrayVarLen {
+                prefix,
+                suffix,
+                min_len,
+            } => {
+                let suffix_args = args.split_off(*prefix);
+                // Pad the prefix so that the pattern shows how many elements are required
+                args.extend((prefix + suffix..*min_len).map(|_| Pat::Wild));
+                Pat::Array {
+                    prefix: args,
+                    suffix: Some(suffix_args),
+                }
+            }
+            Ctor::Literal(l) => Pat::Literal(l.clone()),
+        }
+    }
+}
+
+/// The constructors which appear in the first column of `rows`
+struct Column {
+    ctors: Vec<Ctor>,
+    /// The complete set of constructors for the type of the column, if it is known and finite
+    constructors: Constructors,
+}
+
+impl Column {
+    fn new<'a>(heads: impl IntoIterator<Item = &'a Pat>) -> Column {
+        let mut column = Column {
+            ctors: Vec::new(),
+            constructors: None,
+        };
+        let mut record_fields: Option<(Vec<Symbol>, bool)> = None;
+        // The longest fixed length array and the longest prefix and suffix of any array pattern
+        // containing `..`
+        let mut array_lengths: Option<(Option<usize>, usize, usize)> = None;
+        for head in heads {
+            match head {
+                Pat::Wild => (),
+                Pat::Variant {
+                    name,
+                    args,
+                    constructors,
+                } => {
+                    if constructors.is_some() {
+                        column.constructors = constructors.clone();
+                    }
+                    if !column.ctors.iter().any(|ctor| match ctor {
+                        Ctor::Variant(n, _) => n.name_eq(name),
+                        _ => false,
+                    }) {
+                        column.ctors.push(Ctor::Variant(name.clone(), args.len()));
+                    }
+                }
+                Pat::Record { fields, tuple } => {
+                    let (all_fields, _) = record_fields.get_or_insert_with(|| (Vec::new(), *tuple));
+                    for (name, _) in fields {
+                        if !all_fields.iter().any(|field| field.name_eq(name)) {
+                            all_fields.push(name.clone());
+                        }
+                    }
+                }
+                Pat::Array { prefix, suffix } => {
+                    let (max_len, max_prefix, max_suffix) =
+                        array_lengths.get_or_insert((None, 0, 0));
+                    match suffix {
+                        Some(suffix) => {
+                            *max_prefix = (*max_prefix).max(prefix.len());
+                            *max_suffix = (*max_suffix).max(suffix.len());
+                        }
+                        None => *max_len = Some(max_len.unwrap_or(0).max(prefix.len())),
+                    }
+                }
+                Pat::Literal(l) => {
+                    if !column.ctors.iter().any(|ctor| match ctor {
+                        Ctor::Literal(other) => other == l,
+                        _ => false,
+                    }) {
+                        column.ctors.push(Ctor::Literal(l.clone()));
+                    }
+                }
+                Pat::Or(_) => unreachable!("Or-patterns are expanded before building a column"),
+            }
+        }
+        if let Some((fields, tuple)) = record_fields {
+            column.ctors.push(Ctor::Record(fields, tuple));
+        }
+        if let Some((max_len, prefix, suffix)) = array_lengths {
+            // Arrays which are longer than any fixed length pattern and which are long enough for
+            // every prefix and suffix are all matched by the same patterns
+            let min_len = max_len.map_or(0, |len| len + 1).max(prefix + suffix);
+            column.ctors.extend((0..min_len).map(Ctor::Array));
+            column.ctors.push(Ctor::ArrayVarLen {
+                prefix,
+                suffix,
+                min_len,
+            });
+        }
+        column
+    }
+
+    /// Returns all constructors of the columns type if every one of them appears in the column
+    fn complete_signature(&self) -> Option<Vec<Ctor>> {
+        match self.ctors.first() {
+            Some(Ctor::Record(..)) | Some(Ctor::Array(..)) | Some(Ctor::ArrayVarLen { .. }) => {
+                Some(self.ctors.clone())
+            }
+            Some(Ctor::Variant(..)) => {
+                let constructors = self.constructors.as_ref()?;
+                if constructors.iter().all(|(name, _)| {
+                    self.ctors.iter().any(|ctor| match ctor {
+                        Ctor::Variant(n, _) => n.name_eq(name),
+                        _ => false,
+                    })
+                }) {
+                    Some(
+                        constructors
+                            .iter()
+                            .map(|(name, arity)| Ctor::Variant(name.clone(), *arity))
+                            .collect(),
+                    )
+                } else {
+                    None
+                }
+            }
+            Some(Ctor::Literal(_)) | None => None,
+        }
+    }
+
+    /// Returns the constructors which do not appear in the column (if they are known)
+    fn missing_ctors(&self) -> Option<Vec<Ctor>> {
+        match self.ctors.first() {
+            Some(Ctor::Variant(..)) => {
+                let constructors = self.constructors.as_ref()?;
+                Some(
+                    constructors
+                        .iter()
+                        .filter(|(name, _)| {
+                            !self.ctors.iter().any(|ctor| match ctor {
+                                Ctor::Variant(n, _) => n.name_eq(name),
+                                _ => false,
+                            })
+                        })
+                        .map(|(name, arity)| Ctor::Variant(name.clone(), *arity))
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the arguments of `head` if it is matched by `ctor` (with wildcards for any `Wild`
+/// pattern)
+fn specialize_head(head: &Pat, ctor: &Ctor) -> Option<Vec<Pat>> {
+    match (head, ctor) {
+        (Pat::Wild, _) => Some(vec![Pat::Wild; ctor.arity()]),
+        (Pat::Variant { name, args, .. }, Ctor::Variant(ctor_name, _)) => {
+            if name.name_eq(ctor_name) {
+                Some(args.clone())
+            } else {
+                None
+            }
+        }
+        (Pat::Record { fields, .. }, Ctor::Record(ctor_fields, _)) => Some(
+            ctor_fields
+                .iter()
+                .map(|ctor_field| {
+                    fields
+                        .iter()
+                        .find(|(name, _)| name.name_eq(ctor_field))
+                        .map(|(_, pat)| pat.clone())
+                        .unwrap_or(Pat::Wild)
+                })
+                .collect(),
+        ),
+        (
+            Pat::Array {
+                prefix,
+                suffix: None,
+            },
+            Ctor::Array(len),
+        ) => {
+            if prefix.len() == *len {
+                Some(prefix.clone())
+            } else {
+                None
+            }
+        }
+        (
+            Pat::Array {
+                prefix,
+                suffix: Some(suffix),
+            },
+            Ctor::Array(len),
+        ) => {
+            let wild_len = len.checked_sub(prefix.len() + suffix.len())?;
+            Some(
+                prefix
+                    .iter()
+                    .cloned()
+                    .chain((0..wild_len).map(|_| Pat::Wild))
+                    .chain(suffix.iter().cloned())
+                    .collect(),
+            )
+        }
+        (
+            Pat::Array {
+                prefix,
+                suffix: Some(suffix),
+            },
+            Ctor::ArrayVarLen {
+                prefix: ctor_prefix,
+                suffix: ctor_suffix,
+                ..
+            },
+        ) => Some(
+            prefix
+                .iter()
+                .cloned()
+                .chain((prefix.len()..*ctor_prefix).map(|_| Pat::Wild))
+                .chain((suffix.len()..*ctor_suffix).map(|_| Pat::Wild))
+                .chain(suffix.iter().cloned())
+                .collect(),
+        ),
+        (Pat::Literal(l), Ctor::Literal(r)) => {
+            if l == r {
+                Some(Vec::new())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn specialize_row(row: &[Pat], ctor: &Ctor) -> Option<Vec<Pat>> {
+    specialize_head(&row[0], ctor).map(|mut args| {
+        args.extend(row[1..].iter().cloned());
+        args
+    })
+}
+
+fn specialize(rows: &[Vec<Pat>], ctor: &Ctor) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter_map(|row| specialize_row(row, ctor))
+        .collect()
+}
+
+/// The rows which match any constructor that does not appear in the first column
+fn default_rows(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter(|row| row[0].is_wild())
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// Replaces every row starting with an or-pattern with one row for each of its alternatives
+fn expand_or_rows(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    let mut expanded = Vec::with_capacity(rows.len());
+    for row in rows {
+        expand_or_row(row, &mut expanded);
+    }
+    expanded
+}
+
+fn expand_or_row(row: &[Pat], expanded: &mut Vec<Vec<Pat>>) {
+    match row.first() {
+        Some(Pat::Or(pats)) => {
+            for pat in pats {
+                let mut new_row = vec![pat.clone()];
+                new_row.extend(row[1..].iter().cloned());
+                expand_or_row(&new_row, expanded);
+            }
+        }
+        _ => expanded.push(row.to_vec()),
+    }
+}
+
+/// Returns true if there are values which are matched by `row` but not by any of `rows`
+fn is_useful(rows: &[Vec<Pat>], row: &[Pat]) -> bool {
+    if row.is_empty() {
+        return rows.is_empty();
+    }
+    if let Pat::Or(_) = row[0] {
+        return expand_or_rows(&[row.to_vec()])
+            .iter()
+            .any(|row| is_useful(rows, row));
+    }
+    let rows = &expand_or_rows(rows)[..];
+    let column = Column::new(rows.iter().map(|r| &r[0]).chain(Some(&row[0])));
+    match &row[0] {
+        Pat::Wild => match column.complete_signature() {
+            Some(signature) => signature.iter().any(|ctor| {
+                is_useful(
+                    &specialize(rows, ctor),
+                    &specialize_row(row, ctor).expect("Wildcard"),
+                )
+            }),
+            None => is_useful(&default_rows(rows), &row[1..]),
+        },
+        _ => {
+            // Use the merged constructors from the column so that record patterns are
+            // specialized to the same set of fields. An array pattern containing `..` is matched
+            // by every constructor of a sufficient length.
+            let ctors = column
+                .complete_signature()
+                .unwrap_or_else(|| column.ctors.clone());
+            ctors.iter().any(|ctor| match specialize_row(row, ctor) {
+                Some(specialized_row) => is_useful(&specialize(rows, ctor), &specialized_row),
+                None => false,
+            })
+        }
+    }
+}
+
+/// Returns vectors of `arity` patterns which are not matched by any of the `rows`
+fn missing(rows: &[Vec<Pat>], arity: usize) -> Vec<Vec<Pat>> {
+    if arity == 0 {
+        return if rows.is_empty() {
+            vec![Vec::new()]
+        } else {
+            Vec::new()
+        };
+    }
+    let rows = &expand_or_rows(rows)[..];
+    let column = Column::new(rows.iter().map(|r| &r[0]));
+    match column.complete_signature() {
+        Some(signature) => {
+            let mut witnesses = Vec::new();
+            for ctor in &signature {
+                let ctor_arity = ctor.arity();
+                for mut witness in missing(&specialize(rows, ctor), ctor_arity + arity - 1) {
+                    let rest = witness.split_off(ctor_arity);
+                    let mut new_witness = vec![ctor.construct(witness, &column.constructors)];
+                    new_witness.extend(rest);
+                    witnesses.push(new_witness);
+                    if witnesses.len() >= MAX_MISSING {
+                        return witnesses;
+                    }
+                }
+            }
+            witnesses
+        }
+        None => {
+            let rest = missing(&default_rows(rows), arity - 1);
+            if rest.is_empty() {
+                return rest;
+            }
+            let heads = match column.missing_ctors() {
+                Some(ref ctors) if !ctors.is_empty() => ctors
+                    .iter()
+                    .map(|ctor| ctor.construct(vec![Pat::Wild; ctor.arity()], &column.constructors))
+                    .collect(),
+                _ => vec![Pat::Wild],
+            };
+            let mut witnesses = Vec::new();
+            for head in heads {
+                for witness in &rest {
+                    let mut new_witness = vec![head.clone()];
+                    new_witness.extend(witness.iter().cloned());
+                    witnesses.push(new_witness);
+                    if witnesses.len() >= MAX_MISSING {
+                        return witnesses;
+                    }
+                }
+            }
+            witnesses
+        }
+    }
+}
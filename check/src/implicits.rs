@@ -65,7 +65,7 @@ fn split_type<'a>(
     symbol.map(|s| (s, None))
 }
 
-type ImplicitBinding = (Rc<[TypedIdent<Symbol, RcType>]>, RcType);
+pub(crate) type ImplicitBinding = (Rc<[TypedIdent<Symbol, RcType>]>, RcType);
 
 #[derive(Clone)]
 pub struct Partition<T> {
@@ -163,7 +163,7 @@ impl<T> Partition<T> {
         }
     }
 
-    fn get_candidates<'a>(
+    pub(crate) fn get_candidates<'a>(
         &'a self,
         subs: &Substitution<RcType>,
         typ: &RcType,
@@ -246,10 +246,14 @@ impl ImplicitBindings {
 
 type Result<T> = ::std::result::Result<T, Error<RcType>>;
 
+const MAX_REJECTED_CANDIDATES: usize = 5;
+
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Functor)]
 pub struct Error<T> {
     pub kind: ErrorKind<T>,
     pub reason: rpds::ListSync<T>,
+    /// The candidates which were tried but did not match the implicit parameter
+    pub rejected: Vec<RejectedImplicit<T>>,
 }
 
 impl<I: fmt::Display + Clone> fmt::Display for Error<I> {
@@ -260,7 +264,9 @@ impl<I: fmt::Display + Clone> fmt::Display for Error<I> {
 
 impl<I: fmt::Display + Clone> AsDiagnostic for Error<I> {
     fn as_diagnostic(&self, _map: &base::source::CodeMap) -> Diagnostic<FileId> {
-        let mut diagnostic = Diagnostic::error().with_message(self.to_string());
+        let mut diagnostic = Diagnostic::error()
+            .with_message(self.to_string())
+            .with_code(self.kind.code());
 
         diagnostic.labels.extend(self.reason.iter().map(|reason| {
             Label::secondary(FileId::default(), Default::default()..Default::default())
@@ -270,6 +276,15 @@ impl<I: fmt::Display + Clone> AsDiagnostic for Error<I> {
                 ))
         }));
 
+        diagnostic
+            .notes
+            .extend(self.rejected.iter().map(|rejected| {
+                format!(
+                    "Rejected the candidate `{} : {}`\n{}",
+                    rejected.path, rejected.typ, rejected.reason
+                )
+            }));
+
         diagnostic
     }
 }
@@ -280,6 +295,15 @@ pub struct AmbiguityEntry<T> {
     pub typ: T,
 }
 
+/// An implicit instance which was rejected while resolving an implicit parameter
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Functor)]
+pub struct RejectedImplicit<T> {
+    pub path: String,
+    pub typ: T,
+    /// The unification errors which caused the candidate to be rejected
+    pub reason: String,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Hash, Functor)]
 pub enum ErrorKind<T> {
     /// An implicit parameter were not possible to resolve
@@ -288,6 +312,18 @@ pub enum ErrorKind<T> {
     AmbiguousImplicit(Vec<AmbiguityEntry<T>>),
 }
 
+impl<T> ErrorKind<T> {
+    /// Returns the stable code which identifies this kind of error
+    pub fn code(&self) -> &'static str {
+        use base::error_code::*;
+        match self {
+            ErrorKind::MissingImplicit(_) => E0115,
+            ErrorKind::AmbiguousImplicit(_) => E0119,
+            ErrorKind::LoopInImplicitResolution(_) => E0120,
+        }
+    }
+}
+
 impl<I: fmt::Display> fmt::Display for ErrorKind<I> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use self::ErrorKind::*;
@@ -392,6 +428,7 @@ impl<'a, 'b, 'ast> ResolveImplicitsVisitor<'a, 'b, 'ast> {
                                 reason: to_resolve
                                     .first()
                                     .map_or_else(Default::default, |demand| demand.reason.clone()),
+                                rejected: Vec::new(),
                             })
                             .into(),
                         });
@@ -410,6 +447,38 @@ impl<'a, 'b, 'ast> ResolveImplicitsVisitor<'a, 'b, 'ast> {
         }
     }
 
+    /// Returns the candidates which were tried when resolving `typ`, along with the unification
+    /// errors which caused each of them to be rejected
+    fn rejected_candidates(
+        &mut self,
+        implicit_bindings: &Partition<ImplicitBinding>,
+        typ: &RcType,
+    ) -> Vec<RejectedImplicit<RcType>> {
+        implicit_bindings
+            .get_candidates(&self.tc.subs, typ)
+            .rev()
+            .filter_map(|(path, binding_type)| {
+                let snapshot = self.tc.subs.snapshot();
+
+                let instantiated_type = self.tc.instantiate_generics(binding_type);
+                let mut iter = types::implicit_arg_iter(&instantiated_type);
+                iter.by_ref().for_each(drop);
+
+                let state = unify_type::State::new(&self.tc.environment, &self.tc.subs);
+                let result = unify_type::subsumes(&self.tc.subs, state, typ, iter.typ);
+                self.tc.subs.rollback_to(snapshot);
+
+                let (_, errors) = result.err()?;
+                Some(RejectedImplicit {
+                    path: path.iter().map(|id| &id.name).format(".").to_string(),
+                    typ: binding_type.clone(),
+                    reason: errors.iter().format("\n").to_string(),
+                })
+            })
+            .take(MAX_REJECTED_CANDIDATES)
+            .collect()
+    }
+
     fn resolve_implicit_application(
         &mut self,
         implicit_bindings: &Partition<ImplicitBinding>,
@@ -580,6 +649,7 @@ impl<'a, 'b, 'ast> ResolveImplicitsVisitor<'a, 'b, 'ast> {
                                     .format(".")
                                     .to_string()]),
                                 reason: demand.reason.clone(),
+                                rejected: Vec::new(),
                             });
                         }
                         // Update the demands with to these new, smaller demands
@@ -614,12 +684,14 @@ impl<'a, 'b, 'ast> ResolveImplicitsVisitor<'a, 'b, 'ast> {
                     Err(Error {
                         kind: ErrorKind::AmbiguousImplicit(additional_candidates),
                         reason: demand.reason.clone(),
+                        rejected: Vec::new(),
                     })
                 }
             }
             None => Err(Error {
                 kind: ErrorKind::MissingImplicit(demand.constraint.clone()),
                 reason: demand.reason.clone(),
+                rejected: self.rejected_candidates(implicit_bindings, &demand.constraint),
             }),
         }
     }
@@ -509,6 +509,18 @@ where
     }
 }
 
+/// Returns the stable code which identifies the kind of `error`
+pub fn kind_error_code<I, T>(error: &Error<I, T>) -> &'static str {
+    use crate::base::error_code::*;
+    use crate::unify::Error::*;
+    match *error {
+        TypeMismatch(..) => E0201,
+        Substitution(_) => E0202,
+        Other(KindError::UndefinedType(_)) => E0103,
+        Other(KindError::UndefinedField(..)) => E0104,
+    }
+}
+
 impl Substitutable for ArcKind {
     type Variable = u32;
 
@@ -0,0 +1,498 @@
+//! Lints which are reported as warnings instead of stopping compilation, such as unused or
+//! shadowed bindings.
+//!
+//! Warnings can be silenced with an `#[allow(...)]` attribute on a `let` or `type` binding which
+//! names either the lint itself (`unused_variables`, `unreachable_patterns`, ...), the `unused`
+//! group or `warnings` for every lint.
+//!
+//! Rebinding a name with `let` is idiomatic so the `shadowing` lint is off by default, it is
+//! enabled for a binding and everything inside it with `#[warn(shadowing)]`.
+use std::fmt;
+
+use codespan_reporting::diagnostic::Diagnostic;
+
+use crate::base::{
+    ast::{
+        self, Argument, AstType, Expr, Pattern, PatternField, SpannedExpr, SpannedIdent,
+        SpannedPattern, Visitor,
+    },
+    error::AsDiagnostic,
+    fnv::{FnvMap, FnvSet},
+    metadata::BaseMetadata,
+    pos::{self, BytePos, HasSpan, Span, Spanned},
+    source::FileId,
+    symbol::Symbol,
+    types::{Generic, Type},
+};
+
+/// The lints which can be reported
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    UnusedImports,
+    Shadowing,
+    UnusedTypeVariables,
+    UnreachablePatterns,
+}
+
+impl Lint {
+    /// The name which refers to this lint in `#[allow(...)]` attributes
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnusedImports => "unused_imports",
+            Lint::Shadowing => "shadowing",
+            Lint::UnusedTypeVariables => "unused_type_variables",
+            Lint::UnreachablePatterns => "unreachable_patterns",
+        }
+    }
+
+    /// Returns true if `name` refers to this lint, either directly or through the `unused` or
+    /// `warnings` groups
+    pub fn is_named(self, name: &str) -> bool {
+        name == self.name()
+            || name == "warnings"
+            || (name == "unused"
+                && match self {
+                    Lint::UnusedVariables | Lint::UnusedImports | Lint::UnusedTypeVariables => true,
+                    Lint::Shadowing | Lint::UnreachablePatterns => false,
+                })
+    }
+
+    /// Returns true if the lint is reported when no `#[allow(...)]` or `#[warn(...)]` attribute
+    /// names it
+    pub fn is_enabled_by_default(self) -> bool {
+        match self {
+            Lint::Shadowing => false,
+            Lint::UnusedVariables
+            | Lint::UnusedImports
+            | Lint::UnusedTypeVariables
+            | Lint::UnreachablePatterns => true,
+        }
+    }
+}
+
+/// Type representing a single warning found when checking an expression
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum Warning<I> {
+    /// Variable is bound but never used
+    UnusedVariable(I),
+    /// Binding was imported from another module but is never used
+    UnusedImport(I),
+    /// Variable has the same name as another variable which is already in scope
+    ShadowedVariable(I),
+    /// Type variable is declared but never used in the type
+    UnusedTypeVariable(I),
+    /// An alternative can never be reached since the previous alternatives already match
+    /// everything it matches
+    UnreachablePattern,
+}
+
+impl<I> Warning<I> {
+    pub fn lint(&self) -> Lint {
+        match self {
+            Warning::UnusedVariable(_) => Lint::UnusedVariables,
+            Warning::UnusedImport(_) => Lint::UnusedImports,
+            Warning::ShadowedVariable(_) => Lint::Shadowing,
+            Warning::UnusedTypeVariable(_) => Lint::UnusedTypeVariables,
+            Warning::UnreachablePattern => Lint::UnreachablePatterns,
+        }
+    }
+
+    /// Returns the stable code which identifies this kind of warning
+    pub fn code(&self) -> &'static str {
+        use crate::base::error_code::*;
+        match self {
+            Warning::UnusedVariable(_) => W0001,
+            Warning::UnusedImport(_) => W0002,
+            Warning::ShadowedVariable(_) => W0003,
+            Warning::UnusedTypeVariable(_) => W0004,
+            Warning::UnreachablePattern => W0005,
+        }
+    }
+}
+
+impl<I> fmt::Display for Warning<I>
+where
+    I: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::UnusedVariable(name) => write!(f, "Unused variable `{}`", name),
+            Warning::UnusedImport(name) => write!(f, "Unused import `{}`", name),
+            Warning::ShadowedVariable(name) => write!(
+                f,
+                "`{}` shadows a variable with the same name which is already in scope",
+                name
+            ),
+            Warning::UnusedTypeVariable(name) => write!(f, "Unused type variable `{}`", name),
+            Warning::UnreachablePattern => write!(f, "Unreachable pattern"),
+        }
+    }
+}
+
+impl<I> AsDiagnostic for Warning<I>
+where
+    I: fmt::Display,
+{
+    fn as_diagnostic(&self, _map: &crate::base::source::CodeMap) -> Diagnostic<FileId> {
+        Diagnostic::warning()
+            .with_message(self.to_string())
+            .with_code(self.code())
+            .with_notes(vec![format!(
+                "add `#[allow({})]` to the binding to silence this warning",
+                self.lint().name()
+            )])
+    }
+}
+
+pub type SpannedWarning<I> = Spanned<Warning<I>, BytePos>;
+
+/// Runs all lints on `expr`, adding the warnings found to `warnings`. Afterwards every warning in
+/// `warnings` which is silenced by an `#[allow(...)]` attribute, or which is disabled by default
+/// and not enabled by a `#[warn(...)]` attribute, is removed.
+pub fn lint(expr: &SpannedExpr<'_, Symbol>, warnings: &mut Vec<SpannedWarning<Symbol>>) {
+    let mut visitor = LintVisitor {
+        warnings,
+        bindings: Vec::new(),
+        used: FnvSet::default(),
+        scope: Vec::new(),
+        shadowable: FnvMap::default(),
+        levels: Vec::new(),
+    };
+    visitor.visit_expr(expr);
+
+    let LintVisitor {
+        warnings,
+        bindings,
+        used,
+        levels,
+        ..
+    } = visitor;
+
+    warnings.extend(
+        bindings
+            .into_iter()
+            .filter(|binding| !used.contains(&binding.name))
+            .map(|binding| {
+                let name = Symbol::from(binding.name.declared_name());
+                pos::spanned(
+                    binding.span,
+                    match binding.kind {
+                        BindingKind::Variable => Warning::UnusedVariable(name),
+                        BindingKind::Import => Warning::UnusedImport(name),
+                    },
+                )
+            }),
+    );
+
+    warnings.retain(|warning| {
+        let lint = warning.value.lint();
+        // The innermost attribute which names the lint decides whether it is reported
+        levels
+            .iter()
+            .filter(|(span, _, names)| {
+                span.contains(warning.span) && names.iter().any(|name| lint.is_named(name))
+            })
+            .min_by_key(|(span, _, _)| span.end() - span.start())
+            .map_or(lint.is_enabled_by_default(), |(_, level, _)| {
+                *level == LintLevel::Warn
+            })
+    });
+    warnings.sort_by_key(|warning| warning.span.start());
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LintLevel {
+    Allow,
+    Warn,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BindingKind {
+    Variable,
+    Import,
+}
+
+struct Binding {
+    name: Symbol,
+    span: Span<BytePos>,
+    kind: BindingKind,
+}
+
+struct LintVisitor<'w> {
+    warnings: &'w mut Vec<SpannedWarning<Symbol>>,
+    /// Every binding declared in the expression
+    bindings: Vec<Binding>,
+    used: FnvSet<Symbol>,
+    /// The bindings which are currently in scope
+    scope: Vec<(Symbol, BindingKind)>,
+    /// How many variables with each name are in `scope`, used to detect shadowing
+    shadowable: FnvMap<String, usize>,
+    /// Regions of the source which are covered by `#[allow(...)]` or `#[warn(...)]` attributes
+    levels: Vec<(Span<BytePos>, LintLevel, Vec<String>)>,
+}
+
+fn is_ignored(name: &Symbol) -> bool {
+    name.declared_name().starts_with('_')
+}
+
+/// Returns true if `expr` is an `import!` of another module
+fn is_import(expr: &SpannedExpr<'_, Symbol>) -> bool {
+    match &expr.value {
+        Expr::MacroExpansion { replacement, .. } => is_import(replacement),
+        Expr::Ident(id) => id.name.is_global(),
+        _ => false,
+    }
+}
+
+impl LintVisitor<'_> {
+    fn lint_attributes(&mut self, span: Span<BytePos>, metadata: &BaseMetadata) {
+        for (attribute_name, level) in &[("allow", LintLevel::Allow), ("warn", LintLevel::Warn)] {
+            let names: Vec<_> = metadata
+                .attributes()
+                .filter(|attribute| attribute.name == *attribute_name)
+                .filter_map(|attribute| attribute.arguments.as_ref())
+                .flat_map(|arguments| arguments.split(','))
+                .map(|name| name.trim().to_string())
+                .collect();
+            if !names.is_empty() {
+                self.levels.push((span, *level, names));
+            }
+        }
+    }
+
+    fn in_scope<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let len = self.scope.len();
+        let result = f(self);
+        for (name, kind) in self.scope.drain(len..) {
+            if kind == BindingKind::Variable {
+                if let Some(count) = self.shadowable.get_mut(name.declared_name()) {
+                    *count -= 1;
+                }
+            }
+        }
+        result
+    }
+
+    fn declare(&mut self, name: &Symbol, span: Span<BytePos>, kind: BindingKind) {
+        if is_ignored(name) {
+            return;
+        }
+        if kind == BindingKind::Variable {
+            let count = self
+                .shadowable
+                .entry(name.declared_name().to_string())
+                .or_insert(0);
+            if *count > 0 {
+                self.warnings.push(pos::spanned(
+                    span,
+                    Warning::ShadowedVariable(Symbol::from(name.declared_name())),
+                ));
+            }
+            *count += 1;
+        }
+        self.scope.push((name.clone(), kind));
+        self.bindings.push(Binding {
+            name: name.clone(),
+            span,
+            kind,
+        });
+    }
+
+    fn declare_args(&mut self, args: &[Argument<SpannedIdent<Symbol>>]) {
+        for arg in args {
+            self.declare(&arg.name.value.name, arg.name.span, BindingKind::Variable);
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &SpannedPattern<'_, Symbol>, kind: BindingKind) {
+        match &pattern.value {
+            Pattern::Ident(id) => self.declare(&id.name, pattern.span, kind),
+            Pattern::As(id, pattern) => {
+                self.declare(&id.value, id.span, kind);
+                self.declare_pattern(pattern, kind);
+            }
+            Pattern::Record { fields, .. } => {
+                for field in &**fields {
+                    match field {
+                        PatternField::Value { name, value } => match value {
+                            Some(pattern) => self.declare_pattern(pattern, kind),
+                            None => self.declare(&name.value, name.span, kind),
+                        },
+                        PatternField::Type { .. } => (),
+                    }
+                }
+            }
+            Pattern::Tuple { elems: args, .. }
+            | Pattern::Array { elems: args, .. }
+            | Pattern::Constructor(_, args) => {
+                for arg in &**args {
+                    self.declare_pattern(arg, kind);
+                }
+            }
+            // Every alternative binds the same variables
+            Pattern::Or(patterns) => self.declare_pattern(&patterns[0], kind),
+            Pattern::Literal(_) | Pattern::Error => (),
+        }
+    }
+
+    fn check_type_variables(&mut self, typ: &AstType<'_, Symbol>) {
+        struct ForallVisitor<'a, 'w> {
+            lint: &'a mut LintVisitor<'w>,
+        }
+
+        impl<'a, 'ast> Visitor<'a, 'ast> for ForallVisitor<'_, '_> {
+            type Ident = Symbol;
+
+            fn visit_ast_type(&mut self, typ: &'a AstType<'ast, Symbol>) {
+                if let Type::Forall(params, body) = &**typ {
+                    self.lint.check_unused_params(typ.span(), params, body);
+                }
+                ast::walk_ast_type(self, typ);
+            }
+        }
+
+        ForallVisitor { lint: self }.visit_ast_type(typ);
+    }
+
+    fn check_unused_params(
+        &mut self,
+        span: Span<BytePos>,
+        params: &[Generic<Symbol>],
+        typ: &AstType<'_, Symbol>,
+    ) {
+        struct GenericVisitor<'a> {
+            used: &'a mut FnvSet<String>,
+        }
+
+        impl<'a, 'ast> Visitor<'a, 'ast> for GenericVisitor<'_> {
+            type Ident = Symbol;
+
+            fn visit_ast_type(&mut self, typ: &'a AstType<'ast, Symbol>) {
+                if let Type::Generic(gen) = &**typ {
+                    self.used.insert(gen.id.declared_name().to_string());
+                }
+                ast::walk_ast_type(self, typ);
+            }
+        }
+
+        let mut used = FnvSet::default();
+        GenericVisitor { used: &mut used }.visit_ast_type(typ);
+
+        for param in params {
+            if !is_ignored(&param.id) && !used.contains(param.id.declared_name()) {
+                self.warnings.push(pos::spanned(
+                    span,
+                    Warning::UnusedTypeVariable(Symbol::from(param.id.declared_name())),
+                ));
+            }
+        }
+    }
+}
+
+impl<'a, 'ast> Visitor<'a, 'ast> for LintVisitor<'_> {
+    type Ident = Symbol;
+
+    fn visit_expr(&mut self, expr: &'a SpannedExpr<'ast, Symbol>) {
+        match &expr.value {
+            Expr::Ident(id) => {
+                self.used.insert(id.name.clone());
+            }
+            Expr::Infix { op, .. } => {
+                self.used.insert(op.value.name.clone());
+                ast::walk_expr(self, expr);
+            }
+            Expr::Record { exprs, .. } => {
+                for field in &**exprs {
+                    if field.value.is_none() {
+                        self.used.insert(field.name.value.clone());
+                    }
+                }
+                ast::walk_expr(self, expr);
+            }
+            Expr::LetBindings(binds, body) => self.in_scope(|self_| {
+                for bind in binds {
+                    self_.lint_attributes(
+                        Span::new(bind.name.span.start(), bind.expr.span.end()),
+                        &bind.metadata,
+                    );
+                }
+
+                let declare = |self_: &mut Self, bind: &ast::ValueBinding<'_, Symbol>| {
+                    let kind = if is_import(&bind.expr) {
+                        BindingKind::Import
+                    } else {
+                        BindingKind::Variable
+                    };
+                    self_.declare_pattern(&bind.name, kind);
+                };
+
+                if binds.is_recursive() {
+                    for bind in binds {
+                        declare(self_, bind);
+                    }
+                }
+                for bind in binds {
+                    self_.in_scope(|self_| {
+                        self_.declare_args(&bind.args);
+                        self_.visit_expr(&bind.expr);
+                    });
+                    if let Some(typ) = &bind.typ {
+                        self_.check_type_variables(typ);
+                    }
+                }
+                if !binds.is_recursive() {
+                    for bind in binds {
+                        declare(self_, bind);
+                    }
+                }
+
+                self_.visit_expr(body);
+            }),
+            Expr::TypeBindings(binds, body) => {
+                for bind in &**binds {
+                    self.lint_attributes(bind.span(), &bind.metadata);
+
+                    let alias = &bind.alias.value;
+                    self.check_unused_params(
+                        bind.name.span,
+                        alias.params(),
+                        alias.unresolved_type(),
+                    );
+                    self.check_type_variables(alias.unresolved_type());
+                }
+                self.visit_expr(body);
+            }
+            Expr::Lambda(lambda) => self.in_scope(|self_| {
+                self_.declare_args(&lambda.args);
+                self_.visit_expr(&lambda.body);
+            }),
+            Expr::Match(scrutinee, alts) => {
+                self.visit_expr(scrutinee);
+                for alt in &**alts {
+                    self.in_scope(|self_| {
+                        self_.declare_pattern(&alt.pattern, BindingKind::Variable);
+                        if let Some(guard) = &alt.guard {
+                            self_.visit_expr(guard);
+                        }
+                        self_.visit_expr(&alt.expr);
+                    });
+                }
+            }
+            Expr::Do(do_expr) => {
+                self.visit_expr(&do_expr.bound);
+                self.in_scope(|self_| {
+                    if let Some(id) = &do_expr.id {
+                        self_.declare_pattern(id, BindingKind::Variable);
+                    }
+                    self_.visit_expr(&do_expr.body);
+                });
+                if let Some(flat_map_id) = &do_expr.flat_map_id {
+                    self.visit_expr(flat_map_id);
+                }
+            }
+            _ => ast::walk_expr(self, expr),
+        }
+    }
+}
@@ -170,8 +170,10 @@ pub fn metadata(
                 }
                 Pattern::Constructor(..)
                 | Pattern::Tuple { .. }
+                | Pattern::Array { .. }
                 | Pattern::Record { .. }
                 | Pattern::Literal(_)
+                | Pattern::Or(_)
                 | Pattern::Error => self.new_pattern(metadata, &bind.name),
             }
         }
@@ -229,8 +231,10 @@ pub fn metadata(
                     self.new_pattern(metadata, pat);
                 }
                 Pattern::Tuple { .. }
+                | Pattern::Array { .. }
                 | Pattern::Constructor(..)
                 | Pattern::Literal(_)
+                | Pattern::Or(_)
                 | Pattern::Error => (),
             }
         }
@@ -36,6 +36,9 @@ pub fn rename<'s, 'ast>(
         env: Environment,
         ast_arena: ast::ArenaRef<'s, 'ast, Symbol>,
         hole: ArcType,
+        /// The variables bound by the first alternative of the or-pattern which is currently being
+        /// renamed
+        or_pattern_names: Option<Vec<(Symbol, Symbol, Span<BytePos>)>>,
     }
 
     impl<'a, 'b, 's, 'ast> RenameVisitor<'a, 'b, 's, 'ast> {
@@ -70,7 +73,7 @@ pub fn rename<'s, 'ast>(
                     id.value = new_name;
                     self.new_pattern(pat)
                 }
-                Pattern::Tuple { ref mut elems, .. } => {
+                Pattern::Tuple { ref mut elems, .. } | Pattern::Array { ref mut elems, .. } => {
                     for elem in &mut **elems {
                         self.new_pattern(elem);
                     }
@@ -80,6 +83,29 @@ pub fn rename<'s, 'ast>(
                         self.new_pattern(arg);
                     }
                 }
+                Pattern::Or(ref mut patterns) => {
+                    // Each alternative binds its own symbols but only those of the first
+                    // alternative are left in scope for the guard and expression of the match
+                    let (first, rest) = patterns
+                        .split_first_mut()
+                        .expect("Or-patterns have at least two alternatives");
+
+                    let outer_names = self.or_pattern_names.replace(Vec::new());
+                    self.new_pattern(first);
+                    let first_names = self.or_pattern_names.take().unwrap_or_default();
+
+                    for pattern in rest {
+                        self.new_pattern(pattern);
+                    }
+
+                    self.or_pattern_names = outer_names;
+                    for (id, new_id, span) in first_names {
+                        self.env.stack.insert(id.clone(), (new_id.clone(), span));
+                        if let Some(names) = &mut self.or_pattern_names {
+                            names.push((id, new_id, span));
+                        }
+                    }
+                }
                 Pattern::Literal(_) | Pattern::Error => (),
             }
         }
@@ -114,6 +140,10 @@ pub fn rename<'s, 'ast>(
 
             debug!("Rename binding `{:?}` = `{:?}`", id, new_id);
 
+            if let Some(names) = &mut self.or_pattern_names {
+                names.push((id.clone(), new_id.clone(), span));
+            }
+
             self.env.stack.insert(id, (new_id.clone(), span));
 
             new_id
@@ -191,6 +221,9 @@ pub fn rename<'s, 'ast>(
                     for alt in &mut **alts {
                         self.env.stack.enter_scope();
                         self.new_pattern(&mut alt.pattern);
+                        if let Some(ref mut guard) = alt.guard {
+                            self.visit_expr(guard);
+                        }
                         self.visit_expr(&mut alt.expr);
                         self.env.stack.exit_scope();
                     }
@@ -384,6 +417,7 @@ pub fn rename<'s, 'ast>(
         },
         ast_arena,
         hole: Type::hole(),
+        or_pattern_names: None,
     };
     visitor.visit_expr(expr);
 }
@@ -3,10 +3,13 @@
 //! checking of types are done in the `unify_type` and `kindcheck` modules.
 use std::{
     borrow::{BorrowMut, Cow},
+    cmp::Ordering,
     mem,
     sync::Arc,
 };
 
+use itertools::Itertools;
+
 use crate::base::{
     ast::{
         self, Argument, AstType, DisplayEnv, Do, Expr, IdentEnv, KindedIdent, Literal, MutVisitor,
@@ -29,8 +32,9 @@ use crate::base::{
 };
 
 use crate::{
-    implicits,
+    exhaustiveness, implicits,
     kindcheck::KindCheck,
+    lint::{self, SpannedWarning, Warning},
     substitution::{self, Substitution},
     typ::RcType,
     unify, unify_type, TypecheckEnv,
@@ -41,7 +45,7 @@ use self::{
     mod_type::{ModType, ModTypeRef, TypeModifier},
 };
 
-pub use self::error::{Help, HelpError, SpannedTypeError, TypeError};
+pub use self::error::{Help, HelpError, HoleEntry, SpannedTypeError, TypeError};
 
 mod error;
 mod generalize;
@@ -59,6 +63,18 @@ struct StackBinding {
     typ: ModType,
 }
 
+/// A typed hole which is reported once the whole expression has been typechecked, so that the
+/// type it is expected to have is as resolved as possible
+struct Hole {
+    span: Span<BytePos>,
+    name: Symbol,
+    typ: RcType,
+    /// The local bindings which were in scope at the hole
+    locals: Vec<(Symbol, RcType)>,
+    /// The implicit instances which were in scope at the hole
+    implicits: Vec<implicits::ImplicitBinding>,
+}
+
 pub(crate) struct Environment<'a> {
     /// The global environment which the typechecker extracts types from
     environment: &'a (dyn TypecheckEnv<Type = RcType> + 'a),
@@ -101,6 +117,10 @@ impl<'a> TypeEnv for Environment<'a> {
             .map(|&(_, ref alias)| alias.clone())
             .or_else(|| self.environment.find_type_info(id))
     }
+
+    fn loaded_modules(&self) -> Vec<(String, RcType)> {
+        self.environment.loaded_modules()
+    }
 }
 
 impl<'a> PrimitiveEnv for Environment<'a> {
@@ -122,6 +142,98 @@ impl Environment<'_> {
             .map(|bind| bind.typ.clone())
             .or_else(|| self.environment.find_type(id).map(ModType::rigid))
     }
+
+    /// Adds suggestions to errors about undefined names, listing the names in scope with a similar
+    /// spelling and the loaded modules which export the name
+    fn add_suggestions(&self, mut error: HelpError<Symbol, RcType>) -> HelpError<Symbol, RcType> {
+        if error.help.is_some() {
+            return error;
+        }
+        let (name, similar, modules) = match error.error {
+            TypeError::UndefinedVariable(ref id) => {
+                let name = id.declared_name();
+                let similar =
+                    similar_names(name, self.stack.iter().map(|(id, _)| id.declared_name()));
+                let modules = self.exporting_modules(|typ| {
+                    typ.row_iter()
+                        .any(|field| field.name.declared_name() == name)
+                });
+                (name, similar, modules)
+            }
+            TypeError::UndefinedType(ref id) => {
+                let name = id.declared_name();
+                let similar = similar_names(
+                    name,
+                    self.stack_types
+                        .iter()
+                        .map(|(id, _)| id)
+                        .chain(self.type_variables.iter().map(|(id, _)| id))
+                        .map(|id| id.declared_name()),
+                );
+                let modules = self.exporting_modules(|typ| {
+                    typ.type_field_iter()
+                        .any(|field| field.name.declared_name() == name)
+                });
+                (name, similar, modules)
+            }
+            TypeError::UndefinedField(ref typ, ref field) => {
+                let name = field.declared_name();
+                let similar = similar_names(
+                    name,
+                    typ.type_field_iter()
+                        .map(|field| field.name.declared_name())
+                        .chain(typ.row_iter().map(|field| field.name.declared_name())),
+                );
+                (name, similar, Vec::new())
+            }
+            _ => return error,
+        };
+        if !similar.is_empty() || !modules.is_empty() {
+            error.help = Some(Help::Suggestions {
+                name: name.to_string(),
+                similar,
+                modules,
+            });
+        }
+        error
+    }
+
+    fn exporting_modules(&self, exports: impl Fn(&RcType) -> bool) -> Vec<String> {
+        let mut modules = self
+            .environment
+            .loaded_modules()
+            .into_iter()
+            .filter(|(_, typ)| exports(typ))
+            .map(|(module, _)| module)
+            .collect::<Vec<_>>();
+        // Prefer the shortest module paths as those are usually the most general modules
+        modules.sort_by(|l, r| l.len().cmp(&r.len()).then_with(|| l.cmp(r)));
+        modules.truncate(MAX_SUGGESTIONS);
+        modules
+    }
+}
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the names which are spelled similarly to `name`, the most similar names first
+fn similar_names<'a>(name: &str, names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut names = names
+        .filter(|other| *other != name && !other.starts_with("__"))
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    let mut similar = names
+        .into_iter()
+        .map(|other| (strsim::jaro_winkler(name, other), other))
+        .filter(|&(similarity, _)| similarity >= 0.8)
+        .collect::<Vec<_>>();
+    similar.sort_by(|l, r| r.0.partial_cmp(&l.0).unwrap_or(Ordering::Equal));
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, other)| other.to_string())
+        .collect()
 }
 
 /// Struct which provides methods to typecheck expressions.
@@ -131,12 +243,17 @@ pub struct Typecheck<'a, 'ast> {
     pub(crate) subs: Substitution<RcType>,
     named_variables: FnvMap<Symbol, RcType>,
     pub(crate) errors: Errors<SpannedTypeError<Symbol, RcType<Symbol>>>,
+    warnings: Vec<SpannedWarning<Symbol>>,
     /// Type variables `let test: a -> b` (`a` and `b`)
     kind_cache: KindCache,
 
     pub(crate) implicit_resolver: implicits::ImplicitResolver<'a>,
     unbound_variables: ScopedMap<Symbol, ArcKind>,
     refined_variables: ScopedMap<u32, ()>,
+    holes: Vec<Hole>,
+    /// Bindings which were spliced in front of the expression (such as the implicit prelude) and
+    /// which are therefore not listed as locals of a typed hole
+    hidden_locals: FnvSet<Symbol>,
     pub(crate) ast_arena: ast::ArenaRef<'a, 'ast, Symbol>,
 }
 
@@ -147,7 +264,7 @@ impl<'a> TypeContext<Symbol, RcType> for Typecheck<'a, '_> {
 /// Error returned when unsuccessfully typechecking an expression
 pub type Error = Errors<SpannedTypeError<Symbol>>;
 
-pub use implicits::{Error as ImplicitError, ErrorKind as ImplicitErrorKind};
+pub use implicits::{Error as ImplicitError, ErrorKind as ImplicitErrorKind, RejectedImplicit};
 
 impl<'a, 'ast> Typecheck<'a, 'ast> {
     /// Create a new typechecker which typechecks expressions in `module`
@@ -173,10 +290,13 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
             symbols: symbols,
             named_variables: FnvMap::default(),
             errors: Errors::new(),
+            warnings: Vec::new(),
             kind_cache: interner.kind_cache.clone(),
             implicit_resolver: crate::implicits::ImplicitResolver::new(environment, metadata),
             unbound_variables: ScopedMap::new(),
             refined_variables: ScopedMap::new(),
+            holes: Vec::new(),
+            hidden_locals: FnvSet::default(),
             subs,
             ast_arena,
         }
@@ -186,7 +306,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
     where
         E: Into<HelpError<Symbol, RcType>>,
     {
-        let error = error.into();
+        let error = self.environment.add_suggestions(error.into());
         debug!("Error: {}", error);
         self.errors.push(Spanned {
             span: span,
@@ -233,7 +353,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
             Err(err) => {
                 self.errors.push(Spanned {
                     span: span,
-                    value: err.into(),
+                    value: self.environment.add_suggestions(err.into()),
                 });
                 let hole = self.subs.hole();
                 self.subs.new_alias(id.clone(), Vec::new(), hole)
@@ -268,6 +388,15 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
         );
     }
 
+    /// Returns the type of a variable bound by a pattern, or a type error if the pattern failed to
+    /// bind it
+    fn stack_var_type(&mut self, id: &Symbol) -> RcType {
+        match self.environment.stack.get(id) {
+            Some(bind) => bind.typ.concrete.clone(),
+            None => self.subs.error(),
+        }
+    }
+
     fn stack_type(&mut self, id: Symbol, alias: &Alias<Symbol, RcType>) {
         // Insert variant constructors into the local scope
         //
@@ -397,8 +526,11 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 | DuplicateField(_)
                 | UndefinedRecord { .. }
                 | EmptyCase
+                | NonExhaustivePattern { .. }
+                | Warning(_)
                 | KindError(_)
                 | RecursionCheck(_)
+                | InconsistentOrPattern(_)
                 | Message(_) => (),
                 NotAFunction(ref mut typ)
                 | UndefinedField(ref mut typ, _)
@@ -413,6 +545,9 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 } => self.generalize_type(0, typ, err.span),
                 UnableToResolveImplicit(ref mut inner_err) => {
                     use crate::implicits::ErrorKind::*;
+                    for rejected in &mut inner_err.rejected {
+                        self.generalize_type(0, &mut rejected.typ, err.span);
+                    }
                     match inner_err.kind {
                         MissingImplicit(ref mut typ) => {
                             self.generalize_type(0, typ, err.span);
@@ -435,6 +570,17 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                         })
                         .collect();
                 }
+                Hole {
+                    ref mut expected,
+                    ref mut locals,
+                    ref mut candidates,
+                    ..
+                } => {
+                    self.generalize_type_without_forall(0, expected, err.span);
+                    for entry in locals.iter_mut().chain(candidates) {
+                        self.generalize_type_without_forall(0, &mut entry.typ, err.span);
+                    }
+                }
                 Unification(ref mut expected, ref mut actual, ref mut errors) => {
                     self.generalize_type_without_forall(0, expected, err.span);
                     self.generalize_type_without_forall(0, actual, err.span);
@@ -485,6 +631,11 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
             .map(|t| self.translate_rc_type(&t))
     }
 
+    /// Takes the warnings found by the last successful call to `typecheck_expr`
+    pub fn take_warnings(&mut self) -> Vec<SpannedWarning<Symbol>> {
+        mem::take(&mut self.warnings)
+    }
+
     fn typecheck_expr_expected_(
         &mut self,
         expr: &mut SpannedExpr<'ast, Symbol>,
@@ -537,6 +688,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
             // will have already been generalized
             let tail = tail_expr(expr);
             crate::implicits::resolve(self, tail);
+            self.report_holes();
             self.generalize_type(0, &mut typ, tail.span);
             self.generalize_variables(0, &mut [].iter_mut(), tail);
         }
@@ -592,6 +744,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 .collect())
         } else {
             debug!("Typecheck result: {}", typ);
+            lint::lint(expr, &mut self.warnings);
             Ok(typ.concrete)
         }
     }
@@ -656,7 +809,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 returned_type = ModType::wobbly(self.subs.error());
                 self.errors.push(Spanned {
                     span: expr_check_span(expr),
-                    value: err.into(),
+                    value: self.environment.add_suggestions(err.into()),
                 });
             }
         }
@@ -685,6 +838,34 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 id.typ = self.subs.bind_arc(&typ);
                 Ok((ModType::new(modifier, typ), args))
             }
+            Expr::Hole(ref mut id) => {
+                let typ = match expected_type.take() {
+                    Some(expected_type) => expected_type.concrete.clone(),
+                    None => self.subs.new_var(),
+                };
+                let locals = self
+                    .environment
+                    .stack
+                    .iter()
+                    .map(|(name, bind)| (name.clone(), bind.typ.concrete.clone()))
+                    .collect();
+                let implicits = self
+                    .implicit_resolver
+                    .implicit_bindings
+                    .partition()
+                    .get_candidates(&self.subs, &typ)
+                    .cloned()
+                    .collect();
+                self.holes.push(Hole {
+                    span: expr.span,
+                    name: id.name.clone(),
+                    typ: typ.clone(),
+                    locals,
+                    implicits,
+                });
+                id.typ = self.subs.bind_arc(&typ);
+                Ok((ModType::wobbly(typ), Vec::new()))
+            }
             Expr::Literal(ref lit) => Ok((
                 ModType::rigid(match *lit {
                     Literal::Int(_) => self.subs.int(),
@@ -809,6 +990,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 Ok((new_type, Vec::new()))
             }
             Expr::Match(ref mut expr, ref mut alts) => {
+                let errors_before = self.errors.len();
                 let mut scrutinee_type = self.infer_expr(&mut **expr);
                 let modifier = scrutinee_type.modifier;
                 let expected_type = expected_type.take().map(|t| t.to_owned());
@@ -836,6 +1018,12 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                         scrutinee_type.concrete.clone(),
                     );
 
+                    if let Some(guard) = &mut alt.guard {
+                        let bool_type = self.bool();
+                        let guard_type = self.typecheck(guard, ModType::rigid(&bool_type));
+                        self.unify_span(expr_check_span(guard), &bool_type, guard_type.concrete);
+                    }
+
                     let mut alt_type = self
                         .typecheck_opt(&mut alt.expr, expected_type.as_ref().map(|t| t.as_ref()));
                     alt_type.concrete = self.instantiate_generics(&alt_type);
@@ -860,7 +1048,10 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                     {
                         *unaliased_scrutinee_type = self.subs.zonk(&unaliased_scrutinee_type);
                         let replaced = match (&alt.pattern.value, &**unaliased_scrutinee_type) {
-                            (Pattern::Constructor(id, _), Type::Variant(row)) => {
+                            // A guarded alternative may not match so the variant must be kept
+                            (Pattern::Constructor(id, _), Type::Variant(row))
+                                if alt.guard.is_none() =>
+                            {
                                 let mut variant_iter = row.row_iter();
                                 let variants = variant_iter
                                     .by_ref()
@@ -887,6 +1078,13 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
 
                     expr_type = Some(alt_type);
                 }
+
+                // Only check the patterns if they typechecked as the type information is needed
+                // to find all constructors
+                if errors_before == self.errors.len() && !alts.is_empty() {
+                    self.check_exhaustiveness(expr.span, alts);
+                }
+
                 expr_type
                     .ok_or(TypeError::EmptyCase)
                     .map(|typ| (typ, Vec::new()))
@@ -1241,6 +1439,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 self.unify_span(do_span, &flat_map_type, func_type);
 
                 if let Some(ref mut id) = *id {
+                    self.error_on_or_pattern(id);
                     self.typecheck_pattern(id, ModType::wobbly(id_var.clone()), id_var);
                 }
 
@@ -1274,6 +1473,76 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
         }
     }
 
+    /// Reports each typed hole together with the local bindings in scope at the hole and the values
+    /// whose types fit in it
+    fn report_holes(&mut self) {
+        for hole in mem::take(&mut self.holes) {
+            let expected = self.subs.zonk(&hole.typ);
+
+            let (mut locals, mut hidden): (Vec<_>, Vec<_>) = hole
+                .locals
+                .into_iter()
+                .filter(|(name, _)| !name.declared_name().starts_with("__"))
+                .partition(|(name, _)| !self.hidden_locals.contains(name));
+            locals.sort_by(|l, r| l.0.declared_name().cmp(r.0.declared_name()));
+            hidden.sort_by(|l, r| l.0.declared_name().cmp(r.0.declared_name()));
+
+            let to_entry = |subs: &Substitution<RcType>, (name, typ): (Symbol, RcType)| HoleEntry {
+                name: name.declared_name().to_string(),
+                typ: subs.zonk(&typ),
+            };
+            let locals = locals
+                .into_iter()
+                .map(|local| to_entry(&self.subs, local))
+                .collect::<Vec<_>>();
+
+            // Every value fits in a hole whose type is not yet known so only list the candidates
+            // when there is some type information
+            let mut candidates = Vec::new();
+            if !matches!(*expected, Type::Variable(_)) {
+                let hidden = hidden
+                    .into_iter()
+                    .map(|local| to_entry(&self.subs, local))
+                    .collect::<Vec<_>>();
+                let implicits = hole.implicits.into_iter().map(|(path, typ)| HoleEntry {
+                    name: path
+                        .iter()
+                        .map(|id| id.name.declared_name())
+                        .format(".")
+                        .to_string(),
+                    typ,
+                });
+                for entry in locals.iter().cloned().chain(hidden).chain(implicits) {
+                    if !candidates
+                        .iter()
+                        .any(|c: &HoleEntry<_>| c.name == entry.name)
+                        && self.fits_in_hole(&expected, &entry.typ)
+                    {
+                        candidates.push(entry);
+                    }
+                }
+            }
+
+            self.error(
+                hole.span,
+                TypeError::Hole {
+                    name: hole.name,
+                    expected,
+                    locals,
+                    candidates,
+                },
+            );
+        }
+    }
+
+    fn fits_in_hole(&mut self, expected: &RcType, actual: &RcType) -> bool {
+        let snapshot = self.subs.snapshot();
+        let state = unify_type::State::new(&self.environment, &self.subs);
+        let fits = unify_type::subsumes(&self.subs, state, expected, actual).is_ok();
+        self.subs.rollback_to(snapshot);
+        fits
+    }
+
     fn typecheck_application<'e, I>(
         &mut self,
         span: Span<BytePos>,
@@ -1553,7 +1822,22 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                     TypeError::Message(format!("Unexpected type constructor `{}`", id.name)),
                 )
             }
-            _ => self.typecheck_pattern(pattern, ModType::wobbly(match_type.clone()), match_type),
+            _ => {
+                self.error_on_or_pattern(pattern);
+                self.typecheck_pattern(pattern, ModType::wobbly(match_type.clone()), match_type)
+            }
+        }
+    }
+
+    fn error_on_or_pattern(&mut self, pattern: &SpannedPattern<Symbol>) {
+        if let Some(span) = find_or_pattern(pattern) {
+            self.error(
+                span,
+                TypeError::Message(
+                    "Or-patterns may only be used in the alternatives of `match` expressions"
+                        .into(),
+                ),
+            );
         }
     }
 
@@ -1739,6 +2023,26 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 }
                 tuple_type
             }
+            Pattern::Array { typ, elems, rest } => {
+                let element_type = self.subs.new_var();
+                let array_type = self.subs.array(element_type.clone());
+                let new_type = self.unify_span(span, &array_type, match_type.concrete);
+                *typ = self.subs.bind_arc(&new_type);
+                for (i, elem) in elems.iter_mut().enumerate() {
+                    // The rest pattern matches the remaining elements as an array
+                    let elem_type = if *rest == Some(i) {
+                        array_type.clone()
+                    } else {
+                        element_type.clone()
+                    };
+                    self.typecheck_pattern(
+                        elem,
+                        ModType::new(match_type.modifier, elem_type.clone()),
+                        elem_type,
+                    );
+                }
+                array_type
+            }
             Pattern::Ident(id) => {
                 self.stack_var(id.name.clone(), partial_match_type.clone());
                 id.typ = self.subs.bind_arc(&partial_match_type);
@@ -1750,6 +2054,58 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                 self.unify_span(span, &match_type, typ);
                 match_type.concrete
             }
+            Pattern::Or(patterns) => {
+                // The guard and expression of the match refer to the variables of the first
+                // alternative so every other alternative must bind the same names at the same types
+                let (first, rest) = patterns
+                    .split_first_mut()
+                    .expect("Or-patterns have at least two alternatives");
+                let typ =
+                    self.typecheck_pattern(first, match_type.clone(), partial_match_type.clone());
+
+                let mut first_bindings = Vec::new();
+                pattern_bindings(first, &mut first_bindings);
+                let first_bindings: Vec<_> = first_bindings
+                    .into_iter()
+                    .map(|id| {
+                        let typ = self.stack_var_type(&id);
+                        (id, typ)
+                    })
+                    .collect();
+
+                for pattern in rest {
+                    self.typecheck_pattern(pattern, match_type.clone(), partial_match_type.clone());
+
+                    let mut bindings = Vec::new();
+                    pattern_bindings(pattern, &mut bindings);
+                    for id in &bindings {
+                        match first_bindings
+                            .iter()
+                            .find(|(first_id, _)| first_id.declared_name() == id.declared_name())
+                        {
+                            Some((_, first_type)) => {
+                                let alt_type = self.stack_var_type(id);
+                                self.unify_span(pattern.span, first_type, alt_type);
+                            }
+                            None => {
+                                self.error(
+                                    pattern.span,
+                                    TypeError::InconsistentOrPattern(id.clone()),
+                                );
+                            }
+                        }
+                    }
+                    for (id, _) in &first_bindings {
+                        if !bindings
+                            .iter()
+                            .any(|binding| binding.declared_name() == id.declared_name())
+                        {
+                            self.error(pattern.span, TypeError::InconsistentOrPattern(id.clone()));
+                        }
+                    }
+                }
+                typ
+            }
             Pattern::Error => self.subs.new_var(),
         }
     }
@@ -1856,6 +2212,7 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
         let mut scope_count = 0;
 
         let level = self.subs.var_id();
+        let tail_span = expr_check_span(expr);
 
         loop {
             match expr.value {
@@ -1864,6 +2221,15 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
 
                     scope_count += 1;
 
+                    if !expr.span.contains(tail_span) {
+                        self.hidden_locals.extend(
+                            self.environment
+                                .stack
+                                .current_scope()
+                                .map(|(name, _)| name.clone()),
+                        );
+                    }
+
                     if top_level {
                         self.generalize_and_clear_subs(level, bindings);
                     }
@@ -2106,11 +2472,13 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                     .unresolved_type_mut()
                     .remove_single_forall();
                 if let Err(errors) = check.kindcheck_type(typ) {
-                    self.errors.extend(
-                        errors
-                            .into_iter()
-                            .map(|err| pos::spanned(err.span, TypeError::from(err.value).into())),
-                    );
+                    let environment = &self.environment;
+                    self.errors.extend(errors.into_iter().map(|err| {
+                        pos::spanned(
+                            err.span,
+                            environment.add_suggestions(TypeError::from(err.value).into()),
+                        )
+                    }));
                 }
                 check.exit_scope();
             }
@@ -2191,11 +2559,13 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
             check.kindcheck_type(typ)
         };
         if let Err(errors) = result {
-            self.errors.extend(
-                errors
-                    .into_iter()
-                    .map(|err| pos::spanned(err.span, TypeError::from(err.value).into())),
-            );
+            let environment = &self.environment;
+            self.errors.extend(errors.into_iter().map(|err| {
+                pos::spanned(
+                    err.span,
+                    environment.add_suggestions(TypeError::from(err.value).into()),
+                )
+            }));
         }
     }
 
@@ -2327,6 +2697,29 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                     self.finish_pattern(level, elem, &field_type);
                 }
             }
+            Pattern::Array {
+                ref mut typ,
+                ref mut elems,
+                rest,
+            } => {
+                *typ = self.subs.bind_arc(final_type);
+
+                let array_type = self.instantiate_generics(final_type);
+                let array_type = self.remove_alias(array_type);
+                let element_type = match *array_type {
+                    Type::App(_, ref args) if args.len() == 1 => args[0].clone(),
+                    _ => self.subs.error(),
+                };
+                for (i, elem) in elems.iter_mut().enumerate() {
+                    let mut elem_type = if rest == Some(i) {
+                        array_type.clone()
+                    } else {
+                        element_type.clone()
+                    };
+                    self.generalize_type(level, &mut elem_type, elem.span);
+                    self.finish_pattern(level, elem, &elem_type);
+                }
+            }
             Pattern::Constructor(ref mut id, ref mut args) => {
                 debug!("{}: {}", self.symbols.string(&id.name), final_type);
                 let len = args.len();
@@ -2340,6 +2733,11 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
                     self.finish_pattern(level, arg, &arg_type);
                 }
             }
+            Pattern::Or(ref mut patterns) => {
+                for pattern in &mut **patterns {
+                    self.finish_pattern(level, pattern, final_type);
+                }
+            }
             Pattern::Literal(_) | Pattern::Error => (),
         }
     }
@@ -2893,6 +3291,67 @@ impl<'a, 'ast> Typecheck<'a, 'ast> {
             .unwrap_or(typ)
     }
 
+    fn check_exhaustiveness(
+        &mut self,
+        scrutinee_span: Span<BytePos>,
+        alts: &[ast::Alternative<Symbol>],
+    ) {
+        let patterns = alts
+            .iter()
+            .map(|alt| {
+                (
+                    exhaustiveness::Pat::new(&alt.pattern, &mut |ctor| {
+                        self.variant_constructors(ctor)
+                    }),
+                    alt.guard.is_some(),
+                )
+            })
+            .collect();
+        let report = exhaustiveness::check(patterns);
+
+        for i in report.unreachable {
+            self.warnings.push(pos::spanned(
+                alts[i].pattern.span,
+                Warning::UnreachablePattern,
+            ));
+        }
+        if !report.missing.is_empty() {
+            self.error(
+                scrutinee_span,
+                TypeError::NonExhaustivePattern {
+                    missing: report.missing.iter().map(|pat| pat.to_string()).collect(),
+                },
+            );
+        }
+    }
+
+    /// Returns all the constructors of the variant type that `ctor` belongs to
+    fn variant_constructors(&self, ctor: &Symbol) -> exhaustiveness::Constructors {
+        let ctor_type = self.environment.find_type(ctor)?;
+        let variant_type = self.remove_aliases(ctor_return_type(&ctor_type).clone());
+        // Aliases may be defined with a `forall` wrapping the variant
+        let variant_type = self.remove_aliases(variant_type.remove_forall().clone());
+        match &*variant_type {
+            Type::Variant(row) => {
+                let mut iter = row.row_iter();
+                let constructors = iter
+                    .by_ref()
+                    .map(|variant| {
+                        (
+                            variant.name.clone(),
+                            variant.typ.remove_forall().arg_iter().count(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                match **iter.current_type() {
+                    Type::EmptyRow => Some(constructors.into()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     fn remove_aliases(&self, typ: RcType) -> RcType {
         resolve::remove_aliases(&self.environment, &mut &self.subs, typ)
     }
@@ -3216,6 +3675,55 @@ where
     })
 }
 
+/// Collects the variables bound by `pattern` (only the first alternative of or-patterns are
+/// considered)
+fn pattern_bindings(pattern: &SpannedPattern<Symbol>, bindings: &mut Vec<Symbol>) {
+    match &pattern.value {
+        Pattern::As(id, pat) => {
+            bindings.push(id.value.clone());
+            pattern_bindings(pat, bindings);
+        }
+        Pattern::Ident(id) => bindings.push(id.name.clone()),
+        Pattern::Constructor(_, args) => {
+            for arg in &**args {
+                pattern_bindings(arg, bindings);
+            }
+        }
+        Pattern::Tuple { elems, .. } | Pattern::Array { elems, .. } => {
+            for elem in &**elems {
+                pattern_bindings(elem, bindings);
+            }
+        }
+        Pattern::Record { fields, .. } => {
+            for (name, value) in ast::pattern_values(fields) {
+                match value {
+                    Some(pat) => pattern_bindings(pat, bindings),
+                    None => bindings.push(name.value.clone()),
+                }
+            }
+        }
+        // Every alternative binds the same variables
+        Pattern::Or(patterns) => pattern_bindings(&patterns[0], bindings),
+        Pattern::Literal(_) | Pattern::Error => (),
+    }
+}
+
+/// Returns the span of the first or-pattern in `pattern`
+fn find_or_pattern(pattern: &SpannedPattern<Symbol>) -> Option<Span<BytePos>> {
+    match &pattern.value {
+        Pattern::Or(_) => Some(pattern.span),
+        Pattern::As(_, pat) => find_or_pattern(pat),
+        Pattern::Constructor(_, args) => args.iter().find_map(find_or_pattern),
+        Pattern::Tuple { elems, .. } | Pattern::Array { elems, .. } => {
+            elems.iter().find_map(find_or_pattern)
+        }
+        Pattern::Record { fields, .. } => ast::pattern_values(fields)
+            .filter_map(|(_, value)| value.as_ref())
+            .find_map(find_or_pattern),
+        Pattern::Ident(_) | Pattern::Literal(_) | Pattern::Error => None,
+    }
+}
+
 pub fn extract_generics(args: &[RcType]) -> Vec<Generic<Symbol>> {
     args.iter()
         .map(|arg| match **arg {
@@ -2,6 +2,8 @@ use std::fmt;
 
 use codespan_reporting::diagnostic::Diagnostic;
 
+use itertools::Itertools;
+
 use pretty::Arena;
 
 use base::{
@@ -15,6 +17,7 @@ use base::{
 use crate::{
     implicits,
     kindcheck::{self, Error as KindCheckError, KindError},
+    lint::Warning,
     unify::Error as UnifyError,
     unify_type::{self, Error as UnifyTypeError},
 };
@@ -53,12 +56,66 @@ pub enum TypeError<I, T> {
     },
     /// Found a case expression without any alternatives
     EmptyCase,
+    /// The alternatives of a match expression does not cover every possible value
+    NonExhaustivePattern {
+        missing: Vec<String>,
+    },
+    /// A warning which has been promoted to an error
+    Warning(Warning<I>),
     Message(String),
     UnableToResolveImplicit(implicits::Error<T>),
     TypeConstructorReturnsWrongType {
         expected: I,
         actual: T,
     },
+    /// A variable was bound in some, but not all, alternatives of an or-pattern
+    InconsistentOrPattern(I),
+    /// A typed hole (`_` or `?name`) was found in an expression
+    Hole {
+        name: I,
+        /// The type that the expression replacing the hole must have
+        expected: T,
+        /// The local bindings in scope at the hole
+        locals: Vec<HoleEntry<T>>,
+        /// The values in scope (including implicit instances) whose types fit in the hole
+        candidates: Vec<HoleEntry<T>>,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Functor)]
+pub struct HoleEntry<T> {
+    pub name: String,
+    pub typ: T,
+}
+
+impl<I, T> TypeError<I, T> {
+    /// Returns the stable code which identifies this kind of error
+    pub fn code(&self) -> &'static str {
+        use self::TypeError::*;
+        use base::error_code::*;
+        match self {
+            UndefinedVariable(_) => E0101,
+            NotAFunction(_) => E0102,
+            UndefinedType(_) => E0103,
+            UndefinedField(..) => E0104,
+            PatternError { .. } => E0105,
+            Unification(..) => E0106,
+            KindError(err) => kindcheck::kind_error_code(err),
+            RecursionCheck(_) => E0107,
+            DuplicateTypeDefinition(_) => E0108,
+            DuplicateField(_) => E0109,
+            InvalidProjection(_) => E0110,
+            UndefinedRecord { .. } => E0111,
+            EmptyCase => E0112,
+            NonExhaustivePattern { .. } => E0113,
+            Warning(warning) => warning.code(),
+            Message(_) => E0114,
+            UnableToResolveImplicit(err) => err.kind.code(),
+            TypeConstructorReturnsWrongType { .. } => E0116,
+            InconsistentOrPattern(_) => E0117,
+            Hole { .. } => E0118,
+        }
+    }
 }
 
 impl<I, T> From<KindCheckError<I, T>> for TypeError<I, T> {
@@ -212,6 +269,17 @@ where
                 Ok(())
             }
             EmptyCase => write!(f, "`case` expression with no alternatives"),
+            NonExhaustivePattern { missing } => {
+                write!(f, "Non-exhaustive patterns: ")?;
+                for (i, pattern) in missing.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "`{}`", pattern)?;
+                }
+                write!(f, " not covered")
+            }
+            Warning(warning) => write!(f, "{}", warning),
             Message(msg) => write!(f, "{}", msg),
             UnableToResolveImplicit(err) => write!(f, "{}", err),
             TypeConstructorReturnsWrongType { expected, actual } => write!(
@@ -219,6 +287,35 @@ where
                 "The constructor returns the type `{}` instead of the expected type `{}`",
                 actual, expected
             ),
+            InconsistentOrPattern(id) => write!(
+                f,
+                "Variable `{}` is not bound in all alternatives of the pattern",
+                id
+            ),
+            Hole {
+                name,
+                expected,
+                locals,
+                candidates,
+            } => {
+                if name.as_ref() == "_" {
+                    write!(f, "Found a hole with the type `{}`", expected)?;
+                } else {
+                    write!(f, "Found the hole `?{}` with the type `{}`", name, expected)?;
+                }
+                for (header, entries) in &[
+                    ("Local bindings", locals),
+                    ("Values which fit in the hole", candidates),
+                ] {
+                    if !entries.is_empty() {
+                        write!(f, "\n{}:", header)?;
+                        for entry in entries.iter() {
+                            write!(f, "\n    {} : {}", entry.name, entry.typ)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -237,7 +334,9 @@ where
         use self::TypeError::*;
         match *self {
             UnableToResolveImplicit(ref err) => err.as_diagnostic(map),
-            _ => Diagnostic::error().with_message(self.to_string()),
+            _ => Diagnostic::error()
+                .with_message(self.to_string())
+                .with_code(self.code()),
         }
     }
 }
@@ -246,6 +345,14 @@ where
 pub enum Help {
     UndefinedFlatMapInDo,
     ExtraArgument(u32, u32),
+    /// Suggestions for a name which could not be found
+    Suggestions {
+        name: String,
+        /// Names in scope which are spelled similarly
+        similar: Vec<String>,
+        /// Loaded modules which export a binding with the name
+        modules: Vec<String>,
+    },
 }
 
 impl fmt::Display for Help {
@@ -269,6 +376,49 @@ impl fmt::Display for Help {
                     )
                 }
             }
+            Help::Suggestions {
+                ref name,
+                ref similar,
+                ref modules,
+            } => {
+                match similar.len() {
+                    0 => (),
+                    1 => write!(f, "Did you mean `{}`?", similar[0])?,
+                    _ => write!(
+                        f,
+                        "Did you mean one of {}?",
+                        similar
+                            .iter()
+                            .format_with(", ", |name, f| f(&format_args!("`{}`", name)))
+                    )?,
+                }
+                if let Some(module) = modules.first() {
+                    if !similar.is_empty() {
+                        writeln!(f)?;
+                    }
+                    let binding = if name.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                        name.to_string()
+                    } else {
+                        format!("({})", name)
+                    };
+                    if modules.len() > 1 {
+                        write!(
+                            f,
+                            "`{}` is exported by {}. ",
+                            name,
+                            modules
+                                .iter()
+                                .format_with(", ", |module, f| f(&format_args!("`{}`", module)))
+                        )?;
+                    }
+                    write!(
+                        f,
+                        "Try importing it with `let {{ {} }} = import! {}`",
+                        binding, module
+                    )?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,329 @@
+extern crate gluon_base as base;
+extern crate gluon_check as check;
+extern crate gluon_parser as parser;
+
+#[macro_use]
+mod support;
+
+use crate::check::{lint::Warning, typecheck::TypeError};
+
+fn missing_patterns(text: &str) -> Vec<String> {
+    let errors = support::typecheck(text)
+        .unwrap_err()
+        .unwrap_check()
+        .into_errors();
+    errors
+        .iter()
+        .find_map(|err| match &err.value.error {
+            TypeError::NonExhaustivePattern { missing } => Some(missing.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("Expected a non-exhaustive pattern error: {:?}", errors))
+}
+
+fn unreachable_patterns(text: &str) -> usize {
+    support::warnings(text)
+        .iter()
+        .filter(|warning| warning.value == Warning::UnreachablePattern)
+        .count()
+}
+
+test_check! {
+    exhaustive_variant,
+    r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some x -> x
+| None -> 0
+"#,
+    "Int"
+}
+
+test_check! {
+    exhaustive_nested_variant,
+    r#"
+type Option a = | None | Some a
+match Some (Some 1) with
+| Some (Some x) -> x
+| Some None -> 1
+| None -> 0
+"#,
+    "Int"
+}
+
+test_check! {
+    exhaustive_record_and_tuple,
+    r#"
+type Option a = | None | Some a
+let f x y =
+    match ({ a = x, b = 1 }, y) with
+    | ({ a = Some _ }, _) -> 1
+    | ({ a = None, b }, Some z) -> b
+    | (_, None) -> 0
+f
+"#,
+    "forall a a0 . test.Option a -> test.Option a0 -> Int"
+}
+
+test_check! {
+    literal_with_catch_all,
+    r#"
+match 1 with
+| 0 -> "zero"
+| 1 -> "one"
+| _ -> "many"
+"#,
+    "String"
+}
+
+test_check! {
+    exhaustive_gadt_alias_with_forall,
+    r#"
+type Expr a = forall x . (| Lit x | Pair (Expr x) (Expr x))
+let f e : Expr Int -> Int =
+    match e with
+    | Lit _ -> 1
+    | Pair _ _ -> 2
+f
+"#,
+    "test.Expr Int -> Int"
+}
+
+test_check_err! {
+    missing_variant,
+    r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some x -> x
+"#,
+    TypeError::NonExhaustivePattern { .. }
+}
+
+#[test]
+fn missing_variant_example() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some x -> x
+"#;
+    assert_eq!(missing_patterns(text), ["None"]);
+}
+
+#[test]
+fn missing_nested_variant_example() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type List a = | Nil | Cons a (List a)
+match Nil with
+| Nil -> 0
+| Cons _ Nil -> 1
+"#;
+    assert_eq!(missing_patterns(text), ["Cons _ (Cons _ _)"]);
+}
+
+#[test]
+fn missing_record_field_example() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match { x = Some 1, y = 2 } with
+| { x = Some _ } -> 1
+"#;
+    assert_eq!(missing_patterns(text), ["{ x = None }"]);
+}
+
+#[test]
+fn missing_tuple_example() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match (Some 1, Some 2) with
+| (Some _, _) -> 1
+| (None, None) -> 2
+"#;
+    assert_eq!(missing_patterns(text), ["(None, Some _)"]);
+}
+
+#[test]
+fn missing_literal_example() {
+    let _ = env_logger::try_init();
+    let text = r#"
+match "a" with
+| "a" -> 1
+| "b" -> 2
+"#;
+    assert_eq!(missing_patterns(text), ["_"]);
+}
+
+#[test]
+fn open_polymorphic_variant_requires_catch_all() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Extend r = | A Int | B .. r
+let f x : forall r . Extend r -> Int =
+    match x with
+    | A y -> y
+    | B -> 0
+f
+"#;
+    assert_eq!(missing_patterns(text), ["_"]);
+}
+
+#[test]
+fn unreachable_after_catch_all() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match Some 1 with
+| _ -> 0
+| Some x -> x
+"#;
+    assert_eq!(unreachable_patterns(text), 1);
+}
+
+#[test]
+fn unreachable_duplicate_constructor() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some x -> x
+| None -> 0
+| Some 1 -> 1
+"#;
+    assert_eq!(unreachable_patterns(text), 1);
+}
+
+#[test]
+fn unreachable_covered_by_previous_alternatives() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match (Some 1, 2) with
+| (Some _, _) -> 1
+| (None, _) -> 2
+| (_, 3) -> 3
+"#;
+    assert_eq!(unreachable_patterns(text), 1);
+}
+
+test_check! {
+    exhaustive_or_pattern,
+    r#"
+type T = | A Int | B Int | C
+match A 1 with
+| A x | B x -> x
+| C -> 0
+"#,
+    "Int"
+}
+
+#[test]
+fn missing_nested_or_pattern() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+type T = | A | B | C
+match Some A with
+| Some (A | B) -> 1
+| None -> 0
+"#;
+    assert_eq!(missing_patterns(text), ["Some C"]);
+}
+
+#[test]
+fn guarded_alternative_does_not_cover() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some x if True -> x
+| None -> 0
+"#;
+    assert_eq!(missing_patterns(text), ["Some _"]);
+}
+
+#[test]
+fn unreachable_or_pattern() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type T = | A | B | C
+match A with
+| A | B -> 1
+| C -> 2
+| B | C -> 3
+"#;
+    assert_eq!(unreachable_patterns(text), 1);
+}
+
+#[test]
+fn unreachable_after_guarded_catch_all() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some _ if True -> 0
+| Some x -> x
+| None -> 1
+| _ -> 2
+"#;
+    assert_eq!(unreachable_patterns(text), 1);
+}
+
+test_check! {
+    exhaustive_array_pattern,
+    r#"
+match [1, 2] with
+| [] -> 0
+| [x] -> x
+| [x, .., y] -> y
+"#,
+    "Int"
+}
+
+test_check! {
+    exhaustive_array_pattern_with_prefix_and_suffix,
+    r#"
+match [1, 2] with
+| [1, ..] -> 0
+| [.., x] -> x
+| [] -> 0
+"#,
+    "Int"
+}
+
+#[test]
+fn missing_array_pattern() {
+    let _ = env_logger::try_init();
+    let text = r#"
+match [1] with
+| [] -> 0
+| [x] -> x
+"#;
+    assert_eq!(missing_patterns(text), ["[_, _, ..]"]);
+}
+
+#[test]
+fn missing_array_pattern_element() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type Option a = | None | Some a
+match [None] with
+| [] -> 0
+| [Some x, ..rest] -> x
+"#;
+    assert_eq!(missing_patterns(text), ["[None, ..]"]);
+}
+
+#[test]
+fn unreachable_array_pattern() {
+    let _ = env_logger::try_init();
+    let text = r#"
+match [1] with
+| [x, ..] -> x
+| [.., y] -> y
+| [] -> 0
+"#;
+    assert_eq!(unreachable_patterns(text), 1);
+}
@@ -11,7 +11,7 @@ use crate::base::{
     types::{ArcType, Type},
 };
 
-use crate::check::typecheck::TypeError;
+use crate::check::typecheck::{Help, TypeError};
 
 #[macro_use]
 mod support;
@@ -56,6 +56,71 @@ let x : _ =
     assert_unify_err!(result, TypeMismatch(..));
 }
 
+#[test]
+fn match_guard_must_be_bool() {
+    let _ = env_logger::try_init();
+    let text = r#"
+match 1 with
+| x if x -> 1
+| _ -> 0
+"#;
+    let result = support::typecheck(text);
+
+    assert_err!(result, Unification(..));
+}
+
+#[test]
+fn or_pattern_binds_different_variables() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type T = | A Int | B Int
+match A 1 with
+| A x | B y -> 1
+"#;
+    let result = support::typecheck(text);
+
+    assert_err!(result, InconsistentOrPattern(..), InconsistentOrPattern(..));
+}
+
+#[test]
+fn or_pattern_binds_different_types() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type T = | A Int | B String
+match A 1 with
+| A x | B x -> 1
+"#;
+    let result = support::typecheck(text);
+
+    assert_unify_err!(result, TypeMismatch(..));
+}
+
+#[test]
+fn or_pattern_in_let() {
+    let _ = env_logger::try_init();
+    let text = r#"
+type T = | A Int | B Int
+let (A x | B x) = A 1
+x
+"#;
+    let result = support::typecheck(text);
+
+    assert_err!(result, Message(..));
+}
+
+#[test]
+fn array_pattern_on_non_array() {
+    let _ = env_logger::try_init();
+    let text = r#"
+match 1 with
+| [x, ..] -> x
+| _ -> 0
+"#;
+    let result = support::typecheck(text);
+
+    assert_unify_err!(result, TypeMismatch(..));
+}
+
 #[test]
 fn undefined_type_not_in_scope() {
     let _ = env_logger::try_init();
@@ -662,7 +727,7 @@ f x 1
 
     assert_eq!(
         &*format!("{}", result.unwrap_err()).replace("\t", "        "),
-        r#"error: Expected the following types to be equal
+        r#"error[E0106]: Expected the following types to be equal
 Expected: Int -> Int -> a
 Found: test.Eff [| | r |] Int
 1 errors were found during unification:
@@ -769,3 +834,88 @@ match Test 0 with
 "#,
 PatternError { .. }
 }
+
+#[test]
+fn typed_hole() {
+    let _ = ::env_logger::try_init();
+    let text = r#"
+let add x y : Int -> Int -> Int = x #Int+ y
+let f x : Int -> Int =
+    let s = ""
+    add x _
+f
+"#;
+    let result = support::typecheck(text);
+
+    assert_eq!(
+        &*format!("{}", result.unwrap_err()).replace("\t", "        "),
+        r#"error[E0118]: Found a hole with the type `Int`
+Local bindings:
+    add : Int -> Int -> Int
+    f : Int -> Int
+    s : String
+    x : Int
+Values which fit in the hole:
+    x : Int
+  ┌─ test:5:11
+  │
+5 │     add x _
+  │           ^
+
+"#
+    );
+}
+
+#[test]
+fn named_typed_hole() {
+    let _ = ::env_logger::try_init();
+    let text = r#"
+let f : String -> Int = \s -> ?result
+f
+"#;
+    let result = support::typecheck(text);
+
+    assert_err!(result, Hole { .. });
+}
+
+#[test]
+fn undefined_variable_suggests_similar_names() {
+    let _ = ::env_logger::try_init();
+    let text = r#"
+let length = 1
+let list = 2
+lenght
+"#;
+    let result = support::typecheck(text);
+
+    let errors: Vec<_> = result.unwrap_err().unwrap_check().into_errors().into();
+    assert_eq!(
+        errors[0].value.help,
+        Some(Help::Suggestions {
+            name: "lenght".into(),
+            similar: vec!["length".into()],
+            modules: vec![],
+        })
+    );
+}
+
+#[test]
+fn undefined_type_suggests_similar_names() {
+    let _ = ::env_logger::try_init();
+    let text = r#"
+type Test = Int
+let x : Tset = 1
+x
+"#;
+    let result = support::typecheck(text);
+
+    let errors: Vec<_> = result.unwrap_err().unwrap_check().into_errors().into();
+    assert_eq!(
+        errors[0].value.help,
+        Some(Help::Suggestions {
+            name: "Tset".into(),
+            similar: vec!["Test".into()],
+            modules: vec![],
+        })
+    );
+}
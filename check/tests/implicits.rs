@@ -310,6 +310,32 @@ g 1
     );
 }
 
+#[test]
+fn missing_implicit_lists_rejected_candidates() {
+    let _ = ::env_logger::try_init();
+    let text = r#"
+#[implicit]
+type Show a = { show : a -> String }
+
+let show ?s : [Show a] -> a -> String = s.show
+
+let show_int : Show Int = { show = \_ -> "" }
+
+show ""
+"#;
+    let result = support::typecheck(text);
+
+    let errors: Vec<_> = result.unwrap_err().unwrap_check().into_errors().into();
+    assert_eq!(errors.len(), 1);
+    match errors[0].value.error {
+        TypeError::UnableToResolveImplicit(ref err) => {
+            assert_eq!(err.rejected.len(), 1);
+            assert_eq!(err.rejected[0].path, "show_int");
+        }
+        ref err => panic!("Expected an unresolved implicit, found {}", err),
+    }
+}
+
 #[test]
 fn implicit_ord() {
     let _ = ::env_logger::try_init();
@@ -0,0 +1,193 @@
+extern crate gluon_base as base;
+extern crate gluon_check as check;
+extern crate gluon_parser as parser;
+
+#[macro_use]
+mod support;
+
+fn warnings(text: &str) -> Vec<String> {
+    let _ = env_logger::try_init();
+    support::warnings(text)
+        .iter()
+        .map(|warning| warning.value.to_string())
+        .collect()
+}
+
+#[test]
+fn unused_let_binding() {
+    let text = r#"
+let x = 1
+let y = 2
+y
+"#;
+    assert_eq!(warnings(text), ["Unused variable `x`"]);
+}
+
+#[test]
+fn used_bindings_do_not_warn() {
+    let text = r#"
+let x = 1
+let f y = y #Int+ x
+let { a, b } = { a = 1, b = 2 }
+{ f, a, b }
+"#;
+    assert_eq!(warnings(text), Vec::<String>::new());
+}
+
+#[test]
+fn underscore_prefix_silences_unused() {
+    let text = r#"
+let _x = 1
+let f _y = 2
+f
+"#;
+    assert_eq!(warnings(text), Vec::<String>::new());
+}
+
+#[test]
+fn unused_function_argument() {
+    let text = r#"
+let f x y = x
+f
+"#;
+    assert_eq!(warnings(text), ["Unused variable `y`"]);
+}
+
+#[test]
+fn unused_pattern_variable() {
+    let text = r#"
+type Option a = | None | Some a
+match Some 1 with
+| Some x -> 1
+| None -> 0
+"#;
+    assert_eq!(warnings(text), ["Unused variable `x`"]);
+}
+
+#[test]
+fn recursive_function_is_used() {
+    let text = r#"
+rec let f x = g x
+let g x = f x
+in f
+"#;
+    assert_eq!(warnings(text), Vec::<String>::new());
+}
+
+#[test]
+fn shadowed_variable() {
+    let text = r#"
+let x = 1
+#[warn(shadowing)]
+let f x = x
+{ x, f }
+"#;
+    assert_eq!(
+        warnings(text),
+        ["`x` shadows a variable with the same name which is already in scope"]
+    );
+}
+
+#[test]
+fn shadowing_is_allowed_by_default() {
+    let text = r#"
+let x = 1
+let x = x #Int+ 1
+let f x = (\x -> x) x
+match x with
+| x -> f x
+"#;
+    assert_eq!(warnings(text), Vec::<String>::new());
+}
+
+#[test]
+fn allow_attribute_overrides_enclosing_warn_attribute() {
+    let text = r#"
+#[warn(shadowing)]
+let f x =
+    #[allow(shadowing)]
+    let x = x
+    let g x = x
+    g x
+f
+"#;
+    assert_eq!(
+        warnings(text),
+        ["`x` shadows a variable with the same name which is already in scope"]
+    );
+}
+
+#[test]
+fn unused_alias_type_variable() {
+    let text = r#"
+type Phantom a = Int
+let x : Phantom String = 1
+x
+"#;
+    assert_eq!(warnings(text), ["Unused type variable `a`"]);
+}
+
+#[test]
+fn unused_forall_type_variable() {
+    let text = r#"
+let f : forall a b . a -> a = \x -> x
+f
+"#;
+    assert_eq!(warnings(text), ["Unused type variable `b`"]);
+}
+
+#[test]
+fn allow_attribute_silences_lint() {
+    let text = r#"
+#[allow(unused_variables)]
+let x = 1
+#[allow(unused)]
+let f y = 2
+#[allow(shadowing)]
+let g x = x
+{ f, g }
+"#;
+    assert_eq!(warnings(text), Vec::<String>::new());
+}
+
+#[test]
+fn allow_attribute_only_silences_the_named_lint() {
+    let text = r#"
+#[allow(shadowing)]
+let x = 1
+2
+"#;
+    assert_eq!(warnings(text), ["Unused variable `x`"]);
+}
+
+#[test]
+fn allow_attribute_silences_unreachable_pattern() {
+    let text = r#"
+type Option a = | None | Some a
+#[allow(unreachable_patterns)]
+let f x =
+    match x with
+    | _ -> 0
+    | Some _ -> 1
+f
+"#;
+    assert_eq!(warnings(text), Vec::<String>::new());
+}
+
+#[test]
+fn warnings_have_stable_codes() {
+    use crate::base::error_code::{W0001, W0003};
+
+    let _ = env_logger::try_init();
+    let text = r#"
+let x = 1
+#[warn(shadowing)]
+let f x = x
+f
+"#;
+    let codes: Vec<_> = support::warnings(text)
+        .iter()
+        .map(|warning| warning.value.code())
+        .collect();
+    assert_eq!(codes, [W0001, W0003]);
+}
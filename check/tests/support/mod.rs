@@ -17,6 +17,7 @@ use self::{
         types::{self, Alias, ArcType, Field, Generic, PrimitiveEnv, Type, TypeCache, TypeEnv},
     },
     check::{
+        lint::Warning,
         metadata, rename,
         typecheck::{self, Typecheck},
     },
@@ -240,6 +241,42 @@ pub fn typecheck_expr(text: &str) -> (RootExpr<Symbol>, Result<ArcType, Error>)
     typecheck_expr_expected(text, None)
 }
 
+/// Typechecks `text`, which must succeed, and returns the warnings that were found
+pub fn warnings(text: &str) -> Vec<Spanned<Warning<Symbol>, BytePos>> {
+    let mut expr = parse_new(text).unwrap_or_else(|(_, err)| panic!("{}", err));
+
+    let env = MockEnv::new();
+    let interner = get_local_interner();
+    let mut interner = interner.borrow_mut();
+
+    let source = source::FileMap::new("test".into(), text.to_string());
+
+    let (arena, expr) = expr.arena_expr();
+    let arena = arena.borrow();
+
+    rename::rename(
+        &source,
+        &mut SymbolModule::new("test".into(), &mut interner),
+        arena,
+        expr,
+    );
+    let (_, mut metadata) = metadata::metadata(&env, &expr);
+    reparse_infix(arena, &metadata, &*interner, expr).unwrap_or_else(|err| panic!("{}", err));
+
+    let mut tc = Typecheck::new(
+        "test".into(),
+        &mut interner,
+        &env,
+        &TypeCache::new(),
+        &mut metadata,
+        arena,
+    );
+
+    tc.typecheck_expr(expr)
+        .unwrap_or_else(|err| panic!("{}", in_file_error(text, err)));
+    tc.take_warnings()
+}
+
 #[allow(dead_code)]
 pub fn typecheck_partial_expr(
     text: &str,
@@ -43,7 +43,7 @@ fn gen_impl(container: &Container, ident: Ident, generics: Generics) -> TokenStr
         },
         CrateName::GluonVm => quote! {
             use crate::api as _gluon_api;
-            use crate::thread as _gluon_gc;
+            use crate::gc as _gluon_gc;
             use crate::Result as _gluon_Result;
         },
         CrateName::None => quote! {
@@ -181,11 +181,15 @@ where
                     }
                 }
             }
-            Pattern::Tuple { elems: args, .. } | Pattern::Constructor(_, args) => {
+            Pattern::Tuple { elems: args, .. }
+            | Pattern::Array { elems: args, .. }
+            | Pattern::Constructor(_, args) => {
                 for arg in &**args {
                     self.on_pattern(arg);
                 }
             }
+            // Only the variables of the first alternative are in scope after the pattern
+            Pattern::Or(patterns) => self.on_pattern(&patterns[0]),
             Pattern::Literal(_) | Pattern::Error => (),
         }
     }
@@ -477,10 +481,17 @@ where
                     self.found = MatchState::Empty;
                 }
             }
-            Pattern::Tuple { ref elems, .. } => {
+            Pattern::Tuple { ref elems, .. } | Pattern::Or(ref elems) => {
                 let (_, field) = self.select_spanned(&**elems, |elem| elem.span);
                 self.visit_pattern(field.unwrap());
             }
+            Pattern::Array { ref elems, .. } => {
+                let (_, elem) = self.select_spanned(&**elems, |elem| elem.span);
+                match elem {
+                    Some(elem) => self.visit_pattern(elem),
+                    None => self.found = MatchState::Empty,
+                }
+            }
             Pattern::Ident(_) | Pattern::Literal(_) | Pattern::Error => {
                 self.found = if current.span.containment(self.pos) == Ordering::Equal {
                     MatchState::Found(Match::Pattern(current))
@@ -506,7 +517,7 @@ where
         }
 
         match current.value {
-            Expr::Ident(_) | Expr::Literal(_) => {
+            Expr::Ident(_) | Expr::Hole(_) | Expr::Literal(_) => {
                 self.found = if current.span.containment(self.pos) == Ordering::Equal {
                     MatchState::Found(Match::Expr(current))
                 } else {
@@ -534,8 +545,10 @@ where
                     }
                     Err(alt) => {
                         self.on_found.on_pattern(&alt.pattern);
-                        let iter = [Ok(&alt.pattern), Err(&alt.expr)];
-                        let (_, sel) = self.select_spanned(iter.iter().cloned(), |x| match *x {
+                        let iter = once(Ok(&alt.pattern))
+                            .chain(alt.guard.iter().map(Err))
+                            .chain(once(Err(&alt.expr)));
+                        let (_, sel) = self.select_spanned(iter, |x| match *x {
                             Ok(p) => p.span,
                             Err(e) => e.span,
                         });
@@ -0,0 +1,399 @@
+use std::{
+    fs,
+    sync::mpsc::{self, Sender},
+    thread::JoinHandle,
+};
+
+use serde_json::{json, Value};
+
+use gluon::{
+    base::filename_to_module,
+    debugger::{Command, Debugger, FrameInfo, PauseReason, Paused, Variable},
+    vm::{
+        api::{Hole, OpaqueValue},
+        internal::ValuePrinter,
+    },
+    RootedThread, ThreadExt,
+};
+
+use crate::protocol::{Output, Request};
+
+/// Gluon code runs on a single thread so that is the only thread reported to the client
+const THREAD_ID: u64 = 1;
+
+/// Messages sent to the thread running the program, answered the next time it is paused
+enum Query {
+    Inspect(Box<dyn FnOnce(&mut Paused<'_>) + Send>),
+    Resume(Command),
+}
+
+/// A response which must be sent once the program has paused. Responds with an error if the
+/// program finishes before that happens.
+struct PendingResponse {
+    output: Output,
+    request: Request,
+    responded: bool,
+}
+
+impl PendingResponse {
+    fn respond(mut self, result: Result<Value, String>) {
+        self.output.respond(&self.request, result);
+        self.responded = true;
+    }
+}
+
+impl Drop for PendingResponse {
+    fn drop(&mut self) {
+        if !self.responded {
+            self.output
+                .respond(&self.request, Err("The program is not paused".to_string()));
+        }
+    }
+}
+
+struct Launch {
+    program: String,
+    stop_on_entry: bool,
+}
+
+struct Program {
+    queries: Sender<Query>,
+    handle: JoinHandle<()>,
+}
+
+pub struct Adapter {
+    thread: RootedThread,
+    output: Output,
+    debugger: Debugger,
+    launch: Option<Launch>,
+    configured: bool,
+    program: Option<Program>,
+}
+
+impl Adapter {
+    pub fn new(thread: RootedThread, output: Output) -> Self {
+        Adapter {
+            thread,
+            output,
+            debugger: Debugger::new(),
+            launch: None,
+            configured: false,
+            program: None,
+        }
+    }
+
+    /// Handles a single request. Returns `true` once the client has disconnected.
+    pub fn handle(&mut self, content: &str) -> bool {
+        let request: Request = match serde_json::from_str(content) {
+            Ok(request) => request,
+            Err(err) => {
+                error!("Invalid request `{}`: {}", content, err);
+                return false;
+            }
+        };
+        debug!("Request: {} {}", request.command, request.arguments);
+
+        let result = match &request.command[..] {
+            "initialize" => {
+                self.output.respond(
+                    &request,
+                    Ok(json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsEvaluateForHovers": true,
+                        "supportsTerminateRequest": true,
+                    })),
+                );
+                self.output.event("initialized", Value::Null);
+                return false;
+            }
+            "launch" => self.launch(&request.arguments),
+            "configurationDone" => {
+                self.configured = true;
+                self.start()
+            }
+            "setBreakpoints" => self.set_breakpoints(&request.arguments),
+            "threads" => Ok(json!({
+                "threads": [{ "id": THREAD_ID, "name": "main" }],
+            })),
+            "scopes" => {
+                let level = request.arguments["frameId"].as_u64().unwrap_or(0);
+                Ok(json!({
+                    "scopes": [
+                        {
+                            "name": "Locals",
+                            "variablesReference": level * 2 + 1,
+                            "expensive": false,
+                        },
+                        {
+                            "name": "Closure",
+                            "variablesReference": level * 2 + 2,
+                            "expensive": false,
+                        },
+                    ],
+                }))
+            }
+            "stackTrace" | "variables" | "evaluate" => {
+                self.inspect(request);
+                return false;
+            }
+            "continue" => self
+                .resume(Command::Continue)
+                .map(|()| json!({ "allThreadsContinued": true })),
+            "next" => self.resume(Command::StepOver).map(|()| Value::Null),
+            "stepIn" => self.resume(Command::StepIn).map(|()| Value::Null),
+            "stepOut" => self.resume(Command::StepOut).map(|()| Value::Null),
+            "pause" => {
+                self.debugger.pause();
+                Ok(Value::Null)
+            }
+            "terminate" => {
+                self.stop(true);
+                Ok(Value::Null)
+            }
+            "disconnect" => {
+                let terminate = request.arguments["terminateDebuggee"]
+                    .as_bool()
+                    .unwrap_or(true);
+                self.stop(terminate);
+                self.output.respond(&request, Ok(Value::Null));
+                return true;
+            }
+            command => Err(format!("Unsupported command `{}`", command)),
+        };
+        self.output.respond(&request, result);
+        false
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<Value, String> {
+        let program = arguments["program"]
+            .as_str()
+            .ok_or_else(|| "Expected the path of the program to debug in `program`".to_string())?;
+        self.launch = Some(Launch {
+            program: program.to_string(),
+            stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+        self.start()
+    }
+
+    /// Starts the program once it has been launched and all breakpoints have been configured
+    fn start(&mut self) -> Result<Value, String> {
+        let launch = match &self.launch {
+            Some(launch) if self.configured && self.program.is_none() => launch,
+            _ => return Ok(Value::Null),
+        };
+        let source = fs::read_to_string(&launch.program)
+            .map_err(|err| format!("Unable to read `{}`: {}", launch.program, err))?;
+        let module = filename_to_module(&launch.program);
+
+        self.thread.run_io(true);
+        let thread = self.thread.new_thread().map_err(|err| err.to_string())?;
+
+        let (queries, receiver) = mpsc::channel();
+        self.debugger.stop_on_entry(launch.stop_on_entry);
+        let output = self.output.clone();
+        self.debugger
+            .attach(&thread, move |paused| {
+                let reason = match paused.reason() {
+                    PauseReason::Entry => "entry",
+                    PauseReason::Pause => "pause",
+                    PauseReason::Breakpoint(_) => "breakpoint",
+                    PauseReason::Step => "step",
+                };
+                output.event(
+                    "stopped",
+                    json!({
+                        "reason": reason,
+                        "threadId": THREAD_ID,
+                        "allThreadsStopped": true,
+                    }),
+                );
+                loop {
+                    match receiver.recv() {
+                        Ok(Query::Inspect(f)) => f(paused),
+                        Ok(Query::Resume(command)) => return command,
+                        Err(_) => return Command::Terminate,
+                    }
+                }
+            })
+            .map_err(|err| err.to_string())?;
+
+        let debugger = self.debugger.clone();
+        let output = self.output.clone();
+        let handle = std::thread::spawn(move || {
+            let result = thread.run_expr::<OpaqueValue<RootedThread, Hole>>(&module, &source);
+            debugger.detach(&thread);
+
+            let exit_code = match result {
+                Ok((value, typ)) => {
+                    let env = thread.get_env();
+                    let debug_level = thread.global_env().get_debug_level();
+                    let value = ValuePrinter::new(&env, &typ, value.get_variant(), &debug_level)
+                        .width(80)
+                        .max_level(5)
+                        .to_string();
+                    output.output("console", &format!("{} : {}\n", value, typ));
+                    0
+                }
+                Err(err) => {
+                    output.output("stderr", &format!("{}\n", err));
+                    1
+                }
+            };
+            output.event("exited", json!({ "exitCode": exit_code }));
+            output.event("terminated", Value::Null);
+        });
+        self.program = Some(Program { queries, handle });
+        Ok(Value::Null)
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Result<Value, String> {
+        let path = arguments["source"]["path"]
+            .as_str()
+            .ok_or_else(|| "Expected a source with a `path`".to_string())?;
+        let lines = match arguments["breakpoints"].as_array() {
+            Some(breakpoints) => breakpoints
+                .iter()
+                .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                .collect::<Vec<_>>(),
+            // `lines` is deprecated but may still be sent by older clients
+            None => arguments["lines"]
+                .as_array()
+                .map(|lines| lines.iter().filter_map(|line| line.as_u64()).collect())
+                .unwrap_or_default(),
+        };
+
+        self.debugger
+            .set_breakpoints(path, lines.iter().map(|&line| line as usize));
+        Ok(json!({
+            "breakpoints": lines
+                .iter()
+                .map(|line| json!({ "verified": true, "line": line }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    fn send(&self, query: Query) -> Result<(), String> {
+        self.program
+            .as_ref()
+            .and_then(|program| program.queries.send(query).ok())
+            .ok_or_else(|| "The program is not running".to_string())
+    }
+
+    fn resume(&self, command: Command) -> Result<(), String> {
+        self.send(Query::Resume(command))
+    }
+
+    /// Answers `request` from the thread running the program once it has paused
+    fn inspect(&self, request: Request) {
+        let program = self
+            .launch
+            .as_ref()
+            .map(|launch| launch.program.clone())
+            .unwrap_or_default();
+        let pending = PendingResponse {
+            output: self.output.clone(),
+            request,
+            responded: false,
+        };
+        // If the program has already finished the query is dropped which responds with an error
+        let _ = self.send(Query::Inspect(Box::new(move |paused| {
+            let arguments = &pending.request.arguments;
+            let result = match &pending.request.command[..] {
+                "stackTrace" => Ok(stack_trace(&program, &paused.frames())),
+                "variables" => {
+                    let reference = arguments["variablesReference"].as_u64().unwrap_or(0);
+                    if reference == 0 {
+                        Err("Invalid variables reference".to_string())
+                    } else {
+                        let level = ((reference - 1) / 2) as usize;
+                        let variables = if reference % 2 == 1 {
+                            paused.locals(level)
+                        } else {
+                            paused.upvars(level)
+                        };
+                        Ok(json!({
+                            "variables": variables.iter().map(variable).collect::<Vec<_>>(),
+                        }))
+                    }
+                }
+                "evaluate" => {
+                    let level = arguments["frameId"].as_u64().unwrap_or(0) as usize;
+                    let expr = arguments["expression"].as_str().unwrap_or("");
+                    paused
+                        .evaluate(level, expr)
+                        .map(|variable| {
+                            json!({
+                                "result": variable.value,
+                                "type": variable.typ.to_string(),
+                                "variablesReference": 0,
+                            })
+                        })
+                        .map_err(|err| err.to_string())
+                }
+                command => Err(format!("Unsupported command `{}`", command)),
+            };
+            pending.respond(result);
+        })));
+    }
+
+    /// Stops the debugged program, either by terminating it or by letting it run to completion
+    /// without the debugger pausing it again
+    pub fn stop(&mut self, terminate: bool) {
+        if let Some(Program { queries, handle }) = self.program.take() {
+            if terminate {
+                // Make sure the program pauses so that it receives the command
+                self.debugger.pause();
+                let _ = queries.send(Query::Resume(Command::Terminate));
+            } else {
+                self.debugger.clear_breakpoints();
+                let _ = queries.send(Query::Resume(Command::Continue));
+            }
+            // Any later pause sees the closed channel and terminates the program
+            drop(queries);
+            if handle.join().is_err() {
+                error!("The debugged program panicked");
+            }
+        }
+    }
+}
+
+fn stack_trace(program: &str, frames: &[FrameInfo]) -> Value {
+    let program_module = filename_to_module(program);
+    let frames = frames
+        .iter()
+        .enumerate()
+        .map(|(level, frame)| {
+            let source = if frame.source_name == program_module {
+                json!({ "name": frame.source_name, "path": program })
+            } else {
+                json!({ "name": frame.source_name })
+            };
+            json!({
+                "id": level,
+                "name": frame.function_name.as_deref().unwrap_or("<unknown>"),
+                "source": source,
+                "line": frame.line.unwrap_or(0),
+                "column": 1,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "totalFrames": frames.len(),
+        "stackFrames": frames,
+    })
+}
+
+fn variable(variable: &Variable) -> Value {
+    json!({
+        "name": variable.name,
+        "value": variable.value,
+        "type": variable.typ.to_string(),
+        "variablesReference": 0,
+    })
+}
+
+impl Drop for Adapter {
+    fn drop(&mut self) {
+        self.stop(true);
+    }
+}
@@ -0,0 +1,33 @@
+//! Debug adapter for gluon, communicating over stdin/stdout as specified by the
+//! [debug adapter protocol](https://microsoft.github.io/debug-adapter-protocol/).
+//!
+//! A single program is launched per session. Breakpoints, stepping, pausing, stack traces,
+//! inspecting locals and upvars and evaluating expressions in a paused frame are supported.
+#![doc(html_root_url = "https://docs.rs/gluon_debug_adapter/0.17.2")] // # GLUON
+
+#[macro_use]
+extern crate log;
+
+mod adapter;
+pub mod protocol;
+
+use std::io::{self, BufRead};
+
+use gluon::RootedThread;
+
+pub use crate::adapter::Adapter;
+
+use crate::protocol::Output;
+
+/// Runs a debug adapter which reads requests from `input` and writes responses and events to
+/// `output`. Returns once the client has disconnected or `input` is closed, terminating the
+/// debugged program if it is still running.
+pub fn run(thread: RootedThread, input: &mut dyn BufRead, output: Output) -> io::Result<()> {
+    let mut adapter = Adapter::new(thread, output);
+    while let Some(content) = protocol::read_message(input)? {
+        if adapter.handle(&content) {
+            break;
+        }
+    }
+    Ok(())
+}
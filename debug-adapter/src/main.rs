@@ -0,0 +1,80 @@
+//! Debug adapter for the gluon programming language
+#![doc(html_root_url = "https://docs.rs/gluon_debug_adapter/0.17.2")] // # GLUON
+
+#[macro_use]
+extern crate log;
+
+use std::{io, process};
+
+use gluon_debug_adapter::protocol::Output;
+
+#[cfg(feature = "env_logger")]
+fn init_env_logger() {
+    let _ = ::env_logger::try_init();
+}
+
+#[cfg(not(feature = "env_logger"))]
+fn init_env_logger() {}
+
+/// Moves the protocol off of the stdout file descriptor so that anything the debugged program
+/// prints is forwarded to the client as `output` events instead of corrupting the protocol
+#[cfg(unix)]
+fn protocol_output() -> io::Result<Output> {
+    use std::{
+        fs::File,
+        io::Read,
+        os::unix::io::{FromRawFd, RawFd},
+    };
+
+    fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+
+    let mut fds: [RawFd; 2] = [0; 2];
+    // SAFETY: The file descriptors are only used through the `File`s that take ownership of them
+    let (protocol, mut program_output) = unsafe {
+        let protocol = check(libc::dup(libc::STDOUT_FILENO))?;
+        check(libc::pipe(fds.as_mut_ptr()))?;
+        check(libc::dup2(fds[1], libc::STDOUT_FILENO))?;
+        libc::close(fds[1]);
+        (File::from_raw_fd(protocol), File::from_raw_fd(fds[0]))
+    };
+
+    let output = Output::new(Box::new(protocol));
+    {
+        let output = output.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            loop {
+                match program_output.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => output.output("stdout", &String::from_utf8_lossy(&buffer[..n])),
+                }
+            }
+        });
+    }
+    Ok(output)
+}
+
+#[cfg(not(unix))]
+fn protocol_output() -> io::Result<Output> {
+    Ok(Output::new(Box::new(io::stdout())))
+}
+
+fn main() {
+    init_env_logger();
+
+    let result = protocol_output().and_then(|output| {
+        let thread = gluon::new_vm();
+        let stdin = io::stdin();
+        gluon_debug_adapter::run(thread, &mut stdin.lock(), output)
+    });
+    if let Err(err) = result {
+        error!("{}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,142 @@
+//! Messages of the debug adapter protocol and the `Content-Length` framing they are sent with
+use std::{
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex},
+};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub seq: u64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Reads the content of the next message from `input`. Returns `None` once `input` is exhausted.
+pub fn read_message(input: &mut dyn BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            // Tolerate stray newlines between messages
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        let mut parts = header.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("Content-Length") {
+            content_length = Some(value.parse::<usize>().map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid Content-Length `{}`: {}", value, err),
+                )
+            })?);
+        }
+    }
+
+    let mut content = vec![0; content_length.unwrap_or(0)];
+    input.read_exact(&mut content)?;
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes `message` to `output`, preceded by its `Content-Length` header
+pub fn write_message(output: &mut dyn Write, message: &Value) -> io::Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+struct OutputInner {
+    writer: Box<dyn Write + Send>,
+    seq: u64,
+}
+
+/// Writes responses and events to the client. Responses may be sent from the thread running the
+/// debugged program as well as from the thread reading requests so every message is numbered and
+/// written while holding a lock.
+#[derive(Clone)]
+pub struct Output(Arc<Mutex<OutputInner>>);
+
+impl Output {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Output(Arc::new(Mutex::new(OutputInner { writer, seq: 0 })))
+    }
+
+    fn send(&self, mut message: Value) {
+        let mut inner = self.0.lock().unwrap();
+        inner.seq += 1;
+        message["seq"] = json!(inner.seq);
+        if let Err(err) = write_message(&mut inner.writer, &message) {
+            error!("Unable to write message: {}", err);
+        }
+    }
+
+    pub fn respond(&self, request: &Request, result: Result<Value, String>) {
+        let mut message = json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Value::Null) => (),
+            Ok(body) => message["body"] = body,
+            Err(msg) => message["message"] = json!(msg),
+        }
+        self.send(message);
+    }
+
+    pub fn event(&self, event: &str, body: Value) {
+        let mut message = json!({
+            "type": "event",
+            "event": event,
+        });
+        if !body.is_null() {
+            message["body"] = body;
+        }
+        self.send(message);
+    }
+
+    /// Sends `output` to be displayed in the debug console of the client
+    pub fn output(&self, category: &str, output: &str) {
+        self.event(
+            "output",
+            json!({
+                "category": category,
+                "output": output,
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_framed_messages() {
+        let mut input =
+            &b"Content-Length: 2\r\n\r\n{}Content-Length: 4\r\nContent-Type: utf-8\r\n\r\nnull"[..];
+        assert_eq!(read_message(&mut input).unwrap(), Some("{}".to_string()));
+        assert_eq!(read_message(&mut input).unwrap(), Some("null".to_string()));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    io::{self, BufReader, Read, Write},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+use gluon_debug_adapter::protocol::{self, Output};
+
+const PROGRAM: &str = "tests/count.glu";
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Feeds the requests of a session to the adapter as they are sent
+struct Input {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    buffer: io::Cursor<Vec<u8>>,
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.position() as usize == self.buffer.get_ref().len() {
+            match self.receiver.recv() {
+                Ok(message) => self.buffer = io::Cursor::new(message),
+                Err(_) => return Ok(0),
+            }
+        }
+        self.buffer.read(buf)
+    }
+}
+
+/// A session with an adapter running on another thread. As with a real client, requests which
+/// depend on the state of the program are only sent after waiting for the events the adapter
+/// sends.
+struct Session {
+    sender: mpsc::Sender<Vec<u8>>,
+    output: SharedBuffer,
+    adapter: Option<thread::JoinHandle<()>>,
+    next_seq: u64,
+}
+
+impl Session {
+    fn new() -> Self {
+        let _ = env_logger::try_init();
+
+        let (sender, receiver) = mpsc::channel();
+        let output = SharedBuffer::default();
+        let adapter = {
+            let output = Output::new(Box::new(output.clone()));
+            thread::spawn(move || {
+                let mut input = BufReader::new(Input {
+                    receiver,
+                    buffer: io::Cursor::new(Vec::new()),
+                });
+                gluon_debug_adapter::run(gluon::new_vm(), &mut input, output).unwrap();
+            })
+        };
+        let mut session = Session {
+            sender,
+            output,
+            adapter: Some(adapter),
+            next_seq: 1,
+        };
+        session.request("initialize", json!({ "adapterID": "gluon" }));
+        session
+    }
+
+    fn request(&mut self, command: &str, arguments: Value) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let message = json!({
+            "seq": seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        let mut input = Vec::new();
+        protocol::write_message(&mut input, &message).unwrap();
+        self.sender.send(input).unwrap();
+        seq
+    }
+
+    fn launch(&mut self, breakpoints: &[u64], stop_on_entry: bool) {
+        self.request(
+            "setBreakpoints",
+            json!({
+                "source": { "path": PROGRAM },
+                "breakpoints": breakpoints.iter().map(|line| json!({ "line": line })).collect::<Vec<_>>(),
+            }),
+        );
+        self.request(
+            "launch",
+            json!({ "program": PROGRAM, "stopOnEntry": stop_on_entry }),
+        );
+        self.request("configurationDone", json!({}));
+    }
+
+    fn messages(&self) -> Messages {
+        let output = self.output.0.lock().unwrap();
+        let mut output = &output[..];
+        let mut messages = Vec::new();
+        while let Some(content) = protocol::read_message(&mut output).unwrap() {
+            messages.push(serde_json::from_str(&content).unwrap());
+        }
+        Messages(messages)
+    }
+
+    /// Waits until the adapter has sent `count` events named `event`
+    fn wait_for(&self, event: &str, count: usize) {
+        for _ in 0..1000 {
+            if self.messages().events(event).len() >= count {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("Timed out waiting for `{}` event {}", event, count);
+    }
+
+    /// Disconnects from the adapter and returns every message it sent
+    fn run(mut self, terminate: bool) -> Messages {
+        self.request("disconnect", json!({ "terminateDebuggee": terminate }));
+        self.adapter.take().unwrap().join().unwrap();
+        self.messages()
+    }
+}
+
+struct Messages(Vec<Value>);
+
+impl Messages {
+    fn body(&self, seq: u64) -> &Value {
+        let response = self
+            .0
+            .iter()
+            .find(|message| message["type"] == "response" && message["request_seq"] == json!(seq))
+            .unwrap_or_else(|| panic!("Missing response for request {}", seq));
+        assert_eq!(response["success"], json!(true), "{}", response);
+        &response["body"]
+    }
+
+    fn events(&self, event: &str) -> Vec<&Value> {
+        self.0
+            .iter()
+            .filter(|message| message["type"] == "event" && message["event"] == event)
+            .map(|message| &message["body"])
+            .collect()
+    }
+}
+
+#[test]
+fn run_to_completion() {
+    let mut session = Session::new();
+    session.launch(&[], false);
+    session.wait_for("terminated", 1);
+    let messages = session.run(false);
+
+    assert_eq!(messages.events("initialized").len(), 1);
+    assert_eq!(messages.events("stopped").len(), 0);
+    assert_eq!(
+        messages.events("output"),
+        vec![&json!({ "category": "console", "output": "22 : Int\n" })]
+    );
+    assert_eq!(messages.events("exited"), vec![&json!({ "exitCode": 0 })]);
+    assert_eq!(messages.events("terminated").len(), 1);
+}
+
+#[test]
+fn inspect_paused_program() {
+    let mut session = Session::new();
+    session.launch(&[4], false);
+    session.wait_for("stopped", 1);
+    let stack_trace = session.request("stackTrace", json!({ "threadId": 1 }));
+    let scopes = session.request("scopes", json!({ "frameId": 0 }));
+    let locals = session.request("variables", json!({ "variablesReference": 1 }));
+    let upvars = session.request("variables", json!({ "variablesReference": 2 }));
+    let evaluate = session.request("evaluate", json!({ "expression": "x + y", "frameId": 0 }));
+    session.request("next", json!({ "threadId": 1 }));
+    session.wait_for("stopped", 2);
+    let stepped = session.request("stackTrace", json!({ "threadId": 1 }));
+    session.request("continue", json!({ "threadId": 1 }));
+    session.wait_for("terminated", 1);
+    let messages = session.run(false);
+
+    let stopped = messages.events("stopped");
+    assert_eq!(stopped.len(), 2);
+    assert_eq!(stopped[0]["reason"], "breakpoint");
+    assert_eq!(stopped[1]["reason"], "step");
+
+    let frames = &messages.body(stack_trace)["stackFrames"];
+    assert_eq!(
+        frames[0],
+        json!({
+            "id": 0,
+            "name": "f",
+            "source": { "name": "tests.count", "path": PROGRAM },
+            "line": 4,
+            "column": 1,
+        })
+    );
+    assert_eq!(frames[1]["line"], json!(5));
+
+    assert_eq!(
+        messages.body(scopes)["scopes"][1]["variablesReference"],
+        json!(2)
+    );
+    assert_eq!(
+        messages.body(locals)["variables"],
+        json!([
+            { "name": "x", "value": "10", "type": "Int", "variablesReference": 0 },
+            { "name": "y", "value": "11", "type": "Int", "variablesReference": 0 },
+        ])
+    );
+    assert_eq!(messages.body(upvars)["variables"][0]["name"], "add");
+    assert_eq!(
+        *messages.body(evaluate),
+        json!({ "result": "21", "type": "Int", "variablesReference": 0 })
+    );
+    assert_eq!(messages.body(stepped)["stackFrames"][0]["line"], json!(6));
+
+    assert_eq!(messages.events("exited"), vec![&json!({ "exitCode": 0 })]);
+}
+
+#[test]
+fn terminate_on_disconnect() {
+    let mut session = Session::new();
+    session.launch(&[], true);
+    session.wait_for("stopped", 1);
+    let messages = session.run(true);
+
+    let stopped = messages.events("stopped");
+    assert_eq!(stopped.len(), 1);
+    assert_eq!(stopped[0]["reason"], "entry");
+    assert_eq!(messages.events("exited"), vec![&json!({ "exitCode": 1 })]);
+}
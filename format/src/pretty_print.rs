@@ -201,6 +201,15 @@ where
 
             Expr::Ident(ref id) => pretty_types::ident(arena, id.name.as_ref() as &str),
 
+            Expr::Hole(ref id) => {
+                let name: &str = id.name.as_ref();
+                if name == "_" {
+                    arena.text("_")
+                } else {
+                    chain![arena, "?", name]
+                }
+            }
+
             Expr::IfElse(..) => self.pretty_if_expr(expr),
 
             Expr::Infix {
@@ -315,7 +324,8 @@ where
                     || text.starts_with(|c: char| c.is_digit(10))
                     || text.starts_with('-')
                     || text.starts_with("r\"")
-                    || text.starts_with("r#");
+                    || text.starts_with("r#")
+                    || text.starts_with("f\"");
                 if literally {
                     arena.text(text)
                 } else {
@@ -340,6 +350,10 @@ where
                                 arena,
                                 "| ",
                                 self.pretty_pattern(&alt.pattern),
+                                match alt.guard {
+                                    Some(ref guard) => chain![arena, " if ", pretty(guard)],
+                                    None => arena.nil(),
+                                },
                                 " ->",
                                 self.hang(
                                     arena.nil(),
@@ -943,6 +957,40 @@ where
                 ")"
             ]
             .group(),
+            Pattern::Array {
+                ref elems, rest, ..
+            } => chain![
+                arena,
+                "[",
+                arena.concat(self.comma_sep_paren(
+                    elems.iter().enumerate().map(|(i, elem)| {
+                        let doc = if rest == Some(i) {
+                            match elem.value {
+                                Pattern::Ident(ref id) if id.name.as_ref() != "_" => {
+                                    chain![arena, "..", id.name.as_ref() as &str]
+                                }
+                                _ => arena.text(".."),
+                            }
+                        } else {
+                            self.pretty_pattern(elem)
+                        };
+                        pos::spanned(elem.span, doc)
+                    }),
+                    |elem| elem.value
+                )),
+                "]"
+            ]
+            .group(),
+            Pattern::Or(ref patterns) => prec.enclose(
+                Prec::Function,
+                arena,
+                arena.intersperse(
+                    patterns
+                        .iter()
+                        .map(|pattern| self.pretty_pattern_(pattern, Prec::Function)),
+                    " | ",
+                ),
+            ),
             Pattern::Error => arena.text("<error>"),
             Pattern::Literal(_) => arena.text(self.source.src_slice(pattern.span)),
         }
@@ -75,6 +75,15 @@ r##"abc
     assert_eq!(&format_expr(expr).unwrap(), expr);
 }
 
+#[test]
+fn interpolated_string_literal() {
+    let expr = r#"
+let x = 1
+f"x = {x  +  1}, {{}} {f"{x}"}" ++ f"\t"
+"#;
+    assert_eq!(&format_expr(expr).unwrap(), expr);
+}
+
 #[test]
 fn long_tuple() {
     let expr = r#"
@@ -479,7 +488,8 @@ Test
 #[derive(Show)]
 type Test =
     | Test
-rec let show_Test : Show Test =
+rec #[allow(warnings)]
+let show_Test : Show Test =
     rec let show_ x : Test -> String =
         match x with
         | Test -> "Test"
@@ -501,7 +511,8 @@ End
 type Recursive =
     | End
     | Rec Recursive
-rec let eq_Recursive : Eq Recursive =
+rec #[allow(warnings)]
+let eq_Recursive : Eq Recursive =
     rec let eq l r : Recursive -> Recursive -> _ =
         match (l, r) with
         | (End, End) -> True
@@ -525,7 +536,8 @@ Test 1
 #[derive(Show)]
 type Test a =
     | Test a
-rec let show_Test : [Show a] -> Show (Test a) =
+rec #[allow(warnings)]
+let show_Test : [Show a] -> Show (Test a) =
     rec let show_ x : Test a -> String =
         match x with
         | Test arg_0 -> "Test" ++ " " ++ "(" ++ show arg_0 ++ ")"
@@ -560,11 +572,13 @@ type Test2 a =
     | Nil
 in
 rec
+#[allow(warnings)]
 let show_Test : [Show a] -> Show (Test a) =
     rec let show_ x : Test a -> String =
         match x with
         | Test arg_0 -> "Test" ++ " " ++ "(" ++ show arg_0 ++ ")"
     { show = show_ }
+#[allow(warnings)]
 let show_Test2 : [Show a] -> Show (Test2 a) =
     rec let show_ x : Test2 a -> String =
         match x with
@@ -586,7 +600,8 @@ type Record = { x : Int }
     let expected = r#"
 #[derive(Deserialize)]
 type Record = { x : Int }
-rec let deserialize_Record : Deserialize Record =
+rec #[allow(warnings)]
+let deserialize_Record : Deserialize Record =
     let { ValueDeserializer, deserializer, field, ? } = import! std.json.de
     let { map } = import! std.functor
     let { (<*>) } = import! std.applicative
@@ -608,7 +623,8 @@ type Record = { x : Int, y : Float }
     let expected = r#"
 #[derive(Deserialize)]
 type Record = { x : Int, y : Float }
-rec let deserialize_Record : Deserialize Record =
+rec #[allow(warnings)]
+let deserialize_Record : Deserialize Record =
     let { ValueDeserializer, deserializer, field, ? } = import! std.json.de
     let { map } = import! std.functor
     let { (<*>) } = import! std.applicative
@@ -631,7 +647,8 @@ type Record = { x : Int }
     let expected = r#"
 #[derive(Serialize)]
 type Record = { x : Int }
-rec let serialize_Record : Serialize Record =
+rec #[allow(warnings)]
+let serialize_Record : Serialize Record =
     let { ? } = import! std.result
     let { ValueSerializer, Value, serialize, ? } = import! std.json.ser
     let { map } = import! std.functor
@@ -659,7 +676,8 @@ type Variant = | Int Int | String String
 type Variant =
     | Int Int
     | String String
-rec let serialize_Variant : Serialize Variant =
+rec #[allow(warnings)]
+let serialize_Variant : Serialize Variant =
     let { ? } = import! std.result
     let { ValueSerializer, Value, serialize, ? } = import! std.json.ser
     let { map } = import! std.functor
@@ -837,3 +855,36 @@ io.println "World"
     "#]]
     .assert_eq(&format_expr(expr).unwrap());
 }
+
+#[test]
+fn match_guard_and_or_pattern() {
+    let expr = r#"
+match x with
+| A y | B (C y | D y) if y > 0 -> y
+| z @ (A _ | B _) -> 1
+| _ -> 0
+"#;
+    assert_eq!(&format_expr(expr).unwrap(), expr);
+}
+
+#[test]
+fn array_pattern() {
+    let expr = r#"
+match x with
+| [] -> 0
+| [y] -> y
+| [y, .., z] -> y + z
+| [_, ..rest] -> 1
+"#;
+    assert_eq!(&format_expr(expr).unwrap(), expr);
+}
+
+#[test]
+fn typed_holes() {
+    let expr = r#"
+let f x : Int -> Int = add x _
+let g : String -> Int = \s -> ?result
+f (?y)
+"#;
+    assert_eq!(&format_expr(expr).unwrap(), expr);
+}
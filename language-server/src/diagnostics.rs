@@ -0,0 +1,75 @@
+//! Conversion of gluon's errors and warnings into language server diagnostics
+use std::fmt;
+
+use gluon::{
+    base::{error::InFile, source::FileMap},
+    Error, Warnings,
+};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+
+use crate::position;
+
+const SOURCE: &str = "gluon";
+
+/// Collects the diagnostics of `error`. Errors which do not originate from `file_map` (such as
+/// errors in an imported module) are reported at the start of the document.
+pub fn from_error(file_map: &FileMap, error: &Error, diagnostics: &mut Vec<Diagnostic>) {
+    match error {
+        Error::Parse(err) => from_in_file(file_map, err, DiagnosticSeverity::Error, diagnostics),
+        Error::Typecheck(err) => {
+            from_in_file(file_map, err, DiagnosticSeverity::Error, diagnostics)
+        }
+        Error::Macro(err) => from_in_file(file_map, err, DiagnosticSeverity::Error, diagnostics),
+        Error::Multiple(errors) => {
+            for err in errors {
+                from_error(file_map, err, diagnostics);
+            }
+        }
+        Error::IO(_) | Error::VM(_) | Error::Other(_) => diagnostics.push(diagnostic(
+            Range::default(),
+            DiagnosticSeverity::Error,
+            error.to_string(),
+        )),
+    }
+}
+
+pub fn from_warnings(file_map: &FileMap, warnings: &Warnings, diagnostics: &mut Vec<Diagnostic>) {
+    from_in_file(file_map, warnings, DiagnosticSeverity::Warning, diagnostics)
+}
+
+fn from_in_file<E>(
+    file_map: &FileMap,
+    in_file: &InFile<E>,
+    severity: DiagnosticSeverity,
+    diagnostics: &mut Vec<Diagnostic>,
+) where
+    E: fmt::Display,
+{
+    for error in in_file.errors() {
+        let (range, message) = match position::range(file_map, error.span) {
+            Some(range) => (range, error.value.to_string()),
+            None => {
+                let source_name = in_file
+                    .source()
+                    .get(error.span.start())
+                    .map_or("<unknown>", |source| source.name());
+                (
+                    Range::default(),
+                    format!("{}: {}", source_name, error.value),
+                )
+            }
+        };
+        diagnostics.push(diagnostic(range, severity, message));
+    }
+}
+
+fn diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
@@ -0,0 +1,37 @@
+//! Language server for gluon, communicating over JSON-RPC as specified by the
+//! [language server protocol](https://microsoft.github.io/language-server-protocol/).
+//!
+//! Diagnostics are published whenever a document is opened or changed and hover, completion,
+//! signature help, go to definition, document symbols and formatting requests are answered.
+#![doc(html_root_url = "https://docs.rs/gluon_language_server/0.17.2")] // # GLUON
+
+#[macro_use]
+extern crate log;
+
+mod diagnostics;
+mod position;
+pub mod rpc;
+mod server;
+
+use std::io::{self, BufRead, Write};
+
+use gluon::RootedThread;
+
+pub use crate::server::Server;
+
+/// Runs a language server which reads messages from `input` and writes responses and
+/// notifications to `output`. Returns the exit code of the server once the client has sent `exit`
+/// or `input` is closed.
+pub fn run(
+    thread: RootedThread,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> io::Result<i32> {
+    let mut server = Server::new(thread);
+    while let Some(content) = rpc::read_message(input)? {
+        if let Some(exit_code) = server.handle(&content, output)? {
+            return Ok(exit_code);
+        }
+    }
+    Ok(1)
+}
@@ -0,0 +1,29 @@
+//! Language server for the gluon programming language
+#![doc(html_root_url = "https://docs.rs/gluon_language_server/0.17.2")] // # GLUON
+
+#[macro_use]
+extern crate log;
+
+use std::{io, process};
+
+#[cfg(feature = "env_logger")]
+fn init_env_logger() {
+    let _ = ::env_logger::try_init();
+}
+
+#[cfg(not(feature = "env_logger"))]
+fn init_env_logger() {}
+
+fn main() {
+    init_env_logger();
+
+    let thread = gluon::new_vm();
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let exit_code = gluon_language_server::run(thread, &mut stdin.lock(), &mut stdout.lock())
+        .unwrap_or_else(|err| {
+            error!("{}", err);
+            1
+        });
+    process::exit(exit_code)
+}
@@ -0,0 +1,118 @@
+//! Conversions between the byte positions used by gluon and the line/UTF-16 column positions used
+//! by the language server protocol
+use gluon::base::{
+    pos::{ByteOffset, BytePos, Span},
+    source::FileMap,
+};
+
+use lsp_types::{Position, Range};
+
+/// Returns the byte offset into `source` which `position` refers to. Positions past the end of a
+/// line or past the end of the source are clamped to the end of the line or source.
+pub fn byte_offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match source[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return source.len(),
+        }
+    }
+
+    let line = &source[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut utf16_column = 0;
+    for (i, c) in line.char_indices() {
+        if utf16_column >= position.character {
+            return line_start + i;
+        }
+        utf16_column += c.len_utf16() as u32;
+    }
+    line_start + line.trim_end_matches('\r').len()
+}
+
+/// Returns the `Position` of the byte offset `offset` in `source`
+pub fn position(source: &str, offset: usize) -> Position {
+    let offset = offset.min(source.len());
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..]
+            .chars()
+            .map(|c| c.len_utf16() as u32)
+            .sum(),
+    }
+}
+
+pub fn byte_pos(file_map: &FileMap, position: Position) -> BytePos {
+    file_map.span().start() + ByteOffset::from(byte_offset(file_map.source(), position) as i64)
+}
+
+/// Returns the `Range` of `span` if it lies inside `file_map`
+pub fn range(file_map: &FileMap, span: Span<BytePos>) -> Option<Range> {
+    let file_span = file_map.span();
+    if file_span.start() <= span.start() && span.end() <= file_span.end() {
+        let start = (span.start() - file_span.start()).to_usize();
+        let end = (span.end() - file_span.start()).to_usize();
+        Some(Range {
+            start: position(file_map.source(), start),
+            end: position(file_map.source(), end),
+        })
+    } else {
+        None
+    }
+}
+
+/// Applies an edit of `range` to `source`, replacing the range with `text`
+pub fn apply_edit(source: &mut String, range: Range, text: &str) {
+    let start = byte_offset(source, range.start);
+    let end = byte_offset(source, range.end).max(start);
+    source.replace_range(start..end, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn offsets_count_utf16_columns() {
+        let source = "let x = 1\nlet å𝄞 = \"y\"\nx";
+        assert_eq!(byte_offset(source, pos(0, 4)), 4);
+        assert_eq!(byte_offset(source, pos(1, 4)), 14);
+        assert_eq!(byte_offset(source, pos(1, 7)), 20);
+        assert_eq!(byte_offset(source, pos(1, 100)), 26);
+        assert_eq!(byte_offset(source, pos(2, 0)), 27);
+        assert_eq!(byte_offset(source, pos(10, 0)), source.len());
+
+        assert_eq!(position(source, 14), pos(1, 4));
+        assert_eq!(position(source, 20), pos(1, 7));
+        assert_eq!(position(source, 27), pos(2, 0));
+    }
+
+    #[test]
+    fn apply_edits() {
+        let mut source = "let x = 1\nx".to_string();
+        apply_edit(
+            &mut source,
+            Range {
+                start: pos(0, 8),
+                end: pos(0, 9),
+            },
+            "\"abc\"",
+        );
+        assert_eq!(source, "let x = \"abc\"\nx");
+        apply_edit(
+            &mut source,
+            Range {
+                start: pos(1, 1),
+                end: pos(1, 1),
+            },
+            " ++ x",
+        );
+        assert_eq!(source, "let x = \"abc\"\nx ++ x");
+    }
+}
@@ -0,0 +1,154 @@
+//! JSON-RPC messages and the `Content-Length` framing used by the language server protocol
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+#[derive(Debug, Deserialize)]
+pub struct Incoming {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: Option<String>,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub jsonrpc: &'static str,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+impl Response {
+    pub fn new(id: Value, result: Result<Value, ResponseError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Response {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Notification {
+    pub jsonrpc: &'static str,
+    pub method: &'static str,
+    pub params: Value,
+}
+
+impl Notification {
+    pub fn new(method: &'static str, params: Value) -> Self {
+        Notification {
+            jsonrpc: "2.0",
+            method,
+            params,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResponseError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl ResponseError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        ResponseError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Reads the content of the next message from `input`. Returns `None` once `input` is exhausted.
+pub fn read_message(input: &mut dyn BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            // Tolerate stray newlines between messages
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        let mut parts = header.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("Content-Length") {
+            content_length = Some(value.parse::<usize>().map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid Content-Length `{}`: {}", value, err),
+                )
+            })?);
+        }
+    }
+
+    let mut content = vec![0; content_length.unwrap_or(0)];
+    input.read_exact(&mut content)?;
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes `message` to `output`, preceded by its `Content-Length` header
+pub fn write_message<T>(output: &mut dyn Write, message: &T) -> io::Result<()>
+where
+    T: Serialize,
+{
+    let content = serde_json::to_string(message)?;
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_framed_messages() {
+        let mut input =
+            &b"Content-Length: 2\r\n\r\n{}Content-Length: 4\r\nContent-Type: utf-8\r\n\r\nnull"[..];
+        assert_eq!(read_message(&mut input).unwrap(), Some("{}".to_string()));
+        assert_eq!(read_message(&mut input).unwrap(), Some("null".to_string()));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn write_framed_message() {
+        let mut output = Vec::new();
+        write_message(&mut output, &Notification::new("exit", Value::Null)).unwrap();
+        let expected = r#"{"jsonrpc":"2.0","method":"exit","params":null}"#;
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("Content-Length: {}\r\n\r\n{}", expected.len(), expected)
+        );
+    }
+}
@@ -0,0 +1,624 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use either::Either;
+use serde_json::Value;
+
+use gluon::{
+    base::{
+        ast::{self, Expr, OwnedExpr, Pattern, PatternField, SpannedExpr, SpannedPattern, Visitor},
+        filename_to_module,
+        pos::{BytePos, HasSpan, Span},
+        source::FileMap,
+        symbol::{Symbol, SymbolRef},
+        types::{arg_iter, remove_forall, ArcType},
+    },
+    compiler_pipeline::{Salvage, SalvageResult, TypecheckValue},
+    query::{AsyncCompilation, CompilationBase},
+    RootedThread, ThreadExt,
+};
+
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{
+        Completion, DocumentSymbolRequest, Formatting, GotoDefinition, HoverRequest, Initialize,
+        Request as _, Shutdown, SignatureHelpRequest,
+    },
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentFormattingParams, DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse,
+    Documentation, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, InitializeParams, InitializeResult, Location, MarkupContent,
+    MarkupKind, OneOf, ParameterInformation, ParameterLabel, Position, PublishDiagnosticsParams,
+    Range, ServerCapabilities, ServerInfo, SignatureHelp, SignatureHelpOptions,
+    SignatureHelpParams, SignatureInformation, SymbolKind, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+};
+
+use crate::{
+    diagnostics, position,
+    rpc::{self, Incoming, Notification, Response, ResponseError},
+};
+
+type Module = TypecheckValue<Arc<OwnedExpr<Symbol>>>;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum State {
+    Uninitialized,
+    Running,
+    ShutDown,
+}
+
+struct Document {
+    module: String,
+    text: String,
+    version: i32,
+}
+
+/// A typechecked document along with the source it was checked from
+struct Analysis {
+    file_map: Arc<FileMap>,
+    module: Module,
+}
+
+/// Language server state. Documents are compiled as modules in the database of `thread` which lets
+/// every edit reuse the work done for the modules that the document imports.
+pub struct Server {
+    thread: RootedThread,
+    documents: HashMap<Url, Document>,
+    root: Option<PathBuf>,
+    state: State,
+}
+
+impl Server {
+    pub fn new(thread: RootedThread) -> Self {
+        Server {
+            thread,
+            documents: HashMap::new(),
+            root: None,
+            state: State::Uninitialized,
+        }
+    }
+
+    /// Handles a single message from the client, writing any responses or notifications to
+    /// `output`. Returns the exit code of the server once the client has sent `exit`.
+    pub fn handle(&mut self, content: &str, output: &mut dyn Write) -> io::Result<Option<i32>> {
+        let message: Incoming = match serde_json::from_str(content) {
+            Ok(message) => message,
+            Err(err) => {
+                let error = ResponseError::new(rpc::PARSE_ERROR, err.to_string());
+                rpc::write_message(output, &Response::new(Value::Null, Err(error)))?;
+                return Ok(None);
+            }
+        };
+        match (message.id, message.method) {
+            (Some(id), Some(method)) => {
+                debug!("Request {}: {}", id, method);
+                let result = self.request(&method, message.params);
+                rpc::write_message(output, &Response::new(id, result))?;
+                Ok(None)
+            }
+            (None, Some(method)) => {
+                debug!("Notification: {}", method);
+                self.notification(&method, message.params, output)
+            }
+            // The server does not send any requests so there are no responses to handle
+            (_, None) => Ok(None),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        match self.state {
+            State::Uninitialized if method != Initialize::METHOD => {
+                return Err(ResponseError::new(
+                    rpc::SERVER_NOT_INITIALIZED,
+                    "The server has not been initialized",
+                ))
+            }
+            State::ShutDown => {
+                return Err(ResponseError::new(
+                    rpc::INVALID_REQUEST,
+                    "The server has been shut down",
+                ))
+            }
+            _ => (),
+        }
+
+        match method {
+            Initialize::METHOD => {
+                dispatch::<Initialize, _>(params, |params| self.initialize(params))
+            }
+            Shutdown::METHOD => dispatch::<Shutdown, _>(params, |()| {
+                self.state = State::ShutDown;
+            }),
+            HoverRequest::METHOD => {
+                dispatch::<HoverRequest, _>(params, |params| self.hover(params))
+            }
+            Completion::METHOD => {
+                dispatch::<Completion, _>(params, |params| self.completion(params))
+            }
+            SignatureHelpRequest::METHOD => {
+                dispatch::<SignatureHelpRequest, _>(params, |params| self.signature_help(params))
+            }
+            GotoDefinition::METHOD => {
+                dispatch::<GotoDefinition, _>(params, |params| self.definition(params))
+            }
+            DocumentSymbolRequest::METHOD => {
+                dispatch::<DocumentSymbolRequest, _>(params, |params| self.document_symbols(params))
+            }
+            Formatting::METHOD => dispatch::<Formatting, _>(params, |params| self.format(params)),
+            _ => Err(ResponseError::new(
+                rpc::METHOD_NOT_FOUND,
+                format!("Unknown method `{}`", method),
+            )),
+        }
+    }
+
+    fn notification(
+        &mut self,
+        method: &str,
+        params: Value,
+        output: &mut dyn Write,
+    ) -> io::Result<Option<i32>> {
+        match method {
+            Exit::METHOD => {
+                return Ok(Some(if self.state == State::ShutDown { 0 } else { 1 }));
+            }
+            DidOpenTextDocument::METHOD => {
+                if let Some(params) = parse_params::<DidOpenTextDocumentParams>(params) {
+                    let document = params.text_document;
+                    let module = self.module_name(&document.uri);
+                    self.documents.insert(
+                        document.uri.clone(),
+                        Document {
+                            module,
+                            text: document.text,
+                            version: document.version,
+                        },
+                    );
+                    self.publish_diagnostics(&document.uri, output)?;
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Some(params) = parse_params::<DidChangeTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    if let Some(document) = self.documents.get_mut(&uri) {
+                        for change in params.content_changes {
+                            match change.range {
+                                Some(range) => {
+                                    position::apply_edit(&mut document.text, range, &change.text)
+                                }
+                                None => document.text = change.text,
+                            }
+                        }
+                        document.version = params.text_document.version;
+                        self.publish_diagnostics(&uri, output)?;
+                    }
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Some(params) = parse_params::<DidCloseTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    if self.documents.remove(&uri).is_some() {
+                        let params = PublishDiagnosticsParams::new(uri, Vec::new(), None);
+                        rpc::write_message(
+                            output,
+                            &Notification::new(
+                                PublishDiagnostics::METHOD,
+                                serde_json::to_value(params)?,
+                            ),
+                        )?;
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(None)
+    }
+
+    fn initialize(&mut self, params: InitializeParams) -> InitializeResult {
+        self.root = params.root_uri.and_then(|uri| uri.to_file_path().ok());
+        self.state = State::Running;
+
+        InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::Incremental,
+                )),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec![".".into()]),
+                    ..CompletionOptions::default()
+                }),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec![" ".into(), "(".into()]),
+                    ..SignatureHelpOptions::default()
+                }),
+                definition_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                ..ServerCapabilities::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").into(),
+                version: Some(env!("CARGO_PKG_VERSION").into()),
+            }),
+        }
+    }
+
+    /// Returns the module name of the document at `uri`, relative to the workspace root if the
+    /// document is inside of it
+    fn module_name(&self, uri: &Url) -> String {
+        let path = match uri.to_file_path() {
+            Ok(path) => match &self.root {
+                Some(root) => path
+                    .strip_prefix(root)
+                    .map(|path| path.to_owned())
+                    .unwrap_or(path),
+                None => path,
+            },
+            Err(()) => PathBuf::from(uri.path()),
+        };
+        let path = path.to_string_lossy();
+        filename_to_module(path.trim_start_matches('/'))
+    }
+
+    fn publish_diagnostics(&mut self, uri: &Url, output: &mut dyn Write) -> io::Result<()> {
+        let (file_map, result) = match self.check(uri) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let mut diagnostics = Vec::new();
+        let module = match result {
+            Ok(module) => Some(module),
+            Err(Salvage { value, error }) => {
+                diagnostics::from_error(&file_map, &error, &mut diagnostics);
+                value
+            }
+        };
+        if let Some(warnings) = module.as_ref().and_then(|module| module.warnings.as_ref()) {
+            diagnostics::from_warnings(&file_map, warnings, &mut diagnostics);
+        }
+
+        let version = self.documents.get(uri).map(|document| document.version);
+        let params = PublishDiagnosticsParams::new(uri.clone(), diagnostics, version);
+        rpc::write_message(
+            output,
+            &Notification::new(PublishDiagnostics::METHOD, serde_json::to_value(params)?),
+        )
+    }
+
+    /// Typechecks the document at `uri`. Only the document itself is invalidated when its text
+    /// changes so any imported modules are only checked once.
+    fn check(&self, uri: &Url) -> Option<(Arc<FileMap>, SalvageResult<Module>)> {
+        let document = self.documents.get(uri)?;
+        self.thread
+            .get_database_mut()
+            .add_module(document.module.clone(), &document.text);
+
+        let mut db = self.thread.get_database();
+        let result = futures::executor::block_on(
+            db.typechecked_source_module(document.module.clone(), None),
+        );
+        let file_map = db.get_filemap(&document.module)?;
+        Some((file_map, result))
+    }
+
+    fn analyze(&self, uri: &Url) -> Option<Analysis> {
+        let (file_map, result) = self.check(uri)?;
+        let module = match result {
+            Ok(module) => module,
+            Err(Salvage { value, .. }) => value?,
+        };
+        Some(Analysis { file_map, module })
+    }
+
+    fn hover(&mut self, params: HoverParams) -> Option<Hover> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = params.text_document_position_params;
+        let Analysis { file_map, module } = self.analyze(&text_document.uri)?;
+        let expr = module.expr.expr();
+        let pos = position::byte_pos(&file_map, position);
+
+        let env = self.thread.get_env();
+        let (typ, span) = gluon_completion::completion(
+            (
+                gluon_completion::TypeAt { env: &env },
+                gluon_completion::SpanAt,
+            ),
+            file_map.span(),
+            expr,
+            pos,
+        )
+        .ok()?;
+
+        let mut contents = format!("```gluon\n{}\n```", typ);
+        let metadata =
+            gluon_completion::get_metadata(&module.metadata_map, file_map.span(), expr, pos);
+        if let Some(comment) = metadata.and_then(|metadata| metadata.comment.as_ref()) {
+            contents.push_str("\n\n");
+            contents.push_str(&comment.content);
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: contents,
+            }),
+            range: position::range(&file_map, span),
+        })
+    }
+
+    fn completion(&mut self, params: CompletionParams) -> Option<CompletionResponse> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = params.text_document_position;
+        let Analysis { file_map, module } = self.analyze(&text_document.uri)?;
+        let expr = module.expr.expr();
+        let pos = position::byte_pos(&file_map, position);
+
+        let env = self.thread.get_env();
+        let items = gluon_completion::suggest(&env, file_map.span(), expr, pos)
+            .into_iter()
+            .map(|suggestion| {
+                let documentation = gluon_completion::suggest_metadata(
+                    &module.metadata_map,
+                    &env,
+                    file_map.span(),
+                    expr,
+                    pos,
+                    &suggestion.name,
+                )
+                .and_then(|metadata| metadata.comment.as_ref())
+                .map(|comment| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: comment.content.clone(),
+                    })
+                });
+                let kind = match &suggestion.typ {
+                    Either::Left(_) => CompletionItemKind::Class,
+                    Either::Right(typ) if is_function(typ) => CompletionItemKind::Function,
+                    Either::Right(_) => CompletionItemKind::Variable,
+                };
+                CompletionItem {
+                    detail: Some(suggestion.typ.to_string()),
+                    label: suggestion.name,
+                    kind: Some(kind),
+                    documentation,
+                    ..CompletionItem::default()
+                }
+            })
+            .collect();
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn signature_help(&mut self, params: SignatureHelpParams) -> Option<SignatureHelp> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = params.text_document_position_params;
+        let Analysis { file_map, module } = self.analyze(&text_document.uri)?;
+        let expr = module.expr.expr();
+        let pos = position::byte_pos(&file_map, position);
+
+        let env = self.thread.get_env();
+        let help = gluon_completion::signature_help(&env, file_map.span(), expr, pos)?;
+        let parameters = arg_iter(remove_forall(&help.typ))
+            .map(|arg| ParameterInformation {
+                label: ParameterLabel::Simple(arg.to_string()),
+                documentation: None,
+            })
+            .collect();
+
+        Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: format!("{} : {}", help.name, help.typ),
+                documentation: None,
+                parameters: Some(parameters),
+                active_parameter: help.index,
+            }],
+            active_signature: Some(0),
+            active_parameter: help.index,
+        })
+    }
+
+    fn definition(&mut self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = params.text_document_position_params;
+        let Analysis { file_map, module } = self.analyze(&text_document.uri)?;
+        let expr = module.expr.expr();
+        let pos = position::byte_pos(&file_map, position);
+
+        let symbol = gluon_completion::symbol(file_map.span(), expr, pos).ok()?;
+        let mut visitor = FindDefinition { symbol, span: None };
+        visitor.visit_expr(expr);
+        let range = position::range(&file_map, visitor.span?)?;
+
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: text_document.uri,
+            range,
+        }))
+    }
+
+    fn document_symbols(&mut self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let Analysis { file_map, module } = self.analyze(&params.text_document.uri)?;
+        let symbols = gluon_completion::all_symbols(file_map.span(), module.expr.expr());
+        Some(DocumentSymbolResponse::Nested(
+            symbols
+                .iter()
+                .filter_map(|symbol| document_symbol(&file_map, symbol))
+                .collect(),
+        ))
+    }
+
+    fn format(&mut self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let formatted = self
+            .thread
+            .format_expr(
+                &mut gluon_format::Formatter::default(),
+                &document.module,
+                &document.text,
+            )
+            .ok()?;
+
+        if formatted == document.text {
+            return Some(Vec::new());
+        }
+        Some(vec![TextEdit {
+            range: Range {
+                start: Position::default(),
+                end: position::position(&document.text, document.text.len()),
+            },
+            new_text: formatted,
+        }])
+    }
+}
+
+fn dispatch<R, F>(params: Value, f: F) -> Result<Value, ResponseError>
+where
+    R: lsp_types::request::Request,
+    F: FnOnce(R::Params) -> R::Result,
+{
+    let params = serde_json::from_value(params)
+        .map_err(|err| ResponseError::new(rpc::INVALID_PARAMS, err.to_string()))?;
+    Ok(serde_json::to_value(f(params)).expect("Result could not be serialized"))
+}
+
+fn parse_params<P>(params: Value) -> Option<P>
+where
+    P: serde::de::DeserializeOwned,
+{
+    serde_json::from_value(params)
+        .map_err(|err| error!("Invalid notification parameters: {}", err))
+        .ok()
+}
+
+fn is_function(typ: &ArcType) -> bool {
+    remove_forall(typ).as_function().is_some()
+}
+
+fn document_symbol(
+    file_map: &FileMap,
+    symbol: &gluon_completion::SpCompletionSymbol,
+) -> Option<DocumentSymbol> {
+    use gluon_completion::CompletionSymbolContent;
+
+    let selection_range = position::range(file_map, symbol.span)?;
+    let (kind, detail, end) = match &symbol.value.content {
+        CompletionSymbolContent::Value { typ, expr, .. } => (
+            if is_function(typ) {
+                SymbolKind::Function
+            } else {
+                SymbolKind::Variable
+            },
+            typ.to_string(),
+            expr.map(|expr| expr.span.end()),
+        ),
+        CompletionSymbolContent::Type { typ } => {
+            (SymbolKind::Struct, typ.to_string(), Some(typ.span().end()))
+        }
+    };
+    let range = end
+        .filter(|&end| end > symbol.span.start())
+        .and_then(|end| position::range(file_map, Span::new(symbol.span.start(), end)))
+        .unwrap_or(selection_range);
+
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name: symbol.value.name.declared_name().to_string(),
+        detail: Some(detail),
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: Some(
+            symbol
+                .value
+                .children
+                .iter()
+                .filter_map(|child| document_symbol(file_map, child))
+                .collect(),
+        ),
+    })
+}
+
+/// Finds the span of the pattern, argument or type binding which binds `symbol`
+struct FindDefinition<'a> {
+    symbol: &'a SymbolRef,
+    span: Option<Span<BytePos>>,
+}
+
+impl FindDefinition<'_> {
+    fn found(&mut self, id: &Symbol, span: Span<BytePos>) {
+        if self.span.is_none() && *id == *self.symbol {
+            self.span = Some(span);
+        }
+    }
+}
+
+impl<'a, 'ast> Visitor<'a, 'ast> for FindDefinition<'_> {
+    type Ident = Symbol;
+
+    fn visit_expr(&mut self, expr: &'a SpannedExpr<'ast, Symbol>) {
+        if self.span.is_some() {
+            return;
+        }
+        match &expr.value {
+            Expr::LetBindings(binds, _) => {
+                for bind in &**binds {
+                    for arg in &*bind.args {
+                        self.found(&arg.name.value.name, arg.name.span);
+                    }
+                }
+            }
+            Expr::Lambda(lambda) => {
+                for arg in &*lambda.args {
+                    self.found(&arg.name.value.name, arg.name.span);
+                }
+            }
+            Expr::TypeBindings(binds, _) => {
+                for bind in &**binds {
+                    self.found(&bind.name.value, bind.name.span);
+                }
+            }
+            _ => (),
+        }
+        ast::walk_expr(self, expr)
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a SpannedPattern<'ast, Symbol>) {
+        match &pattern.value {
+            Pattern::Ident(id) => self.found(&id.name, pattern.span),
+            Pattern::As(id, _) => self.found(&id.value, id.span),
+            Pattern::Record { fields, .. } => {
+                for field in &**fields {
+                    match field {
+                        PatternField::Value { name, value: None } => {
+                            self.found(&name.value, name.span)
+                        }
+                        PatternField::Value { .. } => (),
+                        PatternField::Type { name } => self.found(&name.value, name.span),
+                    }
+                }
+            }
+            _ => (),
+        }
+        ast::walk_pattern(self, &pattern.value)
+    }
+}
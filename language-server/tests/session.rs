@@ -0,0 +1,377 @@
+use std::io::{BufReader, Write};
+
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+use gluon_language_server::rpc;
+
+const URI: &str = "file:///test.glu";
+
+/// A scripted session of messages sent from the client to the server
+struct Session {
+    input: Vec<u8>,
+    next_id: u64,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut session = Session {
+            input: Vec::new(),
+            next_id: 0,
+        };
+        session.request("initialize", json!({ "capabilities": {} }));
+        session.notify("initialized", json!({}));
+        session
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        id
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    fn send(&mut self, message: Value) {
+        rpc::write_message(&mut self.input, &message).unwrap();
+    }
+
+    fn open(&mut self, text: &str) {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": URI, "languageId": "gluon", "version": 1, "text": text }
+            }),
+        );
+    }
+
+    fn position_request(&mut self, method: &str, line: u32, character: u32) -> u64 {
+        self.request(
+            method,
+            json!({
+                "textDocument": { "uri": URI },
+                "position": { "line": line, "character": character }
+            }),
+        )
+    }
+
+    /// Shuts down the server and returns every message it sent
+    fn run(mut self) -> Messages {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        run_session(&self.input, 0)
+    }
+}
+
+struct Messages(Vec<Value>);
+
+impl Messages {
+    fn response(&self, id: u64) -> &Value {
+        self.0
+            .iter()
+            .find(|message| message["id"] == json!(id))
+            .unwrap_or_else(|| panic!("Missing response for request {}", id))
+    }
+
+    fn result(&self, id: u64) -> &Value {
+        let response = self.response(id);
+        assert_eq!(response["error"], Value::Null);
+        &response["result"]
+    }
+
+    fn diagnostics(&self) -> Vec<&Value> {
+        self.0
+            .iter()
+            .filter(|message| message["method"] == "textDocument/publishDiagnostics")
+            .map(|message| &message["params"]["diagnostics"])
+            .collect()
+    }
+}
+
+fn run_session(input: &[u8], expected_exit_code: i32) -> Messages {
+    let _ = env_logger::try_init();
+
+    let mut output = Vec::new();
+    let exit_code =
+        gluon_language_server::run(gluon::new_vm(), &mut BufReader::new(input), &mut output)
+            .unwrap();
+    assert_eq!(exit_code, expected_exit_code);
+
+    let mut output = &output[..];
+    let mut messages = Vec::new();
+    while let Some(content) = rpc::read_message(&mut output).unwrap() {
+        messages.push(serde_json::from_str(&content).unwrap());
+    }
+    Messages(messages)
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+#[test]
+fn initialize() {
+    let mut session = Session::new();
+    session.request("textDocument/unknown", json!({}));
+    let messages = session.run();
+
+    let capabilities = &messages.result(0)["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["documentSymbolProvider"], true);
+    assert_eq!(capabilities["documentFormattingProvider"], true);
+    assert_eq!(capabilities["textDocumentSync"], 2);
+    assert_eq!(messages.response(1)["error"]["code"], rpc::METHOD_NOT_FOUND);
+    assert_eq!(messages.result(2), &Value::Null);
+}
+
+#[test]
+fn requests_before_initialize_are_rejected() {
+    let mut input = Vec::new();
+    rpc::write_message(
+        &mut input,
+        &json!({ "jsonrpc": "2.0", "id": 0, "method": "textDocument/hover", "params": {} }),
+    )
+    .unwrap();
+    rpc::write_message(&mut input, &json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap();
+    let messages = run_session(&input, 1);
+
+    assert_eq!(
+        messages.response(0)["error"]["code"],
+        rpc::SERVER_NOT_INITIALIZED
+    );
+}
+
+#[test]
+fn diagnostics_are_published_on_open_and_change() {
+    let mut session = Session::new();
+    session.open("let x : Int = \"abc\"\nx");
+    session.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "range": range((0, 14), (0, 19)), "text": "123" }]
+        }),
+    );
+    let messages = session.run();
+
+    let diagnostics = messages.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+    let error = &diagnostics[0][0];
+    assert_eq!(error["range"], range((0, 14), (0, 19)));
+    assert_eq!(error["severity"], 1);
+    assert!(
+        error["message"].as_str().unwrap().contains("String"),
+        "{}",
+        error["message"]
+    );
+    assert_eq!(diagnostics[1], &json!([]));
+}
+
+#[test]
+fn parse_errors_are_published() {
+    let mut session = Session::new();
+    session.open("let x = \nx");
+    let messages = session.run();
+
+    let diagnostics = messages.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0][0]["severity"], 1);
+}
+
+#[test]
+fn warnings_are_published() {
+    let mut session = Session::new();
+    session.open("let x = 1\n2");
+    let messages = session.run();
+
+    assert_eq!(
+        messages.diagnostics(),
+        [&json!([{
+            "range": range((0, 4), (0, 5)),
+            "severity": 2,
+            "source": "gluon",
+            "message": "Unused variable `x`",
+        }])]
+    );
+}
+
+#[test]
+fn close_clears_diagnostics() {
+    let mut session = Session::new();
+    session.open("let x : Int = \"abc\"\nx");
+    session.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let messages = session.run();
+
+    let diagnostics = messages.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[1], &json!([]));
+}
+
+#[test]
+fn hover_shows_type_and_documentation() {
+    let mut session = Session::new();
+    session.open("/// The answer\nlet answer = 42\nanswer");
+    let id = session.position_request("textDocument/hover", 2, 2);
+    let messages = session.run();
+
+    assert_eq!(
+        messages.result(id),
+        &json!({
+            "contents": { "kind": "markdown", "value": "```gluon\nInt\n```\n\nThe answer" },
+            "range": range((2, 0), (2, 6)),
+        })
+    );
+}
+
+#[test]
+fn completion_after_edit() {
+    let mut session = Session::new();
+    session.open("let abc = 1\nlet abd = \"\"\nabc");
+    session.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "range": range((2, 2), (2, 3)), "text": "" }]
+        }),
+    );
+    let id = session.position_request("textDocument/completion", 2, 2);
+    let messages = session.run();
+
+    let items: Vec<_> = messages
+        .result(id)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| (item["label"].clone(), item["detail"].clone()))
+        .collect();
+    assert_eq!(
+        items,
+        [
+            (json!("abc"), json!("Int")),
+            (json!("abd"), json!("String"))
+        ]
+    );
+}
+
+#[test]
+fn signature_help() {
+    let mut session = Session::new();
+    session.open("let f x y : Int -> String -> Int = x\nf 1 ");
+    let id = session.position_request("textDocument/signatureHelp", 1, 4);
+    let messages = session.run();
+
+    let help = messages.result(id);
+    assert_eq!(help["signatures"][0]["label"], "f : Int -> String -> Int");
+    assert_eq!(
+        help["signatures"][0]["parameters"],
+        json!([{ "label": "Int" }, { "label": "String" }])
+    );
+    assert_eq!(help["activeParameter"], 1);
+}
+
+#[test]
+fn goto_definition() {
+    let mut session = Session::new();
+    session.open("let value = 1\nlet f x = x #Int+ value\nf");
+    let local = session.position_request("textDocument/definition", 1, 10);
+    let outer = session.position_request("textDocument/definition", 1, 19);
+    let messages = session.run();
+
+    assert_eq!(
+        messages.result(local),
+        &json!({ "uri": URI, "range": range((1, 6), (1, 7)) })
+    );
+    assert_eq!(
+        messages.result(outer),
+        &json!({ "uri": URI, "range": range((0, 4), (0, 9)) })
+    );
+}
+
+#[test]
+fn document_symbols() {
+    let mut session = Session::new();
+    session.open("let x = 1\nlet f y = y\n{ x, f }");
+    let id = session.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let messages = session.run();
+
+    let symbols: Vec<_> = messages
+        .result(id)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| (symbol["name"].clone(), symbol["kind"].clone()))
+        .collect();
+    // Variable = 13, Function = 12
+    assert_eq!(symbols, [(json!("x"), json!(13)), (json!("f"), json!(12))]);
+}
+
+#[test]
+fn formatting() {
+    let mut session = Session::new();
+    session.open("let x=1\nx");
+    let id = session.request(
+        "textDocument/formatting",
+        json!({
+            "textDocument": { "uri": URI },
+            "options": { "tabSize": 4, "insertSpaces": true }
+        }),
+    );
+    let messages = session.run();
+
+    assert_eq!(
+        messages.result(id),
+        &json!([{ "range": range((0, 0), (1, 1)), "newText": "let x = 1\nx\n" }])
+    );
+}
+
+#[test]
+fn binary_session_over_stdio() {
+    use std::process::{Command, Stdio};
+
+    let mut session = Session::new();
+    session.open("let x : Int = \"abc\"\nx");
+    session.request("shutdown", Value::Null);
+    session.notify("exit", Value::Null);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_gluon_language-server"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&session.input)
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let mut stdout = &output.stdout[..];
+    let mut methods = Vec::new();
+    while let Some(content) = rpc::read_message(&mut stdout).unwrap() {
+        let message: Value = serde_json::from_str(&content).unwrap();
+        methods.push(message["method"].as_str().map(|s| s.to_string()));
+    }
+    assert_eq!(
+        methods,
+        [
+            None,
+            Some("textDocument/publishDiagnostics".to_string()),
+            None
+        ]
+    );
+}
@@ -51,6 +51,8 @@ enum Context {
     Lambda,
     /// In an attribute
     Attribute,
+    /// In a hole of an interpolated string
+    Interpolation,
 }
 
 #[derive(Debug)]
@@ -94,7 +96,9 @@ impl Contexts {
                     continue;
                 }
                 Context::Block { .. } if skip_block => continue,
-                Context::Brace | Context::Bracket | Context::Paren => return Ok(()),
+                Context::Brace | Context::Bracket | Context::Paren | Context::Interpolation => {
+                    return Ok(())
+                }
                 // New context should not be unindented past the closest enclosing block context
                 Context::MatchClause
                 | Context::Type
@@ -315,6 +319,8 @@ where
                 | (&Token::RBrace, _)
                 | (&Token::RBracket, _)
                 | (&Token::RParen, _)
+                | (&Token::InterpolatedStringMiddle(_), _)
+                | (&Token::InterpolatedStringEnd(_), _)
                 | (&Token::Comma, _) => {
                     self.indent_levels.pop();
 
@@ -338,6 +344,13 @@ where
                             | Context::Bracket
                             | Context::Paren
                             | Context::Attribute => return Ok(token),
+                            Context::Interpolation => {
+                                // The text between two holes opens the next hole directly
+                                if let Token::InterpolatedStringMiddle(_) = token.value {
+                                    self.indent_levels.push(offside)?;
+                                }
+                                return Ok(token);
+                            }
                             Context::Block { .. } if token.value == Token::CloseBlock => {
                                 if let Some(offside) = self.indent_levels.last_mut() {
                                     // The enclosing block should not emit a block separator for the next
@@ -516,6 +529,8 @@ where
                 Token::Type => Some(Context::Type),
                 Token::Let => Some(Context::Let),
                 Token::Do | Token::Seq => Some(Context::Let),
+                // An `if` directly in a match clause is a guard which is ended by `->`
+                Token::If if offside.context == Context::MatchClause => None,
                 Token::If => Some(Context::If),
                 Token::Match => Some(Context::Expr),
                 Token::Lambda => Some(Context::Lambda),
@@ -523,6 +538,7 @@ where
                 Token::LBracket => Some(Context::Bracket),
                 Token::LParen => Some(Context::Paren),
                 Token::AttributeOpen => Some(Context::Attribute),
+                Token::InterpolatedStringStart(_) => Some(Context::Interpolation),
                 _ => None,
             };
             if let Some(context) = push_context {
@@ -615,6 +631,8 @@ fn token_closes_context(token: &BorrowedToken, context: Context) -> bool {
         | (&Token::In, Context::Let)
         | (&Token::In, Context::Type)
         | (&Token::RBracket, Context::Attribute)
+        | (&Token::InterpolatedStringMiddle(_), Context::Interpolation)
+        | (&Token::InterpolatedStringEnd(_), Context::Interpolation)
         | (_, Context::Block { .. }) => true,
         (_, _) => false,
     }
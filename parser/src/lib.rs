@@ -21,8 +21,8 @@ use itertools::Either;
 
 use crate::base::{
     ast::{
-        self, AstType, Do, Expr, IdentEnv, PatternField, RootExpr, Sp, SpannedExpr, SpannedPattern,
-        TypedIdent, ValueBinding,
+        self, AstType, Do, Expr, IdentEnv, Literal, PatternField, RootExpr, Sp, SpannedExpr,
+        SpannedPattern, TypedIdent, ValueBinding,
     },
     error::{AsDiagnostic, Errors},
     fnv::FnvMap,
@@ -95,6 +95,7 @@ fn shrink_hidden_spans<Id: std::fmt::Debug>(mut expr: SpannedExpr<Id>) -> Spanne
         Expr::Annotated(..)
         | Expr::App { .. }
         | Expr::Ident(_)
+        | Expr::Hole(_)
         | Expr::Literal(_)
         | Expr::Projection(_, _, _)
         | Expr::Array(_)
@@ -106,6 +107,118 @@ fn shrink_hidden_spans<Id: std::fmt::Debug>(mut expr: SpannedExpr<Id>) -> Spanne
     expr
 }
 
+/// `import! std.<module>`
+fn import_std<'ast, Id>(
+    arena: ast::ArenaRef<'_, 'ast, Id>,
+    env: MutIdentEnv<Id>,
+    type_cache: &TypeCache<Id, ArcType<Id>>,
+    span: Span<BytePos>,
+    module: &str,
+) -> SpannedExpr<'ast, Id> {
+    let path = Expr::Projection(
+        arena.alloc(pos::spanned(
+            span,
+            Expr::Ident(new_ident(type_cache, env.from_str("std"))),
+        )),
+        env.from_str(module),
+        type_cache.hole(),
+    );
+    pos::spanned(
+        span,
+        Expr::App {
+            func: arena.alloc(pos::spanned(
+                span,
+                Expr::Ident(new_ident(type_cache, env.from_str("import!"))),
+            )),
+            implicit_args: &mut [],
+            args: arena.alloc_extend(Some(pos::spanned(span, path))),
+        },
+    )
+}
+
+/// `std.<module>.<field> lhs rhs...`
+fn app_std<'ast, Id>(
+    arena: ast::ArenaRef<'_, 'ast, Id>,
+    env: MutIdentEnv<Id>,
+    type_cache: &TypeCache<Id, ArcType<Id>>,
+    span: Span<BytePos>,
+    (module, field): (&str, &str),
+    args: impl IntoIterator<Item = SpannedExpr<'ast, Id>>,
+) -> SpannedExpr<'ast, Id> {
+    let import = import_std(arena, &mut *env, type_cache, span, module);
+    let func = Expr::Projection(arena.alloc(import), env.from_str(field), type_cache.hole());
+    pos::spanned(
+        span,
+        Expr::App {
+            func: arena.alloc(pos::spanned(span, func)),
+            implicit_args: &mut [],
+            args: arena.alloc_extend(args),
+        },
+    )
+}
+
+/// Desugars an interpolated string such as `f"a{x}b"` into `"a" ++ show x ++ "b"` where `++` and
+/// `show` come from `std.string` and `std.show`. Each `show` call gets the span of its hole so
+/// that type errors point at the embedded expression.
+fn interpolated_string<'ast, 'input, Id>(
+    arena: ast::ArenaRef<'_, 'ast, Id>,
+    env: MutIdentEnv<Id>,
+    type_cache: &TypeCache<Id, ArcType<Id>>,
+    start: Spanned<&'input str, BytePos>,
+    holes: impl IntoIterator<Item = (SpannedExpr<'ast, Id>, Spanned<&'input str, BytePos>)>,
+) -> Expr<'ast, Id> {
+    let mut span = start.span;
+    let mut template = token::unescape_interpolated_string(start.value);
+
+    let mut parts = Vec::new();
+    let push_text = |parts: &mut Vec<_>, text: Spanned<&str, BytePos>| {
+        let value = token::unescape_interpolated_string(text.value);
+        if !value.is_empty() {
+            parts.push(pos::spanned(
+                text.span,
+                Expr::Literal(Literal::String(value)),
+            ));
+        }
+    };
+    push_text(&mut parts, start);
+
+    for (hole, text) in holes {
+        let hole_span = hole.span;
+        parts.push(app_std(
+            arena,
+            &mut *env,
+            type_cache,
+            hole_span,
+            ("show", "show"),
+            Some(hole),
+        ));
+        span = Span::new(span.start(), text.span.end());
+        template.push_str("{}");
+        template.push_str(&token::unescape_interpolated_string(text.value));
+        push_text(&mut parts, text);
+    }
+
+    let replacement = match parts.pop() {
+        Some(last) => parts.into_iter().rev().fold(last, |acc, part| {
+            let part_span = Span::new(part.span.start(), acc.span.end());
+            app_std(
+                arena,
+                &mut *env,
+                type_cache,
+                part_span,
+                ("string", "++"),
+                vec![part, acc],
+            )
+        }),
+        None => pos::spanned(span, Expr::Literal(Literal::String(String::new()))),
+    };
+
+    Expr::MacroExpansion {
+        original: arena.alloc(pos::spanned(span, Expr::Literal(Literal::String(template)))),
+        replacement: arena.alloc(replacement),
+    }
+}
+
 fn transform_errors<'a, Iter>(
     source_span: Span<BytePos>,
     errors: Iter,
@@ -179,7 +292,9 @@ impl AsDiagnostic for Error {
         &self,
         _map: &base::source::CodeMap,
     ) -> codespan_reporting::diagnostic::Diagnostic<source::FileId> {
-        codespan_reporting::diagnostic::Diagnostic::error().with_message(self.to_string())
+        codespan_reporting::diagnostic::Diagnostic::error()
+            .with_message(self.to_string())
+            .with_code(self.code())
     }
 }
 
@@ -194,6 +309,22 @@ fn remove_extra_quotes(tokens: &mut [String]) {
 }
 
 impl Error {
+    /// Returns the stable code which identifies this kind of error
+    pub fn code(&self) -> &'static str {
+        use base::error_code::*;
+
+        match self {
+            Error::Token(_) => E0001,
+            Error::Layout(_) => E0002,
+            Error::InvalidToken => E0003,
+            Error::UnexpectedToken(..) => E0004,
+            Error::UnexpectedEof(_) => E0005,
+            Error::ExtraToken(_) => E0006,
+            Error::Infix(_) => E0007,
+            Error::Message(_) => E0008,
+        }
+    }
+
     fn from_lalrpop(source_span: Span<BytePos>, err: LalrpopError) -> Spanned<Error, BytePos> {
         use lalrpop_util::ParseError::*;
 
@@ -252,6 +383,11 @@ pub enum Variant<'ast, Id> {
     Simple(Sp<Id>, Vec<AstType<'ast, Id>>),
 }
 
+pub enum ArrayPatternElement<'ast, Id> {
+    Element(SpannedPattern<'ast, Id>),
+    Rest(SpannedPattern<'ast, Id>),
+}
+
 // Hack around LALRPOP's limited type syntax
 type MutIdentEnv<'env, Id> = &'env mut dyn IdentEnv<Ident = Id>;
 type ErrorEnv<'err, 'input> = &'err mut Errors<LalrpopError<'input>>;
@@ -327,6 +463,7 @@ impl_temp_vec! {
     SpannedExpr<'ast, Id> => exprs,
     SpannedPattern<'ast, Id> => patterns,
     ast::PatternField<'ast, Id> => pattern_field,
+    ArrayPatternElement<'ast, Id> => array_pattern_elements,
     ast::ExprField<'ast, Id, ArcType<Id>> => expr_field_types,
     ast::ExprField<'ast, Id, SpannedExpr<'ast, Id>> => expr_field_exprs,
     ast::TypeBinding<'ast, Id> => type_bindings,
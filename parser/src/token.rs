@@ -23,6 +23,14 @@ pub enum Token<S> {
     Operator(S),
 
     StringLiteral(StringLiteral<S>),
+    /// An interpolated string without any holes, `f"text"`
+    InterpolatedString(S),
+    /// The text before the first hole of an interpolated string, `f"text{`
+    InterpolatedStringStart(S),
+    /// The text between two holes of an interpolated string, `}text{`
+    InterpolatedStringMiddle(S),
+    /// The text after the last hole of an interpolated string, `}text"`
+    InterpolatedStringEnd(S),
     CharLiteral(char),
     IntLiteral(i64),
     ByteLiteral(u8),
@@ -81,6 +89,10 @@ where
             Identifier(_) => "Identifier",
             Operator(_) => "Operator",
             StringLiteral(_) => "StringLiteral",
+            InterpolatedString(_) => "InterpolatedString",
+            InterpolatedStringStart(_) => "InterpolatedStringStart",
+            InterpolatedStringMiddle(_) => "InterpolatedStringMiddle",
+            InterpolatedStringEnd(_) => "InterpolatedStringEnd",
             CharLiteral(_) => "CharLiteral",
             IntLiteral(_) => "IntLiteral",
             ByteLiteral(_) => "ByteLiteral",
@@ -142,6 +154,10 @@ impl<S> Token<S> {
                 self::StringLiteral::Escaped(s) => self::StringLiteral::Escaped(f(s)),
                 self::StringLiteral::Raw(s) => self::StringLiteral::Raw(f(s)),
             }),
+            InterpolatedString(s) => InterpolatedString(f(s)),
+            InterpolatedStringStart(s) => InterpolatedStringStart(f(s)),
+            InterpolatedStringMiddle(s) => InterpolatedStringMiddle(f(s)),
+            InterpolatedStringEnd(s) => InterpolatedStringEnd(f(s)),
             CharLiteral(x) => CharLiteral(x),
             IntLiteral(x) => IntLiteral(x),
             ByteLiteral(x) => ByteLiteral(x),
@@ -231,6 +247,13 @@ fn unescape_string_literal(mut s: &str) -> String {
     string
 }
 
+/// Unescapes the text of an interpolated string, turning `{{` and `}}` into `{` and `}`
+pub fn unescape_interpolated_string(s: &str) -> String {
+    unescape_string_literal(s)
+        .replace("{{", "{")
+        .replace("}}", "}")
+}
+
 pub type BorrowedToken<'input> = Token<&'input str>;
 pub type SpannedToken<'input> = Spanned<Token<&'input str>, Location>;
 
@@ -258,6 +281,9 @@ quick_error! {
         UnterminatedStringLiteral {
             display("unterminated string literal")
         }
+        UnmatchedInterpolationBrace {
+            display("unmatched `}}` in interpolated string, use `}}}}` to insert a literal `}}`")
+        }
         InvalidRawStringDelimiter {
             display("raw strings can only use `#` as a delimter")
         }
@@ -349,6 +375,8 @@ pub struct Tokenizer<'input> {
     input: &'input str,
     chars: CharLocations<'input>,
     start_index: BytePos,
+    /// The number of unclosed `{` in each interpolated string hole we are currently inside
+    interpolations: Vec<usize>,
     pub errors: Errors<SpError>,
 }
 
@@ -363,6 +391,7 @@ impl<'input> Tokenizer<'input> {
             input: input.src(),
             chars,
             start_index: input.start_index(),
+            interpolations: Vec::new(),
             errors: Errors::new(),
         }
     }
@@ -565,6 +594,72 @@ impl<'input> Tokenizer<'input> {
         self.recover(start, end, UnterminatedStringLiteral, token)
     }
 
+    /// Scans the text of an interpolated string up to the next hole or the closing `"`. `first`
+    /// is true when scanning the text directly after `f"`, otherwise we are just after the `}`
+    /// which closed a hole.
+    fn interpolated_string(
+        &mut self,
+        start: Location,
+        first: bool,
+    ) -> Result<SpannedToken<'input>, SpError> {
+        let content_start = self.next_loc();
+        loop {
+            let scan_start = self.next_loc();
+            self.take_until(scan_start, |b| {
+                b == b'"' || b == b'\\' || b == b'{' || b == b'}'
+            });
+            match self.bump() {
+                Some((start, b'\\')) => {
+                    self.escape_code(start)?;
+                }
+                Some((_, b'{')) if self.test_lookahead(|b| b == b'{') => {
+                    self.bump();
+                }
+                Some((_, b'}')) if self.test_lookahead(|b| b == b'}') => {
+                    self.bump();
+                }
+                Some((brace, b'}')) => {
+                    let end = self.next_loc();
+                    self.recover(brace, end, UnmatchedInterpolationBrace, ())?;
+                }
+                Some((content_end, b'{')) => {
+                    let text = self.slice(content_start, content_end);
+                    let token = if first {
+                        self.interpolations.push(0);
+                        Token::InterpolatedStringStart(text)
+                    } else {
+                        Token::InterpolatedStringMiddle(text)
+                    };
+                    return Ok(pos::spanned2(start, self.next_loc(), token));
+                }
+                Some((content_end, b'"')) => {
+                    let text = self.slice(content_start, content_end);
+                    return Ok(pos::spanned2(
+                        start,
+                        self.next_loc(),
+                        self.end_interpolated_string(first, text),
+                    ));
+                }
+                _ => break,
+            }
+        }
+
+        let end = self.chars.location;
+
+        let text = self.slice(content_start, end);
+        let token = self.end_interpolated_string(first, text);
+        self.recover(start, end, UnterminatedStringLiteral, token)
+    }
+
+    fn end_interpolated_string(&mut self, first: bool, text: &'input str) -> BorrowedToken<'input> {
+        if first {
+            Token::InterpolatedString(text)
+        } else {
+            self.interpolations.pop();
+            Token::InterpolatedStringEnd(text)
+        }
+    }
+
     fn raw_string_literal(&mut self, start: Location) -> Result<SpannedToken<'input>, SpError> {
         let mut delimiters = 0;
         while let Some((_, ch)) = self.bump() {
@@ -787,10 +882,24 @@ impl<'input> Iterator for Tokenizer<'input> {
             return match ch {
                 b',' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::Comma))),
                 b'\\' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::Lambda))),
-                b'{' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::LBrace))),
+                b'{' => {
+                    if let Some(depth) = self.interpolations.last_mut() {
+                        *depth += 1;
+                    }
+                    Some(Ok(pos::spanned2(start, self.next_loc(), Token::LBrace)))
+                }
                 b'[' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::LBracket))),
                 b'(' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::LParen))),
-                b'}' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::RBrace))),
+                // A `}` which does not close a `{` inside the hole ends the hole
+                b'}' if self.interpolations.last() == Some(&0) => {
+                    Some(self.interpolated_string(start, false))
+                }
+                b'}' => {
+                    if let Some(depth) = self.interpolations.last_mut() {
+                        *depth -= 1;
+                    }
+                    Some(Ok(pos::spanned2(start, self.next_loc(), Token::RBrace)))
+                }
                 b']' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::RBracket))),
                 b')' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::RParen))),
                 b'?' => Some(Ok(pos::spanned2(start, self.next_loc(), Token::Question))),
@@ -798,6 +907,10 @@ impl<'input> Iterator for Tokenizer<'input> {
                 b'r' if self.test_lookahead(|ch| ch == b'"' || ch == b'#') => {
                     Some(self.raw_string_literal(start))
                 }
+                b'f' if self.test_lookahead(|ch| ch == b'"') => {
+                    self.bump();
+                    Some(self.interpolated_string(start, true))
+                }
                 b'"' => Some(self.string_literal(start)),
                 b'\'' => Some(self.char_literal(start)),
 
@@ -1097,6 +1210,52 @@ mod test {
         );
     }
 
+    #[test]
+    fn interpolated_string_literals() {
+        test(
+            r#"f"a{x}b{ { y } }c" f"{{}}""#,
+            vec![
+                (
+                    r#"~~~~                      "#,
+                    InterpolatedStringStart("a"),
+                ),
+                (r#"    ~                     "#, Identifier("x")),
+                (
+                    r#"     ~~~                  "#,
+                    InterpolatedStringMiddle("b"),
+                ),
+                (r#"         ~                "#, LBrace),
+                (r#"           ~              "#, Identifier("y")),
+                (r#"             ~            "#, RBrace),
+                (r#"               ~~~        "#, InterpolatedStringEnd("c")),
+                (r#"                   ~~~~~~~"#, InterpolatedString("{{}}")),
+            ],
+        );
+        assert_eq!(unescape_interpolated_string(r#"{{\"}}"#), r#"{"}"#);
+    }
+
+    #[test]
+    fn interpolated_string_nested() {
+        test(
+            r#"f"{f"{x}"}""#,
+            vec![
+                (r#"~~~        "#, InterpolatedStringStart("")),
+                (r#"   ~~~     "#, InterpolatedStringStart("")),
+                (r#"      ~    "#, Identifier("x")),
+                (r#"       ~~  "#, InterpolatedStringEnd("")),
+                (r#"         ~~"#, InterpolatedStringEnd("")),
+            ],
+        );
+    }
+
+    #[test]
+    fn interpolated_string_unmatched_brace() {
+        assert_eq!(
+            tokenizer(r#"f"a}b""#).next(),
+            Some(error2(3, 4, UnmatchedInterpolationBrace))
+        );
+    }
+
     #[test]
     fn char_literals() {
         test(
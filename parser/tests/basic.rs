@@ -66,6 +66,34 @@ test_parse! {
     )
 }
 
+test_parse! {
+    typed_holes,
+    "f _ (?x)",
+    |arena| app(arena,
+        id("f"),
+        vec![
+            hole("_"),
+            no_loc(Expr::Tuple {
+                typ: Type::hole(),
+                elems: arena.alloc_extend(vec![hole("x")]),
+            }),
+        ]
+    ),
+}
+
+test_parse! {
+    named_hole_argument_requires_parens,
+    "f ?x (?y)",
+    |arena| no_loc(Expr::App {
+        func: arena.alloc(id("f")),
+        implicit_args: arena.alloc_extend(vec![id("x")]),
+        args: arena.alloc_extend(vec![no_loc(Expr::Tuple {
+            typ: Type::hole(),
+            elems: arena.alloc_extend(vec![hole("y")]),
+        })]),
+    }),
+}
+
 test_parse! {
     nested_application,
     "f (g 1 2)",
@@ -297,6 +325,95 @@ test_parse! {
     }
 }
 
+test_parse! {
+    match_guard,
+    r#"
+match x with
+| y if y > 0 -> y
+| _ -> 0
+"#,
+    |arena| {
+        no_loc(Expr::Match(
+            arena.alloc(id("x")),
+            arena.alloc_extend(vec![
+                Alternative {
+                    pattern: no_loc(Pattern::Ident(TypedIdent::new(intern("y")))),
+                    guard: Some(binop(arena, id("y"), ">", int(0))),
+                    expr: id("y"),
+                },
+                Alternative {
+                    pattern: no_loc(Pattern::Ident(TypedIdent::new(intern("_")))),
+                    guard: None,
+                    expr: int(0),
+                },
+            ]),
+        ))
+    }
+}
+
+test_parse! {
+    or_pattern,
+    "match x with | A y | B (C y | D y) -> y",
+    |arena| {
+        let constructor = |name: &str, args| {
+            no_loc(Pattern::Constructor(
+                TypedIdent::new(intern(name)),
+                arena.alloc_extend(args),
+            ))
+        };
+        let y = || no_loc(Pattern::Ident(TypedIdent::new(intern("y"))));
+        let nested = no_loc(Pattern::Or(arena.alloc_extend(vec![
+            constructor("C", vec![y()]),
+            constructor("D", vec![y()]),
+        ])));
+        let pattern = Pattern::Or(arena.alloc_extend(vec![
+            constructor("A", vec![y()]),
+            constructor("B", vec![nested]),
+        ]));
+        case(arena, id("x"), vec![(pattern, id("y"))])
+    }
+}
+
+test_parse! {
+    array_pattern,
+    r#"
+match x with
+| [] -> 0
+| [y, ..rest] -> y
+| [.., z] -> z
+"#,
+    |arena| {
+        let ident = |name: &str| no_loc(Pattern::Ident(TypedIdent::new(intern(name))));
+        let empty = Pattern::Array {
+            typ: Type::hole(),
+            elems: arena.alloc_extend(vec![]),
+            rest: None,
+        };
+        let head = Pattern::Array {
+            typ: Type::hole(),
+            elems: arena.alloc_extend(vec![ident("y"), ident("rest")]),
+            rest: Some(1),
+        };
+        let last = Pattern::Array {
+            typ: Type::hole(),
+            elems: arena.alloc_extend(vec![ident("_"), ident("z")]),
+            rest: Some(0),
+        };
+        case(
+            arena,
+            id("x"),
+            vec![(empty, int(0)), (head, id("y")), (last, id("z"))],
+        )
+    }
+}
+
+#[test]
+fn array_pattern_multiple_rest_is_error() {
+    let _ = ::env_logger::try_init();
+    let result = parse("match x with | [..a, ..b] -> a");
+    assert!(result.is_err());
+}
+
 test_parse! {
     let_pattern,
     "let {x, y} = test in x",
@@ -291,6 +291,10 @@ pub fn id(s: &str) -> SpExpr<'_> {
     no_loc(Expr::Ident(TypedIdent::new(intern(s))))
 }
 
+pub fn hole(s: &str) -> SpExpr<'_> {
+    no_loc(Expr::Hole(TypedIdent::new(intern(s))))
+}
+
 pub fn typ<'ast>(mut arena: ast::ArenaRef<'_, 'ast, String>, s: &str) -> AstType<'ast, String> {
     assert!(s.len() != 0);
     match s.parse() {
@@ -345,6 +349,7 @@ pub fn case<'ast>(
         arena.alloc(e),
         arena.alloc_extend(alts.into_iter().map(|(p, e)| Alternative {
             pattern: no_loc(p),
+            guard: None,
             expr: e,
         })),
     ))
@@ -0,0 +1,303 @@
+//! Runs scripts from the REPL under the step debugger, prompting for commands each time the
+//! script pauses
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+};
+
+use futures::{channel::oneshot, prelude::*};
+
+use gluon::{
+    base::filename_to_module,
+    debugger::{Command, Debugger, FrameInfo, PauseReason, Paused, Variable},
+    vm::{
+        self,
+        api::{Hole, OpaqueValue, WithVM, IO},
+        internal::ValuePrinter,
+    },
+    RootedThread, Thread, ThreadExt,
+};
+
+const HELP: &str = "\
+Available debugger commands
+
+    continue (c)        Continue until the next breakpoint
+    step (s)            Step to the next line, entering any called function
+    next (n)            Step to the next line of the current function
+    finish (f)          Step out of the current function
+    backtrace (bt)      Print the frames of the stack
+    frame (fr) LEVEL    Select the frame at LEVEL for `locals` and `print`
+    locals (l)          Print the locals and upvars of the selected frame
+    print (p) EXPR      Evaluate EXPR in the selected frame
+    break (b) FILE:LINE Set a breakpoint
+    clear (cl) FILE:LINE
+                        Remove a breakpoint
+    quit (q)            Stop running the script
+    help (h)            Print this help";
+
+#[derive(Userdata, Trace, VmType)]
+#[gluon(vm_type = "Debugger")]
+#[gluon_trace(skip)]
+struct ReplDebugger(Debugger);
+
+impl std::fmt::Debug for ReplDebugger {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Debugger(..)")
+    }
+}
+
+/// Parses a location of the form `FILE:LINE`
+fn parse_location(location: &str) -> Result<(&str, usize), String> {
+    let mut iter = location.trim().rsplitn(2, ':');
+    match (iter.next(), iter.next()) {
+        (Some(line), Some(source)) if !source.is_empty() => match line.parse() {
+            Ok(line) if line > 0 => Ok((source, line)),
+            _ => Err(format!("Expected a line number, found `{}`", line)),
+        },
+        _ => Err(format!("Expected `FILE:LINE`, found `{}`", location.trim())),
+    }
+}
+
+fn list_breakpoints(debugger: &Debugger) -> String {
+    let breakpoints = debugger.breakpoints();
+    if breakpoints.is_empty() {
+        "No breakpoints".to_string()
+    } else {
+        breakpoints
+            .iter()
+            .map(|breakpoint| breakpoint.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn set_breakpoint(debugger: &Debugger, location: &str) -> Result<String, String> {
+    let (source, line) = parse_location(location)?;
+    if debugger.add_breakpoint(source, line) {
+        Ok(format!("Breakpoint set at {}:{}", source, line))
+    } else {
+        Ok(format!("Breakpoint already set at {}:{}", source, line))
+    }
+}
+
+fn clear_breakpoint(debugger: &Debugger, location: &str) -> Result<String, String> {
+    let (source, line) = parse_location(location)?;
+    if debugger.remove_breakpoint(source, line) {
+        Ok(format!("Removed breakpoint at {}:{}", source, line))
+    } else {
+        Err(format!("No breakpoint at {}:{}", source, line))
+    }
+}
+
+fn new_debugger(_: ()) -> IO<ReplDebugger> {
+    IO::Value(ReplDebugger(Debugger::new()))
+}
+
+fn add_breakpoint(debugger: &ReplDebugger, location: &str) -> IO<Result<String, String>> {
+    IO::Value(if location.trim().is_empty() {
+        Ok(list_breakpoints(&debugger.0))
+    } else {
+        set_breakpoint(&debugger.0, location)
+    })
+}
+
+fn remove_breakpoint(debugger: &ReplDebugger, location: &str) -> IO<Result<String, String>> {
+    IO::Value(if location.trim().is_empty() {
+        debugger.0.clear_breakpoints();
+        Ok("Removed all breakpoints".to_string())
+    } else {
+        clear_breakpoint(&debugger.0, location)
+    })
+}
+
+fn debug_file(
+    debugger: &ReplDebugger,
+    WithVM {
+        vm,
+        value: filename,
+    }: WithVM<&str>,
+) -> impl Future<Output = IO<Result<String, String>>> {
+    let debugger = debugger.0.clone();
+    let filename = filename.trim().to_string();
+    let thread = vm.new_thread();
+    async move {
+        let thread = match thread {
+            Ok(thread) => thread,
+            Err(err) => return IO::Value(Err(err.to_string())),
+        };
+        // Run the script on its own OS thread so that the runtime is free to run any tasks the
+        // debugger spawns (such as imports) while the script is paused
+        let (sender, receiver) = oneshot::channel();
+        let runtime = tokio::runtime::Handle::current();
+        std::thread::spawn(move || {
+            let result = runtime
+                .enter(|| futures::executor::block_on(debug_file_(&debugger, &thread, &filename)));
+            let _ = sender.send(result.map_err(|err| err.to_string()));
+        });
+        IO::Value(
+            receiver
+                .await
+                .unwrap_or_else(|_| Err("The debugged script panicked".to_string())),
+        )
+    }
+}
+
+async fn debug_file_(
+    debugger: &Debugger,
+    thread: &RootedThread,
+    filename: &str,
+) -> gluon::Result<String> {
+    let source = fs::read_to_string(filename)
+        .map_err(|err| vm::Error::Message(format!("Unable to read `{}`: {}", filename, err)))?;
+    let module = filename_to_module(filename);
+
+    debugger.stop_on_entry(debugger.breakpoints().is_empty());
+    let mut prompt = Prompt {
+        debugger: debugger.clone(),
+        module: module.clone(),
+        lines: source.lines().map(|line| line.to_string()).collect(),
+    };
+    debugger.attach(thread, move |paused| prompt.pause(paused))?;
+    let result = thread
+        .run_expr_async::<OpaqueValue<RootedThread, Hole>>(&module, &source)
+        .await;
+    debugger.detach(thread);
+
+    let (value, typ) = result?;
+    let env = thread.get_env();
+    let debug_level = thread.global_env().get_debug_level();
+    Ok(format!(
+        "{} : {}",
+        ValuePrinter::new(&env, &typ, value.get_variant(), &debug_level)
+            .width(80)
+            .max_level(5),
+        typ
+    ))
+}
+
+struct Prompt {
+    debugger: Debugger,
+    module: String,
+    lines: Vec<String>,
+}
+
+impl Prompt {
+    fn pause(&mut self, paused: &mut Paused) -> Command {
+        let frames = paused.frames();
+        match paused.reason() {
+            PauseReason::Breakpoint(breakpoint) => println!("Breakpoint {}", breakpoint),
+            PauseReason::Entry | PauseReason::Pause | PauseReason::Step => (),
+        }
+        self.print_frame(0, &frames[0]);
+
+        let mut level = 0;
+        let stdin = io::stdin();
+        loop {
+            print!("(debug) ");
+            let _ = io::stdout().flush();
+
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) | Err(_) => return Command::Terminate,
+                Ok(_) => (),
+            }
+            let line = line.trim();
+            let (cmd, arg) = match line.find(char::is_whitespace) {
+                Some(i) => (&line[..i], line[i..].trim()),
+                None => (line, ""),
+            };
+
+            match cmd {
+                "c" | "continue" => return Command::Continue,
+                "s" | "step" => return Command::StepIn,
+                "n" | "next" => return Command::StepOver,
+                "f" | "finish" => return Command::StepOut,
+                "q" | "quit" => return Command::Terminate,
+                "bt" | "backtrace" => {
+                    for (i, frame) in frames.iter().enumerate() {
+                        print!("{} ", if i == level { "*" } else { " " });
+                        self.print_frame(i, frame);
+                    }
+                }
+                "fr" | "frame" => match arg.parse::<usize>() {
+                    Ok(new_level) if new_level < frames.len() => {
+                        level = new_level;
+                        self.print_frame(level, &frames[level]);
+                    }
+                    _ => println!("Expected a frame level between 0 and {}", frames.len() - 1),
+                },
+                "l" | "locals" => {
+                    for variable in paused.upvars(level).iter().chain(&paused.locals(level)) {
+                        print_variable(variable);
+                    }
+                }
+                "p" | "print" => match paused.evaluate(level, arg) {
+                    Ok(variable) => print_variable(&variable),
+                    Err(err) => println!("{}", err),
+                },
+                "b" | "break" => print_result(set_breakpoint(&self.debugger, arg)),
+                "cl" | "clear" => print_result(clear_breakpoint(&self.debugger, arg)),
+                "h" | "help" => println!("{}", HELP),
+                "" => (),
+                _ => println!("Unknown command `{}`. Use `help` to list the commands", cmd),
+            }
+        }
+    }
+
+    fn print_frame(&self, level: usize, frame: &FrameInfo) {
+        let function_name = frame.function_name.as_deref().unwrap_or("<unknown>");
+        match frame.line {
+            Some(line) => {
+                println!(
+                    "#{} {} at {}:{}",
+                    level, function_name, frame.source_name, line
+                );
+                if frame.source_name == self.module {
+                    if let Some(text) = self.lines.get(line - 1) {
+                        println!("{:>5} | {}", line, text);
+                    }
+                }
+            }
+            None => println!("#{} {}", level, function_name),
+        }
+    }
+}
+
+fn print_variable(variable: &Variable) {
+    println!("{} : {} = {}", variable.name, variable.typ, variable.value);
+}
+
+fn print_result(result: Result<String, String>) {
+    match result {
+        Ok(msg) | Err(msg) => println!("{}", msg),
+    }
+}
+
+pub fn load(vm: &Thread) -> vm::Result<vm::ExternModule> {
+    vm.register_type::<ReplDebugger>("Debugger", &[])?;
+
+    vm::ExternModule::new(
+        vm,
+        record!(
+            type Debugger => ReplDebugger,
+            new_debugger => primitive!(1, new_debugger),
+            add_breakpoint => primitive!(2, add_breakpoint),
+            remove_breakpoint => primitive!(2, remove_breakpoint),
+            debug_file => primitive!(2, async fn debug_file),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locations() {
+        assert_eq!(parse_location("test.glu:3"), Ok(("test.glu", 3)));
+        assert_eq!(parse_location(" std.list:10 "), Ok(("std.list", 10)));
+        assert!(parse_location("test.glu").is_err());
+        assert!(parse_location("test.glu:0").is_err());
+        assert!(parse_location(":3").is_err());
+    }
+}
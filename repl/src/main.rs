@@ -32,9 +32,14 @@ use crate::base::{
 };
 
 use gluon::{
-    new_vm_async, vm::thread::ThreadInternal, vm::Error as VMError, Result, Thread, ThreadExt,
+    new_vm_async,
+    vm::profiler::{Metric, Profile},
+    vm::thread::ThreadInternal,
+    vm::Error as VMError,
+    Result, Thread, ThreadExt,
 };
 
+mod debugger;
 mod repl;
 
 quick_error! {
@@ -105,12 +110,27 @@ pub struct FmtOpt {
     input: Vec<PathBuf>,
 }
 
+#[derive(StructOpt)]
+#[structopt(about = "Explains the error codes emitted by the compiler")]
+pub struct ExplainOpt {
+    #[structopt(
+        name = "CODE",
+        help = "The code to explain, such as E0101. Lists every code if omitted"
+    )]
+    code: Option<String>,
+}
+
 #[derive(StructOpt)]
 pub enum SubOpt {
     #[structopt(name = "fmt", about = "Formats gluon source code")]
     Fmt(FmtOpt),
     #[structopt(name = "doc", about = "Documents gluon source code")]
     Doc(::gluon_doc::Opt),
+    #[structopt(
+        name = "explain",
+        about = "Explains the error codes emitted by the compiler"
+    )]
+    Explain(ExplainOpt),
 }
 
 const LONG_VERSION: &str = concat!(clap::crate_version!(), "\n", "commit: ", env!("GIT_HASH"));
@@ -149,6 +169,26 @@ pub struct Opt {
     )]
     no_std: bool,
 
+    #[structopt(
+        long = "profile",
+        help = "Prints a table of the time, instructions and memory used by each function after running the files"
+    )]
+    profile: bool,
+
+    #[structopt(
+        long = "profile-output",
+        parse(from_os_str),
+        help = "Profiles the files and writes the call stacks to FILE in the collapsed format used by flamegraph tools"
+    )]
+    profile_output: Option<PathBuf>,
+
+    #[structopt(
+        long = "profile-metric",
+        default_value = "time",
+        help = "The value written with each call stack to the profile output: time, instructions, allocation"
+    )]
+    profile_metric: Metric,
+
     #[structopt(name = "FILE", help = "Executes each file as a gluon program")]
     input: Vec<String>,
 
@@ -164,6 +204,17 @@ pub struct Opt {
     subcommand_opt: Option<SubOpt>,
 }
 
+fn write_profile(opt: &Opt, profile: &Profile) -> Result<()> {
+    if opt.profile {
+        profile.write_table(io::stderr())?;
+    }
+    if let Some(path) = &opt.profile_output {
+        let file = fs::File::create(path)?;
+        profile.write_collapsed(io::BufWriter::new(file), opt.profile_metric)?;
+    }
+    Ok(())
+}
+
 async fn run_files<I>(vm: &Thread, files: I) -> Result<()>
 where
     I: IntoIterator,
@@ -239,6 +290,27 @@ async fn fmt_stdio(opt: &Opt) -> Result<()> {
     Ok(())
 }
 
+fn explain(opt: &ExplainOpt) -> anyhow::Result<()> {
+    use crate::base::error_code::{self, ERROR_CODES};
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    match opt.code {
+        Some(ref code) => {
+            let error_code = error_code::lookup(code)
+                .ok_or_else(|| anyhow::anyhow!("`{}` is not a known error code", code))?;
+            writeln!(stdout, "{}: {}", error_code.code, error_code.title)?;
+            write!(stdout, "{}", error_code.explanation)?;
+        }
+        None => {
+            for error_code in ERROR_CODES {
+                writeln!(stdout, "{}: {}", error_code.code, error_code.title)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 async fn run(opt: &Opt, color: Color, vm: &Thread) -> std::result::Result<(), Error> {
     vm.global_env().set_debug_level(opt.debug_level.clone());
     match opt.subcommand_opt {
@@ -277,6 +349,7 @@ async fn run(opt: &Opt, color: Color, vm: &Thread) -> std::result::Result<(), Er
             let thread = new_vm_async().await;
             gluon_doc::generate_for_path(&thread, input, output)?;
         }
+        Some(SubOpt::Explain(ref explain_opt)) => explain(explain_opt)?,
         None => {
             if opt.interactive {
                 let prompt = opt.prompt.clone();
@@ -284,7 +357,14 @@ async fn run(opt: &Opt, color: Color, vm: &Thread) -> std::result::Result<(), Er
                 let use_std_lib = !opt.no_std;
                 repl::run(color, &prompt, debug_level, use_std_lib).await?;
             } else if !opt.input.is_empty() {
-                run_files(&vm, &opt.input).await?;
+                if opt.profile || opt.profile_output.is_some() {
+                    vm.start_profiling();
+                }
+                let result = run_files(&vm, &opt.input).await;
+                if let Some(profile) = vm.stop_profiling() {
+                    write_profile(opt, &profile)?;
+                }
+                result?;
             } else {
                 writeln!(io::stderr(), "{}", Opt::clap().get_matches().usage())
                     .expect("Error writing help to stderr");
@@ -552,7 +552,11 @@ fn set_globals(
             );
             set_globals(vm, db, pattern, typ, value)
         }
-        Pattern::Constructor(..) | Pattern::Literal(_) | Pattern::Error => {
+        Pattern::Constructor(..)
+        | Pattern::Array { .. }
+        | Pattern::Literal(_)
+        | Pattern::Or(_)
+        | Pattern::Error => {
             Err(VMError::Message("The repl cannot bind variables from this pattern".into()).into())
         }
     }
@@ -656,6 +660,7 @@ async fn compile_repl(vm: &Thread) -> Result<(), GluonError> {
 
     add_extern_module(vm, "repl.prim", load_repl);
     add_extern_module(vm, "rustyline", load_rustyline);
+    add_extern_module(vm, "repl.debugger", crate::debugger::load);
 
     const REPL_SOURCE: &'static str =
         include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/repl.glu"));
@@ -55,6 +55,19 @@ impl REPL {
         Ok(())
     }
 
+    /// Sends a command to the debugger prompt of a paused script. Unlike the REPL prompt, the
+    /// debugger prompt does not echo the input back (the pty has echo disabled)
+    fn debug(&mut self, send: &str, expect: &str) {
+        self.debug_(send, expect)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    fn debug_(&mut self, send: &str, expect: &str) -> Result<()> {
+        self.session.send_line(send)?;
+        self.session.exp_string(expect)?;
+        Ok(())
+    }
+
     fn quit(&mut self) {
         self.quit_().unwrap_or_else(|err| panic!("{}", err));
     }
@@ -170,3 +183,21 @@ fn assert() {
     repl.test("let { assert } = import! std.test", None);
     repl.test("assert False", None);
 }
+
+#[test]
+fn debugger() {
+    let mut repl = REPL::new();
+
+    repl.test(
+        ":b tests/debug.glu:4",
+        Some("Breakpoint set at tests/debug.glu:4"),
+    );
+    repl.debug(":run tests/debug.glu", "Breakpoint tests/debug.glu:4");
+    repl.debug("locals", "y : Int = 11");
+    repl.debug("print x + y", "x + y : Int = 21");
+    repl.debug("bt", "#1 debug at tests.debug:5");
+    repl.debug("next", "    6 | z");
+    repl.debug("continue", "22 : Int");
+    repl.session.exp_string(repl.prompt).unwrap();
+    repl.test(":cl", Some("Removed all breakpoints"));
+}
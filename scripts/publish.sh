@@ -27,6 +27,8 @@ declare -a PROJECTS=(
     gluon_c-api
     gluon_doc
     gluon_repl
+    gluon_language-server
+    gluon_debug-adapter
 )
 
 for PROJECT in "${PROJECTS[@]}"
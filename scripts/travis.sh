@@ -17,6 +17,8 @@ declare -a PROJECTS=(
     gluon_c-api
     gluon_doc
     gluon_repl
+    gluon_language-server
+    gluon_debug-adapter
 )
 
 if [ -z $NO_NORMAL_TEST ]; then
@@ -0,0 +1,420 @@
+//! On-disk cache of compiled modules.
+//!
+//! When `bytecode_cache` is set in the compiler settings, every module loaded from source is
+//! stored in that directory together with a hash of its source and a fingerprint of each module
+//! it depends on. The next time the module is imported (possibly by another process) the cached
+//! bytecode is used instead of compiling the module again, as long as neither the module itself
+//! nor any of its transitive imports have changed.
+use std::{
+    collections::hash_map::Entry,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    },
+};
+
+use bincode::Options;
+use salsa::OwnedDb;
+
+use crate::{
+    base::{
+        ast::{OwnedExpr, SpannedExpr, TypedIdent, Visitor},
+        fnv::{FnvHasher, FnvMap, FnvSet},
+        metadata::Metadata,
+        serialization::Seed,
+        symbol::{Name, Symbol},
+        types::{self, AliasData, ArcType, Type},
+    },
+    compiler_pipeline::Module,
+    query::{AsyncCompilation, Compilation, ExternLoaderQuery},
+    vm::{
+        api::OpaqueValue,
+        gc::GcPtr,
+        internal::ClosureData,
+        serialization::{DeSeed, SeSeed},
+        thread::RootedThread,
+        types::BYTECODE_VERSION,
+    },
+    Settings,
+};
+
+/// Header of a cache file. Checked before the (much larger) module is deserialized.
+type Header = (
+    (String, u32),
+    u64,
+    Vec<(String, u64)>,
+    Vec<SharedDefinition>,
+);
+
+/// The path of a field in the metadata of a module
+type MetadataPath = Vec<String>;
+
+/// A definition in the metadata which must be the same symbol as the definition at another path,
+/// either in the module itself (`None`) or in one of its dependencies. Implicit resolution relies
+/// on this to recognize that two candidates are the same binding, so it must be restored after
+/// deserializing the module.
+type SharedDefinition = (MetadataPath, Option<String>, MetadataPath);
+
+pub(crate) type CachedModule = (
+    ArcType,
+    Arc<Metadata>,
+    OpaqueValue<RootedThread, GcPtr<ClosureData>>,
+);
+
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+/// The version of gluon and of its bytecode format. Cache files written by any other version are
+/// ignored.
+fn version() -> (String, u32) {
+    (env!("CARGO_PKG_VERSION").into(), BYTECODE_VERSION)
+}
+
+/// Hashes `value` with a hasher which gives the same result in every process, unlike
+/// `DefaultHasher` which is not guaranteed to be stable across Rust releases
+fn hash<T: ?Sized + Hash>(value: &T) -> u64 {
+    let mut hasher = FnvHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cache_path(dir: &Path, module: &str, settings: &Settings) -> PathBuf {
+    // Only the settings which affect the compiled module are part of the key
+    let settings_hash = hash(&(
+        settings.implicit_prelude,
+        settings.emit_debug_info,
+        settings.full_metadata,
+        settings.use_standard_lib,
+        settings.optimize,
+    ));
+    dir.join(format!("{}-{:016x}.bin", module, settings_hash))
+}
+
+/// Returns a hash which changes whenever the interface or implementation of `module` changes.
+/// Returns `None` if the module has not been loaded or can't be cached.
+fn fingerprint(db: &dyn Compilation, module: &str) -> Option<u64> {
+    if let Some(fingerprint) = db.compiler().state().fingerprints.get(module) {
+        return Some(*fingerprint);
+    }
+    if ExternLoaderQuery
+        .in_db(db)
+        .peek(&module.to_string())
+        .is_some()
+    {
+        return db
+            .peek_global(module)
+            .map(|global| hash(&global.typ.to_string()));
+    }
+    None
+}
+
+fn module_fingerprint(source_hash: u64, dependencies: &[(String, u64)]) -> u64 {
+    hash(&(version(), source_hash, dependencies))
+}
+
+/// Collects every alias group reachable from `types`
+fn alias_groups(
+    types: impl IntoIterator<Item = ArcType>,
+) -> Vec<Arc<[AliasData<Symbol, ArcType>]>> {
+    let mut groups = Vec::new();
+    let mut seen = FnvSet::default();
+    let mut stack: Vec<ArcType> = types.into_iter().collect();
+    while let Some(typ) = stack.pop() {
+        types::walk_type(&typ, |typ: &ArcType| match &**typ {
+            Type::Alias(alias) if seen.insert(&alias.group[..] as *const [_] as *const ()) => {
+                stack.extend(
+                    alias
+                        .group
+                        .iter()
+                        .map(|alias| alias.unresolved_type().clone()),
+                );
+                groups.push(alias.group.clone());
+            }
+            // Only the aliased types are walked, not the aliases themselves
+            Type::ExtendTypeRow { types, .. } => {
+                stack.extend(types.iter().map(|field| field.typ.as_type().clone()))
+            }
+            _ => (),
+        });
+    }
+    groups
+}
+
+fn collect_definitions(
+    metadata: &Metadata,
+    path: &mut MetadataPath,
+    definitions: &mut Vec<(MetadataPath, Symbol)>,
+) {
+    if let Some(definition) = &metadata.definition {
+        definitions.push((path.clone(), definition.clone()));
+    }
+    for (field, metadata) in &metadata.module {
+        path.push(field.clone());
+        collect_definitions(metadata, path, definitions);
+        path.pop();
+    }
+}
+
+fn definition_at(metadata: &Metadata, path: &[String]) -> Option<Symbol> {
+    match path.split_first() {
+        Some((field, path)) => definition_at(metadata.module.get(field)?, path),
+        None => metadata.definition.clone(),
+    }
+}
+
+fn set_definition(metadata: &mut Arc<Metadata>, path: &[String], definition: Symbol) -> Option<()> {
+    let metadata = Arc::make_mut(metadata);
+    match path.split_first() {
+        Some((field, path)) => set_definition(metadata.module.get_mut(field)?, path, definition),
+        None => {
+            metadata.definition = Some(definition);
+            Some(())
+        }
+    }
+}
+
+/// Collects the names of the modules that `expr` refers to
+fn dependencies(expr: &SpannedExpr<Symbol>, module_globals: &[Symbol]) -> Vec<String> {
+    struct Dependencies(Vec<String>);
+
+    impl<'a> Visitor<'a, '_> for Dependencies {
+        type Ident = Symbol;
+
+        fn visit_ident(&mut self, id: &TypedIdent<Symbol>) {
+            if id.name.is_global() {
+                self.0.push(id.name.definition_name().to_string());
+            }
+        }
+    }
+
+    let mut visitor = Dependencies(
+        module_globals
+            .iter()
+            .map(|id| id.definition_name().to_string())
+            .collect(),
+    );
+    visitor.visit_expr(expr);
+    let mut dependencies = visitor.0;
+    dependencies.sort();
+    dependencies.dedup();
+    dependencies
+}
+
+/// Loads `module` from the cache if it exists and is up to date with its source and every
+/// module it depends on.
+pub(crate) async fn load(
+    db: &mut OwnedDb<'_, dyn Compilation + '_>,
+    module: &str,
+) -> Option<CachedModule> {
+    let settings = db.compiler_settings();
+    let dir = settings.bytecode_cache.as_ref()?;
+    // Calls the function directly, the `module_text` query may only be a dependency of queries
+    // which are always re-executed (as `typechecked_source_module` is) since salsa can't verify
+    // a synchronous query from an asynchronous query
+    let text = crate::query::module_text(&**db, module.into()).ok()?;
+    let source_hash = hash(&text[..]);
+
+    let path = cache_path(dir, module, &settings);
+    let mut reader = BufReader::new(File::open(&path).ok()?);
+    let (version, cached_source_hash, dependencies, shared_definitions): Header =
+        options().deserialize_from(&mut reader).ok()?;
+    if version != self::version() || cached_source_hash != source_hash {
+        return None;
+    }
+
+    let mut types = Vec::with_capacity(dependencies.len());
+    let mut metadata = FnvMap::default();
+    for (dependency, dependency_fingerprint) in &dependencies {
+        // Any errors are reported when the module is compiled instead
+        let global = db.global(dependency.clone()).await.ok()?;
+        if fingerprint(db.compiler(), dependency) != Some(*dependency_fingerprint) {
+            debug!("Cached `{}` is stale: `{}` has changed", module, dependency);
+            return None;
+        }
+        types.push(global.typ);
+        metadata.insert(dependency, global.metadata);
+    }
+
+    // The module may be loaded again in the same database when salsa re-executes the query. The
+    // previously loaded module must be reused as other modules may already refer to its types.
+    {
+        let state = db.compiler().state();
+        if let Some(cached) = state.cached_modules.get(module) {
+            return Some(cached.clone());
+        }
+        if state.fingerprints.contains_key(module) {
+            // Compiled from source in this database, let the memoized typecheck be used
+            return None;
+        }
+    }
+
+    let thread = db.thread().root_thread();
+    let mut module_value: Module = {
+        let mut context = thread.current_context();
+        let mut seed = DeSeed::new(&thread, &mut context);
+        // Let the types in the module refer to the aliases of the modules that are already loaded
+        std::borrow::BorrowMut::<Seed<Symbol, ArcType>>::borrow_mut(&mut seed)
+            .set_aliases(alias_groups(types));
+        let mut deserializer = bincode::Deserializer::with_reader(reader, options());
+        match seed.deserialize(&mut deserializer) {
+            Ok(module) => module,
+            Err(err) => {
+                warn!(
+                    "Unable to load `{}` from {}: {}",
+                    module,
+                    path.display(),
+                    err
+                );
+                return None;
+            }
+        }
+    };
+
+    for (path, dependency, source_path) in shared_definitions {
+        let source = match dependency {
+            Some(dependency) => metadata.get(&dependency)?,
+            None => &module_value.metadata,
+        };
+        let definition = definition_at(source, &source_path)?;
+        set_definition(&mut module_value.metadata, &path, definition)?;
+    }
+
+    db.compiler().get_or_insert_filemap(module, &text[..]);
+    let closure = thread
+        .global_env()
+        .new_global_thunk(&thread, module_value.module)
+        .ok()?;
+
+    let cached = (module_value.typ, module_value.metadata, closure);
+    let mut state = db.compiler().state();
+    state.fingerprints.insert(
+        module.into(),
+        module_fingerprint(source_hash, &dependencies),
+    );
+    state.cached_modules.insert(module.into(), cached.clone());
+    info!("Loaded `{}` from the bytecode cache", module);
+
+    Some(cached)
+}
+
+/// Stores the compiled `module` into the cache. Modules which depend on a global set through
+/// `set_global`, or whose types can't be restored, are not cached.
+pub(crate) fn store(
+    db: &dyn Compilation,
+    name: &str,
+    source: &str,
+    expr: &OwnedExpr<Symbol>,
+    module: &Module,
+) {
+    let settings = db.compiler_settings();
+    let dir = match &settings.bytecode_cache {
+        Some(dir) => dir,
+        None => return,
+    };
+    let source_hash = hash(source);
+
+    // The module was recompiled so any previous version of it is out of date
+    {
+        let mut state = db.compiler().state();
+        state.fingerprints.remove(name);
+        state.cached_modules.remove(name);
+    }
+
+    let mut dependencies = Vec::new();
+    let mut types = Vec::new();
+    let mut dependency_definitions = FnvMap::default();
+    for dependency in self::dependencies(expr.expr(), &module.module.module_globals) {
+        match (fingerprint(db, &dependency), db.peek_global(&dependency)) {
+            (Some(fingerprint), Some(global)) => {
+                let mut definitions = Vec::new();
+                collect_definitions(&global.metadata, &mut Vec::new(), &mut definitions);
+                for (path, definition) in definitions {
+                    dependency_definitions
+                        .entry(definition)
+                        .or_insert_with(|| (Some(dependency.clone()), path));
+                }
+                types.push(global.typ);
+                dependencies.push((dependency, fingerprint));
+            }
+            _ => {
+                debug!("Not caching `{}` as `{}` can't be cached", name, dependency);
+                return;
+            }
+        }
+    }
+
+    // Every alias which does not come from a dependency must be defined in the module itself,
+    // otherwise it could not be resolved when the module is loaded again
+    let dependency_aliases: FnvMap<_, _> = alias_groups(types)
+        .into_iter()
+        .map(|group| (group[0].name.to_string(), group))
+        .collect();
+    let module_name = Name::new(name);
+    let restorable = alias_groups(Some(module.typ.clone())).iter().all(|group| {
+        match dependency_aliases.get(&group[0].name.to_string()) {
+            Some(dependency_group) => Arc::ptr_eq(group, dependency_group),
+            None => Name::new(group[0].name.as_str()).module() == module_name,
+        }
+    });
+    if !restorable {
+        debug!("Not caching `{}` as its type can't be restored", name);
+        return;
+    }
+
+    let mut definitions = Vec::new();
+    collect_definitions(&module.metadata, &mut Vec::new(), &mut definitions);
+    let mut shared_definitions: Vec<SharedDefinition> = Vec::new();
+    for (path, definition) in definitions {
+        match dependency_definitions.entry(definition) {
+            Entry::Occupied(entry) => {
+                let (dependency, source_path) = entry.get().clone();
+                shared_definitions.push((path, dependency, source_path));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((None, path));
+            }
+        }
+    }
+
+    db.compiler()
+        .state()
+        .fingerprints
+        .insert(name.into(), module_fingerprint(source_hash, &dependencies));
+
+    let path = cache_path(dir, name, &settings);
+    if let Err(err) = write(
+        &path,
+        &(version(), source_hash, dependencies, shared_definitions),
+        module,
+    ) {
+        warn!("Unable to cache `{}` in {}: {}", name, path.display(), err);
+    }
+}
+
+fn write(path: &Path, header: &Header, module: &Module) -> Result<(), Box<bincode::ErrorKind>> {
+    use crate::serde::ser::SerializeState;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Write to a temporary file first so that other processes never see a partially written file
+    static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+    let temp_path = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, atomic::Ordering::Relaxed)
+    ));
+    {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        options().serialize_into(&mut writer, header)?;
+        let mut serializer = bincode::Serializer::new(&mut writer, options());
+        module.serialize_state(&mut serializer, &SeSeed::new())?;
+    }
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! Capabilities which restrict what the standard library of a VM may access outside of the VM.
+//!
+//! Hosts which run untrusted scripts can build the VM with `VmBuilder::capabilities` to, for
+//! instance, prevent scripts from touching the filesystem or spawning processes. Importing a
+//! module which requires a missing capability results in a compile-time error.
+//!
+//! Without `FS_READ` the `import!` macro also refuses to load modules from the filesystem, so
+//! only the standard library and extern modules registered by the host can be imported.
+use std::str::FromStr;
+
+bitflags! {
+    pub struct Capabilities: u32 {
+        /// Printing to and reading from the console (`std.io.print`, `std.io.read_line`, ...)
+        const CONSOLE = 1 << 0;
+        /// Reading files and directories (`std.fs`, `std.path` and reading files through `std.io`)
+        /// as well as importing modules from the filesystem
+        const FS_READ = 1 << 1;
+        /// Creating and writing files through `std.io`
+        const FS_WRITE = 1 << 2;
+        /// Spawning processes (`std.process`)
+        const PROCESS = 1 << 3;
+        /// Reading and modifying the environment of the process (`std.env`)
+        const ENV = 1 << 4;
+        /// Opening network connections and serving requests (`std.http` and `std.net`)
+        const NET = 1 << 5;
+        /// Reading the wall clock (`std.time.system_time.now`)
+        const CLOCK = 1 << 6;
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::FULL
+    }
+}
+
+impl Capabilities {
+    /// No access to anything outside of the VM
+    pub const PURE: Capabilities = Capabilities::empty();
+    /// Access to the console and read-only access to the filesystem
+    pub const IO_READONLY: Capabilities = Capabilities {
+        bits: Capabilities::CONSOLE.bits | Capabilities::FS_READ.bits,
+    };
+    /// Unrestricted access (the default)
+    pub const FULL: Capabilities = Capabilities::all();
+
+    /// Returns the capabilities that the extern module `module` requires to be loaded
+    pub fn required_by(module: &str) -> Capabilities {
+        EXTERN_MODULES
+            .iter()
+            .find(|(name, _)| *name == module)
+            .map_or(Capabilities::empty(), |(_, required)| *required)
+    }
+}
+
+/// Extern modules which may only be imported if the VM has the corresponding capability.
+/// `std.io.prim` is always available as it defines the `IO` type but its functions check
+/// `CONSOLE`, `FS_READ` and `FS_WRITE` individually.
+pub(crate) const EXTERN_MODULES: &[(&str, Capabilities)] = &[
+    ("std.fs.prim", Capabilities::FS_READ),
+    ("std.path.prim", Capabilities::FS_READ),
+    ("std.process.prim", Capabilities::PROCESS),
+    ("std.env.prim", Capabilities::ENV),
+    ("std.http.prim_types", Capabilities::NET),
+    ("std.http.prim", Capabilities::NET),
+    ("std.http.client.prim", Capabilities::NET),
+    ("std.net.prim", Capabilities::NET),
+];
+
+/// Parses one of the named profiles `pure`, `io-readonly` or `full`
+impl FromStr for Capabilities {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pure" => Ok(Capabilities::PURE),
+            "io-readonly" => Ok(Capabilities::IO_READONLY),
+            "full" => Ok(Capabilities::FULL),
+            _ => Err(format!(
+                "Unknown capability profile `{}`. Expected one of 'pure', 'io-readonly', 'full'",
+                s
+            )),
+        }
+    }
+}
@@ -30,7 +30,11 @@ use crate::{
         symbol::{Name, NameBuf, Symbol, SymbolModule},
         types::{ArcType, NullInterner, Type, TypeCache},
     },
-    check::{metadata, rename},
+    check::{
+        lint::SpannedWarning,
+        metadata, rename,
+        typecheck::{HelpError, TypeError},
+    },
     query::{env, AsyncCompilation, Compilation},
     vm::{
         compiler::CompiledModule,
@@ -38,7 +42,7 @@ use crate::{
         macros::MacroExpander,
         thread::{RootedThread, RootedValue, Thread, ThreadInternal, VmRoot},
     },
-    Error, ModuleCompiler, Result,
+    Error, ModuleCompiler, Result, WarningLevel, Warnings,
 };
 
 pub type BoxFuture<'vm, T, E> =
@@ -523,6 +527,8 @@ pub struct TypecheckValue<E> {
     pub typ: ArcType,
     pub metadata_map: FnvMap<Symbol, Arc<Metadata>>,
     pub metadata: Arc<Metadata>,
+    /// Warnings found while typechecking, if any
+    pub warnings: Option<Warnings>,
 }
 
 impl<E> TypecheckValue<E> {
@@ -532,12 +538,14 @@ impl<E> TypecheckValue<E> {
             typ,
             metadata_map,
             metadata,
+            warnings,
         } = self;
         TypecheckValue {
             expr: f(expr),
             typ,
             metadata_map,
             metadata,
+            warnings,
         }
     }
 }
@@ -598,7 +606,7 @@ fn typecheck_expr(
     file: &str,
     expected_type: Option<&ArcType>,
     metadata_map: &mut FnvMap<Symbol, Arc<Metadata>>,
-) -> Result<ArcType> {
+) -> Result<(ArcType, Vec<SpannedWarning<Symbol>>)> {
     use crate::check::typecheck::Typecheck;
     let env = env(&*compiler.database);
     let (arena, expr) = expr.arena_expr();
@@ -611,8 +619,14 @@ fn typecheck_expr(
         arena.borrow(),
     );
 
-    tc.typecheck_expr_expected(expr, expected_type)
-        .map_err(|err| InFile::new(compiler.database.state().code_map.clone(), err).into())
+    let result = tc
+        .typecheck_expr_expected(expr, expected_type)
+        .map(|typ| (typ, tc.take_warnings()));
+    result.map_err(|err| InFile::new(compiler.database.state().code_map.clone(), err).into())
+}
+
+fn is_std_module(file: &str) -> bool {
+    file == "std" || file.starts_with("std.")
 }
 
 #[async_trait::async_trait]
@@ -638,7 +652,7 @@ where
             metadata,
         } = self;
 
-        let typ = match typecheck_expr(
+        let (typ, warnings) = match typecheck_expr(
             expr.borrow_mut(),
             compiler,
             thread,
@@ -646,7 +660,7 @@ where
             expected_type,
             &mut metadata_map,
         ) {
-            Ok(typ) => typ,
+            Ok(x) => x,
             Err(error) => {
                 return Err(Salvage {
                     value: Some(TypecheckValue {
@@ -658,12 +672,43 @@ where
                         expr,
                         metadata_map,
                         metadata,
+                        warnings: None,
                     }),
                     error,
                 })
             }
         };
 
+        let code_map = compiler.code_map();
+        let warning_level = compiler.compiler_settings().warning_level;
+        let warnings: Errors<_> = match warning_level {
+            WarningLevel::Allow => Errors::new(),
+            _ if is_std_module(file) => Errors::new(),
+            // Only report warnings that point into this module, hiding any found in the implicit
+            // prelude
+            _ => warnings
+                .into_iter()
+                .filter(|warning| {
+                    code_map
+                        .get(warning.span.start())
+                        .map_or(false, |source| source.name() == file)
+                })
+                .collect(),
+        };
+        let (warnings, error) = match warning_level {
+            _ if warnings.is_empty() => (None, None),
+            WarningLevel::Deny => {
+                let errors = warnings
+                    .into_iter()
+                    .map(|warning| {
+                        warning.map(|warning| HelpError::from(TypeError::Warning(warning)))
+                    })
+                    .collect();
+                (None, Some(InFile::new(code_map, errors)))
+            }
+            _ => (Some(InFile::new(code_map, warnings)), None),
+        };
+
         // Some metadata requires typechecking so recompute it if full metadata is required
         let (metadata, metadata_map) = if compiler.compiler_settings().full_metadata {
             let env = env(&*compiler.database);
@@ -672,12 +717,20 @@ where
             (metadata, metadata_map)
         };
 
-        Ok(TypecheckValue {
+        let value = TypecheckValue {
             expr,
             typ,
             metadata_map,
             metadata,
-        })
+            warnings,
+        };
+        match error {
+            Some(error) => Err(Salvage {
+                value: Some(value),
+                error: error.into(),
+            }),
+            None => Ok(value),
+        }
     }
 }
 
@@ -0,0 +1,524 @@
+//! A step debugger built on top of the hook API of the virtual machine.
+//!
+//! A `Debugger` is attached to a thread together with a handler which is called each time the
+//! thread pauses, either because it reached a breakpoint or because it finished a step. The
+//! handler can inspect the paused thread through `Paused` and returns a `Command` which decides
+//! how execution continues.
+//!
+//! ```rust,no_run
+//! use gluon::{debugger::{Command, Debugger}, new_vm, ThreadExt};
+//!
+//! let vm = new_vm();
+//! let debugger = Debugger::new();
+//! debugger.add_breakpoint("example", 2);
+//! debugger
+//!     .attach(&vm, |paused| {
+//!         for local in paused.locals(0) {
+//!             println!("{} : {} = {}", local.name, local.typ, local.value);
+//!         }
+//!         Command::StepOver
+//!     })
+//!     .unwrap();
+//! vm.run_expr::<i32>("example", "let x = 1\nx #Int+ 2").unwrap();
+//! debugger.detach(&vm);
+//! ```
+use std::{
+    collections::BTreeSet,
+    fmt,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use futures::{
+    prelude::*,
+    task::{self, ArcWake},
+};
+
+use crate::{
+    base::{
+        filename_to_module,
+        pos::Line,
+        types::{ArcType, Type},
+    },
+    compiler_pipeline::{Executable, ExecuteValue},
+    vm::{
+        api::{generic::A, Function, Getable, OpaqueValue},
+        internal::ValuePrinter,
+        thread::{DebugInfo, HookFlags, RootedThread, StackInfo, Thread, ThreadInternal},
+        Variants,
+    },
+    ModuleCompiler, Result, ThreadExt,
+};
+
+/// A breakpoint at a 1-based line in a source
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Breakpoint {
+    /// The name of the source. Either a module name (`std.list`) or a file name
+    /// (`std/list.glu`)
+    pub source: String,
+    pub line: usize,
+}
+
+impl Breakpoint {
+    pub fn new(source: &str, line: usize) -> Self {
+        Breakpoint {
+            source: source.to_string(),
+            line,
+        }
+    }
+
+    fn matches(&self, source_name: &str, line: Line) -> bool {
+        self.line == line.to_usize() + 1 && filename_to_module(&self.source) == source_name
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.source, self.line)
+    }
+}
+
+/// Decides how execution continues after the thread has been paused
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Run until the next breakpoint
+    Continue,
+    /// Pause at the next line, entering any function that gets called
+    StepIn,
+    /// Pause at the next line of the current function or of the function it returns to
+    StepOver,
+    /// Pause at the next line of the function that the current function returns to
+    StepOut,
+    /// Stop executing by returning an error from the running thread
+    Terminate,
+}
+
+/// The reason for a thread being paused
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PauseReason {
+    Entry,
+    /// `Debugger::pause` was called
+    Pause,
+    Breakpoint(Breakpoint),
+    Step,
+}
+
+/// Information about a stack frame of a paused thread
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FrameInfo {
+    pub function_name: Option<String>,
+    pub source_name: String,
+    /// The 1-based line that is executing in this frame, if it is known
+    pub line: Option<usize>,
+}
+
+/// A local variable, upvar or evaluated expression
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub typ: ArcType,
+    /// The pretty printed value
+    pub value: String,
+}
+
+struct Step {
+    command: Command,
+    depth: usize,
+    function_name: Option<String>,
+}
+
+#[derive(Default)]
+struct State {
+    breakpoints: BTreeSet<Breakpoint>,
+    // Set to pause at the next line regardless of breakpoints or stepping
+    pause: Option<PauseReason>,
+    step: Option<Step>,
+    // The location the thread were last resumed from. Prevents the same line from pausing again
+    // once a function called from it returns
+    resumed_at: Option<(usize, Line)>,
+    // Set once the thread has been terminated so that it is stopped again if execution resumes
+    // after the error (for instance in code which catches it)
+    terminated: bool,
+}
+
+impl State {
+    fn pause_reason(
+        &mut self,
+        stack_info: &StackInfo,
+        depth: usize,
+        line: Line,
+    ) -> Option<PauseReason> {
+        match self.resumed_at {
+            Some(resumed_at) if resumed_at == (depth, line) => return None,
+            Some((resumed_depth, _)) if depth <= resumed_depth => self.resumed_at = None,
+            _ => (),
+        }
+
+        if let Some(reason) = self.pause.take() {
+            return Some(reason);
+        }
+
+        if let Some(breakpoint) = self
+            .breakpoints
+            .iter()
+            .find(|breakpoint| breakpoint.matches(stack_info.source_name(), line))
+        {
+            return Some(PauseReason::Breakpoint(breakpoint.clone()));
+        }
+
+        let step = self.step.as_ref()?;
+        let paused = match step.command {
+            Command::StepIn => true,
+            // A tail call replaces the frame of the current function so only pause at the same
+            // depth if we are still in the same function
+            Command::StepOver => {
+                depth < step.depth
+                    || (depth == step.depth
+                        && stack_info.function_name() == step.function_name.as_deref())
+            }
+            Command::StepOut => depth < step.depth,
+            Command::Continue | Command::Terminate => false,
+        };
+        if paused {
+            Some(PauseReason::Step)
+        } else {
+            None
+        }
+    }
+
+    fn resume(&mut self, command: Command, stack_info: &StackInfo, depth: usize, line: Line) {
+        self.step = match command {
+            Command::StepIn | Command::StepOver | Command::StepOut => Some(Step {
+                command,
+                depth,
+                function_name: stack_info.function_name().map(|name| name.to_string()),
+            }),
+            Command::Continue | Command::Terminate => None,
+        };
+        self.resumed_at = Some((depth, line));
+        self.terminated = command == Command::Terminate;
+    }
+}
+
+/// A step debugger which can be attached to a thread. Cloning a `Debugger` returns a handle to
+/// the same debugger, letting breakpoints be changed while the thread is paused.
+#[derive(Clone, Default)]
+pub struct Debugger {
+    state: Arc<Mutex<State>>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Debugger::default()
+    }
+
+    /// Adds a breakpoint at the 1-based `line` of `source`. Returns `false` if the breakpoint
+    /// already existed.
+    pub fn add_breakpoint(&self, source: &str, line: usize) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .breakpoints
+            .insert(Breakpoint::new(source, line))
+    }
+
+    /// Removes the breakpoint at `line` of `source`. Returns `false` if no such breakpoint
+    /// existed.
+    pub fn remove_breakpoint(&self, source: &str, line: usize) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .breakpoints
+            .remove(&Breakpoint::new(source, line))
+    }
+
+    /// Replaces the breakpoints of `source` with breakpoints at `lines`
+    pub fn set_breakpoints(&self, source: &str, lines: impl IntoIterator<Item = usize>) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .breakpoints
+            .retain(|breakpoint| breakpoint.source != source);
+        state
+            .breakpoints
+            .extend(lines.into_iter().map(|line| Breakpoint::new(source, line)));
+    }
+
+    pub fn clear_breakpoints(&self) {
+        self.state.lock().unwrap().breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> Vec<Breakpoint> {
+        self.state
+            .lock()
+            .unwrap()
+            .breakpoints
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Pauses the thread at the first line it executes after being attached
+    pub fn stop_on_entry(&self, stop: bool) {
+        self.state.lock().unwrap().pause = if stop { Some(PauseReason::Entry) } else { None };
+    }
+
+    /// Pauses the thread at the next line it executes
+    pub fn pause(&self) {
+        self.state.lock().unwrap().pause = Some(PauseReason::Pause);
+    }
+
+    /// Attaches the debugger to `thread`, calling `handler` each time the thread pauses.
+    ///
+    /// The debugger keeps a child thread of `thread` alive to evaluate expressions in so `detach`
+    /// should be called once the debugger is no longer needed.
+    pub fn attach<F>(&self, thread: &Thread, handler: F) -> Result<()>
+    where
+        F: FnMut(&mut Paused) -> Command + Send + 'static,
+    {
+        let eval_thread = thread.new_thread()?;
+        let state = self.state.clone();
+        let handler = Mutex::new(handler);
+
+        let mut context = thread.context();
+        context.set_hook(Some(Box::new(move |thread, info| {
+            if state.lock().unwrap().terminated {
+                return Poll::Ready(Err(terminated()));
+            }
+            let stack_info = match info.stack_info(0) {
+                Some(stack_info) => stack_info,
+                None => return Poll::Ready(Ok(())),
+            };
+            let line = match stack_info.line() {
+                Some(line) => line,
+                None => return Poll::Ready(Ok(())),
+            };
+            let depth = info.stack_info_len();
+            let reason = match state.lock().unwrap().pause_reason(&stack_info, depth, line) {
+                Some(reason) => reason,
+                None => return Poll::Ready(Ok(())),
+            };
+
+            let mut paused = Paused {
+                thread,
+                eval_thread: &eval_thread,
+                info: &info,
+                reason,
+            };
+            let command = (handler.lock().unwrap())(&mut paused);
+            state
+                .lock()
+                .unwrap()
+                .resume(command, &stack_info, depth, line);
+            match command {
+                Command::Terminate => Poll::Ready(Err(terminated())),
+                _ => Poll::Ready(Ok(())),
+            }
+        })));
+        context.set_hook_mask(HookFlags::LINE_FLAG);
+        Ok(())
+    }
+
+    /// Removes the debugger from `thread`
+    pub fn detach(&self, thread: &Thread) {
+        let mut context = thread.context();
+        context.set_hook(None);
+        context.set_hook_mask(HookFlags::empty());
+        let mut state = self.state.lock().unwrap();
+        state.step = None;
+        state.resumed_at = None;
+        state.terminated = false;
+    }
+}
+
+fn terminated() -> crate::vm::Error {
+    crate::vm::Error::Message("Execution was terminated by the debugger".into())
+}
+
+/// A paused thread
+pub struct Paused<'a> {
+    thread: &'a Thread,
+    eval_thread: &'a RootedThread,
+    info: &'a DebugInfo<'a>,
+    reason: PauseReason,
+}
+
+impl<'a> Paused<'a> {
+    pub fn reason(&self) -> &PauseReason {
+        &self.reason
+    }
+
+    /// Returns the frames of the paused thread, starting with the frame that is executing
+    pub fn frames(&self) -> Vec<FrameInfo> {
+        (0..self.info.stack_info_len())
+            .filter_map(|level| self.info.stack_info(level))
+            .map(|stack_info| FrameInfo {
+                function_name: stack_info.function_name().map(|name| name.to_string()),
+                source_name: stack_info.source_name().to_string(),
+                line: stack_info.line().map(|line| line.to_usize() + 1),
+            })
+            .collect()
+    }
+
+    /// Returns the locals which are in scope at the frame at `level`, where `0` is the frame
+    /// that is executing. Locals introduced by the compiler are skipped.
+    pub fn locals(&self, level: usize) -> Vec<Variable> {
+        let stack_info = match self.info.stack_info(level) {
+            Some(stack_info) => stack_info,
+            None => return Vec::new(),
+        };
+        stack_info
+            .locals()
+            .filter(|local| !is_generated(local.name.declared_name()))
+            .filter_map(|local| {
+                let value = stack_info.local_value(local)?;
+                Some(self.variable(local.name.declared_name(), &local.typ, value))
+            })
+            .collect()
+    }
+
+    /// Returns the upvars captured by the function executing at the frame at `level`
+    pub fn upvars(&self, level: usize) -> Vec<Variable> {
+        let stack_info = match self.info.stack_info(level) {
+            Some(stack_info) => stack_info,
+            None => return Vec::new(),
+        };
+        stack_info
+            .upvars()
+            .iter()
+            .enumerate()
+            .filter(|(_, upvar)| !is_generated(&upvar.name))
+            .filter_map(|(i, upvar)| {
+                let value = stack_info.upvar_value(i)?;
+                Some(self.variable(&upvar.name, &upvar.typ, value))
+            })
+            .collect()
+    }
+
+    /// Evaluates `expr` with the locals and upvars of the frame at `level` in scope.
+    ///
+    /// Blocks the current thread until the evaluation is done. If the virtual machine has a
+    /// spawner, the tasks spawned while compiling `expr` must be able to run on another thread.
+    pub fn evaluate(&self, level: usize, expr: &str) -> Result<Variable> {
+        let stack_info = self.info.stack_info(level).ok_or_else(|| {
+            crate::vm::Error::Message(format!("There is no frame at level {}", level))
+        })?;
+
+        let mut bindings = Vec::<(String, ArcType, Variants<'a>)>::new();
+        {
+            let mut bind = |name: &str, typ: &ArcType, value: Option<Variants<'a>>| {
+                if let Some(value) = value {
+                    if is_referenced(expr, name) {
+                        bindings.retain(|binding| binding.0 != name);
+                        bindings.push((name.to_string(), typ.clone(), value));
+                    }
+                }
+            };
+            for (i, upvar) in stack_info.upvars().iter().enumerate() {
+                bind(&upvar.name, &upvar.typ, stack_info.upvar_value(i));
+            }
+            for local in stack_info.locals() {
+                bind(
+                    local.name.declared_name(),
+                    &local.typ,
+                    stack_info.local_value(local),
+                );
+            }
+        }
+
+        let source = if bindings.is_empty() {
+            expr.to_string()
+        } else {
+            let names: Vec<_> = bindings.iter().map(|binding| &binding.0[..]).collect();
+            format!("\\{} -> {}", names.join(" "), expr)
+        };
+        let expected = Type::function(
+            bindings
+                .iter()
+                .map(|binding| binding.1.clone())
+                .collect::<Vec<_>>(),
+            Type::hole(),
+        );
+
+        let vm = &**self.eval_thread;
+        let ExecuteValue { value, typ, .. } = {
+            let mut db = vm.get_database();
+            let mut compiler = ModuleCompiler::new(&mut db);
+            block_on(source.as_str().run_expr(
+                &mut compiler,
+                vm,
+                "<debugger>",
+                &source,
+                Some(&expected),
+            ))?
+        };
+
+        if bindings.is_empty() {
+            return Ok(self.variable(expr, &typ, value.get_variant()));
+        }
+
+        let mut result_type = typ;
+        for _ in 0..bindings.len() {
+            result_type = match result_type.as_function() {
+                Some((_, ret)) => ret.clone(),
+                None => break,
+            };
+        }
+        let function = Function::<&Thread, fn(A) -> A>::from_value(vm, value.get_variant());
+        let result: OpaqueValue<RootedThread, A> =
+            function.call_any(bindings.into_iter().map(|binding| binding.2))?;
+        Ok(self.variable(expr, &result_type, result.get_variant()))
+    }
+
+    fn variable(&self, name: &str, typ: &ArcType, value: Variants) -> Variable {
+        let env = self.thread.get_env();
+        let debug_level = self.thread.global_env().get_debug_level();
+        let value = ValuePrinter::new(&env, typ, value, &debug_level)
+            .width(80)
+            .max_level(5)
+            .to_string();
+        Variable {
+            name: name.to_string(),
+            typ: typ.clone(),
+            value,
+        }
+    }
+}
+
+/// Drives `future` to completion by blocking the current thread.
+///
+/// The thread being debugged is paused in the middle of being polled so this can't yield to the
+/// executor. Any tasks that the evaluation spawns (such as imports) must therefore be able to run
+/// on another thread.
+fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    struct ThreadWaker(std::thread::Thread);
+
+    impl ArcWake for ThreadWaker {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.unpark();
+        }
+    }
+
+    let waker = task::waker(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = task::Context::from_waker(&waker);
+    futures::pin_mut!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(value) => return value,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+// Names introduced by the compiler, such as implicit arguments and the bindings of the implicit
+// prelude
+fn is_generated(name: &str) -> bool {
+    name.contains('?') || name.starts_with("__")
+}
+
+fn is_referenced(expr: &str, name: &str) -> bool {
+    expr.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| word == name)
+}
@@ -39,6 +39,7 @@ use crate::vm::{
 };
 
 use crate::{
+    capabilities::Capabilities,
     compiler_pipeline::SalvageResult,
     query::{AsyncCompilation, Compilation, CompilerDatabase},
     IoError, ModuleCompiler, ThreadExt,
@@ -73,7 +74,21 @@ impl base::error::AsDiagnostic for Error {
         &self,
         _map: &base::source::CodeMap,
     ) -> codespan_reporting::diagnostic::Diagnostic<FileId> {
-        codespan_reporting::diagnostic::Diagnostic::error().with_message(self.to_string())
+        codespan_reporting::diagnostic::Diagnostic::error()
+            .with_message(self.to_string())
+            .with_code(self.code())
+    }
+}
+
+impl Error {
+    /// Returns the stable code which identifies this kind of error
+    pub fn code(&self) -> &'static str {
+        use base::error_code::*;
+        match self {
+            Error::CyclicDependency(..) => E0301,
+            Error::String(_) => E0302,
+            Error::IO(_) => E0303,
+        }
     }
 }
 
@@ -198,6 +213,7 @@ pub struct Import<I = DefaultImporter> {
     pub importer: I,
 
     pub compiler: Mutex<CompilerDatabase>,
+    capabilities: RwLock<Capabilities>,
 }
 
 #[derive(Debug)]
@@ -237,6 +253,7 @@ impl<I> Import<I> {
             paths: RwLock::new(vec![PathBuf::from(".")]),
             compiler: CompilerDatabase::new_base(None).into(),
             importer: importer,
+            capabilities: RwLock::new(Capabilities::default()),
         }
     }
 
@@ -249,6 +266,12 @@ impl<I> Import<I> {
         *self.paths.write().unwrap() = paths;
     }
 
+    /// Sets the capabilities of the VM. Modules outside of the standard library are only loaded
+    /// from the filesystem if `capabilities` contains `FS_READ`.
+    pub fn set_capabilities(&self, capabilities: Capabilities) {
+        *self.capabilities.write().unwrap() = capabilities;
+    }
+
     pub fn modules(&self, compiler: &mut ModuleCompiler<'_, '_>) -> Vec<Cow<'static, str>> {
         STD_LIBS
             .iter()
@@ -314,6 +337,14 @@ impl<I> Import<I> {
         Ok(match std_file {
             Some(tup) => Cow::Borrowed(tup.1),
             None => {
+                let capabilities = *self.capabilities.read().unwrap();
+                if !capabilities.contains(Capabilities::FS_READ) && !is_std_source(module) {
+                    return Err(Error::String(format!(
+                        "Could not load module '{}' from the filesystem as it requires the \
+                         FS_READ capability which this VM was built without",
+                        module
+                    )));
+                }
                 let paths = self.paths.read().unwrap();
                 let file = paths
                     .iter()
@@ -343,6 +374,12 @@ impl<I> Import<I> {
     }
 }
 
+/// Returns true if `module` is part of the standard library but is read from the `std` directory
+/// rather than being embedded in the binary, which is only done when testing gluon itself
+fn is_std_source(module: &str) -> bool {
+    cfg!(feature = "test") && STD_LIB_NAMES.contains(&module)
+}
+
 /// Adds an extern module to `thread`, letting it be loaded with `import! name` from gluon code.
 ///
 /// ```
@@ -507,6 +544,15 @@ where
             Ok(modulename)
         }
 
+        // Errors from the importer itself (missing or cyclic modules) are kept as they are so
+        // they keep their error codes, errors from compiling the module are wrapped
+        fn import_error(err: crate::Error) -> MacroError {
+            match err {
+                crate::Error::Other(err) => err,
+                err => MacroError::new(Error::String(err.to_string())),
+            }
+        }
+
         let modulename = match get_module_name(&args).map_err(MacroError::new) {
             Ok(modulename) => modulename,
             Err(err) => return Box::pin(future::err(err)),
@@ -534,7 +580,7 @@ where
                     let result = std::panic::AssertUnwindSafe(db.import(modulename))
                         .catch_unwind()
                         .await
-                        .map(|r| r.map_err(|err| MacroError::message(err.to_string())))
+                        .map(|r| r.map_err(import_error))
                         .unwrap_or_else(|err| {
                             Err(MacroError::message(
                                 err.downcast::<String>()
@@ -565,7 +611,7 @@ where
                     let result = db
                         .import(modulename)
                         .await
-                        .map_err(|err| MacroError::message(err.to_string()))
+                        .map_err(import_error)
                         .map(move |id| pos::spanned(span, Expr::Ident(id)));
                     drop(db);
                     result
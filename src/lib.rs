@@ -11,6 +11,8 @@ extern crate env_logger;
 
 pub extern crate either;
 #[macro_use]
+extern crate bitflags;
+#[macro_use]
 extern crate log;
 #[macro_use]
 extern crate quick_error;
@@ -45,7 +47,11 @@ macro_rules! try_future {
     };
 }
 
+#[cfg(feature = "serialization")]
+mod cache;
+pub mod capabilities;
 pub mod compiler_pipeline;
+pub mod debugger;
 #[macro_use]
 pub mod import;
 pub mod lift_io;
@@ -85,6 +91,7 @@ use crate::vm::{
 };
 
 use crate::{
+    capabilities::Capabilities,
     compiler_pipeline::*,
     import::{add_extern_module, add_extern_module_with_deps, DefaultImporter, Import},
     query::{AsyncCompilation, Compilation, CompilationBase, CompilerDatabase},
@@ -268,7 +275,19 @@ impl Error {
             Error::IO(err) => write!(writer, "{}", err),
             Error::VM(err) => write!(writer, "{}", err),
             Error::Macro(err) => err.emit(writer),
-            Error::Other(err) => write!(writer, "{}", err),
+            Error::Other(err) => {
+                // Errors without a source location are still emitted through codespan so that
+                // they are displayed with their code
+                use base::error::AsDiagnostic;
+
+                let source = base::source::CodeMap::new();
+                codespan_reporting::term::emit(
+                    writer,
+                    &Default::default(),
+                    &source,
+                    &err.as_diagnostic(&source),
+                )
+            }
             Error::Multiple(errors) => {
                 for err in errors {
                     err.emit(writer)?;
@@ -282,6 +301,26 @@ impl Error {
 /// Type alias for results returned by gluon
 pub type Result<T> = StdResult<T, Error>;
 
+/// Warnings found when compiling gluon code
+pub type Warnings = InFile<check::lint::Warning<Symbol>>;
+
+/// How warnings found during compilation are treated
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum WarningLevel {
+    /// Warnings are not reported
+    Allow,
+    /// Warnings are reported alongside the result of compilation
+    Warn,
+    /// Warnings are promoted to errors
+    Deny,
+}
+
+impl Default for WarningLevel {
+    fn default() -> Self {
+        WarningLevel::Warn
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Settings {
     pub implicit_prelude: bool,
@@ -290,6 +329,9 @@ pub struct Settings {
     pub use_standard_lib: bool,
     pub optimize: bool,
     pub run_io: bool,
+    pub warning_level: WarningLevel,
+    #[cfg(feature = "serialization")]
+    pub bytecode_cache: Option<PathBuf>,
 }
 
 impl Default for Settings {
@@ -301,6 +343,9 @@ impl Default for Settings {
             use_standard_lib: true,
             optimize: true,
             run_io: false,
+            warning_level: WarningLevel::Warn,
+            #[cfg(feature = "serialization")]
+            bytecode_cache: None,
         }
     }
 }
@@ -422,6 +467,24 @@ impl import::DatabaseMut {
         /// (default: false)
         run_io set_run_io: bool
     }
+
+    runtime_option! {
+        /// Sets how warnings such as unused variables are treated. Modules from the standard
+        /// library never report warnings.
+        /// (default: WarningLevel::Warn)
+        warning_level set_warning_level: WarningLevel
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl import::DatabaseMut {
+    runtime_option! {
+        /// Sets a directory where compiled modules are cached. Modules whose source, and the
+        /// source of every module they import, is unchanged are loaded from the cache instead of
+        /// being compiled again.
+        /// (default: None)
+        bytecode_cache set_bytecode_cache: Option<PathBuf>
+    }
 }
 
 /// Extension trait which provides methods to load and execute gluon code
@@ -504,6 +567,33 @@ pub trait ThreadExt: Send + Sync {
         expr_str: &str,
         expected_type: Option<&ArcType>,
     ) -> Result<(Arc<OwnedExpr<Symbol>>, ArcType)> {
+        let (expr, typ, _) = self
+            .typecheck_str_with_warnings_async(file, expr_str, expected_type)
+            .await?;
+        Ok((expr, typ))
+    }
+
+    /// Parse and typecheck `expr_str` returning the typechecked expression, its type and the
+    /// warnings that were found (if any)
+    fn typecheck_str_with_warnings(
+        &self,
+        file: &str,
+        expr_str: &str,
+        expected_type: Option<&ArcType>,
+    ) -> Result<(Arc<OwnedExpr<Symbol>>, ArcType, Option<Warnings>)> {
+        futures::executor::block_on(self.typecheck_str_with_warnings_async(
+            file,
+            expr_str,
+            expected_type,
+        ))
+    }
+
+    async fn typecheck_str_with_warnings_async(
+        &self,
+        file: &str,
+        expr_str: &str,
+        expected_type: Option<&ArcType>,
+    ) -> Result<(Arc<OwnedExpr<Symbol>>, ArcType, Option<Warnings>)> {
         let vm = self.thread();
         {
             let mut db = vm.get_database_mut();
@@ -511,7 +601,12 @@ pub trait ThreadExt: Send + Sync {
         }
         let mut db = vm.get_database();
 
-        let TypecheckValue { expr, typ, .. } = db
+        let TypecheckValue {
+            expr,
+            typ,
+            warnings,
+            ..
+        } = db
             .typechecked_source_module(file.into(), expected_type.cloned())
             .await?;
 
@@ -519,7 +614,7 @@ pub trait ThreadExt: Send + Sync {
         db.module_type(file.into(), None).await?;
         db.module_metadata(file.into(), None).await?;
 
-        Ok((expr, typ))
+        Ok((expr, typ, warnings))
     }
 
     /// Compiles `expr` into a function which can be added and run by the `vm`
@@ -535,6 +630,7 @@ pub trait ThreadExt: Send + Sync {
             typ: vm.global_env().type_cache().hole(),
             metadata: Default::default(),
             metadata_map: Default::default(),
+            warnings: None,
         }
         .compile(
             &mut ModuleCompiler::new(&mut vm.get_database()),
@@ -894,6 +990,9 @@ in ()
 #[derive(Default)]
 pub struct VmBuilder {
     import_paths: Option<Vec<PathBuf>>,
+    capabilities: Capabilities,
+    denied_modules: Vec<String>,
+    clock: Option<Arc<dyn std_lib::time::Clock>>,
 }
 
 impl VmBuilder {
@@ -906,6 +1005,27 @@ impl VmBuilder {
         import_paths set_import_paths: Option<Vec<PathBuf>>
     }
 
+    option! {
+        /// Restricts what the standard library may access outside of the VM. Importing a module
+        /// which requires a missing capability is an error.
+        /// (default: Capabilities::FULL)
+        capabilities set_capabilities: Capabilities
+    }
+
+    option! {
+        /// Extern modules (such as `std.fs.prim`) which may not be imported, regardless of
+        /// `capabilities`
+        /// (default: [])
+        denied_modules set_denied_modules: Vec<String>
+    }
+
+    option! {
+        /// The clock which `std.time` reads the current time from. `None` uses the clock of the
+        /// operating system.
+        /// (default: None)
+        clock set_clock: Option<Arc<dyn std_lib::time::Clock>>
+    }
+
     pub fn build(self) -> RootedThread {
         futures::executor::block_on(self.build_inner(None))
     }
@@ -947,6 +1067,7 @@ impl VmBuilder {
 
             {
                 let import = Import::new(DefaultImporter);
+                import.set_capabilities(self.capabilities);
                 if let Some(import_paths) = self.import_paths {
                     import.set_paths(import_paths);
                 }
@@ -986,13 +1107,21 @@ impl VmBuilder {
             ("std.fs.prim", crate::vm::primitives::load_fs),
             ("std.char.prim", crate::vm::primitives::load_char),
             ("std.char.prim", crate::vm::primitives::load_char),
+            ("std.hash.prim", crate::vm::primitives::load_hash),
             ("std.thread.prim", crate::vm::channel::load_thread),
-            ("std.io.prim", crate::std_lib::io::load),
         ];
         for (name, load_fn) in deps {
             add_extern_module_with_deps(&vm, name, load_fn, vec!["std.types".into()]);
         }
 
+        let capabilities = self.capabilities;
+        add_extern_module_with_deps(
+            &vm,
+            "std.io.prim",
+            move |vm: &Thread| crate::std_lib::io::load(vm, capabilities),
+            vec!["std.types".into()],
+        );
+
         add_extern_module_with_deps(
             &vm,
             "std.path.prim",
@@ -1000,11 +1129,19 @@ impl VmBuilder {
             vec!["std.path.types".into()],
         );
 
+        add_extern_module_with_deps(
+            &vm,
+            "std.channel.prim",
+            crate::vm::channel::load_channel,
+            vec!["std.channel.types".into()],
+        );
+
         let deps: &[(_, fn(&Thread) -> _)] = &[
             ("std.array.prim", crate::vm::primitives::load_array),
             ("std.lazy.prim", crate::vm::lazy::load),
             ("std.reference.prim", crate::vm::reference::load),
-            ("std.channel.prim", crate::vm::channel::load_channel),
+            ("std.effect.st.array.prim", crate::vm::st_array::load),
+            ("std.task.prim", crate::vm::task::load),
             ("std.debug.prim", crate::vm::debug::load),
             ("std.process.prim", crate::std_lib::process::load),
             ("std.env.prim", crate::std_lib::env::load),
@@ -1013,11 +1150,29 @@ impl VmBuilder {
             add_extern_module(&vm, name, load_fn);
         }
 
+        let clock = self
+            .clock
+            .clone()
+            .unwrap_or_else(|| Arc::new(crate::std_lib::time::SystemClock));
+        add_extern_module(&vm, "std.time.prim", move |vm: &Thread| {
+            crate::std_lib::time::load(vm, capabilities, clock.clone())
+        });
+
+        add_extern_module(&vm, "std.bytes.prim", move |vm: &Thread| {
+            crate::std_lib::bytes::load(vm, capabilities)
+        });
+
         add_extern_module(
             &vm,
             "std.effect.st.string.prim",
             crate::vm::primitives::load_string_buf,
         );
+        add_extern_module_with_deps(
+            &vm,
+            "std.effect.st.bytes.prim",
+            crate::std_lib::bytes::load_byte_buffer,
+            vec!["std.bytes".into()],
+        );
 
         add_extern_module_if!(
             #[cfg(feature = "serialization")],
@@ -1033,6 +1188,19 @@ impl VmBuilder {
             args(&vm, "std.regex.prim", crate::std_lib::regex::load)
         );
 
+        add_extern_module_if!(
+            #[cfg(feature = "bignum")],
+            available_if = "gluon is compiled with the 'bignum' feature",
+            args(&vm, "std.bigint.prim", crate::std_lib::bigint::load)
+        );
+
+        add_extern_module_if!(
+            #[cfg(feature = "bignum")],
+            available_if = "gluon is compiled with the 'bignum' feature",
+            dependencies = ["std.bigint"],
+            args(&vm, "std.decimal.prim", crate::std_lib::decimal::load)
+        );
+
         add_extern_module_if!(
             #[cfg(feature = "web")],
             available_if = "gluon is compiled with the 'web' feature",
@@ -1046,16 +1214,52 @@ impl VmBuilder {
             args(&vm, "std.http.prim", crate::std_lib::http::load)
         );
 
+        add_extern_module_if!(
+            #[cfg(feature = "web")],
+            available_if = "gluon is compiled with the 'web' feature",
+            dependencies = ["std.http.types", "std.time"],
+            args(&vm, "std.http.client.prim", crate::std_lib::http::client::load)
+        );
+
+        add_extern_module_if!(
+            #[cfg(feature = "net")],
+            available_if = "gluon is compiled with the 'net' feature",
+            args(&vm, "std.net.prim", crate::std_lib::net::load)
+        );
+
         add_extern_module_if!(
             #[cfg(all(feature = "random", not(target_arch = "wasm32")))],
             available_if = "gluon is compiled with the 'random' feature and is not targeting WASM",
             args(&vm, "std.random.prim", crate::std_lib::random::load)
         );
 
+        for &(name, required) in capabilities::EXTERN_MODULES {
+            if !self.capabilities.contains(required) {
+                deny_extern_module(
+                    &vm,
+                    name,
+                    format!(
+                        "{} requires the {:?} capability which this VM was built without",
+                        name, required
+                    ),
+                );
+            }
+        }
+        for name in &self.denied_modules {
+            deny_extern_module(&vm, name, format!("{} has been denied in this VM", name));
+        }
+
         vm
     }
 }
 
+/// Replaces the loader of `name` with one which fails to load the module
+fn deny_extern_module(vm: &Thread, name: &str, message: String) {
+    add_extern_module(vm, name, move |_: &Thread| {
+        Err(vm::Error::Message(message.clone()))
+    });
+}
+
 /// Creates a new virtual machine with support for importing other modules and with all primitives
 /// loaded.
 pub fn new_vm() -> RootedThread {
@@ -81,6 +81,10 @@ pub struct State {
     pub(crate) inline_modules: FnvMap<String, Arc<Cow<'static, str>>>,
     pub(crate) index_map: FnvMap<String, BytePos>,
     extern_globals: FnvSet<String>,
+    #[cfg(feature = "serialization")]
+    pub(crate) fingerprints: FnvMap<String, u64>,
+    #[cfg(feature = "serialization")]
+    pub(crate) cached_modules: FnvMap<String, crate::cache::CachedModule>,
 }
 
 impl State {
@@ -226,6 +230,14 @@ impl crate::query::CompilationBase for CompilerDatabase {
     }
 
     fn peek_module_type(&self, key: &str) -> Option<ArcType> {
+        // Modules loaded from the bytecode cache are not typechecked (unless the source is
+        // typechecked explicitly, which would create a distinct type)
+        #[cfg(feature = "serialization")]
+        {
+            if let Some((typ, _, _)) = self.state().cached_modules.get(key) {
+                return Some(typ.clone());
+            }
+        }
         ModuleTypeQuery
             .in_db(self)
             .peek(&(key.into(), None))
@@ -233,6 +245,12 @@ impl crate::query::CompilationBase for CompilerDatabase {
     }
 
     fn peek_module_metadata(&self, key: &str) -> Option<Arc<Metadata>> {
+        #[cfg(feature = "serialization")]
+        {
+            if let Some((_, metadata, _)) = self.state().cached_modules.get(key) {
+                return Some(metadata.clone());
+            }
+        }
         ModuleMetadataQuery
             .in_db(self)
             .peek(&(key.into(), None))
@@ -487,7 +505,10 @@ fn get_extern_global(db: &dyn Compilation, name: &str) -> Option<DatabaseGlobal>
     }
 }
 
-fn module_text(db: &dyn Compilation, module: String) -> StdResult<Arc<Cow<'static, str>>, Error> {
+pub(crate) fn module_text(
+    db: &dyn Compilation,
+    module: String,
+) -> StdResult<Arc<Cow<'static, str>>, Error> {
     db.salsa_runtime()
         .report_synthetic_read(salsa::Durability::LOW);
 
@@ -607,6 +628,12 @@ async fn compiled_module(
     module: String,
     expected_type: Option<ArcType>,
 ) -> StdResult<OpaqueValue<RootedThread, GcPtr<ClosureData>>, Error> {
+    #[cfg(feature = "serialization")]
+    let cache = if expected_type.is_none() && db.compiler_settings().bytecode_cache.is_some() {
+        Some(db.typechecked_source_module(module.clone(), None).await?)
+    } else {
+        None
+    };
     let core_expr = db.core_expr(module.clone(), expected_type).await?;
     let settings = db.compiler_settings();
 
@@ -636,6 +663,26 @@ async fn compiled_module(
     let mut compiled_module = compiler.compile_expr(core_expr.value.expr())?;
     let module_id = Symbol::from(format!("@{}", name));
     compiled_module.function.id = module_id.clone();
+
+    #[cfg(feature = "serialization")]
+    {
+        if let Some(TypecheckValue {
+            expr,
+            typ,
+            metadata,
+            ..
+        }) = cache
+        {
+            let cached = Module {
+                typ,
+                metadata,
+                module: compiled_module,
+            };
+            crate::cache::store(&**db, &module, source.source(), &expr, &cached);
+            compiled_module = cached.module;
+        }
+    }
+
     let closure = thread
         .global_env()
         .new_global_thunk(&thread, compiled_module)?;
@@ -677,14 +724,25 @@ async fn global_inner(
         return Ok(global);
     }
 
-    let TypecheckValue { metadata, typ, .. } =
-        db.typechecked_source_module(name.clone(), None).await?;
+    #[cfg(feature = "serialization")]
+    let cached = crate::cache::load(db, &name).await;
+    #[cfg(not(feature = "serialization"))]
+    let cached = None;
 
-    // Ensure the type is stored in the database so we can collect typechecked_source_module later
-    db.module_type(name.clone(), None).await?;
-    db.module_metadata(name.clone(), None).await?;
+    let (typ, metadata, closure) = match cached {
+        Some(cached) => cached,
+        None => {
+            let TypecheckValue { metadata, typ, .. } =
+                db.typechecked_source_module(name.clone(), None).await?;
 
-    let closure = db.compiled_module(name.clone(), None).await?;
+            // Ensure the type is stored in the database so we can collect typechecked_source_module later
+            db.module_type(name.clone(), None).await?;
+            db.module_metadata(name.clone(), None).await?;
+
+            let closure = db.compiled_module(name.clone(), None).await?;
+            (typ, metadata, closure)
+        }
+    };
 
     let module_id = closure.function.name.clone();
 
@@ -766,7 +824,7 @@ async fn global(
 use std::cell::RefCell;
 pub struct Env<T>(RefCell<T>);
 
-pub(crate) fn env(env: &(dyn Compilation + '_)) -> Env<&'_ CompilerDatabase> {
+pub(crate) fn env<'a>(env: &'a (dyn Compilation + 'a)) -> Env<&'a CompilerDatabase> {
     Env(RefCell::new(env.compiler()))
 }
 pub(crate) fn snapshot_env<T>(env: T) -> Env<T>
@@ -855,6 +913,26 @@ where
             None
         }
     }
+
+    fn loaded_modules(&self) -> Vec<(String, ArcType)> {
+        let db = self.0.borrow();
+        let modules = {
+            let state = db.state();
+            let mut modules: Vec<String> = state.index_map.keys().cloned().collect();
+            #[cfg(feature = "serialization")]
+            modules.extend(state.cached_modules.keys().cloned());
+            modules.sort();
+            modules.dedup();
+            modules
+        };
+        modules
+            .into_iter()
+            .filter_map(|module| {
+                let typ = db.peek_module_type(&module)?;
+                Some((module, typ))
+            })
+            .collect()
+    }
 }
 
 impl<T> PrimitiveEnv for Env<T>
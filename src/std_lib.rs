@@ -1,9 +1,33 @@
+/// Uses `$module::$name` if `$capabilities` contains `$required`, otherwise a function which throws
+/// an `IO` exception (created by the `denied` function in scope) when called
+macro_rules! gated {
+    ($capabilities: expr, $required: expr, $arg_count: tt, $($module: ident)::+, $name: ident, fn($($arg: ty),*) -> $ret: ty) => {
+        if $capabilities.contains($required) {
+            primitive!($arg_count, $($module)::+::$name)
+        } else {
+            primitive!(
+                $arg_count,
+                concat!($(stringify!($module), ".",)+ stringify!($name)),
+                |$(_: $arg),*| -> $ret { denied(stringify!($name), $required) }
+            )
+        }
+    };
+}
+
+#[cfg(feature = "bignum")]
+pub mod bigint;
+pub mod bytes;
+#[cfg(feature = "bignum")]
+pub mod decimal;
 pub mod env;
 #[cfg(feature = "http")]
 pub mod http;
 pub mod io;
+#[cfg(feature = "net")]
+pub mod net;
 pub mod process;
 #[cfg(all(feature = "random", not(target_arch = "wasm32")))]
 pub mod random;
 #[cfg(feature = "regex")]
 pub mod regex;
+pub mod time;
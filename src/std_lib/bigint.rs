@@ -0,0 +1,175 @@
+//! Module containing the primitives of `std.bigint`, arbitrary-precision integers backed by the
+//! `num-bigint` crate.
+
+use crate::real_std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use num_bigint::Sign;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+use crate::vm::{self, api::RuntimeResult, thread::Thread, types::VmInt, ExternModule};
+
+#[derive(Clone, Debug, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.bigint.BigInt")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+pub(crate) struct BigInt(pub(crate) num_bigint::BigInt);
+
+fn radix(radix: VmInt) -> Option<u32> {
+    u32::try_from(radix)
+        .ok()
+        .filter(|radix| (2..=36).contains(radix))
+}
+
+pub(crate) mod prim {
+    use super::*;
+
+    pub(crate) fn from_int(i: VmInt) -> BigInt {
+        BigInt(i.into())
+    }
+
+    pub(crate) fn to_int(i: &BigInt) -> Option<VmInt> {
+        i.0.to_i64()
+    }
+
+    pub(crate) fn from_float(f: f64) -> Option<BigInt> {
+        num_bigint::BigInt::from_f64(f.trunc()).map(BigInt)
+    }
+
+    pub(crate) fn to_float(i: &BigInt) -> f64 {
+        i.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    pub(crate) fn parse(s: &str) -> Result<BigInt, ()> {
+        num_bigint::BigInt::from_str(s).map(BigInt).map_err(|_| ())
+    }
+
+    pub(crate) fn from_str_radix(s: &str, r: VmInt) -> Result<BigInt, ()> {
+        let r = radix(r).ok_or(())?;
+        num_bigint::BigInt::parse_bytes(s.as_bytes(), r)
+            .map(BigInt)
+            .ok_or(())
+    }
+
+    pub(crate) fn to_str_radix(i: &BigInt, r: VmInt) -> RuntimeResult<String, String> {
+        match radix(r) {
+            Some(r) => RuntimeResult::Return(i.0.to_str_radix(r)),
+            None => RuntimeResult::Panic(format!("radix must be in the range 2..=36, got {}", r)),
+        }
+    }
+
+    pub(crate) fn show(i: &BigInt) -> String {
+        i.0.to_string()
+    }
+
+    pub(crate) fn eq(l: &BigInt, r: &BigInt) -> bool {
+        l.0 == r.0
+    }
+
+    pub(crate) fn compare(l: &BigInt, r: &BigInt) -> Ordering {
+        l.0.cmp(&r.0)
+    }
+
+    pub(crate) fn hash(i: &BigInt) -> VmInt {
+        let mut hasher = DefaultHasher::new();
+        i.0.hash(&mut hasher);
+        hasher.finish() as VmInt
+    }
+
+    pub(crate) fn add(l: &BigInt, r: &BigInt) -> BigInt {
+        BigInt(&l.0 + &r.0)
+    }
+
+    pub(crate) fn sub(l: &BigInt, r: &BigInt) -> BigInt {
+        BigInt(&l.0 - &r.0)
+    }
+
+    pub(crate) fn mul(l: &BigInt, r: &BigInt) -> BigInt {
+        BigInt(&l.0 * &r.0)
+    }
+
+    pub(crate) fn div(l: &BigInt, r: &BigInt) -> RuntimeResult<BigInt, String> {
+        if r.0.is_zero() {
+            RuntimeResult::Panic(format!("attempted to divide {} by 0", l.0))
+        } else {
+            RuntimeResult::Return(BigInt(&l.0 / &r.0))
+        }
+    }
+
+    pub(crate) fn rem(l: &BigInt, r: &BigInt) -> RuntimeResult<BigInt, String> {
+        if r.0.is_zero() {
+            RuntimeResult::Panic(format!(
+                "attempted to calculate remainder of {} divided by 0",
+                l.0
+            ))
+        } else {
+            RuntimeResult::Return(BigInt(&l.0 % &r.0))
+        }
+    }
+
+    pub(crate) fn negate(i: &BigInt) -> BigInt {
+        BigInt(-&i.0)
+    }
+
+    pub(crate) fn abs(i: &BigInt) -> BigInt {
+        BigInt(i.0.abs())
+    }
+
+    pub(crate) fn signum(i: &BigInt) -> VmInt {
+        match i.0.sign() {
+            Sign::Minus => -1,
+            Sign::NoSign => 0,
+            Sign::Plus => 1,
+        }
+    }
+
+    pub(crate) fn pow(i: &BigInt, exp: VmInt) -> RuntimeResult<BigInt, String> {
+        match u32::try_from(exp) {
+            Ok(exp) => RuntimeResult::Return(BigInt(num_traits::pow(i.0.clone(), exp as usize))),
+            Err(_) => RuntimeResult::Panic(format!("invalid exponent {}", exp)),
+        }
+    }
+}
+
+mod std {
+    pub(crate) mod bigint {
+        pub(crate) use crate::std_lib::bigint::prim;
+    }
+}
+
+pub fn load(vm: &Thread) -> vm::Result<ExternModule> {
+    vm.register_type::<BigInt>("std.bigint.BigInt", &[])?;
+
+    ExternModule::new(
+        vm,
+        record! {
+            type std::bigint::BigInt => BigInt,
+            from_int => primitive!(1, std::bigint::prim::from_int),
+            to_int => primitive!(1, std::bigint::prim::to_int),
+            from_float => primitive!(1, std::bigint::prim::from_float),
+            to_float => primitive!(1, std::bigint::prim::to_float),
+            parse => primitive!(1, std::bigint::prim::parse),
+            from_str_radix => primitive!(2, std::bigint::prim::from_str_radix),
+            to_str_radix => primitive!(2, std::bigint::prim::to_str_radix),
+            show => primitive!(1, std::bigint::prim::show),
+            eq => primitive!(2, std::bigint::prim::eq),
+            compare => primitive!(2, std::bigint::prim::compare),
+            hash => primitive!(1, std::bigint::prim::hash),
+            add => primitive!(2, std::bigint::prim::add),
+            sub => primitive!(2, std::bigint::prim::sub),
+            mul => primitive!(2, std::bigint::prim::mul),
+            div => primitive!(2, std::bigint::prim::div),
+            rem => primitive!(2, std::bigint::prim::rem),
+            negate => primitive!(1, std::bigint::prim::negate),
+            abs => primitive!(1, std::bigint::prim::abs),
+            signum => primitive!(1, std::bigint::prim::signum),
+            pow => primitive!(2, std::bigint::prim::pow),
+        },
+    )
+}
@@ -0,0 +1,568 @@
+//! Module containing the primitives of `std.bytes`, an immutable byte string which can be sliced
+//! without copying, and `std.effect.st.bytes`, a mutable buffer for building byte strings.
+
+use crate::real_std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    convert::{TryFrom, TryInto},
+    fmt, fs,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use crate::vm::{
+    self,
+    api::{generic::S, RuntimeResult, IO},
+    thread::Thread,
+    types::VmInt,
+    ExternModule,
+};
+
+use crate::capabilities::Capabilities;
+
+#[derive(Clone, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.bytes.Bytes")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+pub(crate) struct Bytes {
+    data: Arc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl fmt::Debug for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bytes({:?})", self.as_slice())
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(data: Vec<u8>) -> Self {
+        let end = data.len();
+        Bytes {
+            data: data.into(),
+            start: 0,
+            end,
+        }
+    }
+}
+
+impl Bytes {
+    fn as_slice(&self) -> &[u8] {
+        &self.data[self.start..self.end]
+    }
+
+    // Shares the data of `self` instead of copying it
+    fn slice(&self, start: usize, end: usize) -> Option<Bytes> {
+        if start <= end && end <= self.as_slice().len() {
+            Some(Bytes {
+                data: self.data.clone(),
+                start: self.start + start,
+                end: self.start + end,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+fn out_of_range<T>(start: usize, end: usize, len: usize) -> RuntimeResult<T, String> {
+    RuntimeResult::Panic(format!(
+        "Slice {}..{} is out of range for bytes of length {}",
+        start, end, len
+    ))
+}
+
+fn denied<T>(function: &str, capability: Capabilities) -> IO<T> {
+    IO::Exception(format!(
+        "std.bytes.{} requires the {:?} capability which this VM was built without",
+        function, capability
+    ))
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+mod encoding {
+    use super::*;
+
+    pub(crate) fn to_hex(bytes: &[u8]) -> String {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut hex = String::with_capacity(bytes.len() * 2);
+        for &b in bytes {
+            hex.push(DIGITS[usize::from(b >> 4)] as char);
+            hex.push(DIGITS[usize::from(b & 0xf)] as char);
+        }
+        hex
+    }
+
+    pub(crate) fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+        if hex.len() % 2 != 0 {
+            return Err("Hex string must contain an even number of digits".to_string());
+        }
+        let digit = |c: u8| {
+            (c as char)
+                .to_digit(16)
+                .map(|d| d as u8)
+                .ok_or_else(|| format!("Invalid hex digit `{}`", c as char))
+        };
+        hex.as_bytes()
+            .chunks(2)
+            .map(|pair| Ok(digit(pair[0])? << 4 | digit(pair[1])?))
+            .collect()
+    }
+
+    pub(crate) fn to_base64(bytes: &[u8]) -> String {
+        let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+        for chunk in bytes.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let index = (n >> (18 - 6 * i)) & 0x3f;
+                    encoded.push(BASE64_ALPHABET[index as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+
+    /// Decodes standard base64, the trailing padding is optional
+    pub(crate) fn from_base64(encoded: &str) -> Result<Vec<u8>, String> {
+        let unpadded = encoded.trim_end_matches('=');
+        let padding = encoded.len() - unpadded.len();
+        if padding > 2 || (padding != 0 && encoded.len() % 4 != 0) {
+            return Err("Invalid base64 padding".to_string());
+        }
+        let encoded = unpadded;
+        if encoded.len() % 4 == 1 {
+            return Err("Invalid base64 length".to_string());
+        }
+        let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+        for chunk in encoded.as_bytes().chunks(4) {
+            let mut n = 0u32;
+            for (i, &c) in chunk.iter().enumerate() {
+                let value = BASE64_ALPHABET
+                    .iter()
+                    .position(|&a| a == c)
+                    .ok_or_else(|| format!("Invalid base64 character `{}`", c as char))?;
+                n |= (value as u32) << (18 - 6 * i);
+            }
+            decoded.extend_from_slice(&n.to_be_bytes()[1..chunk.len()]);
+        }
+        Ok(decoded)
+    }
+}
+
+fn read_at(bytes: &[u8], offset: VmInt, len: usize) -> Option<&[u8]> {
+    if offset < 0 {
+        return None;
+    }
+    let start = offset as usize;
+    bytes.get(start..start.checked_add(len)?)
+}
+
+macro_rules! read_numbers {
+    ($($name: ident: $ty: ident :: $from: ident => $ret: ty,)*) => {
+        $(
+            pub(crate) fn $name(bytes: &Bytes, offset: VmInt) -> Option<$ret> {
+                read_at(bytes.as_slice(), offset, mem::size_of::<$ty>())
+                    .map(|slice| $ty::$from(slice.try_into().unwrap()) as $ret)
+            }
+        )*
+    };
+}
+
+pub(crate) mod prim {
+    use super::*;
+
+    pub(crate) fn from_array(array: &[u8]) -> Bytes {
+        array.to_owned().into()
+    }
+
+    pub(crate) fn to_array(bytes: &Bytes) -> Vec<u8> {
+        bytes.as_slice().to_owned()
+    }
+
+    pub(crate) fn len(bytes: &Bytes) -> usize {
+        bytes.as_slice().len()
+    }
+
+    pub(crate) fn get(bytes: &Bytes, index: VmInt) -> Option<u8> {
+        read_at(bytes.as_slice(), index, 1).map(|b| b[0])
+    }
+
+    pub(crate) fn slice(bytes: &Bytes, start: usize, end: usize) -> RuntimeResult<Bytes, String> {
+        match bytes.slice(start, end) {
+            Some(slice) => RuntimeResult::Return(slice),
+            None => out_of_range(start, end, len(bytes)),
+        }
+    }
+
+    pub(crate) fn append(l: &Bytes, r: &Bytes) -> Bytes {
+        if l.as_slice().is_empty() {
+            return r.clone();
+        }
+        if r.as_slice().is_empty() {
+            return l.clone();
+        }
+        let mut data = Vec::with_capacity(len(l) + len(r));
+        data.extend_from_slice(l.as_slice());
+        data.extend_from_slice(r.as_slice());
+        data.into()
+    }
+
+    pub(crate) fn eq(l: &Bytes, r: &Bytes) -> bool {
+        l.as_slice() == r.as_slice()
+    }
+
+    pub(crate) fn compare(l: &Bytes, r: &Bytes) -> Ordering {
+        l.as_slice().cmp(r.as_slice())
+    }
+
+    pub(crate) fn hash(bytes: &Bytes) -> VmInt {
+        let mut hasher = DefaultHasher::new();
+        bytes.as_slice().hash(&mut hasher);
+        hasher.finish() as VmInt
+    }
+
+    pub(crate) fn show(bytes: &Bytes) -> String {
+        format!("{:?}", bytes.as_slice())
+    }
+
+    pub(crate) fn from_string(s: &str) -> Bytes {
+        s.as_bytes().to_owned().into()
+    }
+
+    pub(crate) fn to_string(bytes: &Bytes) -> Result<String, String> {
+        crate::real_std::str::from_utf8(bytes.as_slice())
+            .map(|s| s.to_owned())
+            .map_err(|err| err.to_string())
+    }
+
+    pub(crate) fn to_string_lossy(bytes: &Bytes) -> String {
+        String::from_utf8_lossy(bytes.as_slice()).into_owned()
+    }
+
+    pub(crate) fn to_hex(bytes: &Bytes) -> String {
+        encoding::to_hex(bytes.as_slice())
+    }
+
+    pub(crate) fn from_hex(hex: &str) -> Result<Bytes, String> {
+        encoding::from_hex(hex).map(Bytes::from)
+    }
+
+    pub(crate) fn to_base64(bytes: &Bytes) -> String {
+        encoding::to_base64(bytes.as_slice())
+    }
+
+    pub(crate) fn from_base64(encoded: &str) -> Result<Bytes, String> {
+        encoding::from_base64(encoded).map(Bytes::from)
+    }
+
+    read_numbers! {
+        read_i8: i8::from_be_bytes => VmInt,
+        read_u16_be: u16::from_be_bytes => VmInt,
+        read_u16_le: u16::from_le_bytes => VmInt,
+        read_i16_be: i16::from_be_bytes => VmInt,
+        read_i16_le: i16::from_le_bytes => VmInt,
+        read_u32_be: u32::from_be_bytes => VmInt,
+        read_u32_le: u32::from_le_bytes => VmInt,
+        read_i32_be: i32::from_be_bytes => VmInt,
+        read_i32_le: i32::from_le_bytes => VmInt,
+        read_i64_be: i64::from_be_bytes => VmInt,
+        read_i64_le: i64::from_le_bytes => VmInt,
+        read_f32_be: f32::from_be_bytes => f64,
+        read_f32_le: f32::from_le_bytes => f64,
+        read_f64_be: f64::from_be_bytes => f64,
+        read_f64_le: f64::from_le_bytes => f64,
+    }
+
+    pub(crate) fn read_file(path: &str) -> IO<Bytes> {
+        match fs::read(path) {
+            Ok(data) => IO::Value(data.into()),
+            Err(err) => IO::Exception(format!("Unable to read `{}`: {}", path, err)),
+        }
+    }
+
+    pub(crate) fn write_file(path: &str, bytes: &Bytes) -> IO<()> {
+        match fs::write(path, bytes.as_slice()) {
+            Ok(()) => IO::Value(()),
+            Err(err) => IO::Exception(format!("Unable to write `{}`: {}", path, err)),
+        }
+    }
+}
+
+#[derive(Debug, VmType, Userdata, Trace)]
+#[gluon(vm_type = "std.effect.st.bytes.ByteBuffer")]
+#[gluon(crate_name = "::vm")]
+pub(crate) struct ByteBuffer<S>(Mutex<Vec<u8>>, PhantomData<S>);
+
+macro_rules! push_numbers {
+    ($($name: ident: $ty: ident :: $to: ident,)*) => {
+        $(
+            pub(crate) fn $name(buf: &ByteBuffer<S>, value: VmInt) -> RuntimeResult<(), String> {
+                match $ty::try_from(value) {
+                    Ok(value) => {
+                        buf.0.lock().unwrap().extend_from_slice(&value.$to());
+                        RuntimeResult::Return(())
+                    }
+                    Err(_) => RuntimeResult::Panic(format!(
+                        "{} is out of range for `{}`",
+                        value,
+                        stringify!($ty)
+                    )),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! push_floats {
+    ($($name: ident: $ty: ident :: $to: ident,)*) => {
+        $(
+            pub(crate) fn $name(buf: &ByteBuffer<S>, value: f64) {
+                buf.0.lock().unwrap().extend_from_slice(&(value as $ty).$to());
+            }
+        )*
+    };
+}
+
+pub(crate) mod byte_buffer {
+    use super::*;
+
+    pub(crate) fn new(_: ()) -> ByteBuffer<S> {
+        ByteBuffer(Default::default(), PhantomData)
+    }
+
+    pub(crate) fn len(buf: &ByteBuffer<S>) -> usize {
+        buf.0.lock().unwrap().len()
+    }
+
+    pub(crate) fn get(buf: &ByteBuffer<S>, index: VmInt) -> Option<u8> {
+        read_at(&buf.0.lock().unwrap(), index, 1).map(|b| b[0])
+    }
+
+    pub(crate) fn set(buf: &ByteBuffer<S>, index: usize, byte: u8) -> RuntimeResult<(), String> {
+        let mut buf = buf.0.lock().unwrap();
+        let len = buf.len();
+        match buf.get_mut(index) {
+            Some(b) => {
+                *b = byte;
+                RuntimeResult::Return(())
+            }
+            None => RuntimeResult::Panic(format!(
+                "Index {} is out of range for a buffer of length {}",
+                index, len
+            )),
+        }
+    }
+
+    pub(crate) fn push(buf: &ByteBuffer<S>, byte: u8) {
+        buf.0.lock().unwrap().push(byte)
+    }
+
+    pub(crate) fn push_array(buf: &ByteBuffer<S>, array: &[u8]) {
+        buf.0.lock().unwrap().extend_from_slice(array)
+    }
+
+    pub(crate) fn push_bytes(buf: &ByteBuffer<S>, bytes: &Bytes) {
+        buf.0.lock().unwrap().extend_from_slice(bytes.as_slice())
+    }
+
+    pub(crate) fn push_str(buf: &ByteBuffer<S>, s: &str) {
+        buf.0.lock().unwrap().extend_from_slice(s.as_bytes())
+    }
+
+    push_numbers! {
+        push_i8: i8::to_be_bytes,
+        push_u16_be: u16::to_be_bytes,
+        push_u16_le: u16::to_le_bytes,
+        push_i16_be: i16::to_be_bytes,
+        push_i16_le: i16::to_le_bytes,
+        push_u32_be: u32::to_be_bytes,
+        push_u32_le: u32::to_le_bytes,
+        push_i32_be: i32::to_be_bytes,
+        push_i32_le: i32::to_le_bytes,
+        push_i64_be: i64::to_be_bytes,
+        push_i64_le: i64::to_le_bytes,
+    }
+
+    push_floats! {
+        push_f32_be: f32::to_be_bytes,
+        push_f32_le: f32::to_le_bytes,
+        push_f64_be: f64::to_be_bytes,
+        push_f64_le: f64::to_le_bytes,
+    }
+
+    pub(crate) fn truncate(buf: &ByteBuffer<S>, len: usize) {
+        buf.0.lock().unwrap().truncate(len)
+    }
+
+    pub(crate) fn slice(
+        buf: &ByteBuffer<S>,
+        start: usize,
+        end: usize,
+    ) -> RuntimeResult<Bytes, String> {
+        let buf = buf.0.lock().unwrap();
+        match buf.get(start..end) {
+            Some(slice) => RuntimeResult::Return(slice.to_owned().into()),
+            None => out_of_range(start, end, buf.len()),
+        }
+    }
+
+    pub(crate) fn freeze(buf: &ByteBuffer<S>) -> Bytes {
+        buf.0.lock().unwrap().clone().into()
+    }
+}
+
+mod std {
+    pub(crate) mod bytes {
+        pub(crate) use crate::std_lib::bytes::prim;
+    }
+    pub(crate) mod effect {
+        pub(crate) mod st {
+            pub(crate) mod bytes {
+                pub(crate) use crate::std_lib::bytes::byte_buffer as prim;
+            }
+        }
+    }
+}
+
+pub fn load(vm: &Thread, capabilities: Capabilities) -> vm::Result<ExternModule> {
+    vm.register_type::<Bytes>("std.bytes.Bytes", &[])?;
+
+    let read_file = gated!(
+        capabilities,
+        Capabilities::FS_READ,
+        1,
+        std::bytes::prim,
+        read_file,
+        fn(&str) -> IO<Bytes>
+    );
+    let write_file = gated!(
+        capabilities,
+        Capabilities::FS_WRITE,
+        2,
+        std::bytes::prim,
+        write_file,
+        fn(&str, &Bytes) -> IO<()>
+    );
+
+    ExternModule::new(
+        vm,
+        record! {
+            type std::bytes::Bytes => Bytes,
+            from_array => primitive!(1, std::bytes::prim::from_array),
+            to_array => primitive!(1, std::bytes::prim::to_array),
+            len => primitive!(1, std::bytes::prim::len),
+            get => primitive!(2, std::bytes::prim::get),
+            slice => primitive!(3, std::bytes::prim::slice),
+            append => primitive!(2, std::bytes::prim::append),
+            eq => primitive!(2, std::bytes::prim::eq),
+            compare => primitive!(2, std::bytes::prim::compare),
+            hash => primitive!(1, std::bytes::prim::hash),
+            show => primitive!(1, std::bytes::prim::show),
+            from_string => primitive!(1, std::bytes::prim::from_string),
+            to_string => primitive!(1, std::bytes::prim::to_string),
+            to_string_lossy => primitive!(1, std::bytes::prim::to_string_lossy),
+            to_hex => primitive!(1, std::bytes::prim::to_hex),
+            from_hex => primitive!(1, std::bytes::prim::from_hex),
+            to_base64 => primitive!(1, std::bytes::prim::to_base64),
+            from_base64 => primitive!(1, std::bytes::prim::from_base64),
+            read_i8 => primitive!(2, std::bytes::prim::read_i8),
+            read_u16_be => primitive!(2, std::bytes::prim::read_u16_be),
+            read_u16_le => primitive!(2, std::bytes::prim::read_u16_le),
+            read_i16_be => primitive!(2, std::bytes::prim::read_i16_be),
+            read_i16_le => primitive!(2, std::bytes::prim::read_i16_le),
+            read_u32_be => primitive!(2, std::bytes::prim::read_u32_be),
+            read_u32_le => primitive!(2, std::bytes::prim::read_u32_le),
+            read_i32_be => primitive!(2, std::bytes::prim::read_i32_be),
+            read_i32_le => primitive!(2, std::bytes::prim::read_i32_le),
+            read_i64_be => primitive!(2, std::bytes::prim::read_i64_be),
+            read_i64_le => primitive!(2, std::bytes::prim::read_i64_le),
+            read_f32_be => primitive!(2, std::bytes::prim::read_f32_be),
+            read_f32_le => primitive!(2, std::bytes::prim::read_f32_le),
+            read_f64_be => primitive!(2, std::bytes::prim::read_f64_be),
+            read_f64_le => primitive!(2, std::bytes::prim::read_f64_le),
+            read_file => read_file,
+            write_file => write_file,
+        },
+    )
+}
+
+pub fn load_byte_buffer(vm: &Thread) -> vm::Result<ExternModule> {
+    vm.register_type::<ByteBuffer<S>>("std.effect.st.bytes.ByteBuffer", &["s"])?;
+
+    ExternModule::new(
+        vm,
+        record! {
+            type ByteBuffer s => ByteBuffer<S>,
+            new => primitive!(1, std::effect::st::bytes::prim::new),
+            len => primitive!(1, std::effect::st::bytes::prim::len),
+            get => primitive!(2, std::effect::st::bytes::prim::get),
+            set => primitive!(3, std::effect::st::bytes::prim::set),
+            push => primitive!(2, std::effect::st::bytes::prim::push),
+            push_array => primitive!(2, std::effect::st::bytes::prim::push_array),
+            push_bytes => primitive!(2, std::effect::st::bytes::prim::push_bytes),
+            push_str => primitive!(2, std::effect::st::bytes::prim::push_str),
+            push_i8 => primitive!(2, std::effect::st::bytes::prim::push_i8),
+            push_u16_be => primitive!(2, std::effect::st::bytes::prim::push_u16_be),
+            push_u16_le => primitive!(2, std::effect::st::bytes::prim::push_u16_le),
+            push_i16_be => primitive!(2, std::effect::st::bytes::prim::push_i16_be),
+            push_i16_le => primitive!(2, std::effect::st::bytes::prim::push_i16_le),
+            push_u32_be => primitive!(2, std::effect::st::bytes::prim::push_u32_be),
+            push_u32_le => primitive!(2, std::effect::st::bytes::prim::push_u32_le),
+            push_i32_be => primitive!(2, std::effect::st::bytes::prim::push_i32_be),
+            push_i32_le => primitive!(2, std::effect::st::bytes::prim::push_i32_le),
+            push_i64_be => primitive!(2, std::effect::st::bytes::prim::push_i64_be),
+            push_i64_le => primitive!(2, std::effect::st::bytes::prim::push_i64_le),
+            push_f32_be => primitive!(2, std::effect::st::bytes::prim::push_f32_be),
+            push_f32_le => primitive!(2, std::effect::st::bytes::prim::push_f32_le),
+            push_f64_be => primitive!(2, std::effect::st::bytes::prim::push_f64_be),
+            push_f64_le => primitive!(2, std::effect::st::bytes::prim::push_f64_le),
+            truncate => primitive!(2, std::effect::st::bytes::prim::truncate),
+            slice => primitive!(3, std::effect::st::bytes::prim::slice),
+            freeze => primitive!(1, std::effect::st::bytes::prim::freeze),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encoding::*;
+
+    #[test]
+    fn hex_roundtrip() {
+        assert_eq!(to_hex(&[0x00, 0x7f, 0xab, 0xff]), "007fabff");
+        assert_eq!(from_hex("007FabfF"), Ok(vec![0x00, 0x7f, 0xab, 0xff]));
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+
+    #[test]
+    fn base64_roundtrip() {
+        for &(decoded, encoded) in &[
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(to_base64(decoded), encoded);
+            assert_eq!(from_base64(encoded).as_deref(), Ok(decoded));
+        }
+        assert_eq!(from_base64("Zm8").as_deref(), Ok(&b"fo"[..]));
+        assert!(from_base64("Zm9v!").is_err());
+        assert!(from_base64("Z").is_err());
+    }
+}
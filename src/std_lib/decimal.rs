@@ -0,0 +1,178 @@
+//! Module containing the primitives of `std.decimal`, arbitrary-precision decimal numbers backed
+//! by the `bigdecimal` crate.
+
+use crate::real_std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use bigdecimal::BigDecimal;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::vm::{self, api::RuntimeResult, thread::Thread, types::VmInt, ExternModule};
+
+use super::bigint::BigInt;
+
+#[derive(Clone, Debug, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.decimal.Decimal")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+pub(crate) struct Decimal(BigDecimal);
+
+pub(crate) mod prim {
+    use super::*;
+
+    pub(crate) fn from_int(i: VmInt) -> Decimal {
+        Decimal(i.into())
+    }
+
+    /// Truncates the fractional part
+    pub(crate) fn to_int(d: &Decimal) -> Option<VmInt> {
+        d.0.to_i64()
+    }
+
+    pub(crate) fn from_bigint(i: &BigInt) -> Decimal {
+        Decimal(BigDecimal::new(i.0.clone(), 0))
+    }
+
+    pub(crate) fn to_bigint(d: &Decimal) -> BigInt {
+        BigInt(d.0.with_scale(0).into_bigint_and_exponent().0)
+    }
+
+    // Uses the shortest representation which round trips so that `0.1` becomes `0.1` and not
+    // `0.1000000000000000055511151231257827`
+    pub(crate) fn from_float(f: f64) -> Option<Decimal> {
+        if f.is_finite() {
+            BigDecimal::from_str(&f.to_string()).ok().map(Decimal)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn to_float(d: &Decimal) -> f64 {
+        // Going through the string representation avoids the rounding errors of scaling the
+        // digits by a power of ten
+        d.0.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub(crate) fn parse(s: &str) -> Result<Decimal, ()> {
+        BigDecimal::from_str(s).map(Decimal).map_err(|_| ())
+    }
+
+    pub(crate) fn show(d: &Decimal) -> String {
+        d.0.to_string()
+    }
+
+    pub(crate) fn eq(l: &Decimal, r: &Decimal) -> bool {
+        l.0 == r.0
+    }
+
+    pub(crate) fn compare(l: &Decimal, r: &Decimal) -> Ordering {
+        l.0.cmp(&r.0)
+    }
+
+    pub(crate) fn hash(d: &Decimal) -> VmInt {
+        let mut hasher = DefaultHasher::new();
+        d.0.hash(&mut hasher);
+        hasher.finish() as VmInt
+    }
+
+    pub(crate) fn add(l: &Decimal, r: &Decimal) -> Decimal {
+        Decimal(&l.0 + &r.0)
+    }
+
+    pub(crate) fn sub(l: &Decimal, r: &Decimal) -> Decimal {
+        Decimal(&l.0 - &r.0)
+    }
+
+    pub(crate) fn mul(l: &Decimal, r: &Decimal) -> Decimal {
+        Decimal(&l.0 * &r.0)
+    }
+
+    pub(crate) fn div(l: &Decimal, r: &Decimal) -> RuntimeResult<Decimal, String> {
+        if r.0.is_zero() {
+            RuntimeResult::Panic(format!("attempted to divide {} by 0", l.0))
+        } else {
+            RuntimeResult::Return(Decimal(&l.0 / &r.0))
+        }
+    }
+
+    pub(crate) fn negate(d: &Decimal) -> Decimal {
+        Decimal(-&d.0)
+    }
+
+    pub(crate) fn abs(d: &Decimal) -> Decimal {
+        Decimal(d.0.abs())
+    }
+
+    /// Returns the number of digits after the decimal point
+    pub(crate) fn scale(d: &Decimal) -> VmInt {
+        d.0.as_bigint_and_exponent().1
+    }
+
+    pub(crate) fn truncate(d: &Decimal, places: VmInt) -> Decimal {
+        Decimal(d.0.with_scale(places))
+    }
+
+    /// Rounds half away from zero to `places` digits after the decimal point
+    pub(crate) fn round(d: &Decimal, places: VmInt) -> Decimal {
+        let truncated = d.0.with_scale(places);
+        let remainder = (&d.0 - &truncated).abs();
+        if remainder >= BigDecimal::new(5.into(), places + 1) {
+            let unit = BigDecimal::new(1.into(), places);
+            Decimal(if d.0.is_negative() {
+                truncated - unit
+            } else {
+                truncated + unit
+            })
+        } else {
+            Decimal(truncated)
+        }
+    }
+
+    /// Removes trailing zeros after the decimal point
+    pub(crate) fn normalize(d: &Decimal) -> Decimal {
+        Decimal(d.0.normalized())
+    }
+}
+
+mod std {
+    pub(crate) mod decimal {
+        pub(crate) use crate::std_lib::decimal::prim;
+    }
+}
+
+pub fn load(vm: &Thread) -> vm::Result<ExternModule> {
+    vm.register_type::<Decimal>("std.decimal.Decimal", &[])?;
+
+    ExternModule::new(
+        vm,
+        record! {
+            type std::decimal::Decimal => Decimal,
+            from_int => primitive!(1, std::decimal::prim::from_int),
+            to_int => primitive!(1, std::decimal::prim::to_int),
+            from_bigint => primitive!(1, std::decimal::prim::from_bigint),
+            to_bigint => primitive!(1, std::decimal::prim::to_bigint),
+            from_float => primitive!(1, std::decimal::prim::from_float),
+            to_float => primitive!(1, std::decimal::prim::to_float),
+            parse => primitive!(1, std::decimal::prim::parse),
+            show => primitive!(1, std::decimal::prim::show),
+            eq => primitive!(2, std::decimal::prim::eq),
+            compare => primitive!(2, std::decimal::prim::compare),
+            hash => primitive!(1, std::decimal::prim::hash),
+            add => primitive!(2, std::decimal::prim::add),
+            sub => primitive!(2, std::decimal::prim::sub),
+            mul => primitive!(2, std::decimal::prim::mul),
+            div => primitive!(2, std::decimal::prim::div),
+            negate => primitive!(1, std::decimal::prim::negate),
+            abs => primitive!(1, std::decimal::prim::abs),
+            scale => primitive!(1, std::decimal::prim::scale),
+            truncate => primitive!(2, std::decimal::prim::truncate),
+            round => primitive!(2, std::decimal::prim::round),
+            normalize => primitive!(1, std::decimal::prim::normalize),
+        },
+    )
+}
@@ -23,6 +23,9 @@ use {
 
 use crate::base::types::{ArcType, Type};
 
+#[cfg(feature = "web")]
+pub mod client;
+
 use crate::{
     vm::{
         self,
@@ -125,6 +128,20 @@ impl fmt::Debug for Body {
     }
 }
 
+impl Body {
+    pub(crate) fn new<E>(body: impl Stream<Item = Result<Bytes, E>> + Send + 'static) -> Self
+    where
+        E: fmt::Display,
+    {
+        Body(Arc::new(Mutex::new(Box::pin(
+            body.map_err(|err| vm::Error::Message(format!("{}", err)))
+                // `PushAsRef` makes the `body` parameter act as a `&[u8]` which means it is
+                // marshalled to `Array Byte` in gluon
+                .map_ok(PushAsRef::<_, [u8]>::new),
+        ))))
+    }
+}
+
 // Since `Body` implements `Userdata` gluon will automatically marshal the gluon representation
 // into `&Body` argument
 fn read_chunk(body: &Body) -> impl Future<Output = IO<Option<PushAsRef<Bytes, [u8]>>>> {
@@ -201,13 +218,13 @@ fn write_response(response: &ResponseBody, bytes: &[u8]) -> impl Future<Output =
 #[gluon(crate_name = "::vm")]
 #[gluon_trace(skip)]
 #[gluon_userdata(clone)]
-struct Uri(http::Uri);
+pub(crate) struct Uri(pub(crate) http::Uri);
 
 // Next we define some record types which are marshalled to and from gluon. These have equivalent
 // definitions in http_types.glu
 field_decl! { http, method, uri, status, body, request, response, headers }
 
-type Request = record_type! {
+pub(crate) type Request = record_type! {
     method => String,
     uri => Uri,
     body => Body
@@ -369,13 +386,7 @@ impl Handler {
             method => method.as_str().to_owned(),
             uri => Uri(uri),
             // Since `Body` implements `Userdata` it can be directly pushed to gluon
-            body => Body(Arc::new(Mutex::new(Box::pin(
-                body
-                    .map_err(|err| vm::Error::Message(format!("{}", err)))
-                    // `PushAsRef` makes the `body` parameter act as a `&[u8]` which means it is
-                    // marshalled to `Array Byte` in gluon
-                    .map_ok(PushAsRef::<_, [u8]>::new)
-            ))))
+            body => Body::new(body)
         };
         let (response_sender, response_body) = hyper::Body::channel();
         let response_sender = Arc::new(Mutex::new(Some(response_sender)));
@@ -0,0 +1,371 @@
+//! Module containing the primitives of `std.http.client`, a http client built on top of `hyper`.
+
+use crate::real_std::{io, net as std_net, pin::Pin};
+
+use {
+    futures::{
+        future::{self, BoxFuture},
+        prelude::*,
+        task::{self, Poll},
+    },
+    http::{
+        header::{HeaderMap, AUTHORIZATION, COOKIE, LOCATION, PROXY_AUTHORIZATION},
+        Method, StatusCode,
+    },
+    hyper::client::connect::{Connected, Connection},
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        net::TcpStream,
+    },
+    tokio_native_tls::{TlsConnector, TlsStream},
+};
+
+use crate::vm::{
+    self,
+    api::{UserdataValue, IO},
+    thread::Thread,
+    ExternModule,
+};
+
+use crate::std_lib::{
+    http::{Body, Headers, Uri},
+    net::blocking,
+    time::Duration,
+};
+
+field_decl! { method, uri, status, body, headers }
+
+type Request = record_type! {
+    method => String,
+    uri => UserdataValue<Uri>,
+    body => UserdataValue<Body>
+};
+
+type Response = record_type! {
+    status => u16,
+    headers => Headers,
+    body => Body
+};
+
+#[derive(Getable, VmType)]
+#[gluon(crate_name = "::vm")]
+struct Options {
+    timeout: Option<UserdataValue<Duration>>,
+    max_redirects: usize,
+    headers: Headers,
+}
+
+/// A connection to a server, encrypted if the `https` scheme were used
+enum Stream {
+    Plain(TcpStream),
+    Tls(TlsStream<TcpStream>),
+}
+
+impl Connection for Stream {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Stream::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Stream::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Stream::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Stream::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+// `hyper::client::HttpConnector` connects through tokio which can't create TCP sockets on every
+// platform (see `std_lib::net`) so we provide our own connector which also handles TLS
+#[derive(Clone)]
+struct Connector {
+    tls: TlsConnector,
+}
+
+impl tower_service::Service<http::Uri> for Connector {
+    type Response = Stream;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, io::Result<Stream>>;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Ok(()).into()
+    }
+
+    fn call(&mut self, uri: http::Uri) -> Self::Future {
+        let tls = self.tls.clone();
+        Box::pin(async move {
+            let https = match uri.scheme_str() {
+                Some("https") => true,
+                Some("http") | None => false,
+                Some(scheme) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unsupported scheme `{}`", scheme),
+                    ))
+                }
+            };
+            let host = uri
+                .host()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Uri has no host"))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned();
+            let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+
+            let addr_host = host.clone();
+            let stream =
+                blocking(move || std_net::TcpStream::connect((&addr_host[..], port))).await?;
+            let stream = TcpStream::from_std(stream)?;
+
+            if https {
+                tls.connect(&host, stream)
+                    .await
+                    .map(Stream::Tls)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+            } else {
+                Ok(Stream::Plain(stream))
+            }
+        })
+    }
+}
+
+/// A client which is shared by every request made from a VM so that the TLS configuration is only
+/// loaded once and connections can be reused
+#[derive(Debug, Userdata, Trace, VmType, Clone)]
+#[gluon(vm_type = "std.http.client.Client")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct Client(hyper::Client<Connector, hyper::Body>);
+
+impl Client {
+    fn new() -> Result<Client, String> {
+        let tls = native_tls::TlsConnector::new().map_err(|err| err.to_string())?;
+        Ok(Client(
+            hyper::Client::builder().build(Connector { tls: tls.into() }),
+        ))
+    }
+}
+
+/// Returns the scheme, host and port that `uri` connects to
+fn origin(uri: &http::Uri) -> (Option<&str>, Option<&str>, Option<u16>) {
+    let port = uri.port_u16().or_else(|| match uri.scheme_str() {
+        Some("https") => Some(443),
+        Some("http") => Some(80),
+        _ => None,
+    });
+    (uri.scheme_str(), uri.host(), port)
+}
+
+/// Removes the headers which may carry credentials, so they are not leaked to another origin when
+/// following a redirect
+fn remove_sensitive_headers(headers: &mut HeaderMap) {
+    for header in &[AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION] {
+        headers.remove(header);
+    }
+}
+
+/// Resolves the `Location` header of a redirect against the uri that was requested
+fn resolve_location(base: &http::Uri, location: &str) -> Result<http::Uri, String> {
+    let scheme = base.scheme_str().unwrap_or("http");
+    let authority = base.authority().map(|a| a.as_str()).unwrap_or("");
+    let resolved = if location.contains("://") {
+        location.to_owned()
+    } else if location.starts_with("//") {
+        format!("{}:{}", scheme, location)
+    } else if location.starts_with('/') {
+        format!("{}://{}{}", scheme, authority, location)
+    } else {
+        let path = base.path();
+        let dir = &path[..path.rfind('/').map_or(0, |i| i + 1)];
+        format!("{}://{}{}{}", scheme, authority, dir, location)
+    };
+    resolved
+        .parse()
+        .map_err(|err| format!("Invalid redirect location `{}`: {}", location, err))
+}
+
+async fn read_body_(body: &Body) -> vm::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) =
+        future::poll_fn(|cx| body.0.lock().unwrap().as_mut().poll_next(cx)).await
+    {
+        bytes.extend_from_slice(chunk?.into_inner().as_ref());
+    }
+    Ok(bytes)
+}
+
+fn read_body(body: &Body) -> impl Future<Output = IO<Vec<u8>>> {
+    let body = body.clone();
+    async move { read_body_(&body).await.into() }
+}
+
+fn body_from_bytes(bytes: &[u8]) -> Body {
+    let chunk: Result<_, hyper::Error> = Ok(hyper::body::Bytes::from(bytes.to_owned()));
+    Body::new(stream::once(future::ready(chunk)))
+}
+
+fn parse_uri(uri: &str) -> Result<Uri, String> {
+    uri.parse()
+        .map(Uri)
+        .map_err(|err| format!("Invalid uri `{}`: {}", uri, err))
+}
+
+async fn send(
+    client: Client,
+    options: Options,
+    mut method: Method,
+    mut uri: http::Uri,
+    body: Body,
+) -> Result<http::Response<hyper::Body>, String> {
+    let mut headers = options.headers.0;
+    // The body needs to be resent if we are redirected so it is read fully up front
+    let mut body = read_body_(&body).await.map_err(|err| err.to_string())?;
+    let mut redirects = 0;
+    loop {
+        let mut request = http::Request::builder()
+            .method(method.clone())
+            .uri(uri.clone())
+            .body(hyper::Body::from(body.clone()))
+            .map_err(|err| err.to_string())?;
+        *request.headers_mut() = headers.clone();
+
+        let response = client
+            .0
+            .request(request)
+            .await
+            .map_err(|err| format!("Request to `{}` failed: {}", uri, err))?;
+
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok());
+        let location = match location {
+            Some(location)
+                if response.status().is_redirection() && redirects < options.max_redirects =>
+            {
+                location
+            }
+            _ => return Ok(response),
+        };
+
+        match response.status() {
+            StatusCode::SEE_OTHER => {
+                if method != Method::HEAD {
+                    method = Method::GET;
+                }
+                body.clear();
+            }
+            StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => {
+                if method == Method::POST {
+                    method = Method::GET;
+                    body.clear();
+                }
+            }
+            StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => (),
+            _ => return Ok(response),
+        }
+        let location = resolve_location(&uri, location)?;
+        if origin(&location) != origin(&uri) {
+            remove_sensitive_headers(&mut headers);
+        }
+        uri = location;
+        redirects += 1;
+    }
+}
+
+fn request(
+    client: &Client,
+    options: Options,
+    request: Request,
+) -> impl Future<Output = IO<Response>> {
+    let client = client.clone();
+    let record_p! { method, uri, body } = request;
+    async move {
+        let method = match Method::from_bytes(method.as_bytes()) {
+            Ok(method) => method,
+            Err(err) => return IO::Exception(format!("Invalid method `{}`: {}", method, err)),
+        };
+        let timeout = options.timeout.as_ref().map(|timeout| (timeout.0).0);
+        let uri = (uri.0).0;
+
+        let response = send(client, options, method, uri.clone(), body.0);
+        let response = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, response).await {
+                Ok(response) => response,
+                Err(_) => Err(format!("Request to `{}` timed out", uri)),
+            },
+            None => response.await,
+        };
+
+        match response {
+            Ok(response) => {
+                let (parts, body) = response.into_parts();
+                IO::Value(record_no_decl! {
+                    status => parts.status.as_u16(),
+                    headers => Headers(parts.headers),
+                    body => Body::new(body)
+                })
+            }
+            Err(err) => IO::Exception(err),
+        }
+    }
+}
+
+mod std {
+    pub(crate) mod http {
+        pub(crate) mod client {
+            pub(crate) use crate::std_lib::http::client as prim;
+        }
+    }
+}
+
+pub fn load(vm: &Thread) -> vm::Result<ExternModule> {
+    vm.register_type::<Client>("std.http.client.Client", &[])?;
+    let client = Client::new().map_err(vm::Error::Message)?;
+    ExternModule::new(
+        vm,
+        record! {
+            type std::http::client::Options => Options,
+            type std::http::client::Response => Response,
+            type std::http::client::Client => Client,
+            client => client,
+            request => primitive!(3, async fn std::http::client::prim::request),
+            read_body => primitive!(1, async fn std::http::client::prim::read_body),
+            body_from_bytes => primitive!(1, std::http::client::prim::body_from_bytes),
+            parse_uri => primitive!(1, std::http::client::prim::parse_uri),
+        },
+    )
+}
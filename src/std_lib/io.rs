@@ -22,7 +22,7 @@ use crate::vm::{
     ExternModule, Result,
 };
 
-use crate::{compiler_pipeline::*, Error, ModuleCompiler, ThreadExt};
+use crate::{capabilities::Capabilities, compiler_pipeline::*, Error, ModuleCompiler, ThreadExt};
 
 fn print(s: &str) -> IO<()> {
     print!("{}", s);
@@ -343,13 +343,51 @@ fn load_script(
     }
 }
 
+fn denied<T>(function: &str, capability: Capabilities) -> IO<T> {
+    IO::Exception(format!(
+        "std.io.{} requires the {:?} capability which this VM was built without",
+        function, capability
+    ))
+}
+
+fn open_options_capabilities(opts: &[OpenOptions]) -> Capabilities {
+    opts.iter()
+        .map(|opt| match opt {
+            OpenOptions::Read => Capabilities::FS_READ,
+            _ => Capabilities::FS_WRITE,
+        })
+        .fold(Capabilities::empty(), |capabilities, required| {
+            capabilities | required
+        })
+}
+
+/// `open_file_with` which only opens the file if `opts` only require capabilities in `allowed`
+fn open_file_with_only(allowed: Capabilities, path: &str, opts: Vec<OpenOptions>) -> IO<GluonFile> {
+    let missing = open_options_capabilities(&opts) - allowed;
+    if missing.is_empty() {
+        open_file_with(path, opts)
+    } else {
+        denied("open_file_with", missing)
+    }
+}
+
+/// `open_file_with` which only opens files if the requested options only require capabilities in
+/// `$allowed`
+macro_rules! open_file_with_only {
+    ($allowed: expr) => {
+        primitive!(2, "std.io.prim.open_file_with", |path, opts| {
+            open_file_with_only($allowed, path, opts)
+        })
+    };
+}
+
 mod std {
     pub mod io {
         pub use crate::std_lib::io as prim;
     }
 }
 
-pub fn load(vm: &Thread) -> Result<ExternModule> {
+pub fn load(vm: &Thread, capabilities: Capabilities) -> Result<ExternModule> {
     vm.register_type::<GluonFile>("std.io.File", &[])?;
 
     // flat_map f m : (a -> IO b) -> IO a -> IO b
@@ -367,6 +405,18 @@ pub fn load(vm: &Thread) -> Result<ExternModule> {
 
     let wrap = vec![Pop(1), Return];
 
+    let fs = Capabilities::FS_READ | Capabilities::FS_WRITE;
+    let open_file_with = match capabilities & fs {
+        allowed if allowed == fs => primitive!(2, std::io::prim::open_file_with),
+        allowed if allowed == Capabilities::FS_READ => {
+            open_file_with_only!(Capabilities::FS_READ)
+        }
+        allowed if allowed == Capabilities::FS_WRITE => {
+            open_file_with_only!(Capabilities::FS_WRITE)
+        }
+        _ => open_file_with_only!(Capabilities::empty()),
+    };
+
     // IO functions
     ExternModule::new(
         vm,
@@ -376,22 +426,22 @@ pub fn load(vm: &Thread) -> Result<ExternModule> {
             type std::io::IO a => IO<A>,
             flat_map => TypedBytecode::<FlatMap>::new("std.io.prim.flat_map", 3, flat_map),
             wrap => TypedBytecode::<Wrap>::new("std.io.prim.wrap", 2, wrap),
-            open_file_with => primitive!(2, std::io::prim::open_file_with),
-            read_file_to_string => primitive!(1, std::io::prim::read_file_to_string),
-            read_file_to_array => primitive!(1, std::io::prim::read_file_to_array),
+            open_file_with => open_file_with,
+            read_file_to_string => gated!(capabilities, Capabilities::FS_READ, 1, std::io::prim, read_file_to_string, fn(&str) -> IO<String>),
+            read_file_to_array => gated!(capabilities, Capabilities::FS_READ, 1, std::io::prim, read_file_to_array, fn(&str) -> IO<Vec<u8>>),
             read_file => primitive!(2, std::io::prim::read_file),
             read_file_to_end => primitive!(1, std::io::prim::read_file_to_end),
             write_slice_file => primitive!(4, std::io::prim::write_slice_file),
             flush_file => primitive!(1, std::io::prim::flush_file),
             close_file => primitive!(1, std::io::prim::close_file),
             is_file_closed => primitive!(1, std::io::prim::is_file_closed),
-            read_char => primitive!(0, std::io::prim::read_char),
-            read_line => primitive!(0, std::io::prim::read_line),
-            print => primitive!(1, std::io::prim::print),
-            println => primitive!(1, std::io::prim::println),
-            flush_stdout => primitive!(0, std::io::prim::flush_stdout),
-            eprint => primitive!(1, std::io::prim::eprint),
-            eprintln => primitive!(1, std::io::prim::eprintln),
+            read_char => gated!(capabilities, Capabilities::CONSOLE, 0, std::io::prim, read_char, fn() -> IO<char>),
+            read_line => gated!(capabilities, Capabilities::CONSOLE, 0, std::io::prim, read_line, fn() -> IO<String>),
+            print => gated!(capabilities, Capabilities::CONSOLE, 1, std::io::prim, print, fn(&str) -> IO<()>),
+            println => gated!(capabilities, Capabilities::CONSOLE, 1, std::io::prim, println, fn(&str) -> IO<()>),
+            flush_stdout => gated!(capabilities, Capabilities::CONSOLE, 0, std::io::prim, flush_stdout, fn() -> IO<()>),
+            eprint => gated!(capabilities, Capabilities::CONSOLE, 1, std::io::prim, eprint, fn(&str) -> IO<()>),
+            eprintln => gated!(capabilities, Capabilities::CONSOLE, 1, std::io::prim, eprintln, fn(&str) -> IO<()>),
             catch => primitive!(2, async fn std::io::prim::catch),
             throw => primitive!(1, std::io::prim::throw),
             run_expr => primitive!(1, async fn std::io::prim::run_expr),
@@ -0,0 +1,327 @@
+//! Module containing bindings to tokio's TCP and UDP sockets.
+//!
+//! All functions are asynchronous primitives so they must be run from within a tokio runtime.
+
+use crate::real_std::{
+    fmt, io,
+    net::{self as std_net, SocketAddr},
+    sync::Arc,
+};
+
+use futures::prelude::*;
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{self, tcp},
+    sync::Mutex,
+};
+
+use crate::vm::{self, api::IO, thread::Thread, types::VmInt, ExternModule};
+
+#[derive(Userdata, Trace, VmType, Clone)]
+#[gluon(vm_type = "std.net.TcpListener")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct TcpListener {
+    listener: Arc<Mutex<net::TcpListener>>,
+    local_addr: SocketAddr,
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TcpListener({})", self.local_addr)
+    }
+}
+
+/// A TCP stream which is split into halves so that reading and writing do not block each other
+#[derive(Userdata, Trace, VmType, Clone)]
+#[gluon(vm_type = "std.net.TcpStream")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct TcpStream {
+    read: Arc<Mutex<tcp::OwnedReadHalf>>,
+    write: Arc<Mutex<tcp::OwnedWriteHalf>>,
+    local_addr: SocketAddr,
+    peer_addr: SocketAddr,
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TcpStream({} -> {})", self.local_addr, self.peer_addr)
+    }
+}
+
+impl TcpStream {
+    fn new(stream: net::TcpStream) -> io::Result<Self> {
+        let local_addr = stream.local_addr()?;
+        let peer_addr = stream.peer_addr()?;
+        let (read, write) = stream.into_split();
+        Ok(TcpStream {
+            read: Arc::new(Mutex::new(read)),
+            write: Arc::new(Mutex::new(write)),
+            local_addr,
+            peer_addr,
+        })
+    }
+}
+
+#[derive(Userdata, Trace, VmType, Clone)]
+#[gluon(vm_type = "std.net.UdpSocket")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct UdpSocket {
+    socket: Arc<Mutex<net::UdpSocket>>,
+    local_addr: SocketAddr,
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UdpSocket({})", self.local_addr)
+    }
+}
+
+field_decl! { data, addr }
+
+type Datagram = record_type! {
+    data => Vec<u8>,
+    addr => String
+};
+
+// mio 0.6, which tokio 0.2 uses, passes socket addresses to the OS assuming the memory layout of
+// older versions of `std::net::SocketAddr`. TCP sockets are therefore created through `std` (on a
+// blocking thread as resolving the address may block) and then registered with tokio.
+pub(crate) async fn blocking<T, F>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|err| Err(io::Error::new(io::ErrorKind::Other, err.to_string())))
+}
+
+async fn tcp_listen(addr: String) -> IO<TcpListener> {
+    let bind_addr = addr.clone();
+    let listener = blocking(move || std_net::TcpListener::bind(&bind_addr[..]))
+        .await
+        .and_then(|listener| {
+            let local_addr = listener.local_addr()?;
+            Ok(TcpListener {
+                listener: Arc::new(Mutex::new(net::TcpListener::from_std(listener)?)),
+                local_addr,
+            })
+        });
+    match listener {
+        Ok(listener) => IO::Value(listener),
+        Err(err) => IO::Exception(format!("Unable to bind to `{}`: {}", addr, err)),
+    }
+}
+
+fn tcp_listener_local_addr(listener: &TcpListener) -> String {
+    listener.local_addr.to_string()
+}
+
+fn tcp_accept(listener: &TcpListener) -> impl Future<Output = IO<TcpStream>> {
+    let listener = listener.listener.clone();
+    async move {
+        let (stream, _) = match listener.lock().await.accept().await {
+            Ok(x) => x,
+            Err(err) => return IO::Exception(err.to_string()),
+        };
+        TcpStream::new(stream).into()
+    }
+}
+
+async fn tcp_connect(addr: String) -> IO<TcpStream> {
+    let connect_addr = addr.clone();
+    let stream = blocking(move || std_net::TcpStream::connect(&connect_addr[..]))
+        .await
+        .and_then(|stream| TcpStream::new(net::TcpStream::from_std(stream)?));
+    match stream {
+        Ok(stream) => IO::Value(stream),
+        Err(err) => IO::Exception(format!("Unable to connect to `{}`: {}", addr, err)),
+    }
+}
+
+fn tcp_local_addr(stream: &TcpStream) -> String {
+    stream.local_addr.to_string()
+}
+
+fn tcp_peer_addr(stream: &TcpStream) -> String {
+    stream.peer_addr.to_string()
+}
+
+fn tcp_read(stream: &TcpStream, count: VmInt) -> impl Future<Output = IO<Option<Vec<u8>>>> {
+    let read = stream.read.clone();
+    async move {
+        if count <= 0 {
+            return IO::Value(None);
+        }
+        let mut buf = vec![0; count as usize];
+        match read.lock().await.read(&mut buf).await {
+            Ok(0) => IO::Value(None),
+            Ok(bytes_read) => {
+                buf.truncate(bytes_read);
+                IO::Value(Some(buf))
+            }
+            Err(err) => IO::Exception(err.to_string()),
+        }
+    }
+}
+
+fn tcp_read_to_end(stream: &TcpStream) -> impl Future<Output = IO<Vec<u8>>> {
+    let read = stream.read.clone();
+    async move {
+        let mut buf = Vec::new();
+        match read.lock().await.read_to_end(&mut buf).await {
+            Ok(_) => IO::Value(buf),
+            Err(err) => IO::Exception(err.to_string()),
+        }
+    }
+}
+
+fn tcp_write_slice(
+    stream: &TcpStream,
+    buf: &[u8],
+    start: usize,
+    end: usize,
+) -> impl Future<Output = IO<usize>> {
+    let write = stream.write.clone();
+    let len = buf.len();
+    let buf = buf.get(start..end).map(|buf| buf.to_owned());
+    async move {
+        let buf = match buf {
+            Some(buf) => buf,
+            None => {
+                return IO::Exception(format!(
+                    "Slice {}..{} is out of range for an array of length {}",
+                    start, end, len
+                ))
+            }
+        };
+        write.lock().await.write(&buf).await.into()
+    }
+}
+
+fn tcp_flush(stream: &TcpStream) -> impl Future<Output = IO<()>> {
+    let write = stream.write.clone();
+    async move { write.lock().await.flush().await.into() }
+}
+
+fn tcp_shutdown(stream: &TcpStream) -> impl Future<Output = IO<()>> {
+    let write = stream.write.clone();
+    async move { write.lock().await.shutdown().await.into() }
+}
+
+async fn udp_bind(addr: String) -> IO<UdpSocket> {
+    let socket = match net::UdpSocket::bind(&addr[..]).await {
+        Ok(socket) => socket,
+        Err(err) => return IO::Exception(format!("Unable to bind to `{}`: {}", addr, err)),
+    };
+    socket
+        .local_addr()
+        .map(|local_addr| UdpSocket {
+            socket: Arc::new(Mutex::new(socket)),
+            local_addr,
+        })
+        .into()
+}
+
+fn udp_local_addr(socket: &UdpSocket) -> String {
+    socket.local_addr.to_string()
+}
+
+fn udp_connect(socket: &UdpSocket, addr: String) -> impl Future<Output = IO<()>> {
+    let socket = socket.socket.clone();
+    async move {
+        match socket.lock().await.connect(&addr[..]).await {
+            Ok(()) => IO::Value(()),
+            Err(err) => IO::Exception(format!("Unable to connect to `{}`: {}", addr, err)),
+        }
+    }
+}
+
+fn udp_send(socket: &UdpSocket, buf: &[u8]) -> impl Future<Output = IO<usize>> {
+    let socket = socket.socket.clone();
+    let buf = buf.to_owned();
+    async move { socket.lock().await.send(&buf).await.into() }
+}
+
+fn udp_send_to(socket: &UdpSocket, buf: &[u8], addr: String) -> impl Future<Output = IO<usize>> {
+    let socket = socket.socket.clone();
+    let buf = buf.to_owned();
+    async move { socket.lock().await.send_to(&buf, &addr[..]).await.into() }
+}
+
+fn udp_recv(socket: &UdpSocket, count: usize) -> impl Future<Output = IO<Vec<u8>>> {
+    let socket = socket.socket.clone();
+    async move {
+        let mut buf = vec![0; count];
+        match socket.lock().await.recv(&mut buf).await {
+            Ok(bytes_read) => {
+                buf.truncate(bytes_read);
+                IO::Value(buf)
+            }
+            Err(err) => IO::Exception(err.to_string()),
+        }
+    }
+}
+
+fn udp_recv_from(socket: &UdpSocket, count: usize) -> impl Future<Output = IO<Datagram>> {
+    let socket = socket.socket.clone();
+    async move {
+        let mut buf = vec![0; count];
+        match socket.lock().await.recv_from(&mut buf).await {
+            Ok((bytes_read, addr)) => {
+                buf.truncate(bytes_read);
+                IO::Value(record_no_decl! {
+                    data => buf,
+                    addr => addr.to_string()
+                })
+            }
+            Err(err) => IO::Exception(err.to_string()),
+        }
+    }
+}
+
+mod std {
+    pub mod net {
+        pub use crate::std_lib::net as prim;
+    }
+}
+
+pub fn load(vm: &Thread) -> vm::Result<ExternModule> {
+    vm.register_type::<TcpListener>("std.net.TcpListener", &[])?;
+    vm.register_type::<TcpStream>("std.net.TcpStream", &[])?;
+    vm.register_type::<UdpSocket>("std.net.UdpSocket", &[])?;
+
+    ExternModule::new(
+        vm,
+        record! {
+            type std::net::TcpListener => TcpListener,
+            type std::net::TcpStream => TcpStream,
+            type std::net::UdpSocket => UdpSocket,
+            tcp_listen => primitive!(1, async fn std::net::prim::tcp_listen),
+            tcp_listener_local_addr => primitive!(1, std::net::prim::tcp_listener_local_addr),
+            tcp_accept => primitive!(1, async fn std::net::prim::tcp_accept),
+            tcp_connect => primitive!(1, async fn std::net::prim::tcp_connect),
+            tcp_local_addr => primitive!(1, std::net::prim::tcp_local_addr),
+            tcp_peer_addr => primitive!(1, std::net::prim::tcp_peer_addr),
+            tcp_read => primitive!(2, async fn std::net::prim::tcp_read),
+            tcp_read_to_end => primitive!(1, async fn std::net::prim::tcp_read_to_end),
+            tcp_write_slice => primitive!(4, async fn std::net::prim::tcp_write_slice),
+            tcp_flush => primitive!(1, async fn std::net::prim::tcp_flush),
+            tcp_shutdown => primitive!(1, async fn std::net::prim::tcp_shutdown),
+            udp_bind => primitive!(1, async fn std::net::prim::udp_bind),
+            udp_local_addr => primitive!(1, std::net::prim::udp_local_addr),
+            udp_connect => primitive!(2, async fn std::net::prim::udp_connect),
+            udp_send => primitive!(2, async fn std::net::prim::udp_send),
+            udp_send_to => primitive!(3, async fn std::net::prim::udp_send_to),
+            udp_recv => primitive!(2, async fn std::net::prim::udp_recv),
+            udp_recv_from => primitive!(2, async fn std::net::prim::udp_recv_from),
+        },
+    )
+}
@@ -52,6 +52,11 @@ fn find<'a>(re: &Regex, text: &'a str) -> Option<Match<'a>> {
     re.find(text).map(Match::new)
 }
 
+fn match_indices(re: &Regex, text: &str) -> Vec<(usize, usize)> {
+    let &Regex(ref re) = re;
+    re.find_iter(text).map(|m| (m.start(), m.end())).collect()
+}
+
 fn captures<'a>(re: &Regex, text: &'a str) -> Option<Vec<Option<Match<'a>>>> {
     let &Regex(ref re) = re;
     re.captures(text)
@@ -85,6 +90,7 @@ pub fn load(vm: &Thread) -> vm::Result<ExternModule> {
             new => primitive!(1, std::regex::prim::new),
             is_match => primitive!(2, std::regex::prim::is_match),
             find => primitive!(2, std::regex::prim::find),
+            match_indices => primitive!(2, std::regex::prim::match_indices),
             // Workaround MIR bug in rustc
             captures => primitive!(2, "std.regex.prim.captures", |x, y| std::regex::prim::captures(x, y)),
             error_to_string => primitive!(1, std::regex::prim::error_to_string)
@@ -0,0 +1,699 @@
+//! Module containing bindings to rust's `std::time` module.
+use crate::real_std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    sync::Arc,
+    time::{self, UNIX_EPOCH},
+};
+
+use crate::vm::{
+    self,
+    api::{RuntimeResult, IO},
+    thread::Thread,
+    types::VmInt,
+    ExternModule,
+};
+
+use crate::capabilities::Capabilities;
+
+/// A source of the current time. All clocks in `std.time` are read through a `Clock` so that the
+/// host can replace the system clock, for instance with a fake clock in tests.
+pub trait Clock: Send + Sync {
+    /// Returns the current time of the monotonic clock
+    fn instant(&self) -> time::Instant;
+
+    /// Returns the current wall-clock time
+    fn system_time(&self) -> time::SystemTime;
+}
+
+/// The clock of the operating system
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn instant(&self) -> time::Instant {
+        time::Instant::now()
+    }
+
+    fn system_time(&self) -> time::SystemTime {
+        time::SystemTime::now()
+    }
+}
+
+#[derive(Userdata, Trace, VmType, Clone)]
+#[gluon(vm_type = "std.time.Clock")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct GluonClock {
+    clock: Arc<dyn Clock>,
+    wall_clock: bool,
+}
+
+impl fmt::Debug for GluonClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Clock")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.time.Duration")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+pub(crate) struct Duration(pub(crate) time::Duration);
+
+#[derive(Clone, Copy, Debug, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.time.Instant")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct Instant(time::Instant);
+
+#[derive(Clone, Copy, Debug, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.time.SystemTime")]
+#[gluon(crate_name = "::vm")]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+struct SystemTime(time::SystemTime);
+
+fn to_int<T>(value: T) -> VmInt
+where
+    VmInt: TryFrom<T>,
+{
+    VmInt::try_from(value).unwrap_or(VmInt::MAX)
+}
+
+fn non_negative(function: &str, value: VmInt) -> RuntimeResult<u64, String> {
+    match u64::try_from(value) {
+        Ok(value) => RuntimeResult::Return(value),
+        Err(_) => RuntimeResult::Panic(format!(
+            "std.time.{} expected a non-negative integer but got {}",
+            function, value
+        )),
+    }
+}
+
+fn overflow<T>(function: &str, value: Option<T>) -> RuntimeResult<T, String> {
+    match value {
+        Some(value) => RuntimeResult::Return(value),
+        None => RuntimeResult::Panic(format!("std.time.{} overflowed", function)),
+    }
+}
+
+macro_rules! try_runtime {
+    ($e: expr) => {
+        match $e {
+            RuntimeResult::Return(value) => value,
+            RuntimeResult::Panic(err) => return RuntimeResult::Panic(err),
+        }
+    };
+}
+
+mod duration {
+    use super::*;
+
+    pub(crate) fn from_secs(secs: VmInt) -> RuntimeResult<Duration, String> {
+        let secs = try_runtime!(non_negative("duration.from_secs", secs));
+        RuntimeResult::Return(Duration(time::Duration::from_secs(secs)))
+    }
+
+    pub(crate) fn from_millis(millis: VmInt) -> RuntimeResult<Duration, String> {
+        let millis = try_runtime!(non_negative("duration.from_millis", millis));
+        RuntimeResult::Return(Duration(time::Duration::from_millis(millis)))
+    }
+
+    pub(crate) fn from_micros(micros: VmInt) -> RuntimeResult<Duration, String> {
+        let micros = try_runtime!(non_negative("duration.from_micros", micros));
+        RuntimeResult::Return(Duration(time::Duration::from_micros(micros)))
+    }
+
+    pub(crate) fn from_nanos(nanos: VmInt) -> RuntimeResult<Duration, String> {
+        let nanos = try_runtime!(non_negative("duration.from_nanos", nanos));
+        RuntimeResult::Return(Duration(time::Duration::from_nanos(nanos)))
+    }
+
+    pub(crate) fn from_secs_float(secs: f64) -> RuntimeResult<Duration, String> {
+        if secs.is_finite() && secs >= 0.0 && secs < u64::MAX as f64 {
+            RuntimeResult::Return(Duration(time::Duration::from_secs_f64(secs)))
+        } else {
+            RuntimeResult::Panic(format!(
+                "std.time.duration.from_secs_float can't represent {} seconds",
+                secs
+            ))
+        }
+    }
+
+    pub(crate) fn as_secs(d: &Duration) -> VmInt {
+        to_int(d.0.as_secs())
+    }
+
+    pub(crate) fn as_millis(d: &Duration) -> VmInt {
+        to_int(d.0.as_millis())
+    }
+
+    pub(crate) fn as_micros(d: &Duration) -> VmInt {
+        to_int(d.0.as_micros())
+    }
+
+    pub(crate) fn as_nanos(d: &Duration) -> VmInt {
+        to_int(d.0.as_nanos())
+    }
+
+    pub(crate) fn as_secs_float(d: &Duration) -> f64 {
+        d.0.as_secs_f64()
+    }
+
+    pub(crate) fn subsec_nanos(d: &Duration) -> VmInt {
+        VmInt::from(d.0.subsec_nanos())
+    }
+
+    pub(crate) fn add(l: &Duration, r: &Duration) -> RuntimeResult<Duration, String> {
+        overflow("duration.add", l.0.checked_add(r.0)).map(Duration)
+    }
+
+    pub(crate) fn sub(l: &Duration, r: &Duration) -> RuntimeResult<Duration, String> {
+        match l.0.checked_sub(r.0) {
+            Some(d) => RuntimeResult::Return(Duration(d)),
+            None => RuntimeResult::Panic(format!(
+                "std.time.duration.sub would result in a negative duration ({:?} - {:?})",
+                l.0, r.0
+            )),
+        }
+    }
+
+    pub(crate) fn checked_sub(l: &Duration, r: &Duration) -> Option<Duration> {
+        l.0.checked_sub(r.0).map(Duration)
+    }
+
+    pub(crate) fn mul(d: &Duration, factor: VmInt) -> RuntimeResult<Duration, String> {
+        let factor = try_runtime!(non_negative("duration.mul", factor));
+        let factor = try_runtime!(overflow("duration.mul", u32::try_from(factor).ok()));
+        overflow("duration.mul", d.0.checked_mul(factor)).map(Duration)
+    }
+
+    pub(crate) fn div(d: &Duration, divisor: VmInt) -> RuntimeResult<Duration, String> {
+        match u32::try_from(divisor) {
+            Ok(divisor) if divisor != 0 => RuntimeResult::Return(Duration(d.0 / divisor)),
+            _ => RuntimeResult::Panic(format!(
+                "std.time.duration.div can't divide a duration by {}",
+                divisor
+            )),
+        }
+    }
+
+    pub(crate) fn eq(l: &Duration, r: &Duration) -> bool {
+        l.0 == r.0
+    }
+
+    pub(crate) fn compare(l: &Duration, r: &Duration) -> Ordering {
+        l.0.cmp(&r.0)
+    }
+
+    pub(crate) fn show(d: &Duration) -> String {
+        format!("{:?}", d.0)
+    }
+}
+
+mod instant {
+    use super::*;
+
+    pub(crate) fn now(clock: &GluonClock) -> IO<Instant> {
+        IO::Value(Instant(clock.clock.instant()))
+    }
+
+    pub(crate) fn elapsed(clock: &GluonClock, instant: &Instant) -> IO<Duration> {
+        IO::Value(Duration(
+            clock.clock.instant().saturating_duration_since(instant.0),
+        ))
+    }
+
+    pub(crate) fn duration_since(later: &Instant, earlier: &Instant) -> Duration {
+        Duration(later.0.saturating_duration_since(earlier.0))
+    }
+
+    pub(crate) fn add(instant: &Instant, d: &Duration) -> RuntimeResult<Instant, String> {
+        overflow("instant.add", instant.0.checked_add(d.0)).map(Instant)
+    }
+
+    pub(crate) fn sub(instant: &Instant, d: &Duration) -> RuntimeResult<Instant, String> {
+        overflow("instant.sub", instant.0.checked_sub(d.0)).map(Instant)
+    }
+
+    pub(crate) fn eq(l: &Instant, r: &Instant) -> bool {
+        l.0 == r.0
+    }
+
+    pub(crate) fn compare(l: &Instant, r: &Instant) -> Ordering {
+        l.0.cmp(&r.0)
+    }
+
+    pub(crate) fn show(instant: &Instant) -> String {
+        format!("{:?}", instant.0)
+    }
+}
+
+mod system_time {
+    use super::*;
+
+    const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+    /// Nanoseconds since the Unix epoch (negative for times before the epoch)
+    fn to_unix_nanos_i128(t: time::SystemTime) -> i128 {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i128,
+            Err(err) => -(err.duration().as_nanos() as i128),
+        }
+    }
+
+    fn from_unix_nanos_i128(nanos: i128) -> Option<time::SystemTime> {
+        let secs = u64::try_from(nanos.abs() / NANOS_PER_SEC).ok()?;
+        let d = time::Duration::new(secs, (nanos.abs() % NANOS_PER_SEC) as u32);
+        if nanos >= 0 {
+            UNIX_EPOCH.checked_add(d)
+        } else {
+            UNIX_EPOCH.checked_sub(d)
+        }
+    }
+
+    fn from_unix(function: &str, value: VmInt, unit: i128) -> RuntimeResult<SystemTime, String> {
+        overflow(function, from_unix_nanos_i128(i128::from(value) * unit)).map(SystemTime)
+    }
+
+    pub(crate) fn now(clock: &GluonClock) -> IO<SystemTime> {
+        if clock.wall_clock {
+            IO::Value(SystemTime(clock.clock.system_time()))
+        } else {
+            IO::Exception(format!(
+                "std.time.system_time.now requires the {:?} capability which this VM was built without",
+                Capabilities::CLOCK
+            ))
+        }
+    }
+
+    pub(crate) fn from_unix_secs(secs: VmInt) -> RuntimeResult<SystemTime, String> {
+        from_unix("system_time.from_unix_secs", secs, NANOS_PER_SEC)
+    }
+
+    pub(crate) fn from_unix_millis(millis: VmInt) -> RuntimeResult<SystemTime, String> {
+        from_unix("system_time.from_unix_millis", millis, 1_000_000)
+    }
+
+    pub(crate) fn from_unix_nanos(nanos: VmInt) -> RuntimeResult<SystemTime, String> {
+        from_unix("system_time.from_unix_nanos", nanos, 1)
+    }
+
+    fn to_unix(t: &SystemTime, unit: i128) -> VmInt {
+        let value = to_unix_nanos_i128(t.0).div_euclid(unit);
+        VmInt::try_from(value).unwrap_or(if value < 0 { VmInt::MIN } else { VmInt::MAX })
+    }
+
+    pub(crate) fn to_unix_secs(t: &SystemTime) -> VmInt {
+        to_unix(t, NANOS_PER_SEC)
+    }
+
+    pub(crate) fn to_unix_millis(t: &SystemTime) -> VmInt {
+        to_unix(t, 1_000_000)
+    }
+
+    pub(crate) fn to_unix_nanos(t: &SystemTime) -> VmInt {
+        to_unix(t, 1)
+    }
+
+    pub(crate) fn add(t: &SystemTime, d: &Duration) -> RuntimeResult<SystemTime, String> {
+        overflow("system_time.add", t.0.checked_add(d.0)).map(SystemTime)
+    }
+
+    pub(crate) fn sub(t: &SystemTime, d: &Duration) -> RuntimeResult<SystemTime, String> {
+        overflow("system_time.sub", t.0.checked_sub(d.0)).map(SystemTime)
+    }
+
+    pub(crate) fn duration_since(later: &SystemTime, earlier: &SystemTime) -> Option<Duration> {
+        later.0.duration_since(earlier.0).ok().map(Duration)
+    }
+
+    pub(crate) fn eq(l: &SystemTime, r: &SystemTime) -> bool {
+        l.0 == r.0
+    }
+
+    pub(crate) fn compare(l: &SystemTime, r: &SystemTime) -> Ordering {
+        l.0.cmp(&r.0)
+    }
+
+    pub(crate) fn show(t: &SystemTime) -> String {
+        rfc3339::format(to_unix_nanos_i128(t.0)).unwrap_or_else(|| format!("{:?}", t.0))
+    }
+
+    pub(crate) fn format_rfc3339(t: &SystemTime) -> RuntimeResult<String, String> {
+        match rfc3339::format(to_unix_nanos_i128(t.0)) {
+            Some(s) => RuntimeResult::Return(s),
+            None => RuntimeResult::Panic(format!(
+                "{:?} is outside of the years that RFC 3339 can represent",
+                t.0
+            )),
+        }
+    }
+
+    pub(crate) fn parse_rfc3339(s: &str) -> Result<SystemTime, String> {
+        rfc3339::parse(s)
+            .and_then(|nanos| {
+                from_unix_nanos_i128(nanos).ok_or_else(|| "Timestamp is out of range".to_string())
+            })
+            .map(SystemTime)
+            .map_err(|err| format!("Invalid RFC 3339 timestamp `{}`: {}", s, err))
+    }
+}
+
+/// Conversion between nanoseconds since the Unix epoch and RFC 3339 timestamps
+mod rfc3339 {
+    use crate::real_std::fmt::Write;
+
+    const NANOS_PER_SEC: i128 = 1_000_000_000;
+    const SECS_PER_DAY: i128 = 86_400;
+
+    /// Days since 1970-01-01 of the date `year-month-day` in the proleptic Gregorian calendar
+    fn days_from_civil(year: i128, month: i128, day: i128) -> i128 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The inverse of `days_from_civil`
+    fn civil_from_days(days: i128) -> (i128, i128, i128) {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+
+    fn is_leap_year(year: i128) -> bool {
+        year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    }
+
+    fn days_in_month(year: i128, month: i128) -> i128 {
+        match month {
+            2 if is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// Formats the time as `YYYY-MM-DDTHH:MM:SS[.fraction]Z`. Returns `None` if the year is not in
+    /// `0..=9999`.
+    pub(super) fn format(unix_nanos: i128) -> Option<String> {
+        let secs = unix_nanos.div_euclid(NANOS_PER_SEC);
+        let nanos = unix_nanos.rem_euclid(NANOS_PER_SEC);
+        let (year, month, day) = civil_from_days(secs.div_euclid(SECS_PER_DAY));
+        if !(0..=9999).contains(&year) {
+            return None;
+        }
+        let secs_of_day = secs.rem_euclid(SECS_PER_DAY);
+        let mut s = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        );
+        if nanos != 0 {
+            let fraction = format!("{:09}", nanos);
+            write!(s, ".{}", fraction.trim_end_matches('0')).unwrap();
+        }
+        s.push('Z');
+        Some(s)
+    }
+
+    struct Parser<'a> {
+        input: &'a [u8],
+        pos: usize,
+    }
+
+    impl Parser<'_> {
+        fn peek(&self) -> Option<u8> {
+            self.input.get(self.pos).cloned()
+        }
+
+        fn expect(&mut self, expected: &[u8]) -> Result<u8, String> {
+            match self.peek() {
+                Some(c) if expected.contains(&c) => {
+                    self.pos += 1;
+                    Ok(c)
+                }
+                c => Err(format!(
+                    "Expected {} at position {}, found {}",
+                    expected
+                        .iter()
+                        .map(|&c| format!("`{}`", c as char))
+                        .collect::<Vec<_>>()
+                        .join(" or "),
+                    self.pos,
+                    c.map_or("the end of the input".to_string(), |c| format!(
+                        "`{}`",
+                        c as char
+                    ))
+                )),
+            }
+        }
+
+        fn digits(&mut self, count: usize, what: &str) -> Result<i128, String> {
+            let start = self.pos;
+            let mut value = 0;
+            for _ in 0..count {
+                match self.peek() {
+                    Some(c) if c.is_ascii_digit() => {
+                        value = value * 10 + i128::from(c - b'0');
+                        self.pos += 1;
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Expected {} digits for the {} at position {}",
+                            count, what, start
+                        ))
+                    }
+                }
+            }
+            Ok(value)
+        }
+
+        fn range(
+            &mut self,
+            count: usize,
+            what: &str,
+            min: i128,
+            max: i128,
+        ) -> Result<i128, String> {
+            let value = self.digits(count, what)?;
+            if value < min || value > max {
+                return Err(format!(
+                    "The {} must be between {} and {}, found {}",
+                    what, min, max, value
+                ));
+            }
+            Ok(value)
+        }
+    }
+
+    /// Parses an RFC 3339 timestamp into nanoseconds since the Unix epoch. Leap seconds (`:60`)
+    /// are treated as the last second of the minute.
+    pub(super) fn parse(input: &str) -> Result<i128, String> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            pos: 0,
+        };
+
+        let year = parser.digits(4, "year")?;
+        parser.expect(b"-")?;
+        let month = parser.range(2, "month", 1, 12)?;
+        parser.expect(b"-")?;
+        let day = parser.range(2, "day", 1, days_in_month(year, month))?;
+        parser.expect(b"Tt ")?;
+        let hour = parser.range(2, "hour", 0, 23)?;
+        parser.expect(b":")?;
+        let minute = parser.range(2, "minute", 0, 59)?;
+        parser.expect(b":")?;
+        let second = parser.range(2, "second", 0, 60)?.min(59);
+
+        let mut nanos = 0;
+        if parser.peek() == Some(b'.') {
+            parser.pos += 1;
+            let start = parser.pos;
+            while let Some(c) = parser.peek().filter(u8::is_ascii_digit) {
+                if parser.pos - start < 9 {
+                    nanos = nanos * 10 + i128::from(c - b'0');
+                }
+                parser.pos += 1;
+            }
+            let len = parser.pos - start;
+            if len == 0 {
+                return Err(format!(
+                    "Expected a fraction of a second at position {}",
+                    start
+                ));
+            }
+            if len < 9 {
+                nanos *= 10i128.pow(9 - len as u32);
+            }
+        }
+
+        let offset = match parser.expect(b"Zz+-")? {
+            b'Z' | b'z' => 0,
+            sign => {
+                let hours = parser.range(2, "offset hour", 0, 23)?;
+                parser.expect(b":")?;
+                let minutes = parser.range(2, "offset minute", 0, 59)?;
+                let offset = (hours * 60 + minutes) * 60;
+                if sign == b'-' {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+        };
+
+        if parser.pos != parser.input.len() {
+            return Err(format!(
+                "Unexpected trailing input at position {}",
+                parser.pos
+            ));
+        }
+
+        let secs =
+            days_from_civil(year, month, day) * SECS_PER_DAY + hour * 3600 + minute * 60 + second
+                - offset;
+        Ok(secs * NANOS_PER_SEC + nanos)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for &nanos in &[
+                0,
+                1,
+                -1,
+                951_782_400 * NANOS_PER_SEC + 500_000_000,
+                -62_167_219_200 * NANOS_PER_SEC,
+                253_402_300_799 * NANOS_PER_SEC,
+            ] {
+                let s = format(nanos).unwrap();
+                assert_eq!(parse(&s), Ok(nanos), "{}", s);
+            }
+        }
+
+        #[test]
+        fn format_known_dates() {
+            assert_eq!(format(0).unwrap(), "1970-01-01T00:00:00Z");
+            assert_eq!(
+                format(951_782_400 * NANOS_PER_SEC + 120_000_000).unwrap(),
+                "2000-02-29T00:00:00.12Z"
+            );
+            assert_eq!(format(-NANOS_PER_SEC).unwrap(), "1969-12-31T23:59:59Z");
+            assert_eq!(format(253_402_300_800 * NANOS_PER_SEC), None);
+        }
+
+        #[test]
+        fn parse_offsets() {
+            assert_eq!(parse("1970-01-01T01:00:00+01:00"), Ok(0));
+            assert_eq!(parse("1969-12-31t19:00:00.000-05:00"), Ok(0));
+            assert!(parse("2001-02-29T00:00:00Z").is_err());
+            assert!(parse("2001-01-01T00:00:00").is_err());
+            assert!(parse("2001-01-01T00:00:00Zjunk").is_err());
+        }
+    }
+}
+
+mod std {
+    pub mod time {
+        pub use crate::std_lib::time as prim;
+    }
+}
+
+pub fn load(
+    vm: &Thread,
+    capabilities: Capabilities,
+    clock: Arc<dyn Clock>,
+) -> vm::Result<ExternModule> {
+    vm.register_type::<GluonClock>("std.time.Clock", &[])?;
+    vm.register_type::<Duration>("std.time.Duration", &[])?;
+    vm.register_type::<Instant>("std.time.Instant", &[])?;
+    vm.register_type::<SystemTime>("std.time.SystemTime", &[])?;
+
+    ExternModule::new(
+        vm,
+        record! {
+            type std::time::Clock => GluonClock,
+            type std::time::Duration => Duration,
+            type std::time::Instant => Instant,
+            type std::time::SystemTime => SystemTime,
+            clock => GluonClock {
+                clock,
+                wall_clock: capabilities.contains(Capabilities::CLOCK),
+            },
+            duration => record! {
+                from_secs => primitive!(1, std::time::prim::duration::from_secs),
+                from_millis => primitive!(1, std::time::prim::duration::from_millis),
+                from_micros => primitive!(1, std::time::prim::duration::from_micros),
+                from_nanos => primitive!(1, std::time::prim::duration::from_nanos),
+                from_secs_float => primitive!(1, std::time::prim::duration::from_secs_float),
+                as_secs => primitive!(1, std::time::prim::duration::as_secs),
+                as_millis => primitive!(1, std::time::prim::duration::as_millis),
+                as_micros => primitive!(1, std::time::prim::duration::as_micros),
+                as_nanos => primitive!(1, std::time::prim::duration::as_nanos),
+                as_secs_float => primitive!(1, std::time::prim::duration::as_secs_float),
+                subsec_nanos => primitive!(1, std::time::prim::duration::subsec_nanos),
+                add => primitive!(2, std::time::prim::duration::add),
+                sub => primitive!(2, std::time::prim::duration::sub),
+                checked_sub => primitive!(2, std::time::prim::duration::checked_sub),
+                mul => primitive!(2, std::time::prim::duration::mul),
+                div => primitive!(2, std::time::prim::duration::div),
+                eq => primitive!(2, std::time::prim::duration::eq),
+                compare => primitive!(2, std::time::prim::duration::compare),
+                show => primitive!(1, std::time::prim::duration::show),
+            },
+            instant => record! {
+                now => primitive!(1, std::time::prim::instant::now),
+                elapsed => primitive!(2, std::time::prim::instant::elapsed),
+                duration_since => primitive!(2, std::time::prim::instant::duration_since),
+                add => primitive!(2, std::time::prim::instant::add),
+                sub => primitive!(2, std::time::prim::instant::sub),
+                eq => primitive!(2, std::time::prim::instant::eq),
+                compare => primitive!(2, std::time::prim::instant::compare),
+                show => primitive!(1, std::time::prim::instant::show),
+            },
+            system_time => record! {
+                now => primitive!(1, std::time::prim::system_time::now),
+                unix_epoch => SystemTime(UNIX_EPOCH),
+                from_unix_secs => primitive!(1, std::time::prim::system_time::from_unix_secs),
+                from_unix_millis => primitive!(1, std::time::prim::system_time::from_unix_millis),
+                from_unix_nanos => primitive!(1, std::time::prim::system_time::from_unix_nanos),
+                to_unix_secs => primitive!(1, std::time::prim::system_time::to_unix_secs),
+                to_unix_millis => primitive!(1, std::time::prim::system_time::to_unix_millis),
+                to_unix_nanos => primitive!(1, std::time::prim::system_time::to_unix_nanos),
+                add => primitive!(2, std::time::prim::system_time::add),
+                sub => primitive!(2, std::time::prim::system_time::sub),
+                duration_since => primitive!(2, std::time::prim::system_time::duration_since),
+                eq => primitive!(2, std::time::prim::system_time::eq),
+                compare => primitive!(2, std::time::prim::system_time::compare),
+                show => primitive!(1, std::time::prim::system_time::show),
+                format_rfc3339 => primitive!(1, std::time::prim::system_time::format_rfc3339),
+                parse_rfc3339 => primitive!(1, std::time::prim::system_time::parse_rfc3339),
+            },
+        },
+    )
+}
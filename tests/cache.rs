@@ -0,0 +1,113 @@
+#![cfg(feature = "serialization")]
+
+use std::path::Path;
+
+use gluon::{new_vm, query::CompilationBase, RootedThread, ThreadExt};
+
+fn vm(cache: &Path) -> RootedThread {
+    let vm = new_vm();
+    vm.get_database_mut()
+        .set_bytecode_cache(Some(cache.to_path_buf()));
+    vm
+}
+
+fn load(vm: &RootedThread, module: &str, text: &str) {
+    vm.load_script(module, text)
+        .unwrap_or_else(|err| panic!("{}", err));
+}
+
+fn run(vm: &RootedThread, expr: &str) -> i32 {
+    vm.run_expr::<i32>("test", expr)
+        .unwrap_or_else(|err| panic!("{}", err))
+        .0
+}
+
+fn was_compiled(vm: &RootedThread, module: &str) -> bool {
+    vm.get_database()
+        .peek_typechecked_source_module(module)
+        .is_some()
+}
+
+#[test]
+fn loads_cached_module() {
+    let _ = env_logger::try_init();
+
+    let dir = tempfile::tempdir().unwrap();
+    let text = "let x = 1 + 2 in { x }";
+
+    let vm1 = vm(dir.path());
+    load(&vm1, "cached", text);
+    assert!(was_compiled(&vm1, "cached"));
+    assert_eq!(run(&vm1, "let { x } = import! cached in x"), 3);
+
+    let vm2 = vm(dir.path());
+    load(&vm2, "cached", text);
+    assert!(!was_compiled(&vm2, "cached"));
+    assert!(!was_compiled(&vm2, "std.map"));
+    assert_eq!(run(&vm2, "let { x } = import! cached in x"), 3);
+}
+
+#[test]
+fn changed_import_invalidates_module() {
+    let _ = env_logger::try_init();
+
+    let dir = tempfile::tempdir().unwrap();
+    let importer = "let { x } = import! imported in { y = x * 2 }";
+
+    let vm1 = vm(dir.path());
+    load(&vm1, "imported", "{ x = 1 }");
+    load(&vm1, "importer", importer);
+    assert_eq!(run(&vm1, "let { y } = import! importer in y"), 2);
+
+    // `importer` is unchanged but must be recompiled as one of its imports changed
+    let vm2 = vm(dir.path());
+    load(&vm2, "imported", "{ x = 10 }");
+    load(&vm2, "importer", importer);
+    assert!(was_compiled(&vm2, "importer"));
+    assert_eq!(run(&vm2, "let { y } = import! importer in y"), 20);
+}
+
+#[test]
+fn changed_settings_are_not_loaded() {
+    let _ = env_logger::try_init();
+
+    let dir = tempfile::tempdir().unwrap();
+    let text = "{ x = 1 }";
+
+    let vm1 = vm(dir.path());
+    load(&vm1, "cached", text);
+
+    let vm2 = vm(dir.path());
+    vm2.get_database_mut().set_optimize(false);
+    load(&vm2, "cached", text);
+    assert!(was_compiled(&vm2, "cached"));
+}
+
+#[test]
+fn cached_types_unify_with_loaded_modules() {
+    let _ = env_logger::try_init();
+
+    let dir = tempfile::tempdir().unwrap();
+    let text = r#"
+        let map = import! std.map
+        let m : map.Map String Int = map.singleton "a" 1
+        { m }
+    "#;
+    let expr = r#"
+        let { m } = import! cached
+        let map @ { ? } = import! std.map
+        let { ? } = import! std.string
+        let option = import! std.option
+        let m = map.insert "b" 2 m
+        option.unwrap (map.find "a" m) + option.unwrap (map.find "b" m)
+    "#;
+
+    let vm1 = vm(dir.path());
+    load(&vm1, "cached", text);
+    assert_eq!(run(&vm1, expr), 3);
+
+    let vm2 = vm(dir.path());
+    load(&vm2, "cached", text);
+    assert!(!was_compiled(&vm2, "cached"));
+    assert_eq!(run(&vm2, expr), 3);
+}
@@ -0,0 +1,256 @@
+use std::sync::{Arc, Mutex};
+
+use gluon::{
+    base::types::Type,
+    debugger::{Breakpoint, Command, Debugger, FrameInfo, PauseReason, Paused, Variable},
+    new_vm, RootedThread, ThreadExt,
+};
+
+const SOURCE: &str = r#"
+let add x y = x #Int+ y
+let f x =
+    let y = add x 1
+    y #Int* 2
+let z = f 10
+z #Int+ 1
+"#;
+
+fn vm() -> RootedThread {
+    let vm = new_vm();
+    {
+        let mut db = vm.get_database_mut();
+        db.set_optimize(false);
+        db.set_implicit_prelude(false);
+    }
+    vm
+}
+
+/// Runs `SOURCE` with `debugger` attached, calling `handler` for each pause, and returns the
+/// result
+fn run<F>(debugger: &Debugger, mut handler: F) -> gluon::Result<i32>
+where
+    F: FnMut(&mut Paused) -> Command + Send + 'static,
+{
+    let _ = env_logger::try_init();
+
+    let vm = vm();
+    debugger.attach(&vm, move |paused| handler(paused)).unwrap();
+    let result = vm.run_expr::<i32>("test", SOURCE).map(|(value, _)| value);
+    debugger.detach(&vm);
+    result
+}
+
+fn variable(name: &str, typ: gluon::base::types::ArcType, value: &str) -> Variable {
+    Variable {
+        name: name.to_string(),
+        typ,
+        value: value.to_string(),
+    }
+}
+
+/// Collects the line of the executing frame at each pause, answering each pause with `command`
+fn step_lines(command: Command) -> Vec<usize> {
+    let debugger = Debugger::new();
+    debugger.add_breakpoint("test", 4);
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    {
+        let lines = lines.clone();
+        run(&debugger, move |paused| {
+            lines.lock().unwrap().push(paused.frames()[0].line.unwrap());
+            command
+        })
+        .unwrap();
+    }
+    let lines = lines.lock().unwrap();
+    lines.clone()
+}
+
+#[test]
+fn breakpoint_shows_locals() {
+    let debugger = Debugger::new();
+    assert!(debugger.add_breakpoint("test", 5));
+    assert!(!debugger.add_breakpoint("test", 5));
+
+    let pauses = Arc::new(Mutex::new(Vec::new()));
+    let result = {
+        let pauses = pauses.clone();
+        run(&debugger, move |paused| {
+            pauses
+                .lock()
+                .unwrap()
+                .push((paused.reason().clone(), paused.locals(0)));
+            Command::Continue
+        })
+    };
+    assert_eq!(result.unwrap(), 23);
+
+    let pauses = pauses.lock().unwrap();
+    assert_eq!(
+        *pauses,
+        vec![(
+            PauseReason::Breakpoint(Breakpoint::new("test", 5)),
+            vec![
+                variable("x", Type::int(), "10"),
+                variable("y", Type::int(), "11"),
+            ]
+        )]
+    );
+}
+
+#[test]
+fn breakpoints_can_be_removed() {
+    let debugger = Debugger::new();
+    debugger.add_breakpoint("test.glu", 5);
+    debugger.add_breakpoint("test", 7);
+    assert!(debugger.remove_breakpoint("test", 7));
+    assert_eq!(debugger.breakpoints(), vec![Breakpoint::new("test.glu", 5)]);
+
+    let count = Arc::new(Mutex::new(0));
+    {
+        let count = count.clone();
+        run(&debugger, move |_| {
+            *count.lock().unwrap() += 1;
+            Command::Continue
+        })
+        .unwrap();
+    }
+    assert_eq!(*count.lock().unwrap(), 1);
+}
+
+#[test]
+fn step_over() {
+    assert_eq!(step_lines(Command::StepOver), vec![4, 5, 7]);
+}
+
+#[test]
+fn step_in() {
+    assert_eq!(step_lines(Command::StepIn), vec![4, 2, 5, 7]);
+}
+
+#[test]
+fn step_out() {
+    assert_eq!(step_lines(Command::StepOut), vec![4, 7]);
+}
+
+#[test]
+fn frames() {
+    let debugger = Debugger::new();
+    debugger.add_breakpoint("test", 2);
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    {
+        let frames = frames.clone();
+        run(&debugger, move |paused| {
+            frames.lock().unwrap().push(paused.frames());
+            Command::Continue
+        })
+        .unwrap();
+    }
+
+    let frames = frames.lock().unwrap();
+    let frame = |function_name: &str, line| FrameInfo {
+        function_name: Some(function_name.to_string()),
+        source_name: "test".to_string(),
+        line: Some(line),
+    };
+    // The first pause is at the definition of `add`, the second one inside `add`
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0][0], frame("test", 2));
+    assert_eq!(
+        frames[1][..3],
+        [frame("add", 2), frame("f", 4), frame("test", 6)]
+    );
+}
+
+#[test]
+fn upvars() {
+    let debugger = Debugger::new();
+    debugger.add_breakpoint("test", 4);
+    let upvars = Arc::new(Mutex::new(Vec::new()));
+    {
+        let upvars = upvars.clone();
+        run(&debugger, move |paused| {
+            upvars.lock().unwrap().extend(paused.upvars(0));
+            Command::Continue
+        })
+        .unwrap();
+    }
+
+    let upvars = upvars.lock().unwrap();
+    assert_eq!(upvars.len(), 1);
+    assert_eq!(upvars[0].name, "add");
+    assert_eq!(upvars[0].typ.to_string(), "Int -> Int -> Int");
+}
+
+#[test]
+fn evaluate_in_paused_frame() {
+    let debugger = Debugger::new();
+    debugger.add_breakpoint("test", 5);
+    let results = Arc::new(Mutex::new(Vec::new()));
+    {
+        let results = results.clone();
+        run(&debugger, move |paused| {
+            let mut results = results.lock().unwrap();
+            results.push(paused.evaluate(0, "add x y").map_err(|err| err.to_string()));
+            results.push(paused.evaluate(0, "\"abc\"").map_err(|err| err.to_string()));
+            results.push(paused.evaluate(1, "z").map_err(|err| err.to_string()));
+            Command::Continue
+        })
+        .unwrap();
+    }
+
+    let results = results.lock().unwrap();
+    assert_eq!(results[0], Ok(variable("add x y", Type::int(), "21")));
+    assert_eq!(
+        results[1],
+        Ok(variable("\"abc\"", Type::string(), "\"abc\""))
+    );
+    assert!(results[2].is_err(), "{:?}", results[2]);
+}
+
+#[test]
+fn stop_on_entry_and_terminate() {
+    let debugger = Debugger::new();
+    debugger.stop_on_entry(true);
+    let reasons = Arc::new(Mutex::new(Vec::new()));
+    let result = {
+        let reasons = reasons.clone();
+        run(&debugger, move |paused| {
+            reasons.lock().unwrap().push(paused.reason().clone());
+            Command::Terminate
+        })
+    };
+
+    assert!(result.is_err());
+    assert_eq!(*reasons.lock().unwrap(), vec![PauseReason::Entry]);
+}
+
+#[test]
+fn evaluate_with_implicit_prelude() {
+    let _ = env_logger::try_init();
+
+    let vm = new_vm();
+    let debugger = Debugger::new();
+    debugger.add_breakpoint("test", 2);
+    let results = Arc::new(Mutex::new(Vec::new()));
+    {
+        let results = results.clone();
+        debugger
+            .attach(&vm, move |paused| {
+                results.lock().unwrap().push(
+                    paused
+                        .evaluate(0, "show (x + 1)")
+                        .map_err(|err| err.to_string()),
+                );
+                Command::Continue
+            })
+            .unwrap();
+    }
+    vm.run_expr::<i32>("test", "let x = 1\nlet y = x + 1\ny")
+        .unwrap();
+    debugger.detach(&vm);
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![Ok(variable("show (x + 1)", Type::string(), "\"2\""))]
+    );
+}
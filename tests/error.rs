@@ -1,6 +1,10 @@
 use gluon::{
-    base, check::typecheck::TypeError, compiler_pipeline::*, parser, vm::Error as VMError, Error,
-    ThreadExt,
+    base,
+    check::{lint::Warning, typecheck::TypeError},
+    compiler_pipeline::*,
+    parser,
+    vm::Error as VMError,
+    Error, ThreadExt, WarningLevel,
 };
 
 mod support;
@@ -95,3 +99,154 @@ fn undefined_infix() {
         _ => panic!(),
     }
 }
+
+#[test]
+fn warnings_are_reported_alongside_the_result() {
+    let _ = ::env_logger::try_init();
+    let vm = support::make_vm();
+    let text = r#"
+        #[derive(Eq, Show)]
+        type Test = | A | B
+        let x = 1
+        show A
+    "#;
+    let (_, _, warnings) = vm.typecheck_str_with_warnings("test", text, None).unwrap();
+    let warnings: Vec<_> = warnings
+        .expect("Expected warnings")
+        .into_errors()
+        .into_iter()
+        .map(|warning| warning.value.to_string())
+        .collect();
+    assert_eq!(warnings, ["Unused variable `x`"]);
+}
+
+#[test]
+fn warning_level_allow_silences_warnings() {
+    let _ = ::env_logger::try_init();
+    let vm = support::make_vm();
+    vm.get_database_mut().set_warning_level(WarningLevel::Allow);
+    let text = r#"
+        let x = 1
+        2
+    "#;
+    let (_, _, warnings) = vm.typecheck_str_with_warnings("test", text, None).unwrap();
+    assert_eq!(warnings, None);
+}
+
+#[test]
+fn warning_level_deny_turns_warnings_into_errors() {
+    let _ = ::env_logger::try_init();
+    let vm = support::make_vm();
+    vm.get_database_mut().set_warning_level(WarningLevel::Deny);
+    let text = r#"
+        let x = 1
+        2
+    "#;
+    let error = vm.typecheck_str("test", text, None).unwrap_err();
+    match error {
+        Error::Typecheck(errors) => {
+            let errors: Vec<_> = errors.into_errors().into();
+            match errors[0].value.error {
+                TypeError::Warning(Warning::UnusedVariable(..)) => (),
+                _ => panic!("Unexpected error {:?}", errors),
+            }
+        }
+        error => panic!("{}", error),
+    }
+}
+
+#[test]
+fn st_array_index_out_of_range_panics() {
+    let _ = ::env_logger::try_init();
+
+    let vm = support::make_vm();
+    let text = r#"
+        let { run_pure, ? } = import! std.effect
+        let st = import! std.effect.st
+        let st_array = import! std.effect.st.array
+        let action =
+            do array = st_array.new 2 0
+            st_array.read array 2
+        run_pure (st.run_state action)
+    "#;
+    match vm.run_expr::<i32>("test", text) {
+        Err(Error::VM(VMError::Panic(err, _))) => assert!(
+            err.contains("Index 2 is out of range for an array of length 2"),
+            "{}",
+            err
+        ),
+        result => panic!("Expected an out of range panic: {:?}", result),
+    }
+}
+
+#[test]
+fn st_array_negative_length_panics() {
+    let _ = ::env_logger::try_init();
+
+    let vm = support::make_vm();
+    let text = r#"
+        let { run_pure, ? } = import! std.effect
+        let st = import! std.effect.st
+        let st_array = import! std.effect.st.array
+        let action =
+            do array = st_array.new (-1) 0
+            st_array.len array
+        run_pure (st.run_state action)
+    "#;
+    match vm.run_expr::<i32>("test", text) {
+        Err(Error::VM(VMError::Panic(err, _))) => assert!(
+            err.contains("Unable to create an array of negative length -1"),
+            "{}",
+            err
+        ),
+        result => panic!("Expected a negative length panic: {:?}", result),
+    }
+}
+
+#[test]
+fn interpolated_string_errors_point_at_the_hole() {
+    let _ = ::env_logger::try_init();
+    let vm = support::make_vm();
+    let text = r#"
+let f x = x
+f"value: {1 + "a"}, {f}"
+"#;
+    let error = vm.typecheck_str("test", text, None).unwrap_err();
+    let error = error.to_string();
+    // `"a"` is not an `Int`
+    assert!(error.contains("test:3:15"), "{}", error);
+    // No `Show` implementation for functions
+    assert!(error.contains("test:3:22"), "{}", error);
+}
+
+#[test]
+fn undefined_variable_suggests_import() {
+    let _ = ::env_logger::try_init();
+    let vm = support::make_vm();
+    let text = r#"
+len "abc"
+"#;
+    let error = vm.typecheck_str("test", text, None).unwrap_err();
+    let error = error.to_string();
+    assert!(
+        error.contains("Try importing it with `let { len } = import! std."),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn errors_are_emitted_with_their_code() {
+    let _ = ::env_logger::try_init();
+    let vm = support::make_vm();
+    for &(text, code) in &[
+        ("(1 + 2", "E0005"),
+        ("let x : Int = \"\"\nx", "E0106"),
+        ("import! std.does_not_exist", "E0302"),
+    ] {
+        let error = vm.typecheck_str("test", text, None).unwrap_err();
+        let output = error.emit_string().unwrap();
+        assert!(output.contains(&format!("error[{}]", code)), "{}", output);
+        assert!(base::error_code::lookup(code).is_some());
+    }
+}
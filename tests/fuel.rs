@@ -0,0 +1,104 @@
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{self, Poll},
+};
+
+use futures::{prelude::*, task::ArcWake};
+
+use gluon::{
+    new_vm,
+    vm::{self, thread::FuelExhausted},
+    Error, RootedThread, ThreadExt,
+};
+
+const LOOP: &str = r#"
+let loop n = if n #Int== 0 then 0 else loop (n #Int- 1)
+loop 1000
+"#;
+
+fn vm() -> RootedThread {
+    let vm = new_vm();
+    vm.get_database_mut().set_implicit_prelude(false);
+    vm
+}
+
+#[test]
+fn unlimited_by_default() {
+    let _ = env_logger::try_init();
+
+    let vm = vm();
+    assert_eq!(vm.remaining_fuel(), None);
+    vm.run_expr::<i32>("test", LOOP).unwrap();
+    assert_eq!(vm.remaining_fuel(), None);
+}
+
+#[test]
+fn fuel_is_consumed() {
+    let _ = env_logger::try_init();
+
+    let vm = vm();
+    vm.set_fuel(Some(100_000));
+    vm.run_expr::<i32>("test", LOOP).unwrap();
+    let remaining = vm.remaining_fuel().unwrap();
+    assert!(remaining < 100_000 - 1000, "{}", remaining);
+}
+
+#[test]
+fn out_of_fuel_error() {
+    let _ = env_logger::try_init();
+
+    let vm = vm();
+    vm.set_fuel(Some(100));
+    match vm.run_expr::<i32>("test", LOOP) {
+        Err(Error::VM(vm::Error::OutOfFuel)) => (),
+        result => panic!("Expected the thread to run out of fuel: {:?}", result),
+    }
+    assert_eq!(vm.remaining_fuel(), Some(0));
+
+    // The thread can be used again after refuelling
+    vm.add_fuel(100_000);
+    let (value, _) = vm.run_expr::<i32>("test", "1 #Int+ 2").unwrap();
+    assert_eq!(value, 3);
+}
+
+struct CountWakes(AtomicUsize);
+
+impl ArcWake for CountWakes {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn yield_when_out_of_fuel() {
+    let _ = env_logger::try_init();
+
+    let vm = vm();
+    vm.set_fuel_exhausted(FuelExhausted::Yield);
+    vm.set_fuel(Some(0));
+
+    let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+    let waker = futures::task::waker(wakes.clone());
+    let mut cx = task::Context::from_waker(&waker);
+
+    let mut future = vm.run_expr_async::<i32>("test", LOOP).boxed();
+    let mut suspensions = 0;
+    let value = loop {
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(result) => break result.unwrap().0,
+            Poll::Pending => {
+                suspensions += 1;
+                assert_eq!(vm.remaining_fuel(), Some(0));
+                assert_eq!(wakes.0.load(Ordering::SeqCst), suspensions - 1);
+                vm.add_fuel(500);
+                assert_eq!(wakes.0.load(Ordering::SeqCst), suspensions);
+            }
+        }
+    };
+    assert_eq!(value, 0);
+    assert!(suspensions > 2, "{}", suspensions);
+}
@@ -0,0 +1,318 @@
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+
+use hyper::{
+    header::{HOST, LOCATION},
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+
+use gluon::{
+    vm::api::{Getable, VmType, IO},
+    RootedThread, ThreadExt,
+};
+
+fn redirect(status: StatusCode, location: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let method = request.method().as_str().to_owned();
+    Ok(match request.uri().path() {
+        "/hello" => Response::new(Body::from("Hello world")),
+        "/method" => Response::new(Body::from(method)),
+        "/echo" => {
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            Response::new(Body::from(body))
+        }
+        "/header" | "/authorization" => {
+            let header = if request.uri().path() == "/header" {
+                "x-test"
+            } else {
+                "authorization"
+            };
+            let value = request
+                .headers()
+                .get(header)
+                .map(|value| value.as_bytes().to_owned())
+                .unwrap_or_default();
+            Response::new(Body::from(value))
+        }
+        "/chunked" => Response::new(Body::wrap_stream(futures::stream::iter(vec![
+            Ok::<_, Infallible>("abc"),
+            Ok("def"),
+        ]))),
+        "/redirect" => redirect(StatusCode::FOUND, "/hello"),
+        "/nested/redirect" => redirect(StatusCode::TEMPORARY_REDIRECT, "target"),
+        "/nested/target" => Response::new(Body::from(format!("nested {}", method))),
+        "/see_other" => redirect(StatusCode::SEE_OTHER, "/method"),
+        "/same_origin" => redirect(StatusCode::FOUND, "/authorization"),
+        // Redirects to the same server but through another host name
+        "/cross_origin" => {
+            let host = request.headers()[HOST].to_str().unwrap();
+            let location = format!(
+                "http://{}/authorization",
+                host.replace("127.0.0.1", "localhost")
+            );
+            redirect(StatusCode::FOUND, &location)
+        }
+        "/loop" => redirect(StatusCode::FOUND, "/loop"),
+        "/slow" => {
+            tokio::time::delay_for(Duration::from_secs(5)).await;
+            Response::new(Body::from("slow"))
+        }
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap(),
+    })
+}
+
+/// Starts a server on a free port which stands in for a remote service
+fn start_server() -> SocketAddr {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = Server::from_tcp(listener)
+        .unwrap()
+        .serve(make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(handle))
+        }));
+    tokio::spawn(server);
+    addr
+}
+
+async fn run<T>(vm: &RootedThread, text: &str) -> Result<T, String>
+where
+    T: for<'vm, 'value> Getable<'vm, 'value> + VmType + Send + 'static,
+    T::Type: Sized,
+{
+    match vm.run_expr_async::<IO<T>>("test", text).await {
+        Ok((IO::Value(value), _)) => Ok(value),
+        Ok((IO::Exception(err), _)) => Err(err),
+        // Exceptions thrown from gluon are returned as errors
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+async fn vm() -> RootedThread {
+    let vm = gluon::new_vm_async().await;
+    vm.get_database_mut().run_io(true);
+    vm
+}
+
+const PRELUDE: &str = r#"
+let io @ { ? } = import! std.io
+let { wrap } = import! std.applicative
+let string = import! std.string
+let client = import! std.http.client
+"#;
+
+async fn get(vm: &RootedThread, uri: &str) -> Result<(u16, String), String> {
+    let text = format!(
+        r#"{}
+do response = client.get "{}"
+do body = client.read_body response.body
+wrap (response.status, body)
+"#,
+        PRELUDE, uri
+    );
+    run::<(u16, Vec<u8>)>(vm, &text)
+        .await
+        .map(|(status, body)| (status, String::from_utf8(body).unwrap()))
+}
+
+#[tokio::test]
+async fn get_reads_the_response() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    assert_eq!(
+        get(&vm, &format!("http://{}/hello", addr)).await,
+        Ok((200, "Hello world".to_string()))
+    );
+    assert_eq!(
+        get(&vm, &format!("http://{}/missing", addr)).await,
+        Ok((404, "".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn response_body_is_streamed() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    let text = format!(
+        r#"{}
+let array = import! std.array
+let read_all body acc : _ -> Array Byte -> IO (Array Byte) =
+    do chunk = client.read_chunk body
+    match chunk with
+    | Some chunk -> read_all body (array.append acc chunk)
+    | None -> wrap acc
+do response = client.get "http://{}/chunked"
+read_all response.body []
+"#,
+        PRELUDE, addr
+    );
+    assert_eq!(run::<Vec<u8>>(&vm, &text).await, Ok(b"abcdef".to_vec()));
+}
+
+#[tokio::test]
+async fn post_sends_the_body() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    let text = format!(
+        r#"{}
+do response = client.post "http://{}/echo" (string.as_bytes "ping")
+client.read_body response.body
+"#,
+        PRELUDE, addr
+    );
+    assert_eq!(run::<Vec<u8>>(&vm, &text).await, Ok(b"ping".to_vec()));
+}
+
+#[tokio::test]
+async fn request_sends_headers() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    let text = format!(
+        r#"{}
+do uri = client.parse_uri "http://{}/header"
+let options = {{ headers = [("x-test", string.as_bytes "value")] .. client.default_options }}
+do response =
+    client.request options {{ method = "GET", uri, body = client.body_from_bytes [] }}
+client.read_body response.body
+"#,
+        PRELUDE, addr
+    );
+    assert_eq!(run::<Vec<u8>>(&vm, &text).await, Ok(b"value".to_vec()));
+}
+
+#[tokio::test]
+async fn follows_redirects() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    assert_eq!(
+        get(&vm, &format!("http://{}/redirect", addr)).await,
+        Ok((200, "Hello world".to_string()))
+    );
+    assert_eq!(
+        get(&vm, &format!("http://{}/nested/redirect", addr)).await,
+        Ok((200, "nested GET".to_string()))
+    );
+
+    // `307` repeats the request with the same method while `303` switches to `GET`
+    let text = format!(
+        r#"{}
+do temporary = client.post "http://{addr}/nested/redirect" []
+do temporary = client.read_body temporary.body
+do see_other = client.post "http://{addr}/see_other" []
+do see_other = client.read_body see_other.body
+wrap (temporary, see_other)
+"#,
+        PRELUDE,
+        addr = addr
+    );
+    assert_eq!(
+        run::<(Vec<u8>, Vec<u8>)>(&vm, &text).await,
+        Ok((b"nested POST".to_vec(), b"GET".to_vec()))
+    );
+}
+
+#[tokio::test]
+async fn credentials_are_not_sent_to_other_origins() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    let text = |path| {
+        format!(
+            r#"{}
+do uri = client.parse_uri "http://{}{}"
+let headers = [("authorization", string.as_bytes "secret")]
+let options = {{ headers .. client.default_options }}
+do response =
+    client.request options {{ method = "GET", uri, body = client.body_from_bytes [] }}
+client.read_body response.body
+"#,
+            PRELUDE, addr, path
+        )
+    };
+    assert_eq!(
+        run::<Vec<u8>>(&vm, &text("/same_origin")).await,
+        Ok(b"secret".to_vec())
+    );
+    assert_eq!(
+        run::<Vec<u8>>(&vm, &text("/cross_origin")).await,
+        Ok(b"".to_vec())
+    );
+}
+
+#[tokio::test]
+async fn redirects_are_limited() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    let text = format!(
+        r#"{}
+do uri = client.parse_uri "http://{}/loop"
+let options = {{ max_redirects = 3 .. client.default_options }}
+do response =
+    client.request options {{ method = "GET", uri, body = client.body_from_bytes [] }}
+wrap response.status
+"#,
+        PRELUDE, addr
+    );
+    assert_eq!(run::<u16>(&vm, &text).await, Ok(302));
+}
+
+#[tokio::test]
+async fn request_times_out() {
+    let _ = env_logger::try_init();
+
+    let addr = start_server();
+    let vm = vm().await;
+
+    let text = format!(
+        r#"{}
+let {{ duration }} = import! std.time
+do uri = client.parse_uri "http://{}/slow"
+let options = {{ timeout = Some (duration.from_millis 100) .. client.default_options }}
+do response =
+    client.request options {{ method = "GET", uri, body = client.body_from_bytes [] }}
+wrap response.status
+"#,
+        PRELUDE, addr
+    );
+    let err = run::<u16>(&vm, &text).await.unwrap_err();
+    assert!(err.contains("timed out"), "{}", err);
+}
+
+#[tokio::test]
+async fn invalid_uri_is_an_error() {
+    let _ = env_logger::try_init();
+
+    let vm = vm().await;
+
+    let err = get(&vm, "http://exa mple.com").await.unwrap_err();
+    assert!(err.contains("Invalid uri"), "{}", err);
+}
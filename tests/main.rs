@@ -95,7 +95,7 @@ fn main() {
         if let Some(jobs) = options.jobs {
             builder.core_threads(jobs);
         }
-        builder.threaded_scheduler().build().unwrap()
+        builder.threaded_scheduler().enable_all().build().unwrap()
     };
     runtime.block_on(async move {
         if let Err(err) = main_(&options).await {
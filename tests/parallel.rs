@@ -71,7 +71,8 @@ fn parallel_() -> Result<(), Error> {
         "#;
         let mut f: FunctionRef<fn(OpaqueValue<RootedThread, Receiver<i32>>)> =
             child2.run_expr("<top>", expr)?.0;
-        Ok(f.call(receiver)?)
+        // `recv` waits for the values to be sent so it must be able to suspend
+        Ok(futures::executor::block_on(f.call_async(receiver))?)
     });
 
     // Ensure that both threads stop without any panics (just dropping ignores panics)
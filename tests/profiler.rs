@@ -0,0 +1,149 @@
+use gluon::{
+    new_vm,
+    vm::profiler::{FunctionProfile, Metric},
+    RootedThread, ThreadExt,
+};
+
+fn vm() -> RootedThread {
+    let vm = new_vm();
+    {
+        let mut db = vm.get_database_mut();
+        db.set_optimize(false);
+        db.set_implicit_prelude(false);
+    }
+    vm
+}
+
+fn function<'a>(functions: &'a [FunctionProfile], name: &str) -> &'a FunctionProfile {
+    functions
+        .iter()
+        .find(|function| function.name == name)
+        .unwrap_or_else(|| panic!("`{}` is not in the profile: {:#?}", name, functions))
+}
+
+#[test]
+fn not_profiling() {
+    let _ = env_logger::try_init();
+
+    let vm = vm();
+    vm.run_expr::<i32>("test", "1").unwrap();
+    assert!(vm.stop_profiling().is_none());
+}
+
+#[test]
+fn count_calls_and_instructions() {
+    let _ = env_logger::try_init();
+
+    let text = r#"
+let fib n = if n #Int< 2 then n else fib (n #Int- 1) #Int+ fib (n #Int- 2)
+let x = fib 10
+x
+"#;
+    let vm = vm();
+    vm.start_profiling();
+    let (value, _) = vm.run_expr::<i32>("test", text).unwrap();
+    let profile = vm.stop_profiling().unwrap();
+    assert_eq!(value, 55);
+
+    let functions = profile.functions();
+    let fib = function(&functions, "fib");
+    assert_eq!(fib.exclusive.calls, 177);
+    assert!(fib.exclusive.instructions > 177, "{:?}", fib);
+    assert_eq!(fib.inclusive.instructions, fib.exclusive.instructions);
+
+    let test = function(&functions, "test");
+    assert_eq!(test.exclusive.calls, 1);
+    assert!(test.inclusive.instructions > fib.inclusive.instructions);
+    assert!(test.inclusive.time >= fib.inclusive.time);
+
+    // Every recursive call is its own stack
+    assert!(profile
+        .stacks()
+        .any(|(stack, _)| stack == ["test", "fib", "fib", "fib"]));
+}
+
+#[test]
+fn deep_recursion() {
+    let _ = env_logger::try_init();
+
+    let text = r#"
+let count n = if n #Int== 0 then 0 else 1 #Int+ count (n #Int- 1)
+count 500 #Int+ 0
+"#;
+    let vm = vm();
+    vm.start_profiling();
+    let (value, _) = vm.run_expr::<i32>("test", text).unwrap();
+    let profile = vm.stop_profiling().unwrap();
+    assert_eq!(value, 500);
+
+    let functions = profile.functions();
+    assert_eq!(function(&functions, "count").exclusive.calls, 501);
+    // One stack for `test` and one for each depth of `count`
+    assert_eq!(profile.stacks().count(), 502);
+    assert_eq!(
+        profile.stacks().map(|(stack, _)| stack.len()).max(),
+        Some(502)
+    );
+}
+
+#[test]
+fn attribute_allocations() {
+    let _ = env_logger::try_init();
+
+    let text = r#"
+let make_record x = { x, y = x }
+let loop n = if n #Int== 0 then 0 else (make_record n).x #Int+ loop (n #Int- 1)
+loop 100
+"#;
+    let vm = vm();
+    vm.start_profiling();
+    vm.run_expr::<i32>("test", text).unwrap();
+    let profile = vm.stop_profiling().unwrap();
+
+    let functions = profile.functions();
+    let make_record = function(&functions, "make_record");
+    assert_eq!(make_record.exclusive.calls, 100);
+    assert!(make_record.exclusive.allocated_bytes > 0);
+    assert!(
+        function(&functions, "loop").inclusive.allocated_bytes
+            >= make_record.inclusive.allocated_bytes
+    );
+}
+
+#[test]
+fn collapsed_stacks() {
+    let _ = env_logger::try_init();
+
+    let text = r#"
+let f x = x #Int+ 1
+let g x = f x #Int* 2
+g 1 #Int+ f 2
+"#;
+    let vm = vm();
+    vm.start_profiling();
+    vm.run_expr::<i32>("test", text).unwrap();
+    let profile = vm.stop_profiling().unwrap();
+
+    let mut output = Vec::new();
+    profile
+        .write_collapsed(&mut output, Metric::Instructions)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let stacks = output
+        .lines()
+        .map(|line| {
+            let mut iter = line.rsplitn(2, ' ');
+            let count = iter.next().unwrap().parse::<u64>().unwrap();
+            (iter.next().unwrap(), count)
+        })
+        .collect::<Vec<_>>();
+    let names = stacks.iter().map(|(stack, _)| *stack).collect::<Vec<_>>();
+    assert_eq!(names, ["test", "test;f", "test;g", "test;g;f"]);
+    assert!(stacks.iter().all(|(_, count)| *count > 0));
+
+    let mut table = Vec::new();
+    profile.write_table(&mut table).unwrap();
+    let table = String::from_utf8(table).unwrap();
+    assert_eq!(table.lines().count(), 4, "{}", table);
+    assert!(table.lines().next().unwrap().contains("function"));
+}
@@ -0,0 +1,243 @@
+use gluon::{
+    capabilities::Capabilities,
+    vm::api::{Hole, OpaqueValue, IO},
+    RootedThread, ThreadExt, VmBuilder,
+};
+
+fn vm(capabilities: Capabilities) -> RootedThread {
+    let vm = VmBuilder::new().capabilities(capabilities).build();
+    vm.get_database_mut().run_io(true);
+    vm
+}
+
+/// Runs `text` and returns the exception thrown by the `IO` action it evaluates to
+fn io_exception(vm: &RootedThread, text: &str) -> String {
+    match vm.run_expr::<IO<()>>("test", text) {
+        Ok((IO::Value(()), _)) => panic!("Expected an IO exception"),
+        Ok((IO::Exception(err), _)) => err,
+        Err(err) => err.to_string(),
+    }
+}
+
+fn import_error(vm: &RootedThread, module: &str) -> String {
+    match vm.run_expr::<OpaqueValue<RootedThread, Hole>>("test", &format!("import! {}", module)) {
+        Ok(_) => panic!("Expected importing `{}` to fail", module),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn pure_denies_imports() {
+    let _ = env_logger::try_init();
+
+    let vm = vm(Capabilities::PURE);
+    for &(module, capability) in &[
+        ("std.fs", "FS_READ"),
+        ("std.process", "PROCESS"),
+        ("std.env", "ENV"),
+        ("std.net", "NET"),
+    ] {
+        let err = import_error(&vm, module);
+        assert!(
+            err.contains(&format!(
+                "requires the {} capability which this VM was built without",
+                capability
+            )),
+            "{}",
+            err
+        );
+    }
+
+    // Modules which do not touch the world outside of the VM are still available
+    let (value, _) = vm
+        .run_expr::<String>(
+            "test",
+            r#"let string = import! std.string in string.trim " a ""#,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert_eq!(value, "a");
+}
+
+#[test]
+fn pure_denies_importing_files() {
+    let _ = env_logger::try_init();
+
+    let err = import_error(&vm(Capabilities::PURE), "tests.unrelated_type_error");
+    assert!(
+        err.contains("requires the FS_READ capability which this VM was built without"),
+        "{}",
+        err
+    );
+
+    // The file is found and typechecked (and rejected) once `FS_READ` is available
+    let err = import_error(&vm(Capabilities::IO_READONLY), "tests.unrelated_type_error");
+    assert!(!err.contains("FS_READ"), "{}", err);
+    assert!(
+        err.contains("Expected the following types to be equal"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn pure_denies_importing_std_prefixed_files() {
+    let _ = env_logger::try_init();
+
+    // A module named like a standard library module which is not embedded in the binary
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("std")).unwrap();
+    std::fs::write(dir.path().join("std").join("not_embedded.glu"), "1").unwrap();
+
+    let vm = |capabilities| {
+        let vm = VmBuilder::new()
+            .capabilities(capabilities)
+            .import_paths(Some(vec![".".into(), dir.path().into()]))
+            .build();
+        vm.get_database_mut().run_io(true);
+        vm
+    };
+
+    let err = import_error(&vm(Capabilities::PURE), "std.not_embedded");
+    assert!(
+        err.contains("requires the FS_READ capability which this VM was built without"),
+        "{}",
+        err
+    );
+
+    let (value, _) = vm(Capabilities::IO_READONLY)
+        .run_expr::<i32>("test", "import! std.not_embedded")
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert_eq!(value, 1);
+}
+
+#[test]
+fn pure_io_throws() {
+    let _ = env_logger::try_init();
+
+    let vm = vm(Capabilities::PURE);
+    let err = io_exception(&vm, r#"let io = import! std.io in io.println "Hello""#);
+    assert!(
+        err.contains(
+            "std.io.println requires the CONSOLE capability which this VM was built without"
+        ),
+        "{}",
+        err
+    );
+
+    let text = r#"
+        let io @ { ? } = import! std.io
+        let { map } = import! std.functor
+        map (\_ -> ()) (io.read_file_to_string "Cargo.toml")
+    "#;
+    let err = io_exception(&vm, text);
+    assert!(
+        err.contains("std.io.read_file_to_string requires the FS_READ"),
+        "{}",
+        err
+    );
+
+    let text = r#"
+        let { ? } = import! std.io
+        let bytes = import! std.bytes
+        let { map } = import! std.functor
+        map (\_ -> ()) (bytes.write_file "not-a-file" bytes.empty)
+    "#;
+    let err = io_exception(&vm, text);
+    assert!(
+        err.contains("std.bytes.write_file requires the FS_WRITE"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn io_readonly() {
+    let _ = env_logger::try_init();
+
+    let vm = vm(Capabilities::IO_READONLY);
+    vm.run_expr::<OpaqueValue<RootedThread, Hole>>("test", "import! std.fs")
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert!(import_error(&vm, "std.env").contains("ENV"));
+
+    let text = r#"
+        let io = import! std.io
+        io.read_file_to_string "Cargo.toml"
+    "#;
+    match vm.run_expr::<IO<String>>("test", text) {
+        Ok((IO::Value(contents), _)) => assert!(contents.contains("[package]")),
+        result => panic!("Expected the file to be read: {:?}", result),
+    }
+
+    let text = r#"
+        let io @ { ? } = import! std.io
+        let { OpenOptions } = import! std.io.prim
+        let { map } = import! std.functor
+        map (\_ -> ()) (io.open_file_with "not-a-file" [Write])
+    "#;
+    let err = io_exception(&vm, text);
+    assert!(
+        err.contains("std.io.open_file_with requires the FS_WRITE"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn denied_modules() {
+    let _ = env_logger::try_init();
+
+    let vm = VmBuilder::new()
+        .denied_modules(vec!["std.process.prim".into()])
+        .build();
+    let err = import_error(&vm, "std.process");
+    assert!(
+        err.contains("std.process.prim has been denied in this VM"),
+        "{}",
+        err
+    );
+
+    vm.run_expr::<OpaqueValue<RootedThread, Hole>>("test", "import! std.env")
+        .unwrap_or_else(|err| panic!("{}", err));
+}
+
+#[test]
+fn parse_profiles() {
+    assert_eq!("pure".parse(), Ok(Capabilities::PURE));
+    assert_eq!(
+        "io-readonly".parse(),
+        Ok(Capabilities::CONSOLE | Capabilities::FS_READ)
+    );
+    assert_eq!("full".parse(), Ok(Capabilities::all()));
+    assert!("none".parse::<Capabilities>().is_err());
+}
+
+#[test]
+fn pure_denies_wall_clock() {
+    let _ = env_logger::try_init();
+
+    let vm = vm(Capabilities::PURE);
+    let text = r#"
+        let { ? } = import! std.io
+        let { system_time } = import! std.time
+        let { map } = import! std.functor
+        map (\_ -> ()) system_time.now
+    "#;
+    let err = io_exception(&vm, text);
+    assert!(
+        err.contains("std.time.system_time.now requires the CLOCK capability"),
+        "{}",
+        err
+    );
+
+    // Durations and the monotonic clock do not read the wall clock
+    let text = r#"
+        let { ? } = import! std.io
+        let { duration, instant } = import! std.time
+        let { map } = import! std.functor
+        map (\_ -> duration.as_millis (duration.from_secs 2)) instant.now
+    "#;
+    match vm.run_expr::<IO<i64>>("test", text) {
+        Ok((IO::Value(millis), _)) => assert_eq!(millis, 2000),
+        result => panic!("Expected the action to succeed: {:?}", result),
+    }
+}
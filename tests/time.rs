@@ -0,0 +1,92 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use gluon::{std_lib::time::Clock, vm::api::IO, RootedThread, ThreadExt, VmBuilder};
+
+/// A clock which only advances when `advance` is called
+struct FakeClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl FakeClock {
+    fn new() -> Self {
+        FakeClock {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::from_secs(0)),
+        }
+    }
+
+    fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FakeClock {
+    fn instant(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(951_782_400) + *self.elapsed.lock().unwrap()
+    }
+}
+
+fn vm(clock: Arc<FakeClock>) -> RootedThread {
+    let vm = VmBuilder::new().clock(Some(clock)).build();
+    vm.get_database_mut().run_io(true);
+    vm
+}
+
+fn run_io<T>(vm: &RootedThread, text: &str) -> T
+where
+    T: for<'vm, 'value> gluon::vm::api::Getable<'vm, 'value>
+        + gluon::vm::api::VmType
+        + Send
+        + std::fmt::Debug,
+    T::Type: Sized,
+{
+    match vm.run_expr::<IO<T>>("test", text) {
+        Ok((IO::Value(value), _)) => value,
+        result => panic!("Expected the action to succeed: {:?}", result),
+    }
+}
+
+#[test]
+fn system_time_reads_injected_clock() {
+    let _ = env_logger::try_init();
+
+    let clock = Arc::new(FakeClock::new());
+    let vm = vm(clock.clone());
+
+    let text = r#"
+        let { ? } = import! std.io
+        let { system_time } = import! std.time
+        let { map } = import! std.functor
+        map system_time.format_rfc3339 system_time.now
+    "#;
+    assert_eq!(run_io::<String>(&vm, text), "2000-02-29T00:00:00Z");
+
+    clock.advance(Duration::from_millis(1500));
+    assert_eq!(run_io::<String>(&vm, text), "2000-02-29T00:00:01.5Z");
+}
+
+#[test]
+fn instant_reads_injected_clock() {
+    let _ = env_logger::try_init();
+
+    let clock = Arc::new(FakeClock::new());
+    let vm = vm(clock.clone());
+
+    let text = r#"
+        let { instant, duration } = import! std.time
+        let { wrap } = import! std.applicative
+        let { ? } = import! std.io
+        do start = instant.now
+        do elapsed = instant.elapsed start
+        wrap (duration.as_nanos elapsed)
+    "#;
+    assert_eq!(run_io::<i64>(&vm, text), 0);
+}
@@ -699,6 +699,44 @@ int.max_value * 2
     }
 }
 
+#[test]
+fn pow_with_negative_exponent_is_an_error() {
+    let _ = ::env_logger::try_init();
+    let vm = make_vm();
+    for function in &["saturating_pow", "wrapping_pow"] {
+        let text = format!("let int = import! std.int in int.{} 2 (-1)", function);
+        match vm.run_expr::<i32>("<top>", &text) {
+            Err(err) => assert!(
+                err.to_string()
+                    .contains("attempted to raise 2 to the negative power -1"),
+                "{}",
+                err
+            ),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
+}
+
+#[test]
+fn string_repeat_too_large_is_an_error() {
+    let _ = ::env_logger::try_init();
+    let vm = make_vm();
+    let text = r#"
+let int = import! std.int
+let string = import! std.string
+string.repeat "ab" int.max_value
+"#;
+    match vm.run_expr::<String>("<top>", text) {
+        Err(err) => assert!(
+            err.to_string()
+                .contains("cannot repeat a string of length 2 9223372036854775807 times"),
+            "{}",
+            err
+        ),
+        Ok(_) => panic!("Expected an error"),
+    }
+}
+
 #[test]
 fn partially_applied_constructor_is_lambda() {
     let _ = ::env_logger::try_init();
@@ -1603,6 +1603,10 @@ impl<T, R: ?Sized> PushAsRef<T, R> {
     pub fn new(value: T) -> PushAsRef<T, R> {
         PushAsRef(value, PhantomData)
     }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
 }
 
 impl<T, R: ?Sized> VmType for PushAsRef<T, R>
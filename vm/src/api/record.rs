@@ -80,7 +80,7 @@ where
 
         let args = F::args();
 
-        let alias_name = Symbol::from(F::name().replace("::", "."));
+        let alias_name = Symbol::from(F::name().replace(" ", "").replace("::", "."));
         let field_name = Symbol::from(alias_name.declared_name());
 
         let mut rhs_is_equivalent = None;
@@ -11,9 +11,10 @@ use crate::real_std::{
 use futures::{
     future::{self, Either},
     prelude::*,
-    task::Poll,
+    task::{self, Poll, Waker},
     try_join,
 };
+use futures_timer::Delay;
 
 use crate::base::{
     kind::Kind,
@@ -35,11 +36,97 @@ use crate::{
     Error, ExternModule, Result as VmResult, Variants,
 };
 
+/// The state shared between the `Sender` and `Receiver` of a channel
+struct Channel {
+    queue: VecDeque<Value>,
+    /// The maximum number of values which can be queued before `send` waits for the receiver
+    capacity: Option<usize>,
+    closed: bool,
+    recv_wakers: Vec<Waker>,
+    send_wakers: Vec<Waker>,
+}
+
+unsafe impl Trace for Channel {
+    impl_trace_fields! { self, gc; queue }
+}
+
+fn register(wakers: &mut Vec<Waker>, cx: &task::Context<'_>) {
+    if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+        wakers.push(cx.waker().clone());
+    }
+}
+
+fn wake_all(wakers: &mut Vec<Waker>) {
+    for waker in wakers.drain(..) {
+        waker.wake();
+    }
+}
+
+impl Channel {
+    fn new(capacity: Option<usize>) -> Arc<Mutex<Channel>> {
+        Arc::new(Mutex::new(Channel {
+            queue: VecDeque::new(),
+            capacity,
+            closed: false,
+            recv_wakers: Vec::new(),
+            send_wakers: Vec::new(),
+        }))
+    }
+
+    fn is_full(&self) -> bool {
+        self.capacity
+            .map_or(false, |capacity| self.queue.len() >= capacity)
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        wake_all(&mut self.recv_wakers);
+        wake_all(&mut self.send_wakers);
+    }
+
+    fn try_recv(&mut self) -> Result<Value, RecvError> {
+        match self.queue.pop_front() {
+            Some(value) => {
+                wake_all(&mut self.send_wakers);
+                Ok(value)
+            }
+            None if self.closed => Err(RecvError::Disconnected),
+            None => Err(RecvError::Empty),
+        }
+    }
+
+    /// Receives a value, registering the current task to be woken when a value is sent or the
+    /// channel is closed if the queue is empty
+    fn poll_recv(&mut self, cx: &task::Context<'_>) -> Poll<Result<Value, ()>> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(RecvError::Empty) => {
+                register(&mut self.recv_wakers, cx);
+                Poll::Pending
+            }
+            Err(_) => Poll::Ready(Err(())),
+        }
+    }
+}
+
+/// Returned by `try_recv` and `recv_timeout` when no value could be received
+#[derive(Debug, PartialEq, Pushable, VmType)]
+#[gluon(vm_type = "std.channel.types.RecvError")]
+#[gluon(gluon_vm)]
+pub enum RecvError {
+    /// The channel is empty but still open
+    Empty,
+    /// No value arrived before the timeout elapsed
+    Timeout,
+    /// The channel is empty and has been closed
+    Disconnected,
+}
+
 pub struct Sender<T> {
     // No need to traverse this thread reference as any thread having a reference to this `Sender`
     // would also directly own a reference to the `Thread`
     thread: GcPtr<Thread>,
-    queue: Arc<Mutex<VecDeque<Value>>>,
+    channel: Arc<Mutex<Channel>>,
     _element_type: PhantomData<T>,
 }
 
@@ -50,7 +137,7 @@ where
     T: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", *self.queue.lock().unwrap())
+        write!(f, "{:?}", self.channel.lock().unwrap().queue)
     }
 }
 
@@ -61,21 +148,43 @@ unsafe impl<T> Trace for Sender<T> {
     }
 }
 
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.channel.lock().unwrap().close();
+    }
+}
+
 impl<T> Sender<T> {
-    fn send(&self, value: &Value) {
+    /// Clones `value` into the thread which owns the channel and queues it, waiting for the
+    /// receiver if the channel is full
+    fn poll_send(&self, cx: &task::Context<'_>, value: &Value) -> Poll<Result<(), ()>> {
+        let mut channel = self.channel.lock().unwrap();
+        if channel.closed {
+            return Poll::Ready(Err(()));
+        }
+        if channel.is_full() {
+            register(&mut channel.send_wakers, cx);
+            return Poll::Pending;
+        }
+        let value = self
+            .thread
+            .deep_clone_value(&self.thread, value)
+            .map_err(|_| ())?;
         // SAFETY Rooted when stored in `queue`
         unsafe {
-            self.queue.lock().unwrap().push_back(value.clone_unrooted());
+            channel.queue.push_back(value.get_value().clone_unrooted());
         }
+        wake_all(&mut channel.recv_wakers);
+        Poll::Ready(Ok(()))
     }
 }
 
 unsafe impl<T> Trace for Receiver<T> {
-    impl_trace_fields! { self, gc; queue }
+    impl_trace_fields! { self, gc; channel }
 }
 
 pub struct Receiver<T> {
-    queue: Arc<Mutex<VecDeque<Value>>>,
+    channel: Arc<Mutex<Channel>>,
     _element_type: PhantomData<T>,
 }
 
@@ -86,13 +195,13 @@ where
     T: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", *self.queue.lock().unwrap())
+        write!(f, "{:?}", self.channel.lock().unwrap().queue)
     }
 }
 
-impl<T> Receiver<T> {
-    fn try_recv(&self) -> Result<Value, ()> {
-        self.queue.lock().unwrap().pop_front().ok_or(())
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.channel.lock().unwrap().close();
     }
 }
 
@@ -147,31 +256,104 @@ field_decl! { sender, receiver }
 
 pub type ChannelRecord<S, R> = record_type!(sender => S, receiver => R);
 
-/// FIXME The dummy `a` argument should not be needed to ensure that the channel can only be used
-/// with a single type
-fn channel(WithVM { vm, .. }: WithVM<Generic<A>>) -> ChannelRecord<Sender<A>, Receiver<A>> {
+fn new_channel(vm: &Thread, capacity: Option<usize>) -> ChannelRecord<Sender<A>, Receiver<A>> {
     let sender = Sender {
         thread: unsafe { GcPtr::from_raw(vm) },
-        queue: Arc::new(Mutex::new(VecDeque::new())),
+        channel: Channel::new(capacity),
         _element_type: PhantomData,
     };
     let receiver = Receiver {
-        queue: sender.queue.clone(),
+        channel: sender.channel.clone(),
         _element_type: PhantomData,
     };
     record_no_decl!(sender => sender, receiver => receiver)
 }
 
-fn recv(receiver: &Receiver<A>) -> Result<Unrooted<A>, ()> {
-    receiver.try_recv().map_err(|_| ()).map(Unrooted::from)
+/// FIXME The dummy `a` argument should not be needed to ensure that the channel can only be used
+/// with a single type
+fn channel(WithVM { vm, .. }: WithVM<Generic<A>>) -> ChannelRecord<Sender<A>, Receiver<A>> {
+    new_channel(vm, None)
+}
+
+fn bounded_channel(
+    capacity: VmInt,
+    WithVM { vm, .. }: WithVM<Generic<A>>,
+) -> RuntimeResult<ChannelRecord<Sender<A>, Receiver<A>>, String> {
+    if capacity < 1 {
+        return RuntimeResult::Panic(format!(
+            "The capacity of a bounded channel must be at least 1, got {}",
+            capacity
+        ));
+    }
+    RuntimeResult::Return(new_channel(vm, Some(capacity as usize)))
+}
+
+fn try_recv(receiver: &Receiver<A>) -> Result<Unrooted<A>, RecvError> {
+    receiver
+        .channel
+        .lock()
+        .unwrap()
+        .try_recv()
+        .map(Unrooted::from)
+}
+
+fn recv(receiver: &Receiver<A>) -> impl Future<Output = Result<Unrooted<A>, ()>> {
+    let channel = receiver.channel.clone();
+    future::poll_fn(move |cx| channel.lock().unwrap().poll_recv(cx).map_ok(Unrooted::from))
+}
+
+fn recv_timeout(
+    receiver: &Receiver<A>,
+    ms: VmInt,
+) -> impl Future<Output = Result<Unrooted<A>, RecvError>> {
+    let channel = receiver.channel.clone();
+    let mut delay = Delay::new(Duration::from_millis(ms.max(0) as u64));
+    future::poll_fn(move |cx| match channel.lock().unwrap().poll_recv(cx) {
+        Poll::Ready(result) => Poll::Ready(
+            result
+                .map(Unrooted::from)
+                .map_err(|()| RecvError::Disconnected),
+        ),
+        Poll::Pending => delay.poll_unpin(cx).map(|()| Err(RecvError::Timeout)),
+    })
+}
+
+/// Waits until one of `receivers` has a value or is closed and returns its index along with the
+/// result of receiving from it. Receivers earlier in the array are preferred if several are ready.
+fn select(
+    receivers: Vec<&Receiver<A>>,
+) -> impl Future<Output = RuntimeResult<(VmInt, Result<Unrooted<A>, ()>), String>> {
+    let channels: Vec<_> = receivers
+        .iter()
+        .map(|receiver| receiver.channel.clone())
+        .collect();
+    future::poll_fn(move |cx| {
+        if channels.is_empty() {
+            return Poll::Ready(RuntimeResult::Panic(
+                "select requires at least one receiver".into(),
+            ));
+        }
+        for (i, channel) in channels.iter().enumerate() {
+            if let Poll::Ready(result) = channel.lock().unwrap().poll_recv(cx) {
+                return Poll::Ready(RuntimeResult::Return((
+                    i as VmInt,
+                    result.map(Unrooted::from),
+                )));
+            }
+        }
+        Poll::Pending
+    })
+}
+
+fn send(sender: &Sender<A>, value: Generic<A>) -> impl Future<Output = Result<(), ()>> {
+    // SAFETY The sender and the value are kept alive by the stack frame of this call until the
+    // future completes
+    let sender: GcPtr<Sender<A>> = unsafe { GcPtr::from_raw(sender) };
+    future::poll_fn(move |cx| sender.poll_send(cx, value.get_value()))
 }
 
-fn send(sender: &Sender<A>, value: Generic<A>) -> Result<(), ()> {
-    let value = sender
-        .thread
-        .deep_clone_value(&sender.thread, value.get_value())
-        .map_err(|_| ())?;
-    Ok(sender.send(value.get_value()))
+fn close(sender: &Sender<A>) {
+    sender.channel.lock().unwrap().close();
 }
 
 async fn resume(child: RootedThread) -> RuntimeResult<Result<(), String>, String> {
@@ -345,8 +527,10 @@ fn join(
     WithVM { vm: vm_a, value: a }: WithVM<OpaqueRef<IO<A>>>,
     b: OpaqueRef<IO<B>>,
 ) -> impl Future<Output = RuntimeResult<IO<(Generic<A>, Generic<B>)>, Error>> {
-    let vm_b = match vm_a.new_thread() {
-        Ok(vm) => vm,
+    // Both actions run on threads of their own as the action which is still running gets dropped
+    // when the other one fails
+    let (vm_a, vm_b) = match vm_a.new_thread().and_then(|a| Ok((a, vm_a.new_thread()?))) {
+        Ok(vms) => vms,
         Err(err) => return Either::Right(future::ready(RuntimeResult::Panic(err))),
     };
 
@@ -394,10 +578,15 @@ pub fn load_channel<'vm>(vm: &'vm Thread) -> VmResult<ExternModule> {
         vm,
         record! {
             type Sender a => Sender<A>,
-            type Receiver a => Sender<A>,
+            type Receiver a => Receiver<A>,
             channel => primitive!(1, std::channel::channel),
-            recv => primitive!(1, std::channel::recv),
-            send => primitive!(2, std::channel::send),
+            bounded_channel => primitive!(2, std::channel::bounded_channel),
+            try_recv => primitive!(1, std::channel::try_recv),
+            recv => primitive!(1, async fn std::channel::recv),
+            recv_timeout => primitive!(2, async fn std::channel::recv_timeout),
+            select => primitive!(1, async fn std::channel::select),
+            send => primitive!(2, async fn std::channel::send),
+            close => primitive!(1, std::channel::close),
         },
     )
 }
@@ -962,6 +962,29 @@ impl<'a> Compiler<'a> {
         function: &mut FunctionEnvs,
         tail_position: bool,
     ) -> Result<()> {
+        fn const_index(expr: &Expr) -> VmIndex {
+            match *expr {
+                Expr::Const(Literal::Int(i), _) => i as VmIndex,
+                _ => ice!("Expected a constant array index, found {}", expr),
+            }
+        }
+
+        let array_instr = match self.symbols.string(op) {
+            "#ArrayLen" => Some(ArrayLen),
+            "#ArrayIndex" => Some(GetIndex(const_index(&args[1]))),
+            "#ArrayIndexFromEnd" => Some(GetIndexFromEnd(const_index(&args[1]))),
+            "#ArraySlice" => Some(SliceArray {
+                start: const_index(&args[1]),
+                from_end: const_index(&args[2]),
+            }),
+            _ => None,
+        };
+        if let Some(instr) = array_instr {
+            self.compile(&args[0], function, false)?;
+            function.emit(instr);
+            return Ok(());
+        }
+
         assert!(args.len() == 2, "Invalid primitive application: {}", op);
         let lhs = &args[0];
         let rhs = &args[1];
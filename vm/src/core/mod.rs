@@ -1042,11 +1042,42 @@ impl<'a, 'e> Translator<'a, 'e> {
 
             ast::Expr::Match(ref expr, ref alts) => {
                 let expr = self.translate_alloc(&**expr);
+                // The guard and expression of an alternative refer to the variables bound by the
+                // first alternative of each or-pattern. As every alternative binds the variables
+                // to different places we instead refer to new variables which are bound by `let`
+                // once it is known which alternative matched.
+                let or_bindings: Vec<Vec<_>> = alts
+                    .iter()
+                    .map(|alt| {
+                        let mut variables = Vec::new();
+                        pattern_variables(&self.env, &alt.pattern, true, &mut variables);
+                        variables
+                    })
+                    .collect();
                 let alts: Vec<_> = alts
                     .iter()
-                    .map(|alt| Equation {
-                        patterns: vec![&alt.pattern],
-                        result: self.translate_alloc(&alt.expr),
+                    .zip(&or_bindings)
+                    .map(|(alt, or_bindings)| {
+                        let or_bindings = &or_bindings[..];
+                        self.ident_replacements.borrow_mut().extend(
+                            or_bindings
+                                .iter()
+                                .map(|(id, new_id)| (id.name.clone(), new_id.name.clone())),
+                        );
+                        let guard = alt.guard.as_ref().map(|guard| self.translate_alloc(guard));
+                        let result = self.translate_alloc(&alt.expr);
+                        {
+                            let mut replacements = self.ident_replacements.borrow_mut();
+                            for (id, _) in or_bindings {
+                                replacements.remove(&id.name);
+                            }
+                        }
+                        Equation {
+                            patterns: vec![&alt.pattern],
+                            guard,
+                            result,
+                            or_bindings,
+                        }
                     })
                     .collect();
                 PatternTranslator(self).translate_top(expr, &alts).clone()
@@ -1216,7 +1247,9 @@ impl<'a, 'e> Translator<'a, 'e> {
                             id_expr,
                             &[Equation {
                                 patterns: vec![&pat],
+                                guard: None,
                                 result: core_body,
+                                or_bindings: &[],
                             }],
                         );
                         self.allocator.arena.alloc(e)
@@ -1254,6 +1287,7 @@ impl<'a, 'e> Translator<'a, 'e> {
             }
 
             ast::Expr::Error(_) => self.error_expr("Evaluated an invalid exprssion"),
+            ast::Expr::Hole(_) => self.error_expr("Evaluated a typed hole"),
         }
     }
 
@@ -1333,7 +1367,9 @@ impl<'a, 'e> Translator<'a, 'e> {
                             bind_expr,
                             &[Equation {
                                 patterns: vec![&bind.name],
+                                guard: None,
                                 result: tail,
+                                or_bindings: &[],
                             }],
                         );
                     }
@@ -1554,7 +1590,12 @@ pub struct PatternTranslator<'a, 'e: 'a>(&'a Translator<'a, 'e>);
 #[derive(Clone, PartialEq, Debug)]
 struct Equation<'a, 'p, 'ast> {
     patterns: Vec<&'p SpannedPattern<'ast, Symbol>>,
+    /// Expression which must evaluate to `True` for `result` to be selected
+    guard: Option<&'a Expr<'a>>,
     result: &'a Expr<'a>,
+    /// The variables of the or-patterns in `patterns` along with the variables that `guard` and
+    /// `result` refer to them as
+    or_bindings: &'p [(TypedIdent<Symbol>, TypedIdent<Symbol>)],
 }
 
 impl<'a, 'p, 'ast> fmt::Display for Equation<'a, 'p, 'ast> {
@@ -1574,6 +1615,7 @@ enum CType {
     Record,
     Variable,
     Literal,
+    Array,
 }
 
 /// `PatternTranslator` translated nested (AST) patterns into non-nested (core) patterns.
@@ -1607,6 +1649,7 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
             CType::Record => self.compile_record(default, variables, equations),
             CType::Variable => self.compile_variable(default, variables, equations),
             CType::Literal => self.compile_literal(default, variables, equations),
+            CType::Array => self.compile_array(default, variables, equations),
         }
     }
 
@@ -1704,15 +1747,16 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
             // (since those need to be solved first) and then the remaining_patterns
             let new_equations = equations
                 .iter()
-                .map(|equation| (&equation.patterns[1..], equation.result))
                 .zip(&temp)
-                .map(|((remaining_equations, result), first)| Equation {
+                .map(|(equation, first)| Equation {
                     patterns: first
                         .iter()
                         .map(|pattern| &**pattern)
-                        .chain(remaining_equations.iter().cloned())
+                        .chain(equation.patterns[1..].iter().cloned())
                         .collect(),
-                    result,
+                    guard: equation.guard,
+                    result: equation.result,
+                    or_bindings: equation.or_bindings,
                 })
                 .collect::<Vec<_>>();
 
@@ -1760,6 +1804,8 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                 | ast::Pattern::Record { .. }
                 | ast::Pattern::Ident(_)
                 | ast::Pattern::Literal(_)
+                | ast::Pattern::Array { .. }
+                | ast::Pattern::Or(_)
                 | ast::Pattern::Error => unreachable!(),
             }
         }
@@ -1808,7 +1854,9 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                                 .iter()
                                 .chain(equation.patterns.iter().cloned().skip(1))
                                 .collect(),
+                            guard: equation.guard,
                             result: equation.result,
+                            or_bindings: equation.or_bindings,
                         }
                     })
                     .collect::<Vec<_>>();
@@ -1847,7 +1895,9 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                 .iter()
                 .map(|equation| Equation {
                     patterns: equation.patterns[1..].to_owned(),
+                    guard: equation.guard,
                     result: equation.result,
+                    or_bindings: equation.or_bindings,
                 })
                 .collect::<Vec<_>>(),
         );
@@ -1897,6 +1947,8 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                 | ast::Pattern::Tuple { .. }
                 | ast::Pattern::Record { .. }
                 | ast::Pattern::Ident(_)
+                | ast::Pattern::Array { .. }
+                | ast::Pattern::Or(_)
                 | ast::Pattern::Error => unreachable!(),
             }
         }
@@ -1916,7 +1968,9 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                     .iter()
                     .map(|equation| Equation {
                         patterns: equation.patterns.iter().cloned().skip(1).collect(),
+                        guard: equation.guard,
                         result: equation.result,
+                        or_bindings: equation.or_bindings,
                     })
                     .collect::<Vec<_>>();
 
@@ -1940,6 +1994,210 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
         self.0.allocator.arena.alloc(expr)
     }
 
+    fn compile_array<'p>(
+        &mut self,
+        default: &'a Expr<'a>,
+        variables: &[&'a Expr<'a>],
+        equations: &[Equation<'a, 'p, '_>],
+    ) -> &'a Expr<'a> {
+        // Patterns with a different number of elements (or a different placement of `..`) can
+        // overlap so only adjacent patterns of the same shape can be compiled together
+        let groups = equations
+            .iter()
+            .group_by(|equation| ArrayShape::of(&equation.patterns[0].value));
+        let groups = (&groups).into_iter().collect::<Vec<_>>();
+        groups
+            .into_iter()
+            .rev()
+            .fold(default, |next, (shape, group)| {
+                let equation_group = group.collect::<Vec<_>>();
+                self.compile_array_shape(next, variables, shape, &equation_group)
+            })
+    }
+
+    // Translates array patterns of the same shape into
+    //
+    // match #Int== (#ArrayLen xs) 2 with
+    // | True ->
+    //     let x = #ArrayIndex xs 0
+    //     let y = #ArrayIndex xs 1
+    //     match x, y with ...
+    // | False -> default
+    fn compile_array_shape<'p>(
+        &mut self,
+        default: &'a Expr<'a>,
+        variables: &[&'a Expr<'a>],
+        shape: ArrayShape,
+        equations: &[&Equation<'a, 'p, '_>],
+    ) -> &'a Expr<'a> {
+        let translator = self.0;
+        let allocator = &translator.allocator;
+        let array = variables[0];
+
+        let array_type = match *unwrap_as(&equations[0].patterns[0].value) {
+            ast::Pattern::Array { ref typ, .. } => typ.clone(),
+            _ => unreachable!(),
+        };
+        let element_type =
+            match **remove_aliases_cow(translator.env, &mut NullInterner, &array_type) {
+                Type::App(_, ref args) if args.len() == 1 => args[0].clone(),
+                _ => Type::hole(),
+            };
+
+        let primitive = |name: &str, args: Vec<ArcType>, ret: ArcType| -> &'a Expr<'a> {
+            allocator.arena.alloc(Expr::Ident(
+                TypedIdent {
+                    name: Symbol::from(name),
+                    typ: Type::function(args, ret),
+                },
+                Span::default(),
+            ))
+        };
+        let int = |i: usize| Expr::Const(Literal::Int(i as i64), Span::default());
+        let call = |f: &'a Expr<'a>, args: Vec<Expr<'a>>| -> &'a Expr<'a> {
+            allocator
+                .arena
+                .alloc(Expr::Call(f, allocator.arena.alloc_fixed(args)))
+        };
+
+        // Pick one variable for each element (and the rest of the array if it is bound) and make
+        // the other equations refer to it
+        let bind_rest = shape.suffix.is_some()
+            && equations
+                .iter()
+                .any(|equation| !is_wildcard(array_rest(equation).unwrap()));
+        let positions = shape.prefix + shape.suffix.unwrap_or(0);
+        let mut ids = Vec::with_capacity(positions + 1);
+        {
+            let mut replacements = translator.ident_replacements.borrow_mut();
+            let mut pick = |i: usize, patterns: &[&SpannedPattern<Symbol>]| {
+                let id = patterns
+                    .iter()
+                    .find(|pattern| !is_wildcard(pattern))
+                    .unwrap_or(&patterns[0]);
+                let id = self.extract_ident(i, &id.value);
+                for pattern in patterns {
+                    if let Some(duplicate) = get_ident(&pattern.value) {
+                        if duplicate.name != id.name && !is_wildcard(pattern) {
+                            replacements.insert(duplicate.name, id.name.clone());
+                        }
+                    }
+                }
+                ids.push(id);
+            };
+            for i in 0..positions {
+                let patterns = equations
+                    .iter()
+                    .map(|equation| array_elements(equation, i))
+                    .collect::<Vec<_>>();
+                pick(i, &patterns);
+            }
+            if bind_rest {
+                let patterns = equations
+                    .iter()
+                    .map(|equation| array_rest(equation).unwrap())
+                    .collect::<Vec<_>>();
+                pick(positions, &patterns);
+            }
+        }
+
+        let new_equations = equations
+            .iter()
+            .map(|equation| Equation {
+                patterns: (0..positions)
+                    .map(|i| array_elements(equation, i))
+                    .chain(if bind_rest {
+                        array_rest(equation)
+                    } else {
+                        None
+                    })
+                    .chain(equation.patterns[1..].iter().cloned())
+                    .collect(),
+                guard: equation.guard,
+                result: equation.result,
+                or_bindings: equation.or_bindings,
+            })
+            .collect::<Vec<_>>();
+
+        let new_variables = ids
+            .iter()
+            .map(|id| {
+                &*allocator
+                    .arena
+                    .alloc(Expr::Ident(id.clone(), Span::default()))
+            })
+            .chain(variables[1..].iter().cloned())
+            .collect::<Vec<_>>();
+        let body = self.translate(default, &new_variables, &new_equations);
+
+        let body = ids.iter().enumerate().rev().fold(body, |body, (i, id)| {
+            let expr = if i == positions {
+                call(
+                    primitive(
+                        "#ArraySlice",
+                        vec![array_type.clone(), Type::int(), Type::int()],
+                        array_type.clone(),
+                    ),
+                    vec![
+                        array.clone(),
+                        int(shape.prefix),
+                        int(shape.suffix.unwrap_or(0)),
+                    ],
+                )
+            } else if i < shape.prefix || shape.suffix.is_none() {
+                call(
+                    primitive(
+                        "#ArrayIndex",
+                        vec![array_type.clone(), Type::int()],
+                        element_type.clone(),
+                    ),
+                    vec![array.clone(), int(i)],
+                )
+            } else {
+                call(
+                    primitive(
+                        "#ArrayIndexFromEnd",
+                        vec![array_type.clone(), Type::int()],
+                        element_type.clone(),
+                    ),
+                    vec![array.clone(), int(positions - i - 1)],
+                )
+            };
+            &*allocator.arena.alloc(Expr::Let(
+                allocator.let_binding_arena.alloc(LetBinding {
+                    name: id.clone(),
+                    expr: Named::Expr(expr),
+                    span_start: body.span().start(),
+                }),
+                body,
+            ))
+        });
+
+        let len = call(
+            primitive("#ArrayLen", vec![array_type.clone()], Type::int()),
+            vec![array.clone()],
+        );
+        let bool_type = translator.env.get_bool();
+        match shape.suffix {
+            None => {
+                let test = call(
+                    primitive("#Int==", vec![Type::int(), Type::int()], bool_type.clone()),
+                    vec![len.clone(), int(positions)],
+                );
+                self.guarded(test, body, default)
+            }
+            // `[..rest]` matches every array
+            Some(_) if positions == 0 => body,
+            Some(_) => {
+                let test = call(
+                    primitive("#Int<", vec![Type::int(), Type::int()], bool_type.clone()),
+                    vec![len.clone(), int(positions)],
+                );
+                self.guarded(test, default, body)
+            }
+        }
+    }
+
     // Generates a variable for each of the new equations we inserted
     // This variable is what we `match` the expression(s) on
     fn insert_new_variables(
@@ -2003,26 +2261,24 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                 ast::Pattern::Record { .. } | ast::Pattern::Tuple { .. } => CType::Record,
                 ast::Pattern::Constructor(_, _) => CType::Constructor,
                 ast::Pattern::Literal(_) => CType::Literal,
+                ast::Pattern::Array { .. } => CType::Array,
+                ast::Pattern::Or(_) => unreachable!("Or-patterns are expanded before compilation"),
                 ast::Pattern::Error => ice!("ICE: Error pattern survived typechecking"),
             }
         }
 
         let mut binder = Binder::default();
 
-        // The equations must be processed by group
-        //
-        // | Some (Some x) ->
-        // | Some None ->
-        // | x ->
-        let groups = equations
-            .iter()
-            .group_by(|equation| varcon(&equation.patterns.first().expect("Pattern").value));
-
         let expr = match variables.first() {
+            // Select the first equation whose guard succeeds, falling back to `default` if every
+            // equation has a failing guard
             None => equations
-                .first()
-                .map(|equation| equation.result)
-                .unwrap_or(default),
+                .iter()
+                .rev()
+                .fold(default, |next, equation| match equation.guard {
+                    Some(guard) => self.guarded(guard, equation.result, next),
+                    None => equation.result,
+                }),
             Some(_) => {
                 fn bind_variables<'b>(
                     env: &dyn PrimitiveEnv<Type = ArcType>,
@@ -2053,6 +2309,11 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                                 variable,
                             );
                         }
+                        ast::Pattern::Or(ref patterns) => {
+                            for pat in &**patterns {
+                                bind_variables(env, pat, variable, binder);
+                            }
+                        }
                         _ => (),
                     }
                 }
@@ -2069,6 +2330,16 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                     }
                 }
 
+                let equations = self.expand_or_patterns(equations);
+
+                // The equations must be processed by group
+                //
+                // | Some (Some x) ->
+                // | Some None ->
+                // | x ->
+                let groups = equations.iter().group_by(|equation| {
+                    varcon(&equation.patterns.first().expect("Pattern").value)
+                });
                 let groups = (&groups).into_iter().collect::<Vec<_>>();
                 groups
                     .into_iter()
@@ -2089,6 +2360,104 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
         binder.into_expr_ref(allocator, expr)
     }
 
+    /// Replaces each equation whose first pattern is an or-pattern with one equation for each of
+    /// its alternatives
+    fn expand_or_patterns<'p, 'ast>(
+        &self,
+        equations: &[Equation<'a, 'p, 'ast>],
+    ) -> Vec<Equation<'a, 'p, 'ast>> {
+        let mut expanded = Vec::with_capacity(equations.len());
+        for equation in equations {
+            self.expand_or_pattern(equation.clone(), &mut expanded);
+        }
+        expanded
+    }
+
+    fn expand_or_pattern<'p, 'ast>(
+        &self,
+        equation: Equation<'a, 'p, 'ast>,
+        expanded: &mut Vec<Equation<'a, 'p, 'ast>>,
+    ) {
+        let first: &'p SpannedPattern<'ast, Symbol> = equation.patterns[0];
+        match *unwrap_as(&first.value) {
+            ast::Pattern::Or(ref patterns) => {
+                for pattern in &**patterns {
+                    let bind = |expr| self.bind_or_variables(pattern, equation.or_bindings, expr);
+                    let alternative = Equation {
+                        patterns: Some(pattern)
+                            .into_iter()
+                            .chain(equation.patterns[1..].iter().cloned())
+                            .collect(),
+                        guard: equation.guard.map(bind),
+                        result: bind(equation.result),
+                        or_bindings: equation.or_bindings,
+                    };
+                    self.expand_or_pattern(alternative, expanded);
+                }
+            }
+            _ => expanded.push(equation),
+        }
+    }
+
+    /// Binds the variables that `expr` uses to refer to the variables of `pattern` (an alternative
+    /// of an or-pattern). Variables in nested or-patterns are bound when those are expanded.
+    fn bind_or_variables(
+        &self,
+        pattern: &SpannedPattern<Symbol>,
+        or_bindings: &[(TypedIdent<Symbol>, TypedIdent<Symbol>)],
+        expr: &'a Expr<'a>,
+    ) -> &'a Expr<'a> {
+        let mut variables = Vec::new();
+        pattern_variables(&self.0.env, pattern, false, &mut variables);
+
+        let allocator = &self.0.allocator;
+        variables.into_iter().fold(expr, |expr, (id, _)| {
+            match or_bindings
+                .iter()
+                .find(|(_, new_id)| new_id.name.declared_name() == id.name.declared_name())
+            {
+                Some((_, new_id)) => allocator.arena.alloc(Expr::Let(
+                    allocator.let_binding_arena.alloc(LetBinding {
+                        name: new_id.clone(),
+                        expr: Named::Expr(allocator.arena.alloc(Expr::Ident(
+                            TypedIdent {
+                                name: id.name,
+                                typ: new_id.typ.clone(),
+                            },
+                            expr.span(),
+                        ))),
+                        span_start: expr.span().start(),
+                    }),
+                    expr,
+                )),
+                None => expr,
+            }
+        })
+    }
+
+    // match guard with
+    // | True -> result
+    // | False -> next
+    fn guarded(
+        &self,
+        guard: &'a Expr<'a>,
+        result: &'a Expr<'a>,
+        next: &'a Expr<'a>,
+    ) -> &'a Expr<'a> {
+        let allocator = &self.0.allocator;
+        let alts = allocator.alternative_arena.alloc_fixed(iterator!(
+            Alternative {
+                pattern: Pattern::Constructor(self.0.bool_constructor(true), vec![]),
+                expr: result,
+            },
+            Alternative {
+                pattern: Pattern::Constructor(self.0.bool_constructor(false), vec![]),
+                expr: next,
+            },
+        ));
+        allocator.arena.alloc(Expr::Match(guard, alts))
+    }
+
     fn extract_ident(&self, index: usize, pattern: &ast::Pattern<Symbol>) -> TypedIdent<Symbol> {
         get_ident(pattern).unwrap_or_else(|| TypedIdent {
             name: Symbol::from(format!("pattern_{}", index)),
@@ -2157,7 +2526,7 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                         }
                     }
                 }
-                ast::Pattern::As(..) => unreachable!(),
+                ast::Pattern::As(..) | ast::Pattern::Or(_) => unreachable!(),
                 ast::Pattern::Ident(ref id) => {
                     if core_pattern.is_none() {
                         core_pattern = Some(Pattern::Ident(id.clone()));
@@ -2224,7 +2593,7 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
                         }
                     }
                 }
-                ast::Pattern::Literal(_) | ast::Pattern::Error => (),
+                ast::Pattern::Literal(_) | ast::Pattern::Array { .. } | ast::Pattern::Error => (),
             }
         }
 
@@ -2243,6 +2612,176 @@ impl<'a, 'e> PatternTranslator<'a, 'e> {
     }
 }
 
+/// Collects the variables bound by `pattern`, paired with new variables of the same name and type.
+/// If `or_patterns` is true only the variables bound by or-patterns (in their first alternative)
+/// are collected, otherwise or-patterns are skipped.
+fn pattern_variables(
+    env: &dyn PrimitiveEnv<Type = ArcType>,
+    pattern: &SpannedPattern<Symbol>,
+    or_patterns: bool,
+    variables: &mut Vec<(TypedIdent<Symbol>, TypedIdent<Symbol>)>,
+) {
+    fn add(
+        variables: &mut Vec<(TypedIdent<Symbol>, TypedIdent<Symbol>)>,
+        in_or_pattern: bool,
+        id: TypedIdent<Symbol>,
+    ) {
+        if in_or_pattern {
+            let new_id = TypedIdent {
+                name: Symbol::from(id.name.declared_name()),
+                typ: id.typ.clone(),
+            };
+            variables.push((id, new_id));
+        }
+    }
+
+    fn go(
+        env: &dyn PrimitiveEnv<Type = ArcType>,
+        pattern: &SpannedPattern<Symbol>,
+        or_patterns: bool,
+        in_or_pattern: bool,
+        variables: &mut Vec<(TypedIdent<Symbol>, TypedIdent<Symbol>)>,
+    ) {
+        match pattern.value {
+            ast::Pattern::As(ref id, ref pat) => {
+                add(
+                    variables,
+                    in_or_pattern,
+                    TypedIdent {
+                        name: id.value.clone(),
+                        typ: pat.env_type_of(&env),
+                    },
+                );
+                go(env, pat, or_patterns, in_or_pattern, variables);
+            }
+            ast::Pattern::Ident(ref id) => add(variables, in_or_pattern, id.clone()),
+            ast::Pattern::Constructor(_, ref args) => {
+                for arg in &**args {
+                    go(env, arg, or_patterns, in_or_pattern, variables);
+                }
+            }
+            ast::Pattern::Tuple { ref elems, .. } | ast::Pattern::Array { ref elems, .. } => {
+                for elem in &**elems {
+                    go(env, elem, or_patterns, in_or_pattern, variables);
+                }
+            }
+            ast::Pattern::Record {
+                ref typ,
+                ref fields,
+                ref implicit_import,
+            } => {
+                let typ = remove_aliases_cow(env, &mut NullInterner, typ);
+                for (name, value) in ast::pattern_values(fields) {
+                    match value {
+                        Some(pat) => go(env, pat, or_patterns, in_or_pattern, variables),
+                        None => {
+                            let typ = typ
+                                .row_iter()
+                                .find(|f| f.name.name_eq(&name.value))
+                                .map(|f| f.typ.clone())
+                                .unwrap_or_else(Type::hole);
+                            add(
+                                variables,
+                                in_or_pattern,
+                                TypedIdent {
+                                    name: name.value.clone(),
+                                    typ,
+                                },
+                            )
+                        }
+                    }
+                }
+                if let Some(implicit_import) = implicit_import {
+                    add(
+                        variables,
+                        in_or_pattern,
+                        TypedIdent {
+                            name: implicit_import.value.clone(),
+                            typ: pattern.env_type_of(&env),
+                        },
+                    );
+                }
+            }
+            ast::Pattern::Or(ref patterns) => {
+                if or_patterns {
+                    // Every alternative binds the same variables
+                    go(env, &patterns[0], or_patterns, true, variables);
+                }
+            }
+            ast::Pattern::Literal(_) | ast::Pattern::Error => (),
+        }
+    }
+
+    go(env, pattern, or_patterns, !or_patterns, variables)
+}
+
+/// The number of elements an array pattern matches before and after its `..` (`suffix` is `None`
+/// if the pattern has no `..`)
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct ArrayShape {
+    prefix: usize,
+    suffix: Option<usize>,
+}
+
+impl ArrayShape {
+    fn of(pattern: &ast::Pattern<Symbol>) -> ArrayShape {
+        match *unwrap_as(pattern) {
+            ast::Pattern::Array {
+                ref elems,
+                rest: Some(rest),
+                ..
+            } => ArrayShape {
+                prefix: rest,
+                suffix: Some(elems.len() - rest - 1),
+            },
+            ast::Pattern::Array {
+                ref elems,
+                rest: None,
+                ..
+            } => ArrayShape {
+                prefix: elems.len(),
+                suffix: None,
+            },
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns the pattern of the `i`th element (skipping `..`) of the array pattern of `equation`
+fn array_elements<'p, 'ast>(
+    equation: &Equation<'_, 'p, 'ast>,
+    i: usize,
+) -> &'p SpannedPattern<'ast, Symbol> {
+    match *unwrap_as(&equation.patterns[0].value) {
+        ast::Pattern::Array {
+            ref elems, rest, ..
+        } => match rest {
+            Some(rest) if i >= rest => &elems[i + 1],
+            _ => &elems[i],
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// Returns the `..` pattern of the array pattern of `equation`
+fn array_rest<'p, 'ast>(
+    equation: &Equation<'_, 'p, 'ast>,
+) -> Option<&'p SpannedPattern<'ast, Symbol>> {
+    match *unwrap_as(&equation.patterns[0].value) {
+        ast::Pattern::Array {
+            ref elems, rest, ..
+        } => rest.map(|rest| &elems[rest]),
+        _ => unreachable!(),
+    }
+}
+
+fn is_wildcard(pattern: &SpannedPattern<Symbol>) -> bool {
+    match pattern.value {
+        ast::Pattern::Ident(ref id) => id.name.declared_name() == "_",
+        _ => false,
+    }
+}
+
 fn get_ident(pattern: &ast::Pattern<Symbol>) -> Option<TypedIdent<Symbol>> {
     match *pattern {
         ast::Pattern::Ident(ref id) => Some(id.clone()),
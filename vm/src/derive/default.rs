@@ -0,0 +1,89 @@
+use crate::base::{
+    ast::{self, Expr, ExprField, Pattern, TypeBinding, TypedIdent, ValueBinding},
+    pos,
+    symbol::{Symbol, Symbols},
+    types::{remove_forall, row_iter, Type},
+};
+
+use crate::macros::Error;
+
+use crate::derive::*;
+
+pub fn generate<'ast>(
+    mut arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> Result<ValueBinding<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+
+    let default_expr = match **remove_forall(bind.alias.value.unresolved_type()) {
+        Type::Record(ref row) => {
+            let fields = row_iter(row)
+                .map(|field| {
+                    // The default value would need to contain itself
+                    if is_self_type(&bind.alias.value.name, &field.typ) {
+                        return Err(Error::message(
+                            "Unable to derive Default for a recursive record",
+                        ));
+                    }
+                    Ok(ExprField {
+                        metadata: Default::default(),
+                        name: field.name.clone(),
+                        value: Some(ident(span, symbols.simple_symbol("default"))),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            pos::spanned(
+                span,
+                Expr::Record {
+                    typ: Type::hole(),
+                    types: &mut [],
+                    exprs: arena.alloc_extend(fields),
+                    base: None,
+                },
+            )
+        }
+        _ => {
+            return Err(Error::message(
+                "Default can only be derived for record types",
+            ))
+        }
+    };
+
+    let default_import =
+        arena.generate_import_(span, symbols, &[], &["default"], true, "std.default");
+
+    let default_record_expr = pos::spanned(
+        span,
+        Expr::Record {
+            typ: Type::hole(),
+            types: &mut [],
+            exprs: arena.alloc_extend(Some(ExprField {
+                metadata: Default::default(),
+                name: pos::spanned(span, symbols.simple_symbol("default")),
+                value: Some(default_expr),
+            })),
+            base: None,
+        },
+    );
+
+    let self_type = bind.alias.value.self_type(&mut arena);
+
+    Ok(ValueBinding {
+        name: pos::spanned(
+            span,
+            Pattern::Ident(TypedIdent::new(symbols.simple_symbol(format!(
+                "default_{}",
+                bind.alias.value.name.declared_name()
+            )))),
+        ),
+        args: &mut [],
+        expr: pos::spanned(
+            span,
+            Expr::let_binding(arena, default_import, default_record_expr),
+        ),
+        metadata: Default::default(),
+        typ: Some(binding_type(arena, symbols, "Default", self_type, bind)),
+        resolved_type: Type::hole(),
+    })
+}
@@ -66,6 +66,7 @@ pub fn generate<'ast>(
                     span,
                     Pattern::Ident(TypedIdent::new(symbols.simple_symbol("_"))),
                 ),
+                guard: None,
                 expr: ident(span, symbols.simple_symbol("False")),
             };
 
@@ -112,6 +113,7 @@ pub fn generate<'ast>(
                                 ]),
                             },
                         ),
+                        guard: None,
                         expr,
                     }
                 })
@@ -165,6 +167,7 @@ pub fn generate<'ast>(
                             typ: Type::hole(),
                         },
                     ),
+                    guard: None,
                     expr,
                 }]),
             )
@@ -0,0 +1,221 @@
+use crate::base::{
+    ast::{
+        self, Argument, AstType, Expr, ExprField, Pattern, SpannedExpr, TypeBinding, TypedIdent,
+        ValueBinding,
+    },
+    pos::{self, BytePos, Span},
+    symbol::{Symbol, Symbols},
+    types::{Generic, Type, TypeContext},
+};
+
+use crate::macros::Error;
+
+use crate::derive::*;
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Right,
+    Left,
+}
+
+struct Folder<'a, 'b, 'ast> {
+    arena: ast::ArenaRef<'b, 'ast, Symbol>,
+    symbols: &'a mut Symbols,
+    bind: &'a TypeBinding<'ast, Symbol>,
+    span: Span<BytePos>,
+    param: Symbol,
+    f: Symbol,
+    foldr_fn: Symbol,
+    foldl_fn: Symbol,
+}
+
+impl<'ast> Folder<'_, '_, 'ast> {
+    /// Returns an expression which folds every value of the parameter type in `expr` into `acc`
+    fn fold_field(
+        &mut self,
+        direction: Direction,
+        typ: &AstType<'ast, Symbol>,
+        expr: SpannedExpr<'ast, Symbol>,
+        acc: SpannedExpr<'ast, Symbol>,
+        depth: usize,
+    ) -> Result<SpannedExpr<'ast, Symbol>, Error> {
+        let span = self.span;
+        let (fold_fn, folder) = match param_field("Foldable", self.bind, &self.param, typ)? {
+            ParamField::Absent => return Ok(acc),
+            ParamField::Param => {
+                let args = match direction {
+                    Direction::Right => vec![expr, acc],
+                    Direction::Left => vec![acc, expr],
+                };
+                return Ok(self.arena.app(span, self.f.clone(), args));
+            }
+            ParamField::SelfType(inner) => {
+                let folder = self.folder(direction, inner, depth)?;
+                let fold_fn = match direction {
+                    Direction::Right => self.foldr_fn.clone(),
+                    Direction::Left => self.foldl_fn.clone(),
+                };
+                (fold_fn, folder)
+            }
+            ParamField::Container(inner) => {
+                let folder = self.folder(direction, inner, depth)?;
+                let fold_fn = match direction {
+                    Direction::Right => self.symbols.simple_symbol("foldr"),
+                    Direction::Left => self.symbols.simple_symbol("foldl"),
+                };
+                (fold_fn, folder)
+            }
+        };
+        Ok(self.arena.app(span, fold_fn, vec![folder, acc, expr]))
+    }
+
+    /// Returns a function which folds the values of the parameter type in a value of type `typ`
+    fn folder(
+        &mut self,
+        direction: Direction,
+        typ: &AstType<'ast, Symbol>,
+        depth: usize,
+    ) -> Result<SpannedExpr<'ast, Symbol>, Error> {
+        if let ParamField::Param = param_field("Foldable", self.bind, &self.param, typ)? {
+            return Ok(ident(self.span, self.f.clone()));
+        }
+        let y = Symbol::from(format!("y_{}", depth));
+        let acc = Symbol::from(format!("acc_{}", depth));
+        let body = self.fold_field(
+            direction,
+            typ,
+            ident(self.span, y.clone()),
+            ident(self.span, acc.clone()),
+            depth + 1,
+        )?;
+        let args = match direction {
+            Direction::Right => vec![y, acc],
+            Direction::Left => vec![acc, y],
+        };
+        Ok(self.arena.lambda(self.span, self.symbols, args, body))
+    }
+}
+
+pub fn generate<'ast>(
+    arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> Result<ValueBinding<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+
+    let param = last_param("Foldable", bind)?.id.clone();
+    let x = Symbol::from("x");
+    let f = Symbol::from("f");
+    let z = Symbol::from("z");
+    let foldr_fn = TypedIdent::new(symbols.simple_symbol("foldr_"));
+    let foldl_fn = TypedIdent::new(symbols.simple_symbol("foldl_"));
+
+    let mut folder = Folder {
+        arena,
+        symbols: &mut *symbols,
+        bind,
+        span,
+        param,
+        f: f.clone(),
+        foldr_fn: foldr_fn.name.clone(),
+        foldl_fn: foldl_fn.name.clone(),
+    };
+    let mut fold_expr = |direction| {
+        match_fields(arena, "Foldable", bind, &x, &mut |_, fields| {
+            let mut fold = |acc, field: &BoundField<'_, 'ast>| {
+                let expr = ident(span, field.ident.clone());
+                folder.fold_field(direction, field.typ, expr, acc, 0)
+            };
+            let z = ident(span, z.clone());
+            match direction {
+                Direction::Right => fields.iter().rev().try_fold(z, &mut fold),
+                Direction::Left => fields.iter().try_fold(z, &mut fold),
+            }
+        })
+    };
+    let foldr_expr = fold_expr(Direction::Right)?;
+    let foldl_expr = fold_expr(Direction::Left)?;
+
+    let foldable_import =
+        arena.generate_import(span, symbols, &[], &["foldr", "foldl"], "std.foldable");
+
+    // The parameter is quantified in the signature so it can't refer to the type's own parameter
+    let a = fresh_generic(symbols, bind, "a");
+    let b = fresh_generic(symbols, bind, "b");
+    let fold_type = |direction| {
+        let generic = |g: &Generic<Symbol>| arena.clone().generic(g.clone());
+        let f_args = match direction {
+            Direction::Right => vec![generic(&a), generic(&b)],
+            Direction::Left => vec![generic(&b), generic(&a)],
+        };
+        let f_type = arena.clone().function(f_args, generic(&b));
+        let self_type = partial_self_type(arena, bind, Some(generic(&a)));
+        arena
+            .clone()
+            .function(vec![f_type, generic(&b), self_type], generic(&b))
+    };
+
+    let fold_binding = |fold_fn: &TypedIdent<Symbol>, expr, typ| ValueBinding {
+        name: pos::spanned(span, Pattern::Ident(fold_fn.clone())),
+        args: arena.alloc_extend(
+            vec![f.clone(), z.clone(), x.clone()]
+                .into_iter()
+                .map(|arg| Argument::explicit(pos::spanned(span, TypedIdent::new(arg)))),
+        ),
+        expr: pos::spanned(span, expr),
+        metadata: Default::default(),
+        typ: Some(typ),
+        resolved_type: Type::hole(),
+    };
+    let bindings = vec![
+        fold_binding(&foldr_fn, foldr_expr, fold_type(Direction::Right)),
+        fold_binding(&foldl_fn, foldl_expr, fold_type(Direction::Left)),
+    ];
+
+    let foldable_record_expr = Expr::rec_let_bindings(
+        arena,
+        bindings,
+        pos::spanned(
+            span,
+            Expr::Record {
+                typ: Type::hole(),
+                types: &mut [],
+                exprs: arena.alloc_extend(vec![
+                    ExprField {
+                        metadata: Default::default(),
+                        name: pos::spanned(span, symbols.simple_symbol("foldr")),
+                        value: Some(ident(span, foldr_fn.name.clone())),
+                    },
+                    ExprField {
+                        metadata: Default::default(),
+                        name: pos::spanned(span, symbols.simple_symbol("foldl")),
+                        value: Some(ident(span, foldl_fn.name.clone())),
+                    },
+                ]),
+                base: None,
+            },
+        ),
+    );
+
+    Ok(ValueBinding {
+        name: pos::spanned(
+            span,
+            Pattern::Ident(TypedIdent::new(symbols.simple_symbol(format!(
+                "foldable_{}",
+                bind.alias.value.name.declared_name()
+            )))),
+        ),
+        args: &mut [],
+        expr: pos::spanned(
+            span,
+            Expr::let_binding(
+                arena,
+                foldable_import,
+                pos::spanned(span, foldable_record_expr),
+            ),
+        ),
+        metadata: Default::default(),
+        typ: Some(partial_binding_type(arena, symbols, "Foldable", bind)),
+        resolved_type: Type::hole(),
+    })
+}
@@ -0,0 +1,155 @@
+use crate::base::{
+    ast::{
+        self, Argument, AstType, Expr, ExprField, Pattern, SpannedExpr, TypeBinding, TypedIdent,
+        ValueBinding,
+    },
+    pos::{self, BytePos, Span},
+    symbol::{Symbol, Symbols},
+    types::{Generic, Type, TypeContext},
+};
+
+use crate::macros::Error;
+
+use crate::derive::*;
+
+struct Mapper<'a, 'b, 'ast> {
+    arena: ast::ArenaRef<'b, 'ast, Symbol>,
+    symbols: &'a mut Symbols,
+    bind: &'a TypeBinding<'ast, Symbol>,
+    span: Span<BytePos>,
+    param: Symbol,
+    f: Symbol,
+    map_fn: Symbol,
+}
+
+impl<'ast> Mapper<'_, '_, 'ast> {
+    /// Returns an expression which applies `f` to every value of the parameter type in `expr`
+    fn map_field(
+        &mut self,
+        typ: &AstType<'ast, Symbol>,
+        expr: SpannedExpr<'ast, Symbol>,
+        depth: usize,
+    ) -> Result<SpannedExpr<'ast, Symbol>, Error> {
+        let span = self.span;
+        Ok(match param_field("Functor", self.bind, &self.param, typ)? {
+            ParamField::Absent => expr,
+            ParamField::Param => self.arena.app(span, self.f.clone(), vec![expr]),
+            ParamField::SelfType(inner) => {
+                let mapper = self.mapper(inner, depth)?;
+                self.arena
+                    .app(span, self.map_fn.clone(), vec![mapper, expr])
+            }
+            ParamField::Container(inner) => {
+                let mapper = self.mapper(inner, depth)?;
+                let map = self.symbols.simple_symbol("map");
+                self.arena.app(span, map, vec![mapper, expr])
+            }
+        })
+    }
+
+    /// Returns a function which maps the values of the parameter type in a value of type `typ`
+    fn mapper(
+        &mut self,
+        typ: &AstType<'ast, Symbol>,
+        depth: usize,
+    ) -> Result<SpannedExpr<'ast, Symbol>, Error> {
+        if let ParamField::Param = param_field("Functor", self.bind, &self.param, typ)? {
+            return Ok(ident(self.span, self.f.clone()));
+        }
+        let y = Symbol::from(format!("y_{}", depth));
+        let body = self.map_field(typ, ident(self.span, y.clone()), depth + 1)?;
+        Ok(self.arena.lambda(self.span, self.symbols, Some(y), body))
+    }
+}
+
+pub fn generate<'ast>(
+    arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> Result<ValueBinding<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+
+    let param = last_param("Functor", bind)?.id.clone();
+    let x = Symbol::from("x");
+    let f = Symbol::from("f");
+    let map_fn = TypedIdent::new(symbols.simple_symbol("map_"));
+
+    let map_expr = {
+        let mut mapper = Mapper {
+            arena,
+            symbols: &mut *symbols,
+            bind,
+            span,
+            param,
+            f: f.clone(),
+            map_fn: map_fn.name.clone(),
+        };
+        match_fields(arena, "Functor", bind, &x, &mut |ctor, fields| {
+            let values = fields
+                .iter()
+                .map(|field| mapper.map_field(field.typ, ident(span, field.ident.clone()), 0))
+                .collect::<Result<_, _>>()?;
+            Ok(construct(arena, span, ctor, fields, values))
+        })?
+    };
+
+    let functor_import = arena.generate_import(span, symbols, &[], &["map"], "std.functor");
+
+    // The parameter is quantified in the signature so it can't refer to the type's own parameter
+    let a = fresh_generic(symbols, bind, "a");
+    let b = fresh_generic(symbols, bind, "b");
+    let map_type = {
+        let generic = |g: &Generic<Symbol>| arena.clone().generic(g.clone());
+        let f_type = arena.clone().function(vec![generic(&a)], generic(&b));
+        let from = partial_self_type(arena, bind, Some(generic(&a)));
+        let to = partial_self_type(arena, bind, Some(generic(&b)));
+        arena.clone().function(vec![f_type, from], to)
+    };
+
+    let map_record_expr = Expr::rec_let_bindings(
+        arena,
+        Some(ValueBinding {
+            name: pos::spanned(span, Pattern::Ident(map_fn.clone())),
+            args: arena.alloc_extend(
+                vec![f, x]
+                    .into_iter()
+                    .map(|arg| Argument::explicit(pos::spanned(span, TypedIdent::new(arg)))),
+            ),
+            expr: pos::spanned(span, map_expr),
+            metadata: Default::default(),
+            typ: Some(map_type),
+            resolved_type: Type::hole(),
+        }),
+        pos::spanned(
+            span,
+            Expr::Record {
+                typ: Type::hole(),
+                types: &mut [],
+                exprs: arena.alloc_extend(Some(ExprField {
+                    metadata: Default::default(),
+                    name: pos::spanned(span, symbols.simple_symbol("map")),
+                    value: Some(ident(span, map_fn.name.clone())),
+                })),
+                base: None,
+            },
+        ),
+    );
+
+    Ok(ValueBinding {
+        name: pos::spanned(
+            span,
+            Pattern::Ident(TypedIdent::new(symbols.simple_symbol(format!(
+                "functor_{}",
+                bind.alias.value.name.declared_name()
+            )))),
+        ),
+        args: &mut [],
+        expr: pos::spanned(
+            span,
+            Expr::let_binding(arena, functor_import, pos::spanned(span, map_record_expr)),
+        ),
+        metadata: Default::default(),
+        typ: Some(partial_binding_type(arena, symbols, "Functor", bind)),
+        resolved_type: Type::hole(),
+    })
+}
@@ -0,0 +1,167 @@
+use crate::base::{
+    ast::{
+        self, Alternative, Argument, Expr, ExprField, Literal, Pattern, TypeBinding, TypedIdent,
+        ValueBinding,
+    },
+    pos,
+    symbol::{Symbol, Symbols},
+    types::{ctor_args, remove_forall, row_iter, Type, TypeContext},
+};
+
+use crate::macros::Error;
+
+use crate::derive::*;
+
+pub fn generate<'ast>(
+    mut arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> Result<ValueBinding<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+
+    let x = Symbol::from("x");
+    let hash_fn = TypedIdent::new(symbols.simple_symbol("hash_"));
+
+    // Hashes each field in order, combining it with the hash of the previous fields
+    let mut combine_fields = |seed: i64, fields: &[(bool, TypedIdent<Symbol>)]| {
+        fields.iter().fold(
+            pos::spanned(span, Expr::Literal(Literal::Int(seed))),
+            |acc, &(self_type, ref field)| {
+                let hash_function = if self_type {
+                    hash_fn.name.clone()
+                } else {
+                    symbols.simple_symbol("hash")
+                };
+                let hash = arena.app(span, hash_function, vec![ident(span, field.name.clone())]);
+                arena.app(span, symbols.simple_symbol("combine"), vec![acc, hash])
+            },
+        )
+    };
+
+    let hash_expr = match **remove_forall(bind.alias.value.unresolved_type()) {
+        Type::Variant(ref variants) => {
+            let alts: Vec<_> = row_iter(variants)
+                .enumerate()
+                .map(|(tag, variant)| {
+                    let pattern_args: Vec<_> = ctor_args(&variant.typ)
+                        .enumerate()
+                        .map(|(i, field)| {
+                            (
+                                is_self_type(&bind.alias.value.name, field),
+                                TypedIdent::new(Symbol::from(format!("arg_{}", i))),
+                            )
+                        })
+                        .collect();
+
+                    let expr = combine_fields(tag as i64, &pattern_args);
+
+                    let pattern = pos::spanned(
+                        span,
+                        Pattern::Constructor(
+                            TypedIdent::new(variant.name.value.clone()),
+                            arena.alloc_extend(
+                                pattern_args
+                                    .into_iter()
+                                    .map(|(_, arg)| pos::spanned(span, Pattern::Ident(arg))),
+                            ),
+                        ),
+                    );
+                    Alternative {
+                        pattern,
+                        guard: None,
+                        expr,
+                    }
+                })
+                .collect();
+            Expr::Match(
+                arena.alloc(ident(span, x.clone())),
+                arena.alloc_extend(alts),
+            )
+        }
+        Type::Record(ref row) => {
+            let field_symbols: Vec<_> = row_iter(row)
+                .map(|field| {
+                    (
+                        is_self_type(&bind.alias.value.name, &field.typ),
+                        TypedIdent::new(Symbol::from(format!("{}", field.name.declared_name()))),
+                    )
+                })
+                .collect();
+
+            let expr = combine_fields(0, &field_symbols);
+
+            Expr::Match(
+                arena.alloc(ident(span, x.clone())),
+                arena.alloc_extend(Some(Alternative {
+                    pattern: arena.generate_record_pattern(
+                        span,
+                        row,
+                        field_symbols
+                            .into_iter()
+                            .map(|(_, field)| field)
+                            .collect::<Vec<_>>(),
+                    ),
+                    guard: None,
+                    expr,
+                })),
+            )
+        }
+        _ => return Err(Error::message("Unable to derive Hash for this type")),
+    };
+
+    let mut self_type = {
+        let mut arena = arena;
+        move || bind.alias.value.self_type(&mut arena)
+    };
+
+    let hash_import =
+        arena.generate_import_(span, symbols, &[], &["hash", "combine"], true, "std.hash");
+
+    let hash_binding = ValueBinding {
+        name: pos::spanned(span, Pattern::Ident(hash_fn.clone())),
+        args: arena.alloc_extend(Some(Argument::explicit(pos::spanned(
+            span,
+            TypedIdent::new(x.clone()),
+        )))),
+        expr: pos::spanned(span, hash_expr),
+        metadata: Default::default(),
+        typ: Some(arena.clone().function(vec![self_type()], arena.int())),
+        resolved_type: Type::hole(),
+    };
+
+    let hash_record_expr = Expr::rec_let_bindings(
+        arena,
+        Some(hash_binding),
+        pos::spanned(
+            span,
+            Expr::Record {
+                typ: Type::hole(),
+                types: &mut [],
+                exprs: arena.alloc_extend(Some(ExprField {
+                    metadata: Default::default(),
+                    name: pos::spanned(span, symbols.simple_symbol("hash")),
+                    value: Some(ident(span, hash_fn.name.clone())),
+                })),
+                base: None,
+            },
+        ),
+    );
+
+    Ok(ValueBinding {
+        name: pos::spanned(
+            span,
+            Pattern::Ident(TypedIdent::new(symbols.simple_symbol(format!(
+                "hash_{}",
+                bind.alias.value.name.declared_name()
+            )))),
+        ),
+        args: &mut [],
+        expr: pos::spanned(
+            span,
+            Expr::let_binding(arena, hash_import, pos::spanned(span, hash_record_expr)),
+        ),
+        metadata: Default::default(),
+        typ: Some(binding_type(arena, symbols, "Hash", self_type(), bind)),
+        resolved_type: Type::hole(),
+    })
+}
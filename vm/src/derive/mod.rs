@@ -3,18 +3,25 @@ use crate::base::{
         self, Argument, AstAlloc, AstType, Expr, Lambda, Literal, Pattern, PatternField,
         SpannedExpr, SpannedPattern, TypeBinding, TypedIdent, ValueBinding,
     },
-    metadata::Attribute,
+    kind::Kind,
+    metadata::{Attribute, BaseMetadata, Metadata},
     pos::{self, BytePos, Span},
     symbol::{Symbol, Symbols},
-    types::{row_iter, KindedIdent, Type, TypeContext},
+    types::{self, row_iter, Generic, KindedIdent, Type, TypeContext},
 };
 
 use crate::macros::Error;
 
+mod default;
 mod deserialize;
 mod eq;
+mod foldable;
+mod functor;
+mod hash;
+mod ord;
 mod serialize;
 mod show;
+mod traversable;
 
 pub fn generate<'ast>(
     arena: ast::ArenaRef<'_, 'ast, Symbol>,
@@ -22,14 +29,20 @@ pub fn generate<'ast>(
     derive: &Attribute,
     bind: &TypeBinding<'ast, Symbol>,
 ) -> Result<Vec<ValueBinding<'ast, Symbol>>, Error> {
-    match derive.arguments {
+    let mut bindings: Vec<_> = match derive.arguments {
         Some(ref args) => args
             .split(',')
             .map(|s| s.trim())
             .map(|arg| {
                 Ok(match arg {
                     "Eq" => eq::generate(arena, symbols, bind),
+                    "Ord" => ord::generate(arena, symbols, bind),
                     "Show" => show::generate(arena, symbols, bind),
+                    "Hash" => hash::generate(arena, symbols, bind),
+                    "Functor" => functor::generate(arena, symbols, bind),
+                    "Foldable" => foldable::generate(arena, symbols, bind),
+                    "Traversable" => traversable::generate(arena, symbols, bind),
+                    "Default" => default::generate(arena, symbols, bind),
                     "Deserialize" => deserialize::generate(arena, symbols, bind),
                     "Serialize" => serialize::generate(arena, symbols, bind),
                     _ => {
@@ -42,7 +55,21 @@ pub fn generate<'ast>(
             })
             .collect::<Result<_, _>>()?,
         _ => Err(Error::message("Invalid `derive` attribute")),
+    }?;
+
+    // Generated code should not report warnings such as unused variables
+    for binding in &mut bindings {
+        binding.metadata.merge(BaseMetadata {
+            metadata: Some(arena.alloc(Metadata {
+                attributes: vec![Attribute {
+                    name: "allow".into(),
+                    arguments: Some("warnings".into()),
+                }],
+                ..Metadata::default()
+            })),
+        });
     }
+    Ok(bindings)
 }
 
 impl<'ast> ArenaExt<'ast> for ast::ArenaRef<'_, 'ast, Symbol> {
@@ -211,6 +238,26 @@ pub trait ArenaExt<'ast>: Sized + Copy {
             .unwrap()
     }
 
+    fn lambda(
+        self,
+        span: Span<BytePos>,
+        symbols: &mut Symbols,
+        args: impl IntoIterator<Item = Symbol>,
+        body: SpannedExpr<'ast, Symbol>,
+    ) -> SpannedExpr<'ast, Symbol> {
+        pos::spanned(
+            span,
+            Expr::Lambda(Lambda {
+                args: self.alloc_extend(
+                    args.into_iter()
+                        .map(|id| Argument::explicit(pos::spanned(span, TypedIdent::new(id)))),
+                ),
+                body: self.alloc(body),
+                id: TypedIdent::new(symbols.simple_symbol("lambda")),
+            }),
+        )
+    }
+
     fn paren(
         self,
         span: Span<BytePos>,
@@ -307,3 +354,254 @@ fn binding_type<'ast>(
         ),
     )
 }
+
+/// How a field refers to the type parameter that `Functor`, `Foldable` and `Traversable` are
+/// derived over
+enum ParamField<'a, 'ast> {
+    /// The field does not contain the parameter
+    Absent,
+    /// The field is the parameter itself
+    Param,
+    /// The field is the derived type applied to the contained type
+    SelfType(&'a AstType<'ast, Symbol>),
+    /// The field is some other type applied to the contained type
+    Container(&'a AstType<'ast, Symbol>),
+}
+
+/// Returns the last parameter of the type which is the one that `Functor`, `Foldable` and
+/// `Traversable` are derived over
+fn last_param<'b>(
+    derive_name: &str,
+    bind: &'b TypeBinding<'_, Symbol>,
+) -> Result<&'b Generic<Symbol>, Error> {
+    bind.alias.value.params().last().ok_or_else(|| {
+        Error::message(format!(
+            "Unable to derive {} for a type without type parameters",
+            derive_name
+        ))
+    })
+}
+
+fn contains_generic(param: &Symbol, typ: &AstType<Symbol>) -> bool {
+    match **typ {
+        Type::Generic(ref gen) => gen.id == *param,
+        Type::App(ref f, ref args) => {
+            contains_generic(param, f) || args.iter().any(|arg| contains_generic(param, arg))
+        }
+        Type::Function(_, ref arg, ref ret) => {
+            contains_generic(param, arg) || contains_generic(param, ret)
+        }
+        Type::Record(ref row) | Type::Variant(ref row) | Type::Effect(ref row) => {
+            row_iter(row).any(|field| contains_generic(param, &field.typ))
+        }
+        Type::Forall(_, ref typ) => contains_generic(param, typ),
+        _ => false,
+    }
+}
+
+fn param_field<'a, 'ast>(
+    derive_name: &str,
+    bind: &TypeBinding<'ast, Symbol>,
+    param: &Symbol,
+    typ: &'a AstType<'ast, Symbol>,
+) -> Result<ParamField<'a, 'ast>, Error> {
+    if !contains_generic(param, typ) {
+        return Ok(ParamField::Absent);
+    }
+    match **typ {
+        Type::Generic(ref gen) if gen.id == *param => return Ok(ParamField::Param),
+        Type::App(ref f, ref args) => {
+            if let Some((last, init)) = args.split_last() {
+                if !contains_generic(param, f)
+                    && init.iter().all(|arg| !contains_generic(param, arg))
+                {
+                    return Ok(if is_self_type(&bind.alias.value.name, f) {
+                        ParamField::SelfType(last)
+                    } else {
+                        ParamField::Container(last)
+                    });
+                }
+            }
+        }
+        _ => (),
+    }
+    Err(Error::message(format!(
+        "Unable to derive {} for a type where the last type parameter is used in `{}`",
+        derive_name, typ
+    )))
+}
+
+/// Returns the derived type applied to all but its last parameter, followed by `last` if it is
+/// given.
+fn partial_self_type<'ast>(
+    mut arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    bind: &TypeBinding<'ast, Symbol>,
+    last: Option<AstType<'ast, Symbol>>,
+) -> AstType<'ast, Symbol> {
+    let alias = &bind.alias.value;
+    let f = arena.ident(KindedIdent {
+        name: alias.name.clone(),
+        typ: alias.kind(&Default::default()).into_owned(),
+    });
+    let params = alias.params();
+    let args: Vec<_> = params[..params.len().saturating_sub(1)]
+        .iter()
+        .cloned()
+        .map(|g| arena.generic(g))
+        .chain(last)
+        .collect();
+    if args.is_empty() {
+        f
+    } else {
+        let args = arena.clone().alloc_extend(args);
+        TypeContext::app(&mut arena, f, args)
+    }
+}
+
+/// Returns the type of a derived `derive_type_name` binding for a type constructor, such as
+/// `Functor (Tree)`
+fn partial_binding_type<'ast>(
+    mut arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    derive_type_name: &str,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> AstType<'ast, Symbol> {
+    let derive_type = arena.ident(KindedIdent::new(symbols.simple_symbol(derive_type_name)));
+    let self_type = partial_self_type(arena, bind, None);
+    let args = arena.clone().alloc_extend(Some(self_type));
+    TypeContext::app(&mut arena, derive_type, args)
+}
+
+/// Creates a type variable which is not one of the parameters of the derived type
+fn fresh_generic(symbols: &mut Symbols, bind: &TypeBinding<Symbol>, name: &str) -> Generic<Symbol> {
+    let mut name = name.to_string();
+    while bind
+        .alias
+        .value
+        .params()
+        .iter()
+        .any(|g| g.id.declared_name() == name)
+    {
+        name.push('_');
+    }
+    Generic::new(symbols.simple_symbol(name), Kind::hole())
+}
+
+/// A field of a constructor or record which has been bound by `match_fields`
+struct BoundField<'a, 'ast> {
+    /// The name of the field if it belongs to a record
+    name: Option<Symbol>,
+    typ: &'a AstType<'ast, Symbol>,
+    ident: Symbol,
+}
+
+/// Generates the expression of a `match_fields` alternative
+type AlternativeExpr<'a, 'b, 'ast> = dyn FnMut(Option<&Symbol>, &[BoundField<'a, 'ast>]) -> Result<SpannedExpr<'ast, Symbol>, Error>
+    + 'b;
+
+/// Generates `match x with` with an alternative for each constructor of the derived type (or a
+/// single alternative binding each field of a record). `expr` generates the expression of each
+/// alternative from the constructor name and its bound fields.
+fn match_fields<'a, 'ast>(
+    arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    derive_name: &str,
+    bind: &'a TypeBinding<'ast, Symbol>,
+    x: &Symbol,
+    expr: &mut AlternativeExpr<'a, '_, 'ast>,
+) -> Result<Expr<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+    let alts = match **types::remove_forall(bind.alias.value.unresolved_type()) {
+        Type::Variant(ref variants) => row_iter(variants)
+            .map(|variant| {
+                let fields: Vec<_> = types::ctor_args(&variant.typ)
+                    .enumerate()
+                    .map(|(i, typ)| BoundField {
+                        name: None,
+                        typ,
+                        ident: Symbol::from(format!("arg_{}", i)),
+                    })
+                    .collect();
+                let expr = expr(Some(&variant.name.value), &fields)?;
+                let pattern = pos::spanned(
+                    span,
+                    Pattern::Constructor(
+                        TypedIdent::new(variant.name.value.clone()),
+                        arena.alloc_extend(fields.into_iter().map(|field| {
+                            pos::spanned(span, Pattern::Ident(TypedIdent::new(field.ident)))
+                        })),
+                    ),
+                );
+                Ok(ast::Alternative {
+                    pattern,
+                    guard: None,
+                    expr,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?,
+        Type::Record(ref row) => {
+            let fields: Vec<_> = row_iter(row)
+                .map(|field| BoundField {
+                    name: Some(field.name.value.clone()),
+                    typ: &field.typ,
+                    ident: Symbol::from(field.name.declared_name()),
+                })
+                .collect();
+            let expr = expr(None, &fields)?;
+            let pattern = arena.generate_record_pattern(
+                span,
+                row,
+                fields
+                    .iter()
+                    .map(|field| TypedIdent::new(field.ident.clone()))
+                    .collect::<Vec<_>>(),
+            );
+            vec![ast::Alternative {
+                pattern,
+                guard: None,
+                expr,
+            }]
+        }
+        _ => {
+            return Err(Error::message(format!(
+                "Unable to derive {} for this type",
+                derive_name
+            )))
+        }
+    };
+    Ok(Expr::Match(
+        arena.alloc(ident(span, x.clone())),
+        arena.alloc_extend(alts),
+    ))
+}
+
+/// Constructs a value of the derived type from `values`, using the constructor `ctor` or, if it
+/// is `None`, a record with the names of `fields`
+fn construct<'ast>(
+    arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    span: Span<BytePos>,
+    ctor: Option<&Symbol>,
+    fields: &[BoundField<'_, 'ast>],
+    values: Vec<SpannedExpr<'ast, Symbol>>,
+) -> SpannedExpr<'ast, Symbol> {
+    match ctor {
+        Some(ctor) => arena.app(span, ctor.clone(), values),
+        None => pos::spanned(
+            span,
+            Expr::Record {
+                typ: Type::hole(),
+                types: &mut [],
+                exprs: arena.alloc_extend(fields.iter().zip(values).map(|(field, value)| {
+                    ast::ExprField {
+                        metadata: Default::default(),
+                        name: pos::spanned(
+                            span,
+                            field.name.clone().expect("Record field without a name"),
+                        ),
+                        value: Some(value),
+                    }
+                })),
+                base: None,
+            },
+        ),
+    }
+}
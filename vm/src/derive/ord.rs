@@ -0,0 +1,342 @@
+use crate::base::{
+    ast::{
+        self, Alternative, Argument, Expr, ExprField, Pattern, SpannedExpr, TypeBinding,
+        TypedIdent, ValueBinding,
+    },
+    pos::{self, BytePos, Span},
+    symbol::{Symbol, Symbols},
+    types::{ctor_args, remove_forall, row_iter, Type, TypeContext},
+};
+
+use crate::macros::Error;
+
+use crate::derive::*;
+
+/// `match scrutinee with | EQ -> if_eq | ordering -> ordering`, or `if_not_eq` instead of `ordering`
+fn match_ordering<'ast>(
+    arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    span: Span<BytePos>,
+    scrutinee: SpannedExpr<'ast, Symbol>,
+    if_eq: SpannedExpr<'ast, Symbol>,
+    if_not_eq: Option<SpannedExpr<'ast, Symbol>>,
+) -> SpannedExpr<'ast, Symbol> {
+    let ordering = TypedIdent::new(Symbol::from("ordering"));
+    pos::spanned(
+        span,
+        Expr::Match(
+            arena.alloc(scrutinee),
+            arena.alloc_extend(vec![
+                Alternative {
+                    pattern: pos::spanned(
+                        span,
+                        Pattern::Constructor(TypedIdent::new(symbols.simple_symbol("EQ")), &mut []),
+                    ),
+                    guard: None,
+                    expr: if_eq,
+                },
+                Alternative {
+                    pattern: pos::spanned(span, Pattern::Ident(ordering.clone())),
+                    guard: None,
+                    expr: if_not_eq.unwrap_or_else(|| ident(span, ordering.name)),
+                },
+            ]),
+        ),
+    )
+}
+
+pub fn generate<'ast>(
+    mut arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> Result<ValueBinding<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+
+    let compare_fn = TypedIdent::new(symbols.simple_symbol("compare_"));
+    let tag_fn = TypedIdent::new(symbols.simple_symbol("tag_"));
+    let l = Symbol::from("l");
+    let r = Symbol::from("r");
+
+    let matcher = arena.alloc(pos::spanned(
+        span,
+        Expr::Tuple {
+            typ: Type::hole(),
+            elems: arena.alloc_extend(vec![ident(span, l.clone()), ident(span, r.clone())]),
+        },
+    ));
+
+    // Compares each field in order, stopping at the first field which is not equal
+    let compare_fields =
+        |symbols: &mut Symbols, fields: &[(bool, TypedIdent<Symbol>, TypedIdent<Symbol>)]| {
+            fields
+                .iter()
+                .rev()
+                .fold(None, |acc, &(self_type, ref l, ref r)| {
+                    let compare_function = if self_type {
+                        compare_fn.name.clone()
+                    } else {
+                        symbols.simple_symbol("compare")
+                    };
+                    let compare = arena.app(
+                        span,
+                        compare_function,
+                        vec![ident(span, l.name.clone()), ident(span, r.name.clone())],
+                    );
+                    Some(match acc {
+                        Some(acc) => match_ordering(arena, symbols, span, compare, acc, None),
+                        None => compare,
+                    })
+                })
+                .unwrap_or_else(|| ident(span, symbols.simple_symbol("EQ")))
+        };
+
+    let mut tag_binding = None;
+
+    let comparison_expr = match **remove_forall(bind.alias.value.unresolved_type()) {
+        Type::Variant(ref variants) => {
+            let ctor_pattern = |ctor: &Symbol, pattern_args: Vec<_>| {
+                pos::spanned(
+                    span,
+                    Pattern::Constructor(
+                        TypedIdent::new(ctor.clone()),
+                        arena.alloc_extend(
+                            pattern_args
+                                .into_iter()
+                                .map(|arg| pos::spanned(span, Pattern::Ident(arg))),
+                        ),
+                    ),
+                )
+            };
+
+            // Values with different constructors are ordered by the order of the constructors
+            let x = Symbol::from("x");
+            let tag_alts: Vec<_> = row_iter(variants)
+                .enumerate()
+                .map(|(i, variant)| Alternative {
+                    pattern: ctor_pattern(
+                        &variant.name.value,
+                        ctor_args(&variant.typ)
+                            .map(|_| TypedIdent::new(symbols.simple_symbol("_")))
+                            .collect(),
+                    ),
+                    guard: None,
+                    expr: pos::spanned(span, Expr::Literal(ast::Literal::Int(i as i64))),
+                })
+                .collect();
+            tag_binding = Some(ValueBinding {
+                name: pos::spanned(span, Pattern::Ident(tag_fn.clone())),
+                args: arena.alloc_extend(Some(Argument::explicit(pos::spanned(
+                    span,
+                    TypedIdent::new(x.clone()),
+                )))),
+                expr: pos::spanned(
+                    span,
+                    Expr::Match(
+                        arena.alloc(ident(span, x.clone())),
+                        arena.alloc_extend(tag_alts),
+                    ),
+                ),
+                metadata: Default::default(),
+                typ: None,
+                resolved_type: Type::hole(),
+            });
+
+            let catch_all_alternative = Alternative {
+                pattern: pos::spanned(
+                    span,
+                    Pattern::Ident(TypedIdent::new(symbols.simple_symbol("_"))),
+                ),
+                guard: None,
+                expr: pos::spanned(
+                    span,
+                    Expr::IfElse(
+                        arena.alloc(arena.infix(
+                            span,
+                            arena.app(span, tag_fn.name.clone(), vec![ident(span, l.clone())]),
+                            symbols.simple_symbol("#Int<"),
+                            arena.app(span, tag_fn.name.clone(), vec![ident(span, r.clone())]),
+                        )),
+                        arena.alloc(ident(span, symbols.simple_symbol("LT"))),
+                        arena.alloc(ident(span, symbols.simple_symbol("GT"))),
+                    ),
+                ),
+            };
+
+            let alts: Vec<_> = row_iter(variants)
+                .map(|variant| {
+                    let fields: Vec<_> = ctor_args(&variant.typ)
+                        .enumerate()
+                        .map(|(i, field)| {
+                            (
+                                is_self_type(&bind.alias.value.name, field),
+                                TypedIdent::new(Symbol::from(format!("arg_l{}", i))),
+                                TypedIdent::new(Symbol::from(format!("arg_r{}", i))),
+                            )
+                        })
+                        .collect();
+
+                    let expr = compare_fields(symbols, &fields);
+
+                    let (l_args, r_args) = fields.into_iter().map(|(_, l, r)| (l, r)).unzip();
+                    Alternative {
+                        pattern: pos::spanned(
+                            span,
+                            Pattern::Tuple {
+                                typ: Type::hole(),
+                                elems: arena.alloc_extend(vec![
+                                    ctor_pattern(&variant.name.value, l_args),
+                                    ctor_pattern(&variant.name.value, r_args),
+                                ]),
+                            },
+                        ),
+                        guard: None,
+                        expr,
+                    }
+                })
+                .chain(Some(catch_all_alternative))
+                .collect();
+            Expr::Match(matcher, arena.alloc_extend(alts))
+        }
+        Type::Record(ref row) => {
+            let fields: Vec<_> = row_iter(row)
+                .map(|field| {
+                    let name = field.name.declared_name();
+                    (
+                        is_self_type(&bind.alias.value.name, &field.typ),
+                        TypedIdent::new(Symbol::from(format!("{}_l", name))),
+                        TypedIdent::new(Symbol::from(format!("{}_r", name))),
+                    )
+                })
+                .collect();
+
+            let expr = compare_fields(symbols, &fields);
+
+            let (l_symbols, r_symbols): (Vec<_>, Vec<_>) =
+                fields.into_iter().map(|(_, l, r)| (l, r)).unzip();
+            Expr::Match(
+                matcher,
+                arena.alloc_extend(vec![Alternative {
+                    pattern: pos::spanned(
+                        span,
+                        Pattern::Tuple {
+                            elems: arena.alloc_extend(vec![
+                                arena.generate_record_pattern(span, row, l_symbols),
+                                arena.generate_record_pattern(span, row, r_symbols),
+                            ]),
+                            typ: Type::hole(),
+                        },
+                    ),
+                    guard: None,
+                    expr,
+                }]),
+            )
+        }
+        _ => return Err(Error::message("Unable to derive Ord for this type")),
+    };
+
+    let mut self_type = {
+        let mut arena = arena;
+        move || bind.alias.value.self_type(&mut arena)
+    };
+
+    let cmp_import = arena.generate_import(span, symbols, &["Ordering"], &["compare"], "std.cmp");
+
+    let compare_binding = ValueBinding {
+        name: pos::spanned(span, Pattern::Ident(compare_fn.clone())),
+        args: arena.alloc_extend(
+            [l.clone(), r.clone()]
+                .iter()
+                .map(|arg| Argument::explicit(pos::spanned(span, TypedIdent::new(arg.clone())))),
+        ),
+        expr: pos::spanned(span, comparison_expr),
+        metadata: Default::default(),
+        typ: Some(
+            arena
+                .clone()
+                .function(vec![self_type(), self_type()], arena.hole()),
+        ),
+        resolved_type: Type::hole(),
+    };
+
+    // `Ord` contains an `Eq` implementation which is derived from `compare`
+    let eq_expr = {
+        let true_ = ident(span, symbols.simple_symbol("True"));
+        let false_ = ident(span, symbols.simple_symbol("False"));
+        let eq_body = match_ordering(
+            arena,
+            symbols,
+            span,
+            arena.app(
+                span,
+                compare_fn.name.clone(),
+                vec![ident(span, l.clone()), ident(span, r.clone())],
+            ),
+            true_,
+            Some(false_),
+        );
+        pos::spanned(
+            span,
+            Expr::Record {
+                typ: Type::hole(),
+                types: &mut [],
+                exprs: arena.alloc_extend(Some(ExprField {
+                    metadata: Default::default(),
+                    name: pos::spanned(span, symbols.simple_symbol("==")),
+                    value: Some(arena.lambda(span, symbols, vec![l.clone(), r.clone()], eq_body)),
+                })),
+                base: None,
+            },
+        )
+    };
+
+    let ord_record_expr = pos::spanned(
+        span,
+        Expr::rec_let_bindings(
+            arena,
+            Some(compare_binding),
+            pos::spanned(
+                span,
+                Expr::Record {
+                    typ: Type::hole(),
+                    types: &mut [],
+                    exprs: arena.alloc_extend(vec![
+                        ExprField {
+                            metadata: Default::default(),
+                            name: pos::spanned(span, symbols.simple_symbol("eq")),
+                            value: Some(eq_expr),
+                        },
+                        ExprField {
+                            metadata: Default::default(),
+                            name: pos::spanned(span, symbols.simple_symbol("compare")),
+                            value: Some(ident(span, compare_fn.name.clone())),
+                        },
+                    ]),
+                    base: None,
+                },
+            ),
+        ),
+    );
+
+    let ord_expr = Some(cmp_import)
+        .into_iter()
+        .chain(tag_binding)
+        .rev()
+        .fold(ord_record_expr, |expr, bind| {
+            pos::spanned(span, Expr::let_binding(arena, bind, expr))
+        });
+
+    Ok(ValueBinding {
+        name: pos::spanned(
+            span,
+            Pattern::Ident(TypedIdent::new(symbols.simple_symbol(format!(
+                "ord_{}",
+                bind.alias.value.name.declared_name()
+            )))),
+        ),
+        args: &mut [],
+        expr: ord_expr,
+        metadata: Default::default(),
+        typ: Some(binding_type(arena, symbols, "Ord", self_type(), bind)),
+        resolved_type: Type::hole(),
+    })
+}
@@ -0,0 +1,243 @@
+use crate::base::{
+    ast::{
+        self, Argument, AstType, Expr, ExprField, Pattern, SpannedExpr, TypeBinding, TypedIdent,
+        ValueBinding,
+    },
+    pos::{self, BytePos, Span},
+    symbol::{Symbol, Symbols},
+    types::{Generic, Type, TypeContext},
+};
+
+use crate::macros::Error;
+
+use crate::derive::*;
+
+struct Traverser<'a, 'b, 'ast> {
+    arena: ast::ArenaRef<'b, 'ast, Symbol>,
+    symbols: &'a mut Symbols,
+    bind: &'a TypeBinding<'ast, Symbol>,
+    span: Span<BytePos>,
+    param: Symbol,
+    app: Symbol,
+    f: Symbol,
+    traverse_fn: Symbol,
+}
+
+impl<'ast> Traverser<'_, '_, 'ast> {
+    /// Calls the `field` function of the `Applicative` passed to the derived `traverse`
+    fn applicative(
+        &mut self,
+        field: &str,
+        args: Vec<SpannedExpr<'ast, Symbol>>,
+    ) -> SpannedExpr<'ast, Symbol> {
+        let span = self.span;
+        let func = pos::spanned(
+            span,
+            Expr::Projection(
+                self.arena.alloc(ident(span, self.app.clone())),
+                self.symbols.simple_symbol(field),
+                Type::hole(),
+            ),
+        );
+        pos::spanned(
+            span,
+            Expr::App {
+                func: self.arena.alloc(func),
+                implicit_args: &mut [],
+                args: self.arena.alloc_extend(args),
+            },
+        )
+    }
+
+    /// Returns an action which runs `f` on every value of the parameter type in `expr`
+    fn traverse_field(
+        &mut self,
+        typ: &AstType<'ast, Symbol>,
+        expr: SpannedExpr<'ast, Symbol>,
+        depth: usize,
+    ) -> Result<SpannedExpr<'ast, Symbol>, Error> {
+        let span = self.span;
+        Ok(
+            match param_field("Traversable", self.bind, &self.param, typ)? {
+                ParamField::Absent => self.applicative("wrap", vec![expr]),
+                ParamField::Param => self.arena.app(span, self.f.clone(), vec![expr]),
+                ParamField::SelfType(inner) => {
+                    let traverser = self.traverser(inner, depth)?;
+                    let app = ident(span, self.app.clone());
+                    self.arena
+                        .app(span, self.traverse_fn.clone(), vec![app, traverser, expr])
+                }
+                ParamField::Container(inner) => {
+                    let traverser = self.traverser(inner, depth)?;
+                    let traverse = self.symbols.simple_symbol("traverse");
+                    self.arena.app(span, traverse, vec![traverser, expr])
+                }
+            },
+        )
+    }
+
+    /// Returns a function which traverses the values of the parameter type in a value of type
+    /// `typ`
+    fn traverser(
+        &mut self,
+        typ: &AstType<'ast, Symbol>,
+        depth: usize,
+    ) -> Result<SpannedExpr<'ast, Symbol>, Error> {
+        if let ParamField::Param = param_field("Traversable", self.bind, &self.param, typ)? {
+            return Ok(ident(self.span, self.f.clone()));
+        }
+        let y = Symbol::from(format!("y_{}", depth));
+        let body = self.traverse_field(typ, ident(self.span, y.clone()), depth + 1)?;
+        Ok(self.arena.lambda(self.span, self.symbols, Some(y), body))
+    }
+}
+
+pub fn generate<'ast>(
+    arena: ast::ArenaRef<'_, 'ast, Symbol>,
+    symbols: &mut Symbols,
+    bind: &TypeBinding<'ast, Symbol>,
+) -> Result<ValueBinding<'ast, Symbol>, Error> {
+    let span = bind.name.span;
+
+    let param = last_param("Traversable", bind)?.id.clone();
+    let x = Symbol::from("x");
+    let app = Symbol::from("app");
+    let f = Symbol::from("f");
+    let traverse_fn = TypedIdent::new(symbols.simple_symbol("traverse_"));
+
+    // `Traversable` contains the `Functor` and `Foldable` of the type so derive those as well
+    let functor_binding = functor::generate(arena, symbols, bind)?;
+    let foldable_binding = foldable::generate(arena, symbols, bind)?;
+
+    let traverse_expr = {
+        let mut traverser = Traverser {
+            arena,
+            symbols: &mut *symbols,
+            bind,
+            span,
+            param,
+            app: app.clone(),
+            f: f.clone(),
+            traverse_fn: traverse_fn.name.clone(),
+        };
+        match_fields(arena, "Traversable", bind, &x, &mut |ctor, fields| {
+            let field_symbols: Vec<_> = (0..fields.len())
+                .map(|i| Symbol::from(format!("field_{}", i)))
+                .collect();
+            let constructed = construct(
+                arena,
+                span,
+                ctor,
+                fields,
+                field_symbols
+                    .iter()
+                    .map(|symbol| ident(span, symbol.clone()))
+                    .collect(),
+            );
+            let constructor = if fields.is_empty() {
+                constructed
+            } else {
+                arena.lambda(span, traverser.symbols, field_symbols, constructed)
+            };
+            let wrapped = traverser.applicative("wrap", vec![constructor]);
+            fields.iter().try_fold(wrapped, |acc, field| {
+                let action =
+                    traverser.traverse_field(field.typ, ident(span, field.ident.clone()), 0)?;
+                Ok(traverser.applicative("apply", vec![acc, action]))
+            })
+        })?
+    };
+
+    let imports = vec![
+        arena.generate_import(span, symbols, &["Functor"], &[], "std.functor"),
+        arena.generate_import(span, symbols, &["Foldable"], &[], "std.foldable"),
+        arena.generate_import(span, symbols, &[], &["traverse"], "std.traversable"),
+    ];
+
+    // The parameter is quantified in the signature so it can't refer to the type's own parameter
+    let a = fresh_generic(symbols, bind, "a");
+    let b = fresh_generic(symbols, bind, "b");
+    let m = fresh_generic(symbols, bind, "m");
+    let traverse_type = {
+        let generic = |g: &Generic<Symbol>| arena.clone().generic(g.clone());
+        let wrap_m = |typ| {
+            let args = arena.clone().alloc_extend(Some(typ));
+            TypeContext::app(&mut arena.clone(), generic(&m), args)
+        };
+        let applicative = {
+            let applicative = arena
+                .clone()
+                .ident(KindedIdent::new(symbols.simple_symbol("Applicative")));
+            let args = arena.clone().alloc_extend(Some(generic(&m)));
+            TypeContext::app(&mut arena.clone(), applicative, args)
+        };
+        let f_type = arena
+            .clone()
+            .function(vec![generic(&a)], wrap_m(generic(&b)));
+        let from = partial_self_type(arena, bind, Some(generic(&a)));
+        let to = wrap_m(partial_self_type(arena, bind, Some(generic(&b))));
+        arena.clone().function(vec![applicative, f_type, from], to)
+    };
+
+    let type_name = bind.alias.value.name.declared_name();
+    let functor = symbols.simple_symbol(format!("functor_{}", type_name));
+    let foldable = symbols.simple_symbol(format!("foldable_{}", type_name));
+    let field = |symbols: &mut Symbols, name: &str, value: &Symbol| ExprField {
+        metadata: Default::default(),
+        name: pos::spanned(span, symbols.simple_symbol(name)),
+        value: Some(ident(span, value.clone())),
+    };
+    let traversable_record = pos::spanned(
+        span,
+        Expr::Record {
+            typ: Type::hole(),
+            types: &mut [],
+            exprs: arena.alloc_extend(vec![
+                field(symbols, "functor", &functor),
+                field(symbols, "foldable", &foldable),
+                field(symbols, "traverse", &traverse_fn.name),
+            ]),
+            base: None,
+        },
+    );
+
+    let traverse_binding = ValueBinding {
+        name: pos::spanned(span, Pattern::Ident(traverse_fn.clone())),
+        args: arena.alloc_extend(
+            vec![app, f, x]
+                .into_iter()
+                .map(|arg| Argument::explicit(pos::spanned(span, TypedIdent::new(arg)))),
+        ),
+        expr: pos::spanned(span, traverse_expr),
+        metadata: Default::default(),
+        typ: Some(traverse_type),
+        resolved_type: Type::hole(),
+    };
+    let traversable_expr = pos::spanned(
+        span,
+        Expr::rec_let_bindings(arena, Some(traverse_binding), traversable_record),
+    );
+
+    let traversable_expr = imports
+        .into_iter()
+        .chain(vec![functor_binding, foldable_binding])
+        .rev()
+        .fold(traversable_expr, |expr, bind| {
+            pos::spanned(span, Expr::let_binding(arena, bind, expr))
+        });
+
+    Ok(ValueBinding {
+        name: pos::spanned(
+            span,
+            Pattern::Ident(TypedIdent::new(symbols.simple_symbol(format!(
+                "traversable_{}",
+                bind.alias.value.name.declared_name()
+            )))),
+        ),
+        args: &mut [],
+        expr: traversable_expr,
+        metadata: Default::default(),
+        typ: Some(partial_binding_type(arena, symbols, "Traversable", bind)),
+        resolved_type: Type::hole(),
+    })
+}
@@ -225,6 +225,9 @@ pub struct Gc {
     values: Option<AllocPtr>,
     /// How many bytes which is currently allocated
     allocated_memory: usize,
+    /// How many bytes have been allocated in total, including those which have been collected
+    #[cfg_attr(feature = "serde_derive", serde(skip))]
+    total_allocated_memory: u64,
     /// How many bytes this garbage collector can allocate before a collection is run
     collect_limit: usize,
     /// The maximum number of bytes this garbage collector may contain
@@ -1053,6 +1056,7 @@ impl Gc {
         Gc {
             values: None,
             allocated_memory: 0,
+            total_allocated_memory: 0,
             collect_limit: 100,
             memory_limit: memory_limit,
             type_infos: FnvMap::default(),
@@ -1066,6 +1070,10 @@ impl Gc {
         self.allocated_memory
     }
 
+    pub fn total_allocated_memory(&self) -> u64 {
+        self.total_allocated_memory
+    }
+
     pub fn set_memory_limit(&mut self, memory_limit: usize) {
         self.memory_limit = memory_limit;
     }
@@ -1228,6 +1236,7 @@ impl Gc {
         let mut ptr = AllocPtr::new::<D::Value>(type_info, size);
         ptr.next = self.values.take();
         self.allocated_memory += ptr.size();
+        self.total_allocated_memory += ptr.size() as u64;
         unsafe {
             let p: *mut D::Value = D::Value::make_ptr(&def, ptr.value());
             let ret: *const D::Value = &*def.initialize(WriteOnly::new(p));
@@ -57,8 +57,11 @@ pub mod dynamic;
 pub mod lazy;
 pub mod macros;
 pub mod primitives;
+pub mod profiler;
 pub mod reference;
+pub mod st_array;
 pub mod stack;
+pub mod task;
 pub mod thread;
 pub mod types;
 pub mod vm;
@@ -188,6 +191,9 @@ quick_error! {
         Interrupted {
             display("Thread was interrupted")
         }
+        OutOfFuel {
+            display("Thread is out of fuel")
+        }
         Panic(err: String, stacktrace: Option<Stacktrace>) {
             display("{}", Panic { err, stacktrace })
         }
@@ -203,7 +203,9 @@ impl Error {
 
         impl AsDiagnostic for StringError {
             fn as_diagnostic(&self, _map: &base::source::CodeMap) -> Diagnostic<FileId> {
-                Diagnostic::error().with_message(self.to_string())
+                Diagnostic::error()
+                    .with_message(self.to_string())
+                    .with_code(base::error_code::E0401)
             }
         }
 
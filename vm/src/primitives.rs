@@ -21,7 +21,7 @@ use crate::{
     gc::{DataDef, Trace, WriteOnly},
     stack::{ExternState, StackFrame},
     types::VmInt,
-    value::{GcStr, Repr, ValueArray},
+    value::{ArraySliceDef, GcStr, Repr, ValueArray},
     vm::{Status, Thread},
     Error, ExternModule, Result, Variants,
 };
@@ -65,38 +65,8 @@ pub mod array {
             )));
         }
 
-        #[derive(Trace)]
-        #[gluon(gluon_vm)]
-        struct Slice<'a> {
-            start: usize,
-            end: usize,
-            array: &'a ValueArray,
-        }
-
-        unsafe impl<'a> DataDef for Slice<'a> {
-            type Value = ValueArray;
-
-            fn size(&self) -> usize {
-                ValueArray::size_of(self.array.repr(), self.end - self.start)
-            }
-
-            fn initialize<'w>(self, mut result: WriteOnly<'w, ValueArray>) -> &'w mut ValueArray {
-                unsafe {
-                    let result = &mut *result.as_mut_ptr();
-                    result.set_repr(self.array.repr());
-                    result.initialize(
-                        self.array
-                            .iter()
-                            .skip(self.start)
-                            .take(self.end - self.start),
-                    );
-                    result
-                }
-            }
-        }
-
         let mut context = array.vm().context();
-        let result = context.alloc(Slice {
+        let result = context.alloc(ArraySliceDef {
             start,
             end,
             array: &array.get_array(),
@@ -165,7 +135,7 @@ pub mod array {
 
 mod int {
     use super::*;
-    use crate::types::VmInt;
+    use crate::{real_std::convert::TryFrom, types::VmInt};
 
     pub(crate) fn rem(dividend: VmInt, divisor: VmInt) -> RuntimeResult<VmInt, String> {
         if divisor != 0 {
@@ -214,6 +184,62 @@ mod int {
         }
     }
 
+    /// Returns `base` raised to `exp`, an exponent too large to fit in an `u32`, if the result is
+    /// in range. Only 0, 1 and -1 stay in range when raised to exponents this large.
+    fn large_pow(base: VmInt, exp: VmInt) -> Option<VmInt> {
+        match base {
+            0 | 1 => Some(base),
+            -1 if exp % 2 == 0 => Some(1),
+            -1 => Some(-1),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn checked_pow(base: VmInt, exp: VmInt) -> Option<VmInt> {
+        match u32::try_from(exp) {
+            Ok(exp) => base.checked_pow(exp),
+            Err(_) if exp < 0 => None,
+            Err(_) => large_pow(base, exp),
+        }
+    }
+
+    pub(crate) fn saturating_pow(base: VmInt, exp: VmInt) -> RuntimeResult<VmInt, String> {
+        if exp < 0 {
+            return RuntimeResult::Panic(format!(
+                "attempted to raise {} to the negative power {}",
+                base, exp
+            ));
+        }
+        RuntimeResult::Return(match u32::try_from(exp) {
+            Ok(exp) => base.saturating_pow(exp),
+            Err(_) => large_pow(base, exp).unwrap_or(if base > 0 || exp % 2 == 0 {
+                VmInt::MAX
+            } else {
+                VmInt::MIN
+            }),
+        })
+    }
+
+    pub(crate) fn wrapping_pow(base: VmInt, exp: VmInt) -> RuntimeResult<VmInt, String> {
+        if exp < 0 {
+            return RuntimeResult::Panic(format!(
+                "attempted to raise {} to the negative power {}",
+                base, exp
+            ));
+        }
+        // Exponentiation by squaring so that exponents which do not fit in an `u32` still wrap
+        // the same way as `VmInt::wrapping_pow`
+        let (mut base, mut exp, mut result) = (base, exp, 1 as VmInt);
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = result.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+            exp /= 2;
+        }
+        RuntimeResult::Return(result)
+    }
+
     pub(crate) fn overflowing_rem(
         dividend: VmInt,
         divisor: VmInt,
@@ -243,9 +269,51 @@ mod int {
     }
 }
 
+mod hash {
+    use crate::real_std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    use crate::types::VmInt;
+
+    // Finalizer of splitmix64, spreads the bits of `x` so that similar values get dissimilar
+    // hashes
+    fn mix(mut x: u64) -> u64 {
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+
+    pub(crate) fn int(i: VmInt) -> VmInt {
+        mix(i as u64) as VmInt
+    }
+
+    pub(crate) fn float(f: f64) -> VmInt {
+        // `0.0 == -0.0` so they must hash to the same value
+        let f = if f == 0.0 { 0.0 } else { f };
+        mix(f.to_bits()) as VmInt
+    }
+
+    pub(crate) fn string(s: &str) -> VmInt {
+        let mut hasher = DefaultHasher::new();
+        s.hash(&mut hasher);
+        hasher.finish() as VmInt
+    }
+
+    pub(crate) fn combine(seed: VmInt, hash: VmInt) -> VmInt {
+        mix((seed as u64).rotate_left(5) ^ hash as u64) as VmInt
+    }
+}
+
 mod string {
     use super::*;
-    use crate::value::ValueStr;
+    use crate::{
+        real_std::{convert::TryFrom, iter},
+        value::ValueStr,
+    };
+
+    use unicode_segmentation::UnicodeSegmentation;
 
     pub(crate) fn append(lhs: WithVM<&str>, rhs: &str) -> RuntimeResult<Pushed<String>, Error> {
         #[derive(Trace)]
@@ -360,6 +428,187 @@ mod string {
             &s[..(s.len() - iter.as_str().len())]
         ))
     }
+
+    pub fn checked_split_at(s: &str, index: VmInt) -> Option<(&str, &str)> {
+        let index = usize::try_from(index).ok()?;
+        if s.is_char_boundary(index) {
+            Some(s.split_at(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn checked_slice(s: &str, start: VmInt, end: VmInt) -> Option<&str> {
+        s.get(usize::try_from(start).ok()?..usize::try_from(end).ok()?)
+    }
+
+    pub fn checked_char_at(s: &str, index: VmInt) -> Option<char> {
+        s.get(usize::try_from(index).ok()?..)?.chars().next()
+    }
+
+    pub fn char_count(s: &str) -> usize {
+        s.chars().count()
+    }
+
+    /// Converts an index counted in characters into a byte index. The length of the string in
+    /// characters maps to the length in bytes.
+    pub fn byte_index(s: &str, char_index: VmInt) -> Option<usize> {
+        let char_index = usize::try_from(char_index).ok()?;
+        s.char_indices()
+            .map(|(i, _)| i)
+            .chain(Some(s.len()))
+            .nth(char_index)
+    }
+
+    /// Converts a byte index into an index counted in characters
+    pub fn char_index(s: &str, byte_index: VmInt) -> Option<usize> {
+        let byte_index = usize::try_from(byte_index).ok()?;
+        s.get(..byte_index).map(|prefix| prefix.chars().count())
+    }
+
+    pub fn nth_char(s: &str, index: VmInt) -> Option<char> {
+        s.chars().nth(usize::try_from(index).ok()?)
+    }
+
+    pub fn char_slice(s: &str, start: VmInt, end: VmInt) -> Option<&str> {
+        if start > end {
+            return None;
+        }
+        let start_byte = byte_index(s, start)?;
+        let end_byte = start_byte + byte_index(&s[start_byte..], end - start)?;
+        Some(&s[start_byte..end_byte])
+    }
+
+    pub fn char_split_at(s: &str, index: VmInt) -> Option<(&str, &str)> {
+        byte_index(s, index).map(|index| s.split_at(index))
+    }
+
+    pub fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    pub fn char_indices(s: &str) -> Vec<(usize, char)> {
+        s.char_indices().collect()
+    }
+
+    pub fn graphemes(s: &str) -> Vec<&str> {
+        s.graphemes(true).collect()
+    }
+
+    pub fn lines(s: &str) -> Vec<&str> {
+        s.lines().collect()
+    }
+
+    pub fn split_whitespace(s: &str) -> Vec<&str> {
+        s.split_whitespace().collect()
+    }
+
+    pub fn repeat(s: &str, count: VmInt) -> RuntimeResult<StdString, StdString> {
+        let count = match usize::try_from(count) {
+            Ok(count) => count,
+            Err(_) => {
+                return RuntimeResult::Panic(format!("cannot repeat a string {} times", count))
+            }
+        };
+        let mut repeated = StdString::new();
+        match s.len().checked_mul(count) {
+            Some(len) if repeated.try_reserve_exact(len).is_ok() => {
+                if !s.is_empty() {
+                    repeated.extend(iter::repeat(s).take(count));
+                }
+                RuntimeResult::Return(repeated)
+            }
+            _ => RuntimeResult::Panic(format!(
+                "cannot repeat a string of length {} {} times",
+                s.len(),
+                count
+            )),
+        }
+    }
+
+    pub fn join(strings: Vec<&str>, separator: &str) -> StdString {
+        strings.join(separator)
+    }
+
+    pub fn match_indices_str(s: &str, pattern: &str) -> Vec<(usize, usize)> {
+        s.match_indices(pattern)
+            .map(|(i, m)| (i, i + m.len()))
+            .collect()
+    }
+
+    pub fn match_indices_char(s: &str, pattern: char) -> Vec<(usize, usize)> {
+        s.match_indices(pattern)
+            .map(|(i, m)| (i, i + m.len()))
+            .collect()
+    }
+
+    /// Checks that `ranges` are sorted, do not overlap and lie on character boundaries
+    fn check_ranges(s: &str, ranges: &[(usize, usize)]) -> StdResult<(), StdString> {
+        let mut previous_end = 0;
+        for &(start, end) in ranges {
+            if start < previous_end || s.get(start..end).is_none() {
+                return Err(format!(
+                    "invalid match ({}, {}) in a string of length {}",
+                    start,
+                    end,
+                    s.len()
+                ));
+            }
+            previous_end = end;
+        }
+        Ok(())
+    }
+
+    pub fn split_ranges(
+        s: &str,
+        ranges: Vec<(usize, usize)>,
+    ) -> RuntimeResult<Vec<&str>, StdString> {
+        if let Err(err) = check_ranges(s, &ranges) {
+            return RuntimeResult::Panic(err);
+        }
+        let mut previous_end = 0;
+        let mut pieces = Vec::with_capacity(ranges.len() + 1);
+        for (start, end) in ranges {
+            pieces.push(&s[previous_end..start]);
+            previous_end = end;
+        }
+        pieces.push(&s[previous_end..]);
+        RuntimeResult::Return(pieces)
+    }
+
+    pub fn slice_ranges(
+        s: &str,
+        ranges: Vec<(usize, usize)>,
+    ) -> RuntimeResult<Vec<&str>, StdString> {
+        match check_ranges(s, &ranges) {
+            Ok(()) => RuntimeResult::Return(
+                ranges
+                    .into_iter()
+                    .map(|(start, end)| &s[start..end])
+                    .collect(),
+            ),
+            Err(err) => RuntimeResult::Panic(err),
+        }
+    }
+
+    pub fn replace_ranges(
+        s: &str,
+        ranges: Vec<(usize, usize)>,
+        replacement: &str,
+    ) -> RuntimeResult<StdString, StdString> {
+        if let Err(err) = check_ranges(s, &ranges) {
+            return RuntimeResult::Panic(err);
+        }
+        let mut previous_end = 0;
+        let mut result = StdString::with_capacity(s.len());
+        for (start, end) in ranges {
+            result.push_str(&s[previous_end..start]);
+            result.push_str(replacement);
+            previous_end = end;
+        }
+        result.push_str(&s[previous_end..]);
+        RuntimeResult::Return(result)
+    }
 }
 
 fn parse<T>(s: &str) -> StdResult<T, ()>
@@ -622,15 +871,27 @@ pub fn load_int(vm: &Thread) -> Result<ExternModule> {
             abs => primitive!(1, std::int::prim::abs),
             rem => primitive!(2, "std::int::prim::rem", int::rem),
             rem_euclid => primitive!(2, "std::int::prim::rem_euclid", int::rem_euclid),
+            checked_add => primitive!(2, std::int::prim::checked_add),
+            checked_sub => primitive!(2, std::int::prim::checked_sub),
+            checked_mul => primitive!(2, std::int::prim::checked_mul),
+            checked_div => primitive!(2, std::int::prim::checked_div),
+            checked_div_euclid => primitive!(2, std::int::prim::checked_div_euclid),
             checked_rem => primitive!(2, std::int::prim::checked_rem),
             checked_rem_euclid => primitive!(2, std::int::prim::checked_rem_euclid),
+            checked_pow => primitive!(2, "std::int::prim::checked_pow", int::checked_pow),
+            checked_abs => primitive!(1, std::int::prim::checked_abs),
+            checked_negate => primitive!(1, "std.int.prim.checked_negate", std::int::prim::checked_neg),
             saturating_add => primitive!(2, std::int::prim::saturating_add),
             saturating_sub => primitive!(2, std::int::prim::saturating_sub),
             saturating_mul => primitive!(2, std::int::prim::saturating_mul),
+            saturating_pow => primitive!(2, "std::int::prim::saturating_pow", int::saturating_pow),
+            saturating_abs => primitive!(1, std::int::prim::saturating_abs),
+            saturating_negate => primitive!(1, "std.int.prim.saturating_negate", std::int::prim::saturating_neg),
             wrapping_add => primitive!(2, std::int::prim::wrapping_add),
             wrapping_sub => primitive!(2, std::int::prim::wrapping_sub),
             wrapping_mul => primitive!(2, std::int::prim::wrapping_mul),
             wrapping_div => primitive!(2, std::int::prim::wrapping_div),
+            wrapping_pow => primitive!(2, "std::int::prim::wrapping_pow", int::wrapping_pow),
             wrapping_abs => primitive!(1, std::int::prim::wrapping_abs),
             wrapping_rem => primitive!(2, "std::int::prim::wrapping_rem", int::wrapping_rem),
             wrapping_rem_euclid => primitive!(2, "std::int::prim::wrapping_rem", int::wrapping_rem_euclid),
@@ -695,7 +956,30 @@ pub fn load_string(vm: &Thread) -> Result<ExternModule> {
                 "std.string.prim.from_utf8",
                 string::from_utf8
             ),
-            char_at => primitive!(2, "std.string.prim.char_at", string::char_at)
+            char_at => primitive!(2, "std.string.prim.char_at", string::char_at),
+            checked_split_at => primitive!(2, "std.string.prim.checked_split_at", string::checked_split_at),
+            checked_slice => primitive!(3, "std.string.prim.checked_slice", string::checked_slice),
+            checked_char_at => primitive!(2, "std.string.prim.checked_char_at", string::checked_char_at),
+            char_count => primitive!(1, "std.string.prim.char_count", string::char_count),
+            byte_index => primitive!(2, "std.string.prim.byte_index", string::byte_index),
+            char_index => primitive!(2, "std.string.prim.char_index", string::char_index),
+            nth_char => primitive!(2, "std.string.prim.nth_char", string::nth_char),
+            char_slice => primitive!(3, "std.string.prim.char_slice", string::char_slice),
+            char_split_at => primitive!(2, "std.string.prim.char_split_at", string::char_split_at),
+            chars => primitive!(1, "std.string.prim.chars", string::chars),
+            char_indices => primitive!(1, "std.string.prim.char_indices", string::char_indices),
+            graphemes => primitive!(1, "std.string.prim.graphemes", string::graphemes),
+            lines => primitive!(1, "std.string.prim.lines", string::lines),
+            split_whitespace => primitive!(1, "std.string.prim.split_whitespace", string::split_whitespace),
+            to_uppercase => primitive!(1, std::string::prim::to_uppercase),
+            to_lowercase => primitive!(1, std::string::prim::to_lowercase),
+            repeat => primitive!(2, "std.string.prim.repeat", string::repeat),
+            join => primitive!(2, "std.string.prim.join", string::join),
+            match_indices_str => primitive!(2, "std.string.prim.match_indices_str", string::match_indices_str),
+            match_indices_char => primitive!(2, "std.string.prim.match_indices_char", string::match_indices_char),
+            split_ranges => primitive!(2, "std.string.prim.split_ranges", string::split_ranges),
+            slice_ranges => primitive!(2, "std.string.prim.slice_ranges", string::slice_ranges),
+            replace_ranges => primitive!(3, "std.string.prim.replace_ranges", string::replace_ranges)
         },
     )
 }
@@ -814,6 +1098,21 @@ pub fn load_path(vm: &Thread) -> Result<ExternModule> {
     )
 }
 
+#[allow(non_camel_case_types)]
+pub fn load_hash(vm: &Thread) -> Result<ExternModule> {
+    ExternModule::new(
+        vm,
+        record! {
+            int => primitive!(1, "std.hash.prim.int", hash::int),
+            float => primitive!(1, "std.hash.prim.float", hash::float),
+            byte => primitive!(1, "std.hash.prim.byte", |b: u8| hash::int(b.into())),
+            char => primitive!(1, "std.hash.prim.char", |c: char| hash::int(c as VmInt)),
+            string => primitive!(1, "std.hash.prim.string", hash::string),
+            combine => primitive!(2, "std.hash.prim.combine", hash::combine),
+        },
+    )
+}
+
 #[allow(non_camel_case_types)]
 pub fn load_char(vm: &Thread) -> Result<ExternModule> {
     ExternModule::new(
@@ -0,0 +1,345 @@
+//! An instrumenting profiler which attributes executed instructions, wall time and allocated
+//! bytes to the call stacks of gluon functions.
+//!
+//! Profiling is started with `Thread::start_profiling` and the recorded `Profile` is returned from
+//! `Thread::stop_profiling`.
+use std::{
+    fmt, io,
+    ops::AddAssign,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    base::{fnv::FnvMap, symbol::Symbol},
+    stack::{ExternCallState, ExternState, Stack, State},
+};
+
+/// The resources used while a function was executing
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Sample {
+    /// The number of times the function was called
+    pub calls: u64,
+    /// The number of bytecode instructions executed
+    pub instructions: u64,
+    /// The wall time spent executing
+    pub time: Duration,
+    /// The number of bytes allocated by the garbage collector
+    pub allocated_bytes: u64,
+}
+
+impl AddAssign<&'_ Sample> for Sample {
+    fn add_assign(&mut self, other: &Sample) {
+        self.calls += other.calls;
+        self.instructions += other.instructions;
+        self.time += other.time;
+        self.allocated_bytes += other.allocated_bytes;
+    }
+}
+
+/// Aggregated samples for a single function
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionProfile {
+    pub name: String,
+    /// Resources used by the function itself
+    pub exclusive: Sample,
+    /// Resources used by the function and every function it called
+    pub inclusive: Sample,
+}
+
+/// The value which `Profile::write_collapsed` weights each stack with
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Metric {
+    Instructions,
+    /// Wall time in microseconds
+    Time,
+    /// Allocated bytes
+    Allocation,
+}
+
+impl Metric {
+    fn value(self, sample: &Sample) -> u64 {
+        match self {
+            Metric::Instructions => sample.instructions,
+            Metric::Time => sample.time.as_micros() as u64,
+            Metric::Allocation => sample.allocated_bytes,
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "instructions" => Metric::Instructions,
+            "time" => Metric::Time,
+            "allocation" => Metric::Allocation,
+            _ => return Err("Expected one of 'instructions', 'time', 'allocation'"),
+        })
+    }
+}
+
+/// A profile recorded by `Thread::stop_profiling`
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    // Sorted by stack, outermost function first
+    stacks: Vec<(Vec<String>, Sample)>,
+}
+
+impl Profile {
+    /// Returns every recorded call stack, with the outermost function first, and the resources
+    /// used while that stack was executing
+    pub fn stacks(&self) -> impl Iterator<Item = (&[String], &Sample)> {
+        self.stacks
+            .iter()
+            .map(|(stack, sample)| (&stack[..], sample))
+    }
+
+    /// Returns the resources used by each function, sorted with the most time consuming function
+    /// first
+    pub fn functions(&self) -> Vec<FunctionProfile> {
+        let mut functions: FnvMap<&str, FunctionProfile> = FnvMap::default();
+        for (stack, sample) in &self.stacks {
+            // Recursive functions appear several times in a stack but must only be counted once
+            for (i, name) in stack.iter().enumerate() {
+                if !stack[..i].contains(name) {
+                    functions
+                        .entry(&name[..])
+                        .or_insert_with(|| FunctionProfile {
+                            name: name.to_string(),
+                            exclusive: Sample::default(),
+                            inclusive: Sample::default(),
+                        })
+                        .inclusive += sample;
+                }
+            }
+            if let Some(name) = stack.last() {
+                functions.get_mut(&name[..]).unwrap().exclusive += sample;
+            }
+        }
+
+        let mut functions = functions.into_values().collect::<Vec<_>>();
+        functions.sort_by(|l, r| {
+            r.exclusive
+                .time
+                .cmp(&l.exclusive.time)
+                .then_with(|| l.name.cmp(&r.name))
+        });
+        functions
+    }
+
+    /// Writes the profile as a table with a row for each function
+    pub fn write_table(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{:>10} {:>14} {:>14} {:>10} {:>10} {:>12} {:>12}  function",
+            "calls", "instructions", "(total)", "time (ms)", "(total)", "bytes", "(total)"
+        )?;
+        for function in self.functions() {
+            writeln!(
+                writer,
+                "{:>10} {:>14} {:>14} {:>10.3} {:>10.3} {:>12} {:>12}  {}",
+                function.exclusive.calls,
+                function.exclusive.instructions,
+                function.inclusive.instructions,
+                millis(function.exclusive.time),
+                millis(function.inclusive.time),
+                function.exclusive.allocated_bytes,
+                function.inclusive.allocated_bytes,
+                function.name,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the profile in the collapsed stack format used by flamegraph tools, one line per
+    /// call stack with the functions separated by `;` followed by the value of `metric`
+    pub fn write_collapsed(&self, mut writer: impl io::Write, metric: Metric) -> io::Result<()> {
+        for (stack, sample) in &self.stacks {
+            let value = metric.value(sample);
+            if value != 0 {
+                writeln!(writer, "{} {}", stack.join(";"), value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = Vec::new();
+        self.write_table(&mut buffer).map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&buffer))
+    }
+}
+
+/// A unique call stack, stored as a node in a trie of every call stack seen so far
+struct StackNode {
+    name: Symbol,
+    parent: Option<usize>,
+    sample: Sample,
+}
+
+/// A frame of the thread's stack as seen by the profiler
+struct Frame {
+    /// `None` for frames which do not execute a function
+    name: Option<Symbol>,
+    /// The node of the innermost function at or below this frame
+    node: Option<usize>,
+}
+
+fn frame_name(state: &State) -> Option<&Symbol> {
+    match state {
+        State::Closure(state) => Some(&state.closure.function.name),
+        State::Extern(ExternState { function, .. }) => Some(&function.id),
+        State::Unknown => None,
+    }
+}
+
+/// Records samples while a thread executes. Stored in the context of the thread being profiled.
+pub(crate) struct Profiler {
+    nodes: Vec<StackNode>,
+    /// Maps a parent node (`None` for the outermost function) and the name of a called function
+    /// to the node of the resulting call stack
+    children: FnvMap<(Option<usize>, Symbol), usize>,
+    /// Mirrors the frames of the stack which is currently executing
+    current: Vec<Frame>,
+    /// Instructions executed since the last sample was recorded
+    instructions: u64,
+    time: Instant,
+    total_allocated: u64,
+}
+
+impl Profiler {
+    pub(crate) fn new(total_allocated: u64) -> Self {
+        Profiler {
+            nodes: Vec::new(),
+            children: FnvMap::default(),
+            current: Vec::new(),
+            instructions: 0,
+            time: Instant::now(),
+            total_allocated,
+        }
+    }
+
+    pub(crate) fn instruction(&mut self) {
+        self.instructions += 1;
+    }
+
+    /// Called when execution of gluon code is started or resumed. The time since the executing
+    /// frame last changed is discarded as it includes time where no gluon code were executing.
+    pub(crate) fn resume(&mut self, total_allocated: u64) {
+        self.time = Instant::now();
+        self.record(total_allocated);
+    }
+
+    /// Called each time the executing frame may have changed.
+    ///
+    /// Only the top of the stack can change between two calls (a function is called, returns or
+    /// tail calls another function) so `current` is updated incrementally instead of being
+    /// rebuilt from every frame.
+    pub(crate) fn frame(&mut self, stack: &Stack, total_allocated: u64) {
+        self.record(total_allocated);
+
+        let frames = stack.get_frames();
+        self.current.truncate(frames.len());
+        if self.current.len() == frames.len() {
+            // A tail call replaces the top frame
+            let top = frames.last().and_then(|frame| frame_name(&frame.state));
+            if self.current.last().map(|frame| frame.name.as_ref()) != Some(top) {
+                self.current.pop();
+            }
+        }
+        while self.current.len() < frames.len() {
+            let name = frame_name(&frames[self.current.len()].state).cloned();
+            let parent = self.current.last().and_then(|frame| frame.node);
+            let node = match &name {
+                Some(name) => Some(self.node(parent, name)),
+                None => parent,
+            };
+            self.current.push(Frame { name, node });
+        }
+
+        let called = match frames.last().map(|frame| &frame.state) {
+            Some(State::Closure(state)) => state.instruction_index == 0,
+            Some(State::Extern(ExternState { call_state, .. })) => {
+                *call_state == ExternCallState::Start
+            }
+            Some(State::Unknown) | None => false,
+        };
+        if called {
+            if let Some(sample) = self.sample() {
+                sample.calls += 1;
+            }
+        }
+    }
+
+    fn node(&mut self, parent: Option<usize>, name: &Symbol) -> usize {
+        let nodes = &mut self.nodes;
+        *self
+            .children
+            .entry((parent, name.clone()))
+            .or_insert_with(|| {
+                nodes.push(StackNode {
+                    name: name.clone(),
+                    parent,
+                    sample: Sample::default(),
+                });
+                nodes.len() - 1
+            })
+    }
+
+    /// Attributes everything since the last recorded sample to the current call stack
+    fn record(&mut self, total_allocated: u64) {
+        let now = Instant::now();
+        let instructions = self.instructions;
+        let time = now.duration_since(self.time);
+        let allocated_bytes = total_allocated.saturating_sub(self.total_allocated);
+        if let Some(sample) = self.sample() {
+            sample.instructions += instructions;
+            sample.time += time;
+            sample.allocated_bytes += allocated_bytes;
+        }
+        self.instructions = 0;
+        self.time = now;
+        self.total_allocated = total_allocated;
+    }
+
+    fn sample(&mut self) -> Option<&mut Sample> {
+        let node = self.current.last()?.node?;
+        Some(&mut self.nodes[node].sample)
+    }
+
+    pub(crate) fn finish(mut self, total_allocated: u64) -> Profile {
+        self.record(total_allocated);
+        let nodes = &self.nodes;
+        let mut stacks = nodes
+            .iter()
+            .map(|node| {
+                let mut stack = Vec::new();
+                let mut current = Some(node);
+                while let Some(node) = current {
+                    stack.push(node.name.to_string());
+                    current = node.parent.map(|parent| &nodes[parent]);
+                }
+                stack.reverse();
+                (stack, node.sample)
+            })
+            .collect::<Vec<_>>();
+        stacks.sort_by(|l, r| l.0.cmp(&r.0));
+        // Different symbols may display as the same name
+        stacks.dedup_by(|r, l| {
+            if l.0 == r.0 {
+                l.1 += &r.1;
+                true
+            } else {
+                false
+            }
+        });
+        Profile { stacks }
+    }
+}
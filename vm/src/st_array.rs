@@ -0,0 +1,219 @@
+//! Mutable arrays for the `std.effect.st` effect.
+//!
+//! The `s` parameter of `STArray s a` ties the array to the `State s` region it was created in so
+//! `std.effect.st.run_state` prevents it from being used after the region has finished.
+
+use crate::real_std::{any::Any, convert::TryFrom, fmt, marker::PhantomData, sync::Mutex};
+
+use crate::{
+    api::{
+        generic::{A, S},
+        Array, Collect, Generic, RuntimeResult, Unrooted, Userdata, WithVM,
+    },
+    gc::{CloneUnrooted, GcPtr, GcRef, Move, Trace},
+    thread::ThreadInternal,
+    types::VmInt,
+    value::{Cloner, Value},
+    vm::Thread,
+    ExternModule, Result,
+};
+
+#[derive(VmType)]
+#[gluon(gluon_vm)]
+#[gluon(vm_type = "std.effect.st.array.STArray")]
+pub struct STArray<S, T> {
+    values: Mutex<Vec<Value>>,
+    thread: GcPtr<Thread>,
+    _marker: PhantomData<(S, T)>,
+}
+
+impl<S, T> Userdata for STArray<S, T>
+where
+    S: Any + Send + Sync,
+    T: Any + Send + Sync,
+{
+    fn deep_clone<'gc>(
+        &self,
+        deep_cloner: &'gc mut Cloner,
+    ) -> Result<GcRef<'gc, Box<dyn Userdata>>> {
+        let values = self.values.lock().unwrap();
+        // SAFETY During the `alloc` call the unrooted values are scanned through the `DataDef`
+        unsafe {
+            let cloned_values = values
+                .iter()
+                .map(|value| Ok(deep_cloner.deep_clone(value)?.unrooted()))
+                .collect::<Result<Vec<_>>>()?;
+            let data: Box<dyn Userdata> = Box::new(STArray {
+                values: Mutex::new(cloned_values),
+                thread: GcPtr::from_raw(deep_cloner.thread()),
+                _marker: PhantomData::<(S, T)>,
+            });
+            deep_cloner.gc().alloc(Move(data))
+        }
+    }
+}
+
+impl<S, T> fmt::Debug for STArray<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "STArray({:?})", *self.values.lock().unwrap())
+    }
+}
+
+unsafe impl<S, T> Trace for STArray<S, T> {
+    impl_trace_fields! { self, gc; values }
+}
+
+fn out_of_range<T>(index: usize, len: usize) -> RuntimeResult<T, String> {
+    RuntimeResult::Panic(format!(
+        "Index {} is out of range for an array of length {}",
+        index, len
+    ))
+}
+
+impl<S> STArray<S, A> {
+    // Values from other threads must be cloned into the thread which owns the array
+    fn store(
+        &self,
+        value: &Generic<A>,
+        f: impl FnOnce(&mut Vec<Value>, Value),
+    ) -> RuntimeResult<(), String> {
+        match self
+            .thread
+            .deep_clone_value(&self.thread, value.get_value())
+        {
+            // SAFETY Rooted when stored in the array
+            Ok(value) => unsafe {
+                f(
+                    &mut self.values.lock().unwrap(),
+                    value.get_value().clone_unrooted(),
+                );
+                RuntimeResult::Return(())
+            },
+            Err(err) => RuntimeResult::Panic(format!("{}", err)),
+        }
+    }
+}
+
+fn new(
+    len: VmInt,
+    WithVM { vm, value }: WithVM<Generic<A>>,
+) -> RuntimeResult<STArray<S, A>, String> {
+    let len = match usize::try_from(len) {
+        Ok(len) => len,
+        Err(_) => {
+            return RuntimeResult::Panic(format!(
+                "Unable to create an array of negative length {}",
+                len
+            ))
+        }
+    };
+    let mut values = Vec::new();
+    if values.try_reserve_exact(len).is_err() {
+        return RuntimeResult::Panic(format!("Unable to allocate an array of length {}", len));
+    }
+    // SAFETY The values are rooted by `value` until the array is pushed to the stack
+    unsafe {
+        values.extend((0..len).map(|_| value.get_value().clone_unrooted()));
+        RuntimeResult::Return(STArray {
+            values: Mutex::new(values),
+            thread: GcPtr::from_raw(vm),
+            _marker: PhantomData,
+        })
+    }
+}
+
+fn thaw<'vm>(WithVM { vm, value: array }: WithVM<'vm, Array<'vm, A>>) -> STArray<S, A> {
+    // SAFETY The values are rooted by `array` until the array is pushed to the stack
+    unsafe {
+        STArray {
+            values: Mutex::new(
+                array
+                    .get_array()
+                    .iter()
+                    .map(|value| value.unrooted())
+                    .collect(),
+            ),
+            thread: GcPtr::from_raw(vm),
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn len(array: &STArray<S, A>) -> usize {
+    array.values.lock().unwrap().len()
+}
+
+fn read(array: &STArray<S, A>, index: usize) -> RuntimeResult<Unrooted<A>, String> {
+    let values = array.values.lock().unwrap();
+    match values.get(index) {
+        // SAFETY The returned, unrooted value gets pushed immediately to the stack
+        Some(value) => RuntimeResult::Return(unsafe { Unrooted::from(value.clone_unrooted()) }),
+        None => out_of_range(index, values.len()),
+    }
+}
+
+fn write(array: &STArray<S, A>, index: usize, value: Generic<A>) -> RuntimeResult<(), String> {
+    let len = len(array);
+    if index >= len {
+        return out_of_range(index, len);
+    }
+    array.store(&value, |values, value| values[index] = value)
+}
+
+fn push(array: &STArray<S, A>, value: Generic<A>) -> RuntimeResult<(), String> {
+    array.store(&value, |values, value| values.push(value))
+}
+
+fn swap(array: &STArray<S, A>, i: usize, j: usize) -> RuntimeResult<(), String> {
+    let mut values = array.values.lock().unwrap();
+    let len = values.len();
+    match (i < len, j < len) {
+        (true, true) => {
+            values.swap(i, j);
+            RuntimeResult::Return(())
+        }
+        (false, _) => out_of_range(i, len),
+        (_, false) => out_of_range(j, len),
+    }
+}
+
+fn freeze(array: &STArray<S, A>) -> Collect<Vec<Unrooted<A>>> {
+    // SAFETY The values are rooted by `array` until they have been pushed to the stack
+    Collect::new(
+        array
+            .values
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|value| unsafe { Unrooted::from(value.clone_unrooted()) })
+            .collect(),
+    )
+}
+
+mod std {
+    pub mod effect {
+        pub mod st {
+            pub mod array {
+                pub use crate::st_array as prim;
+            }
+        }
+    }
+}
+
+pub fn load(vm: &Thread) -> Result<ExternModule> {
+    let _ = vm.register_type::<STArray<S, A>>("std.effect.st.array.STArray", &["s", "a"]);
+    ExternModule::new(
+        vm,
+        record! {
+            type STArray s a => STArray<S, A>,
+            new => primitive!(2, std::effect::st::array::prim::new),
+            thaw => primitive!(1, std::effect::st::array::prim::thaw),
+            len => primitive!(1, std::effect::st::array::prim::len),
+            read => primitive!(2, std::effect::st::array::prim::read),
+            write => primitive!(3, std::effect::st::array::prim::write),
+            push => primitive!(2, std::effect::st::array::prim::push),
+            swap => primitive!(3, std::effect::st::array::prim::swap),
+            freeze => primitive!(1, std::effect::st::array::prim::freeze),
+        },
+    )
+}
@@ -0,0 +1,388 @@
+//! Primitives of `std.task`, structured concurrency on top of gluon's green threads.
+//!
+//! Every action started by these primitives runs on a fresh child thread so that a task which
+//! loses a race, times out or is cancelled can be interrupted and dropped without disturbing the
+//! thread which started it.
+
+use crate::real_std::{
+    any::Any,
+    fmt,
+    marker::PhantomData,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{
+    future::{self, BoxFuture, Either, Shared},
+    prelude::*,
+    stream::FuturesUnordered,
+    task::{self, Poll, Waker},
+};
+use futures_timer::Delay;
+
+use crate::{
+    api::{generic::A, Generic, Getable, OpaqueRef, OwnedFunction, WithVM, IO},
+    gc::{GcRef, Move, Trace},
+    thread::Thread,
+    types::VmInt,
+    value::{Cloner, Userdata},
+    vm::RootedThread,
+    ExternModule, Result as VmResult,
+};
+
+type TaskFuture = BoxFuture<'static, Result<Generic<A>, String>>;
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: bool,
+    wakers: Vec<Waker>,
+    threads: Vec<RootedThread>,
+}
+
+/// A token which can be shared between tasks to cooperatively cancel them. Cancelling the token
+/// interrupts every thread that is currently running on behalf of it.
+#[derive(Clone, Default, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.task.CancelToken")]
+#[gluon(gluon_vm)]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+pub struct CancelToken(Arc<Mutex<CancelState>>);
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CancelToken({})", self.is_cancelled())
+    }
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.cancelled = true;
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+        for thread in state.threads.drain(..) {
+            thread.interrupt();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.lock().unwrap().cancelled
+    }
+
+    fn poll_cancelled(&self, cx: &task::Context<'_>) -> Poll<()> {
+        let mut state = self.0.lock().unwrap();
+        if state.cancelled {
+            Poll::Ready(())
+        } else {
+            if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                state.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+
+    /// Interrupts `thread` when the token is cancelled, or immediately if it already is
+    fn register(&self, thread: &RootedThread) {
+        let mut state = self.0.lock().unwrap();
+        if state.cancelled {
+            thread.interrupt();
+        } else {
+            state.threads.push(thread.clone());
+        }
+    }
+
+    fn unregister(&self, thread: &Thread) {
+        self.0
+            .lock()
+            .unwrap()
+            .threads
+            .retain(|t| !::std::ptr::eq::<Thread>(&**t, thread));
+    }
+}
+
+/// Prepares `action` to run on a new child thread of `vm`. The returned future unregisters the
+/// thread from `token` once the action has finished.
+fn start(
+    vm: &Thread,
+    action: &OpaqueRef<IO<A>>,
+    token: Option<&CancelToken>,
+) -> VmResult<(RootedThread, TaskFuture)> {
+    let thread = vm.new_thread()?;
+    let mut function: OwnedFunction<fn(()) -> Generic<A>> =
+        Getable::from_value(&thread, action.get_variant());
+    let token = token.cloned();
+    if let Some(token) = &token {
+        token.register(&thread);
+    }
+    let child = thread.clone();
+    let future = async move {
+        let result = function.call_async(()).await;
+        if let Some(token) = token {
+            token.unregister(&child);
+        }
+        result.map_err(|err| err.to_string())
+    };
+    Ok((thread, future.boxed()))
+}
+
+fn io<T>(result: Result<T, String>) -> IO<T> {
+    match result {
+        Ok(value) => IO::Value(value),
+        Err(err) => IO::Exception(err),
+    }
+}
+
+/// A handle to an action spawned in a `Scope`
+#[derive(VmType)]
+#[gluon(gluon_vm)]
+#[gluon(vm_type = "std.task.Task")]
+pub struct Task<T> {
+    result: Shared<TaskFuture>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Userdata for Task<T>
+where
+    T: Any + Send + Sync,
+{
+    fn deep_clone<'gc>(
+        &self,
+        deep_cloner: &'gc mut Cloner,
+    ) -> VmResult<GcRef<'gc, Box<dyn Userdata>>> {
+        let data: Box<dyn Userdata> = Box::new(Task {
+            result: self.result.clone(),
+            _marker: PhantomData::<T>,
+        });
+        deep_cloner.gc().alloc(Move(data))
+    }
+}
+
+impl<T> fmt::Debug for Task<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Task")
+    }
+}
+
+// The result is rooted in the thread which ran the task
+unsafe impl<T> Trace for Task<T> {
+    impl_trace! { self, _gc, { } }
+}
+
+#[derive(Default)]
+struct ScopeState {
+    /// Tasks spawned since the scope last polled its children
+    spawned: Vec<BoxFuture<'static, Result<(), String>>>,
+    waker: Option<Waker>,
+}
+
+/// Owns the tasks spawned into it. The scope waits for all of them to finish before it returns and
+/// cancels the remaining ones as soon as one of them fails.
+#[derive(Clone, Default, Userdata, Trace, VmType)]
+#[gluon(vm_type = "std.task.Scope")]
+#[gluon(gluon_vm)]
+#[gluon_userdata(clone)]
+#[gluon_trace(skip)]
+pub struct Scope {
+    state: Arc<Mutex<ScopeState>>,
+    token: CancelToken,
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Scope")
+    }
+}
+
+fn new_token(_: ()) -> IO<CancelToken> {
+    IO::Value(CancelToken::default())
+}
+
+fn cancel(token: &CancelToken) -> IO<()> {
+    token.cancel();
+    IO::Value(())
+}
+
+fn is_cancelled(token: &CancelToken) -> IO<bool> {
+    IO::Value(token.is_cancelled())
+}
+
+fn wait_cancelled(token: &CancelToken) -> impl Future<Output = IO<()>> {
+    let token = token.clone();
+    future::poll_fn(move |cx| token.poll_cancelled(cx)).map(IO::Value)
+}
+
+fn with_cancel(
+    token: &CancelToken,
+    WithVM { vm, value: action }: WithVM<OpaqueRef<IO<A>>>,
+) -> impl Future<Output = IO<Option<Generic<A>>>> {
+    let token = token.clone();
+    let (_, mut task) = match start(vm, &action, Some(&token)) {
+        Ok(x) => x,
+        Err(err) => return Either::Right(future::ready(IO::Exception(err.to_string()))),
+    };
+    Either::Left(future::poll_fn(move |cx| {
+        if let Poll::Ready(()) = token.poll_cancelled(cx) {
+            return Poll::Ready(IO::Value(None));
+        }
+        task.poll_unpin(cx).map(|result| io(result.map(Some)))
+    }))
+}
+
+fn race(
+    WithVM { vm, value: a }: WithVM<OpaqueRef<IO<A>>>,
+    b: OpaqueRef<IO<A>>,
+) -> impl Future<Output = IO<Generic<A>>> {
+    let started = start(vm, &a, None).and_then(|a| Ok((a, start(vm, &b, None)?)));
+    let ((thread_a, task_a), (thread_b, task_b)) = match started {
+        Ok(x) => x,
+        Err(err) => return Either::Right(future::ready(IO::Exception(err.to_string()))),
+    };
+    Either::Left(async move {
+        match future::select(task_a, task_b).await {
+            Either::Left((result, _)) => {
+                thread_b.interrupt();
+                io(result)
+            }
+            Either::Right((result, _)) => {
+                thread_a.interrupt();
+                io(result)
+            }
+        }
+    })
+}
+
+fn timeout(
+    ms: VmInt,
+    WithVM { vm, value: action }: WithVM<OpaqueRef<IO<A>>>,
+) -> impl Future<Output = IO<Option<Generic<A>>>> {
+    let (thread, task) = match start(vm, &action, None) {
+        Ok(x) => x,
+        Err(err) => return Either::Right(future::ready(IO::Exception(err.to_string()))),
+    };
+    let delay = Delay::new(Duration::from_millis(ms.max(0) as u64));
+    Either::Left(async move {
+        match future::select(task, delay).await {
+            Either::Left((result, _)) => io(result.map(Some)),
+            Either::Right(((), _)) => {
+                thread.interrupt();
+                IO::Value(None)
+            }
+        }
+    })
+}
+
+/// Suspends the calling thread for `ms` milliseconds without blocking other threads
+fn sleep(ms: VmInt) -> impl Future<Output = IO<()>> {
+    Delay::new(Duration::from_millis(ms.max(0) as u64)).map(IO::Value)
+}
+
+fn new_scope(_: ()) -> IO<Scope> {
+    IO::Value(Scope::default())
+}
+
+fn token(scope: &Scope) -> CancelToken {
+    scope.token.clone()
+}
+
+fn spawn(scope: &Scope, WithVM { vm, value: action }: WithVM<OpaqueRef<IO<A>>>) -> IO<Task<A>> {
+    let (_, task) = match start(vm, &action, Some(&scope.token)) {
+        Ok(x) => x,
+        Err(err) => return IO::Exception(err.to_string()),
+    };
+    let result = task.shared();
+    let mut state = scope.state.lock().unwrap();
+    state.spawned.push(result.clone().map_ok(|_| ()).boxed());
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+    IO::Value(Task {
+        result,
+        _marker: PhantomData,
+    })
+}
+
+fn await_(task: &Task<A>) -> impl Future<Output = IO<Generic<A>>> {
+    task.result.clone().map(io)
+}
+
+/// Runs `body` alongside the tasks spawned into `scope`, returning the result of `body` once all of
+/// them have finished. If any of them fails the scope's token is cancelled and the error is
+/// returned.
+fn run(
+    scope: &Scope,
+    WithVM { vm, value: body }: WithVM<OpaqueRef<IO<A>>>,
+) -> impl Future<Output = IO<Generic<A>>> {
+    let scope = scope.clone();
+    let (_, body) = match start(vm, &body, Some(&scope.token)) {
+        Ok(x) => x,
+        Err(err) => return Either::Right(future::ready(IO::Exception(err.to_string()))),
+    };
+    let mut body = Some(body);
+    let mut value = None;
+    let mut children = FuturesUnordered::new();
+    Either::Left(async move {
+        let result = future::poll_fn(|cx| {
+            if let Some(Poll::Ready(result)) = body.as_mut().map(|body| body.poll_unpin(cx)) {
+                body = None;
+                value = Some(result?);
+            }
+            loop {
+                {
+                    let mut state = scope.state.lock().unwrap();
+                    state.waker = Some(cx.waker().clone());
+                    children.extend(mem::take(&mut state.spawned));
+                }
+                match children.poll_next_unpin(cx) {
+                    Poll::Ready(Some(result)) => result?,
+                    Poll::Ready(None) | Poll::Pending => break,
+                }
+            }
+            if body.is_none() && children.is_empty() {
+                Poll::Ready(Ok(value.take().expect("scope body to have finished")))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        if result.is_err() {
+            scope.token.cancel();
+        }
+        io(result)
+    })
+}
+
+mod std {
+    pub mod task {
+        pub use crate::task as prim;
+    }
+}
+
+pub fn load(vm: &Thread) -> VmResult<ExternModule> {
+    vm.register_type::<CancelToken>("std.task.CancelToken", &[])?;
+    vm.register_type::<Scope>("std.task.Scope", &[])?;
+    let _ = vm.register_type::<Task<A>>("std.task.Task", &["a"]);
+
+    ExternModule::new(
+        vm,
+        record! {
+            type CancelToken => CancelToken,
+            type Scope => Scope,
+            type Task a => Task<A>,
+            new_token => primitive!(1, std::task::prim::new_token),
+            cancel => primitive!(1, std::task::prim::cancel),
+            is_cancelled => primitive!(1, std::task::prim::is_cancelled),
+            wait_cancelled => primitive!(1, async fn std::task::prim::wait_cancelled),
+            with_cancel => primitive!(2, async fn std::task::prim::with_cancel),
+            race => primitive!(2, async fn std::task::prim::race),
+            timeout => primitive!(2, async fn std::task::prim::timeout),
+            sleep => primitive!(1, async fn std::task::prim::sleep),
+            new_scope => primitive!(1, std::task::prim::new_scope),
+            token => primitive!(1, std::task::prim::token),
+            spawn => primitive!(2, std::task::prim::spawn),
+            (await_ "await") => primitive!(1, "std.task.prim.await", async fn std::task::prim::await_),
+            run => primitive!(2, async fn std::task::prim::run),
+        },
+    )
+}
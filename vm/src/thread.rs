@@ -39,7 +39,8 @@ use crate::{
     gc::{self, CloneUnrooted, DataDef, Gc, GcPtr, GcRef, Generation, Move},
     interner::InternedStr,
     macros::MacroEnv,
-    source_map::LocalIter,
+    profiler::{Profile, Profiler},
+    source_map::{Local, LocalIter},
     stack::{
         ClosureState, ExternCallState, ExternState, Frame, Lock, Stack, StackFrame, StackState,
         State,
@@ -948,6 +949,57 @@ impl Thread {
         self.owned_context().gc.set_memory_limit(memory_limit)
     }
 
+    /// Limits the number of instructions and extern function calls which the thread may execute
+    /// before running out of fuel. `None` removes the limit.
+    pub fn set_fuel(&self, fuel: Option<u64>) {
+        self.context().fuel.remaining = fuel;
+    }
+
+    /// Returns the remaining fuel or `None` if the thread is not limited
+    pub fn remaining_fuel(&self) -> Option<u64> {
+        self.context().fuel.remaining
+    }
+
+    /// Adds `fuel` to the remaining fuel, resuming execution if it were suspended due to running
+    /// out of fuel. Does nothing if the thread is not limited.
+    pub fn add_fuel(&self, fuel: u64) {
+        let waker = {
+            let mut context = self.context();
+            if let Some(remaining) = &mut context.fuel.remaining {
+                *remaining = remaining.saturating_add(fuel);
+            }
+            context.fuel.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Decides what happens once the thread has run out of fuel
+    pub fn set_fuel_exhausted(&self, exhausted: FuelExhausted) {
+        self.context().fuel.exhausted = exhausted;
+    }
+
+    /// Starts recording a profile of the gluon code executed by this thread. Any profile which
+    /// were already being recorded is discarded.
+    pub fn start_profiling(&self) {
+        let mut context = self.context();
+        let total_allocated = context.gc.total_allocated_memory();
+        context.hook.profiler = Some(Box::new(Profiler::new(total_allocated)));
+    }
+
+    /// Stops profiling the thread, returning the recorded profile if `start_profiling` had been
+    /// called
+    pub fn stop_profiling(&self) -> Option<Profile> {
+        let mut context = self.context();
+        let total_allocated = context.gc.total_allocated_memory();
+        context
+            .hook
+            .profiler
+            .take()
+            .map(|profiler| profiler.finish(total_allocated))
+    }
+
     pub fn interrupt(&self) {
         self.interrupt.store(true, atomic::Ordering::Relaxed)
     }
@@ -1446,6 +1498,27 @@ impl<'a> StackInfo<'a> {
             _ => &[],
         }
     }
+
+    /// Returns the value of `local` which must be one of the locals returned from `locals`
+    pub fn local_value(&self, local: &Local) -> Option<Variants<'a>> {
+        let stack: &'a Stack = self.info.stack;
+        let frame = &stack.get_frames()[self.index];
+        stack
+            .get_values()
+            .get((frame.offset + local.index) as usize)
+            .map(Variants::new)
+    }
+
+    /// Returns the value of the upvar at `index` (the same index as in `upvars`)
+    pub fn upvar_value(&self, index: usize) -> Option<Variants<'a>> {
+        let stack: &'a Stack = self.info.stack;
+        match stack.get_frames()[self.index].state {
+            State::Closure(ClosureState { ref closure, .. }) => {
+                closure.upvars.get(index).map(Variants::new)
+            }
+            _ => None,
+        }
+    }
 }
 
 bitflags::bitflags! {
@@ -1464,6 +1537,59 @@ struct Hook {
     flags: HookFlags,
     // The index of the last executed instruction
     previous_instruction_index: usize,
+    profiler: Option<Box<Profiler>>,
+}
+
+/// Decides what happens when a thread runs out of fuel
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FuelExhausted {
+    /// Execution fails with `Error::OutOfFuel`
+    Error,
+    /// Execution is suspended, returning `Poll::Pending` from the executing future. The future
+    /// is woken once more fuel is added with `Thread::add_fuel`.
+    Yield,
+}
+
+impl Default for FuelExhausted {
+    fn default() -> Self {
+        FuelExhausted::Error
+    }
+}
+
+#[derive(Default)]
+struct Fuel {
+    // `None` if the thread may execute without limit
+    remaining: Option<u64>,
+    exhausted: FuelExhausted,
+    // The task which were suspended when the fuel ran out
+    waker: Option<task::Waker>,
+}
+
+impl Fuel {
+    /// Registers the task to wake once the fuel is refilled
+    fn register(&mut self, cx: &task::Context<'_>) {
+        if self.remaining.is_some() && self.exhausted == FuelExhausted::Yield {
+            match &self.waker {
+                Some(waker) if waker.will_wake(cx.waker()) => (),
+                _ => self.waker = Some(cx.waker().clone()),
+            }
+        }
+    }
+
+    /// Consumes one unit of fuel, failing or yielding if there is none left
+    fn consume(&mut self) -> Poll<Result<()>> {
+        match &mut self.remaining {
+            None => Ok(()).into(),
+            Some(0) => match self.exhausted {
+                FuelExhausted::Error => Err(Error::OutOfFuel).into(),
+                FuelExhausted::Yield => Poll::Pending,
+            },
+            Some(remaining) => {
+                *remaining -= 1;
+                Ok(()).into()
+            }
+        }
+    }
 }
 
 type PollFnInner<'a> = Box<
@@ -1499,6 +1625,8 @@ pub struct Context {
     pub(crate) gc: Gc,
     #[cfg_attr(feature = "serde_derive", serde(skip))]
     hook: Hook,
+    #[cfg_attr(feature = "serde_derive", serde(skip))]
+    fuel: Fuel,
 
     /// Stack of polling functions used for extern functions returning futures
     #[cfg_attr(feature = "serde_derive", serde(skip))]
@@ -1514,7 +1642,9 @@ impl Context {
                 function: None,
                 flags: HookFlags::empty(),
                 previous_instruction_index: usize::max_value(),
+                profiler: None,
             },
+            fuel: Fuel::default(),
             poll_fns: Vec::new(),
         }
     }
@@ -1737,12 +1867,19 @@ impl<'b> OwnedContext<'b> {
 
     fn execute(mut self, cx: &mut task::Context<'_>) -> Poll<Result<Option<OwnedContext<'b>>>> {
         let mut context = self.borrow_mut();
+        context.fuel.register(cx);
+        if let Some(profiler) = &mut context.hook.profiler {
+            profiler.resume(context.gc.total_allocated_memory());
+        }
         // Return when the starting frame is finished
         loop {
             if context.thread.interrupted() {
                 return Err(Error::Interrupted).into();
             }
             trace!("STACK\n{:?}", context.stack.stack().get_frames());
+            if let Some(profiler) = &mut context.hook.profiler {
+                profiler.frame(&context.stack.stack(), context.gc.total_allocated_memory());
+            }
             let state = &context.stack.frame().state;
 
             if context.hook.flags.contains(HookFlags::CALL_FLAG) {
@@ -1800,6 +1937,9 @@ impl<'b> OwnedContext<'b> {
                             }
                         }
                     }
+                    if ext.call_state == ExternCallState::Start {
+                        ready!(context.fuel.consume())?;
+                    }
                     match &mut context.stack.frame_mut().state {
                         State::Extern(ext) => ext.call_state = ExternCallState::Poll,
                         _ => unreachable!(),
@@ -1975,6 +2115,7 @@ impl<'b> OwnedContext<'b> {
             gc: &mut context.gc,
             stack: StackFrame::current(&mut context.stack),
             hook: &mut context.hook,
+            fuel: &mut context.fuel,
             poll_fns: &context.poll_fns,
         }
     }
@@ -2009,6 +2150,7 @@ pub struct ExecuteContext<'b, 'gc, S: StackState = ClosureState> {
     pub stack: StackFrame<'b, S>,
     pub gc: &'gc mut Gc,
     hook: &'b mut Hook,
+    fuel: &'b mut Fuel,
     poll_fns: &'b [PollFn],
 }
 
@@ -2100,6 +2242,19 @@ impl<'b, 'gc> ExecuteContext<'b, 'gc> {
 
             debug_instruction(&self.stack, instruction_index, instr);
 
+            match self.fuel.consume() {
+                Poll::Ready(Ok(())) => (),
+                result => {
+                    // Continue from this instruction once execution is resumed
+                    self.stack.frame_mut().state.instruction_index = instruction_index;
+                    return result.map_ok(|()| None);
+                }
+            }
+
+            if let Some(profiler) = &mut self.hook.profiler {
+                profiler.instruction();
+            }
+
             if !self.hook.flags.is_empty() && self.hook.flags.contains(HookFlags::LINE_FLAG) {
                 ready!(self.run_hook(&function, instruction_index))?;
             }
@@ -2303,6 +2458,59 @@ impl<'b, 'gc> ExecuteContext<'b, 'gc> {
                     }
                     x => return Err(Error::Message(format!("GetOffset on {:?}", x))).into(),
                 },
+                ArrayLen => match self.stack.pop().get_repr() {
+                    ValueRepr::Array(array) => {
+                        let len = array.len() as VmInt;
+                        self.stack.push(Int(len));
+                    }
+                    x => return Err(Error::Message(format!("ArrayLen on {:?}", x))).into(),
+                },
+                GetIndex(i) => match self.stack.pop().get_repr() {
+                    ValueRepr::Array(array) => {
+                        let v = array
+                            .get(i as usize)
+                            .unwrap_or_else(|| ice!("Array index {} out of bounds", i));
+                        self.stack.push(v);
+                    }
+                    x => return Err(Error::Message(format!("GetIndex on {:?}", x))).into(),
+                },
+                GetIndexFromEnd(i) => match self.stack.pop().get_repr() {
+                    ValueRepr::Array(array) => {
+                        let v = array
+                            .len()
+                            .checked_sub(i as usize + 1)
+                            .and_then(|index| array.get(index))
+                            .unwrap_or_else(|| ice!("Array index {} (from end) out of bounds", i));
+                        self.stack.push(v);
+                    }
+                    x => return Err(Error::Message(format!("GetIndexFromEnd on {:?}", x))).into(),
+                },
+                SliceArray { start, from_end } => {
+                    // The array is left on the stack while the slice is allocated so that it stays
+                    // rooted
+                    let d = {
+                        let array = match self.stack.top().get_repr() {
+                            ValueRepr::Array(array) => array,
+                            x => {
+                                return Err(Error::Message(format!("SliceArray on {:?}", x))).into()
+                            }
+                        };
+                        let start = start as usize;
+                        let end = array
+                            .len()
+                            .checked_sub(from_end as usize)
+                            .filter(|&end| start <= end)
+                            .unwrap_or_else(|| ice!("Array slice out of bounds"));
+                        alloc(
+                            self.gc,
+                            self.thread,
+                            self.stack.stack(),
+                            crate::value::ArraySliceDef { start, end, array },
+                        )?
+                    };
+                    self.stack.pop();
+                    self.stack.push(Variants::from(d));
+                }
                 GetField(i) => {
                     let field = &function.strings[i as usize];
                     match self.stack.pop().get_repr() {
@@ -2546,6 +2754,7 @@ impl<'b, 'gc> ExecuteContext<'b, 'gc, State> {
             stack: self.stack.from_state(),
             gc: self.gc,
             hook: self.hook,
+            fuel: self.fuel,
             poll_fns: self.poll_fns,
         }
     }
@@ -2561,6 +2770,7 @@ where
             stack: self.stack.to_state(),
             gc: self.gc,
             hook: self.hook,
+            fuel: self.fuel,
             poll_fns: self.poll_fns,
         }
     }
@@ -2581,6 +2791,7 @@ where
             stack,
             gc: self.gc,
             hook: self.hook,
+            fuel: self.fuel,
             poll_fns: self.poll_fns,
         })
     }
@@ -2603,6 +2814,7 @@ where
                     stack,
                     gc: self.gc,
                     hook: self.hook,
+                    fuel: self.fuel,
                     poll_fns: self.poll_fns,
                 })
             }
@@ -2611,6 +2823,7 @@ where
                 stack: StackFrame::current(stack),
                 gc: self.gc,
                 hook: self.hook,
+                fuel: self.fuel,
                 poll_fns: self.poll_fns,
             }),
         }
@@ -2895,6 +3108,7 @@ impl<'vm> ActiveThread<'vm> {
             gc: &mut context.gc,
             stack: StackFrame::current(&mut context.stack),
             hook: &mut context.hook,
+            fuel: &mut context.fuel,
             poll_fns: &context.poll_fns,
         }
     }
@@ -51,6 +51,11 @@ impl std::hash::Hash for EqFloat {
     }
 }
 
+/// Version of the format of compiled code. Must be incremented whenever `Instruction` or any other
+/// type which is serialized as part of a compiled module changes, as modules cached by an older
+/// version can't be loaded.
+pub const BYTECODE_VERSION: u32 = 1;
+
 /// Enum which represent the instructions executed by the virtual machine.
 ///
 /// The binary arithmetic instructions pop two values of the stack and then push the result.
@@ -118,6 +123,22 @@ pub enum Instruction {
     /// Retrieves the field at `offset` of an object at the top of the stack. The result of the
     /// field access replaces the object on the stack.
     GetOffset(VmIndex),
+    /// Retrieves the length of the array at the top of the stack. The length replaces the array on
+    /// the stack.
+    ArrayLen,
+    /// Retrieves the element at `index` of the array at the top of the stack. The element replaces
+    /// the array on the stack.
+    GetIndex(VmIndex),
+    /// Retrieves the element at `index`, counted from the end of the array (so `0` is the last
+    /// element), of the array at the top of the stack. The element replaces the array on the
+    /// stack.
+    GetIndexFromEnd(VmIndex),
+    /// Copies the elements of the array at the top of the stack, skipping the first `start` and
+    /// the last `from_end` elements. The new array replaces the array on the stack.
+    SliceArray {
+        start: VmIndex,
+        from_end: VmIndex,
+    },
     /// Retrieves the field of a polymorphic record by retrieving the string constant at `index`
     /// and using that to retrieve lookup the field. The result of the
     /// field access replaces the object on the stack.
@@ -195,6 +216,7 @@ impl Instruction {
             | ConstructRecord { args, .. }
             | ConstructArray(args) => 1 - args as i32,
             GetField(_) | GetOffset(_) => 0,
+            ArrayLen | GetIndex(_) | GetIndexFromEnd(_) | SliceArray { .. } => 0,
             // The number of added stack slots are handled separately as the type is needed to
             // calculate the number of slots needed
             Split => -1,
@@ -1473,6 +1473,37 @@ unsafe impl<'a> DataDef for &'a ValueArray {
     }
 }
 
+/// Allocates a copy of the elements `start..end` of `array`
+#[derive(Trace)]
+#[gluon(gluon_vm)]
+pub(crate) struct ArraySliceDef<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub array: &'a ValueArray,
+}
+
+unsafe impl<'a> DataDef for ArraySliceDef<'a> {
+    type Value = ValueArray;
+
+    fn size(&self) -> usize {
+        ValueArray::size_of(self.array.repr(), self.end - self.start)
+    }
+
+    fn initialize<'w>(self, mut result: WriteOnly<'w, ValueArray>) -> &'w mut ValueArray {
+        unsafe {
+            let result = &mut *result.as_mut_ptr();
+            result.set_repr(self.array.repr());
+            result.initialize(
+                self.array
+                    .iter()
+                    .skip(self.start)
+                    .take(self.end - self.start),
+            );
+            result
+        }
+    }
+}
+
 #[derive(Trace)]
 #[gluon(gluon_vm)]
 pub(crate) struct ArrayDef<'b>(pub &'b [Value]);